@@ -5,7 +5,68 @@
 //! Linker scripts like `memory.x` are not normally a part of the build process and changes to it
 //! would ordinarily be ignored by the build process.
 
-use std::{env, fs::File, io::Write, path::PathBuf};
+use std::{
+    env,
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Optional settings that may be set in `./.env`, `~/.pico.env`, or the environment.
+const OPTIONAL_SETTINGS: &[&str] = &[
+    "PM_INDICATOR",
+    "LEADING_ZERO_HOURS",
+    "ROTATE_180",
+    "DAYS_SINCE",
+    "CAROUSEL",
+    "CAROUSEL_MESSAGE",
+    "LATITUDE",
+    "LONGITUDE",
+    "NIGHT_BRIGHTNESS",
+    "AUTO_RETURN_MINUTES",
+    "BUTTON_ACTIVE_LOW",
+    "BUTTON_DEBOUNCE_MS",
+    "LONG_PRESS_MS",
+    "DOUBLE_PRESS_MS",
+    "VERY_LONG_PRESS_MS",
+    "IR_MODE_KEY",
+    "IR_SET_KEY",
+    "TOUCH_PAD",
+    "DEEP_SLEEP",
+    "TX_DIMMING",
+    "PAGE_ROTATION",
+    "SUPPLY_DIVIDER",
+    "LOW_SUPPLY_MV",
+    "CRITICAL_SUPPLY_MV",
+    "BATTERY",
+    "GPS_BAUD",
+    "RADIO_TIME",
+    "RADIO_ACTIVE_LOW",
+    "WS2812_COLORS",
+    "TICK_QUIET_HOURS",
+    "ALARM_MELODY",
+    "ALARM_AT",
+    "ALARM_ACTIONS",
+    "TIMER_ACTIONS",
+    "LEAP_SMEAR",
+    "MAX_TIME_JUMP_SECONDS",
+    "MAX_SLEW_MS",
+    "MAX_BACKWARD_HOLD_SECONDS",
+    "TIME_SERVER",
+    "TIME_AUTH_KEY",
+    "TIME_FALLBACK_URL",
+    "PORTAL_CHECK_URL",
+    "WEATHER_URL",
+    "TLS_PINS",
+    "NETWORK_QUIET_HOURS",
+    "WEATHER_JSON_KEY",
+    "MQTT_BROKER",
+    "MQTT_USER",
+    "MQTT_PASSWORD",
+    "MQTT_TOPIC",
+];
 
 fn main() -> Result<(), Box<dyn core::error::Error>> {
     // Put `memory.x` in our output directory and ensure it's on the linker search path.
@@ -17,41 +78,98 @@ fn main() -> Result<(), Box<dyn core::error::Error>> {
     // Tell `cargo` to rebuild project if `memory.x` linker script file changes
     println!("cargo:rerun-if-changed=memory.x");
 
+    // `defmt`'s linker script only exists when the `logging` feature pulls `defmt` in
+    if env::var_os("CARGO_FEATURE_LOGGING").is_some() {
+        println!("cargo:rustc-link-arg=-Tdefmt.x");
+    }
+
     println!("cargo:rerun-if-changed=build.rs"); // Re-run if this file changes
     println!("cargo:rerun-if-changed=*"); // Re-run if any file in the project changes
 
-    // WiFi credentials and timezone configuration
+    // Compile-time defaults. The WiFi network and time zone are not among them: they live on
+    // each clock's config page, written by `clock-flash`.
     // 1) Try project-local .env (ignored by git)
     let _ = dotenvy::from_filename(".env");
 
-    // 2) Fall back to HOME/.pico.env (Windows: USERPROFILE)
-    if env::var("WIFI_SSID").is_err() || env::var("WIFI_PASS").is_err() || env::var("UTC_OFFSET_MINUTES").is_err() {
-        let home = env::var_os("USERPROFILE").or_else(|| env::var_os("HOME"))
-            .expect("Could not determine home directory (USERPROFILE/HOME not set)");
+    // 2) Then HOME/.pico.env (Windows: USERPROFILE), for anything .env leaves unset
+    if let Some(home) = env::var_os("USERPROFILE").or_else(|| env::var_os("HOME")) {
         let mut p = PathBuf::from(home);
         p.push(".pico.env");
         let _ = dotenvy::from_path(&p);
     }
 
-    // 3) Require all vars (fail fast with clear message)
-    let ssid = env::var("WIFI_SSID")
-        .expect("Missing WIFI_SSID (set in ./.env or ~/.pico.env)");
-    let pass = env::var("WIFI_PASS")
-        .expect("Missing WIFI_PASS (set in ./.env or ~/.pico.env)");
-    let utc_offset = env::var("UTC_OFFSET_MINUTES")
-        .expect("Missing UTC_OFFSET_MINUTES (set in ./.env or ~/.pico.env, e.g., -420 for PST)");
+    // 3) Optional display, button, and time settings (read with `option_env!`, so pass through only if set)
+    for name in OPTIONAL_SETTINGS {
+        if let Ok(value) = env::var(name) {
+            println!("cargo:rustc-env={name}={value}");
+        }
+        println!("cargo:rerun-if-env-changed={name}");
+    }
+
+    // 4) Build time, so implausibly late network times can be rejected
+    let built_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    println!("cargo:rustc-env=BUILD_UNIX_SECONDS={built_at}");
 
-    // 4) Expose as compile-time constants
-    println!("cargo:rustc-env=WIFI_SSID={ssid}");
-    println!("cargo:rustc-env=WIFI_PASS={pass}");
-    println!("cargo:rustc-env=UTC_OFFSET_MINUTES={utc_offset}");
+    // 5) Version, commit, and build date, so units in the field can be identified
+    write_build_info(out, built_at)?;
+    println!("cargo:rerun-if-changed=.git/HEAD");
+
+    // 6) The talking clock's recorded words, embedded by `src/talker.rs`
+    if env::var_os("CARGO_FEATURE_TALKING_CLOCK").is_some() {
+        let voice = env::var("TALKING_CLOCK_VOICE").expect(
+            "Missing TALKING_CLOCK_VOICE (the path of a voice file from tools/make_voice.py)",
+        );
+        fs::copy(&voice, out.join("voice.bin"))?;
+        println!("cargo:rerun-if-changed={voice}");
+    }
+    println!("cargo:rerun-if-env-changed=TALKING_CLOCK_VOICE");
 
     // Optional: don't rebuild unless these change
-    println!("cargo:rerun-if-env-changed=WIFI_SSID");
-    println!("cargo:rerun-if-env-changed=WIFI_PASS");
-    println!("cargo:rerun-if-env-changed=UTC_OFFSET_MINUTES");
     println!("cargo:rerun-if-env-changed=DST_OFFSET_MINUTES");
     println!("cargo:rerun-if-changed=.env");
 
     Ok(())
 }
+
+/// Writes `build_info.rs` (included by `src/build_info.rs`) into `out`.
+fn write_build_info(out: &Path, built_at: u64) -> std::io::Result<()> {
+    let version = env!("CARGO_PKG_VERSION");
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map_or_else(|| "unknown".to_owned(), |hash| hash.trim().to_owned());
+    let (year, month, day) = civil_from_days(built_at / 86_400);
+    let build_date = format!("{year:04}-{month:02}-{day:02}");
+    let mut file = File::create(out.join("build_info.rs"))?;
+    writeln!(file, "/// The crate version, from `Cargo.toml`.")?;
+    writeln!(file, "pub const VERSION: &str = {version:?};")?;
+    writeln!(file, "/// The short hash of the commit built, or `unknown` outside a git checkout.")?;
+    writeln!(file, "pub const GIT_HASH: &str = {git_hash:?};")?;
+    writeln!(file, "/// The UTC date of the build, as `YYYY-MM-DD`.")?;
+    writeln!(file, "pub const BUILD_DATE: &str = {build_date:?};")?;
+    writeln!(file, "/// All three, as shown on the display: `v0.1.1 abc1234 2026-10-16`.")?;
+    writeln!(file, "pub const SUMMARY: &str = \"v{version} {git_hash} {build_date}\";")
+}
+
+/// The proleptic Gregorian date `days` after 1970-01-01 (Howard Hinnant's algorithm).
+#[expect(
+    clippy::arithmetic_side_effects,
+    clippy::integer_division_remainder_used,
+    reason = "Days since 1970 are far from overflowing, and every divisor is a non-zero constant."
+)]
+fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let shifted = days + 719_468;
+    let era = shifted / 146_097;
+    let day_of_era = shifted - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
@@ -0,0 +1,101 @@
+/// How many decimal points the battery glyph has.
+pub const BATTERY_GLYPH_LEVELS: u8 = 4;
+
+/// A single lithium-polymer cell, from empty to full charge, in millivolts and percent.
+const LIPO_CURVE: [(u16, u8); 8] = [
+    (3300, 0),
+    (3600, 10),
+    (3700, 30),
+    (3800, 55),
+    (3900, 70),
+    (4000, 82),
+    (4100, 92),
+    (4200, 100),
+];
+
+/// Three alkaline AA cells in series, from empty to fresh, in millivolts and percent.
+const THREE_AA_CURVE: [(u16, u8); 6] = [
+    (3000, 0),
+    (3300, 10),
+    (3600, 40),
+    (3900, 75),
+    (4200, 90),
+    (4500, 100),
+];
+
+/// What powers the clock, which sets how its voltage maps to a charge percentage.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BatteryChemistry {
+    /// A single lithium-polymer (or lithium-ion) cell, 4.2 V full.
+    #[default]
+    LiPo,
+    /// Three alkaline AA cells in series, 4.5 V fresh.
+    ThreeAa,
+}
+
+impl BatteryChemistry {
+    /// Parses `lipo` or `3xaa` (in any case).
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        if text.eq_ignore_ascii_case("lipo") {
+            Some(Self::LiPo)
+        } else if text.eq_ignore_ascii_case("3xaa") {
+            Some(Self::ThreeAa)
+        } else {
+            None
+        }
+    }
+
+    /// An estimate of the charge left, 0 to 100, interpolated along the chemistry's
+    /// discharge curve.
+    #[must_use]
+    pub fn percent(self, millivolts: u16) -> u8 {
+        let curve: &[(u16, u8)] = match self {
+            Self::LiPo => &LIPO_CURVE,
+            Self::ThreeAa => &THREE_AA_CURVE,
+        };
+        let mut below = (0, 0);
+        for &(curve_millivolts, curve_percent) in curve {
+            if millivolts < curve_millivolts {
+                return interpolate(below, (curve_millivolts, curve_percent), millivolts);
+            }
+            below = (curve_millivolts, curve_percent);
+        }
+        100
+    }
+}
+
+/// How many of the `BATTERY_GLYPH_LEVELS` decimal points to light for `percent`: one for any
+/// charge at all, and all four when (nearly) full.
+#[must_use]
+#[expect(
+    clippy::integer_division_remainder_used,
+    reason = "Rounding up to whole quarters is intended."
+)]
+pub const fn battery_glyph_level(percent: u8) -> u8 {
+    let quarters = percent.saturating_add(24) / 25;
+    if quarters > BATTERY_GLYPH_LEVELS {
+        BATTERY_GLYPH_LEVELS
+    } else {
+        quarters
+    }
+}
+
+/// The percent at `millivolts`, on the straight line from `low` to `high`. Below the first
+/// point of a curve (`low` is `(0, 0)`), the battery counts as empty.
+#[expect(
+    clippy::integer_division_remainder_used,
+    reason = "Whole percents need no rounding."
+)]
+fn interpolate(low: (u16, u8), high: (u16, u8), millivolts: u16) -> u8 {
+    let ((low_millivolts, low_percent), (high_millivolts, high_percent)) = (low, high);
+    if low_millivolts == 0 {
+        return 0;
+    }
+    let span = u32::from(high_millivolts.saturating_sub(low_millivolts));
+    let rise = u32::from(high_percent.saturating_sub(low_percent));
+    let along = u32::from(millivolts.saturating_sub(low_millivolts));
+    let gained = along.saturating_mul(rise).checked_div(span).unwrap_or_default();
+    low_percent.saturating_add(u8::try_from(gained).unwrap_or(u8::MAX))
+}
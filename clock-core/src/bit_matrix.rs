@@ -0,0 +1,301 @@
+use crate::{
+    error::Error::BitsToIndexesNotEnoughSpace,
+    shared_constants::{BitsToIndexes, Segments, CELL_COUNT},
+    CELL_COUNT_U8,
+};
+use core::{array, num::NonZero, ops::BitOrAssign, slice};
+
+use heapless::Vec;
+
+use crate::{leds::Leds, Result};
+
+/// One character per cell of the display.
+pub type CellText = [char; CELL_COUNT];
+
+/// The segment bits to light, one `Segments` per cell of the display, and which cells show
+/// dimmed.
+///
+/// The dimmed cells are a per-cell intensity mask: they show at about half the display's
+/// brightness, and hold steady while the rest of a blinking frame blinks.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitMatrix([Segments; CELL_COUNT], u8);
+
+// The dimmed cells are one bit per cell of a `u8`.
+const _: () = assert!(CELL_COUNT <= u8::BITS as usize, "too many cells for the dimmed mask");
+
+impl BitMatrix {
+    #[must_use]
+    pub const fn new(bits: [Segments; CELL_COUNT]) -> Self {
+        Self(bits, 0)
+    }
+
+    #[must_use]
+    pub const fn from_bits(bits: Segments) -> Self {
+        Self([bits; CELL_COUNT], 0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Segments> {
+        self.0.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, Segments> {
+        self.0.iter_mut()
+    }
+
+    pub fn from_text(text: &CellText) -> Self {
+        let bytes = text.map(Leds::segments);
+        Self::new(bytes)
+    }
+
+    /// The characters this shows, as best they can be told apart: the inverse of `from_text`
+    /// for debug logs and simulators. A lit decimal point is ignored, and unrecognized
+    /// patterns become `?`.
+    #[must_use]
+    pub fn decode(&self) -> CellText {
+        self.0.map(|bits| {
+            Leds::char_for(bits)
+                .or_else(|| Leds::char_for(bits & !Leds::DECIMAL))
+                .unwrap_or('?')
+        })
+    }
+
+    /// Turns on the decimal point of the cell at `index`. Out-of-range indexes are ignored.
+    pub fn set_dot(&mut self, index: usize) {
+        if let Some(bits) = self.0.get_mut(index) {
+            *bits |= Leds::DECIMAL;
+        }
+    }
+
+    /// Dims the cell at `index`. Out-of-range indexes are ignored.
+    pub const fn dim(&mut self, index: usize) {
+        if index < CELL_COUNT {
+            self.1 |= cell_bit(index);
+        }
+    }
+
+    /// Whether the cell at `index` shows dimmed.
+    #[must_use]
+    pub const fn is_dimmed(&self, index: usize) -> bool {
+        self.1 & cell_bit(index) != 0
+    }
+
+    /// The same segments with every cell dimmed.
+    #[must_use]
+    pub const fn dimmed(self) -> Self {
+        Self(self.0, cell_bit(CELL_COUNT).wrapping_sub(1))
+    }
+
+    /// What a blinking frame shows while dark: its dimmed cells, which hold steady, and
+    /// nothing else.
+    #[must_use]
+    pub fn blinked_off(self) -> Self {
+        self.keep_cells(|dimmed| dimmed)
+    }
+
+    /// The cells that are not dimmed, with the dimmed ones blank.
+    #[must_use]
+    pub fn without_dimmed(self) -> Self {
+        self.keep_cells(|dimmed| !dimmed)
+    }
+
+    /// Blanks the cells for which `keep`, given whether the cell is dimmed, is false.
+    fn keep_cells(mut self, keep: impl Fn(bool) -> bool) -> Self {
+        for (index, bits) in self.0.iter_mut().enumerate() {
+            if !keep(self.1 & cell_bit(index) != 0) {
+                *bits = Leds::SPACE;
+            }
+        }
+        self
+    }
+
+    #[expect(
+        clippy::indexing_slicing,
+        clippy::integer_division_remainder_used,
+        reason = "Indexing and arithmetic are safe: Leds::DIGITS has 10 elements, and (number % 10) is in 0..9. \
+        Modulo is required for digit extraction in no_std."
+    )]
+    #[must_use]
+    pub fn from_number(mut number: u16, padding: Segments) -> Self {
+        let mut bit_matrix = Self::from_bits(padding);
+
+        for bits in bit_matrix.iter_mut().rev() {
+            *bits = Leds::DIGITS[(number % 10) as usize]; // Get the last digit
+            number /= 10; // Remove the last digit
+            if number == 0 {
+                break;
+            }
+        }
+        // If the original number was out of range, turn on all decimal points
+        if number > 0 {
+            bit_matrix |= Leds::DECIMAL;
+        }
+
+        bit_matrix
+    }
+
+    /// Returns the matrix as it looks on a display mounted upside down.
+    ///
+    /// Cells are reversed and each cell's segments are swapped top-for-bottom and
+    /// left-for-right. The middle segment and the decimal point keep their bits.
+    #[must_use]
+    pub fn rotated_180(self) -> Self {
+        let mut cells = self.0;
+        cells.reverse();
+        let mut rotated = Self(cells.map(rotate_segments_180), 0);
+        for index in (0..CELL_COUNT).filter(|&index| self.is_dimmed(index)) {
+            rotated.dim(CELL_COUNT.saturating_sub(1).saturating_sub(index));
+        }
+        rotated
+    }
+
+    /// Groups the indexes of the lit cells by their segment bits, into `bits_to_index`, so
+    /// cells showing the same character light together.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BitsToIndexesNotEnoughSpace` if `bits_to_index` cannot hold the groups. Its
+    /// capacities of `CELL_COUNT` groups of `CELL_COUNT` indexes always can.
+    pub fn bits_to_indexes(&self, bits_to_index: &mut BitsToIndexes) -> Result<()> {
+        bits_to_index.clear();
+        for (&bits, index) in self.iter().zip(0..CELL_COUNT_U8) {
+            if let Some(nonzero_bits) = NonZero::new(bits) {
+                if let Some(vec) = bits_to_index.get_mut(&nonzero_bits) {
+                    vec.push(index).map_err(|_| BitsToIndexesNotEnoughSpace)?;
+                } else {
+                    let vec =
+                        Vec::from_slice(&[index]).map_err(|_| BitsToIndexesNotEnoughSpace)?;
+                    bits_to_index
+                        .insert(nonzero_bits, vec)
+                        .map_err(|_| BitsToIndexesNotEnoughSpace)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The dimmed-mask bit for the cell at `index`, or 0 past the last bit.
+const fn cell_bit(index: usize) -> u8 {
+    if index < u8::BITS as usize {
+        1 << index
+    } else {
+        0
+    }
+}
+
+/// Maps one cell's segments to the segments that appear in the same place when rotated 180°.
+///
+/// A, B, C (bits 0-2) trade places with D, E, F (bits 3-5).
+#[cfg(not(feature = "fourteen-segment"))]
+const fn rotate_segments_180(bits: Segments) -> Segments {
+    const UNMOVED: Segments = Leds::SEG_G | Leds::DECIMAL;
+    const TOP_AND_RIGHT: Segments = Leds::SEG_A | Leds::SEG_B | Leds::SEG_C;
+    (bits & UNMOVED) | ((bits & TOP_AND_RIGHT) << 3) | ((bits >> 3) & TOP_AND_RIGHT)
+}
+
+/// Maps one cell's segments to the segments that appear in the same place when rotated 180°.
+///
+/// A, B, C (bits 0-2) trade places with D, E, F (bits 3-5), the halves of the middle bar
+/// (G and G2) trade places, and so do opposite diagonals (H with M, J with K) and the upper
+/// and lower verticals (I with L).
+#[cfg(feature = "fourteen-segment")]
+const fn rotate_segments_180(bits: Segments) -> Segments {
+    // Each pair, and how far apart their bits are
+    const PAIRS: [(Segments, Segments, u32); 5] = [
+        (Leds::SEG_A | Leds::SEG_B | Leds::SEG_C, Leds::SEG_D | Leds::SEG_E | Leds::SEG_F, 3),
+        (Leds::SEG_G, Leds::SEG_G2, 2),
+        (Leds::SEG_H, Leds::SEG_M, 5),
+        (Leds::SEG_I, Leds::SEG_L, 3),
+        (Leds::SEG_J, Leds::SEG_K, 1),
+    ];
+    let mut rotated = bits & Leds::DECIMAL;
+    let mut pairs: &[(Segments, Segments, u32)] = &PAIRS;
+    while let Some((&(first, second, shift), rest)) = pairs.split_first() {
+        rotated |= ((bits & first) << shift) | ((bits & second) >> shift);
+        pairs = rest;
+    }
+    rotated
+}
+
+impl core::str::FromStr for BitMatrix {
+    type Err = (); // Replace with a meaningful error type if needed
+
+    /// Parse a string into a `BitMatrix`. If too long, the decimal point will be turned on.
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut bit_matrix = Self::default();
+
+        for (bits, char) in bit_matrix.iter_mut().zip(input.chars()) {
+            *bits = Leds::ASCII_TABLE.get(char as usize).copied().ok_or(())?;
+        }
+
+        if input.len() > CELL_COUNT {
+            bit_matrix |= Leds::DECIMAL;
+        }
+
+        Ok(bit_matrix)
+    }
+}
+impl Default for BitMatrix {
+    fn default() -> Self {
+        Self([0; CELL_COUNT], 0)
+    }
+}
+
+// Implement `|=` for `BitMatrix`
+impl BitOrAssign<Segments> for BitMatrix {
+    fn bitor_assign(&mut self, rhs: Segments) {
+        self.iter_mut().for_each(|bits| *bits |= rhs);
+    }
+}
+
+impl IntoIterator for BitMatrix {
+    type Item = Segments;
+    type IntoIter = array::IntoIter<Segments, CELL_COUNT>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a BitMatrix {
+    type Item = &'a Segments;
+    type IntoIter = slice::Iter<'a, Segments>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut BitMatrix {
+    type Item = &'a mut Segments;
+    type IntoIter = slice::IterMut<'a, Segments>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter_mut()
+    }
+}
+
+// implement index for BitMatrix and &BitMatrix
+impl core::ops::Index<u8> for BitMatrix {
+    type Output = Segments;
+
+    #[expect(
+        clippy::indexing_slicing,
+        reason = "Bounds checking is the caller's responsibility."
+    )]
+    fn index(&self, index: u8) -> &Self::Output {
+        &self.0[index as usize]
+    }
+}
+
+// index that you can assign to
+#[expect(
+    clippy::indexing_slicing,
+    reason = "Bounds checking is the caller's responsibility."
+)]
+impl core::ops::IndexMut<u8> for BitMatrix {
+    fn index_mut(&mut self, index: u8) -> &mut Self::Output {
+        &mut self.0[index as usize]
+    }
+}
@@ -0,0 +1,11 @@
+/// Whether the display shows its text steadily or blinks it.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlinkState {
+    #[default]
+    Solid,
+    BlinkingAndOn,
+    BlinkingButOff,
+    /// Blinks twice as fast as `BlinkingAndOn`, for something that needs attention now.
+    Fast,
+}
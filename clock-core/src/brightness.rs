@@ -0,0 +1,110 @@
+use embassy_time::Duration;
+
+/// How much light each level gives, in thousandths of fully lit: the level's share of
+/// `Brightness::MAX` raised to the power 2.2. The eye sees light about as the square root of its
+/// power, so with this curve each level looks about as much brighter than the one below as the
+/// next, rather than the low levels looking alike and the high ones leaping apart.
+const GAMMA_DUTY: [u16; 16] = [
+    0, 3, 12, 29, 55, 89, 133, 187, 251, 325, 410, 505, 612, 730, 859, 1_000,
+];
+
+/// How brightly the display is lit, from `Brightness::OFF` to `Brightness::MAX` in 16 levels.
+///
+/// The display lights each cell for a share of its multiplex slot given by the level's
+/// gamma-corrected `duty`, so the levels are evenly spaced to the eye.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Brightness(u8);
+
+impl Brightness {
+    /// Nothing lit.
+    pub const OFF: Self = Self(0);
+    /// The dimmest level that still shows something.
+    pub const MIN: Self = Self(1);
+    /// Fully lit.
+    pub const MAX: Self = Self(15);
+    /// The `duty` of `Brightness::MAX`.
+    pub const FULL_DUTY: u16 = 1_000;
+
+    /// Creates a `Brightness`, clamping `level` to `Brightness::MAX`.
+    #[must_use]
+    pub const fn new(level: u8) -> Self {
+        if level > Self::MAX.0 {
+            Self::MAX
+        } else {
+            Self(level)
+        }
+    }
+
+    /// The level, from 0 (off) to 15 (fully lit).
+    #[must_use]
+    pub const fn level(self) -> u8 {
+        self.0
+    }
+
+    /// How much light this level gives, from 0 to `Brightness::FULL_DUTY`, on a gamma curve.
+    #[must_use]
+    pub fn duty(self) -> u16 {
+        GAMMA_DUTY
+            .get(usize::from(self.0))
+            .copied()
+            .unwrap_or(Self::FULL_DUTY)
+    }
+
+    /// Whether nothing is lit.
+    #[must_use]
+    pub const fn is_off(self) -> bool {
+        self.0 == 0
+    }
+
+    /// About half this brightness, for text shown dimmed. Anything lit stays at least
+    /// `Brightness::MIN`.
+    #[must_use]
+    pub const fn dimmed(self) -> Self {
+        Self(self.0.div_ceil(2))
+    }
+
+    /// Where a ramp from this brightness to `target` is after `step` of `steps` equal steps.
+    /// The ramp eases in and out (a smoothstep), so it starts and ends gently, and it reaches
+    /// `target` at the last step.
+    #[must_use]
+    #[expect(
+        clippy::arithmetic_side_effects,
+        clippy::integer_division_remainder_used,
+        reason = "Levels are at most 15 and the eased fraction at most one, so nothing overflows."
+    )]
+    pub fn ramp_toward(self, target: Self, step: u16, steps: u16) -> Self {
+        if step >= steps {
+            return target;
+        }
+        // smoothstep(x) = 3x² - 2x³ with x = step / steps, scaled by steps³
+        let (done, total) = (i64::from(step), i64::from(steps));
+        let eased = done * done * (3 * total - 2 * done);
+        let scale = total * total * total;
+        let change = i64::from(target.0) - i64::from(self.0);
+        // Round to the nearest level, away from zero at the halfway point
+        let offset = (2 * change * eased + change.signum() * scale) / (2 * scale);
+        Self::new(u8::try_from(i64::from(self.0) + offset).unwrap_or(target.0))
+    }
+
+    /// Splits `period` into the part a cell is lit and the part it is dark, by `duty`.
+    #[must_use]
+    #[expect(
+        clippy::arithmetic_side_effects,
+        clippy::integer_division_remainder_used,
+        reason = "The duty is at most `FULL_DUTY`, so the lit part never exceeds the period."
+    )]
+    pub fn split(self, period: Duration) -> (Duration, Duration) {
+        let lit_ticks = period.as_ticks() * u64::from(self.duty()) / u64::from(Self::FULL_DUTY);
+        (
+            Duration::from_ticks(lit_ticks),
+            Duration::from_ticks(period.as_ticks() - lit_ticks),
+        )
+    }
+}
+
+impl Default for Brightness {
+    fn default() -> Self {
+        Self::MAX
+    }
+}
@@ -0,0 +1,119 @@
+use embassy_time::Duration;
+
+use crate::{Error, Result, Text};
+
+/// The most items a `Carousel` sequence holds.
+pub const MAX_CAROUSEL_ITEMS: usize = 8;
+
+/// The longest message a `Carousel` scrolls.
+pub const MAX_MESSAGE_LEN: usize = 32;
+
+/// What one step of the carousel shows.
+#[expect(missing_docs, reason = "The variants are self-explanatory.")]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CarouselItem {
+    Time,
+    Date,
+    Temperature,
+    Message,
+}
+
+/// A repeating sequence of items, each shown for its own dwell time, plus the message that
+/// `CarouselItem::Message` scrolls.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Carousel {
+    /// Each item and its dwell time in seconds.
+    items: [Option<(CarouselItem, u16)>; MAX_CAROUSEL_ITEMS],
+    message: Text<MAX_MESSAGE_LEN>,
+}
+
+impl Carousel {
+    /// Creates an empty carousel.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            items: [None; MAX_CAROUSEL_ITEMS],
+            message: Text::new(),
+        }
+    }
+
+    /// Parses a sequence such as `time:10,date:5,temp:5,msg:8` (item names and dwell seconds)
+    /// and the message for `msg`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an item is unknown, a dwell time is not 1 to 3600 seconds, there
+    /// are more than `MAX_CAROUSEL_ITEMS`, or the message is longer than `MAX_MESSAGE_LEN`.
+    pub fn parse(sequence: &str, message: &str) -> Result<Self> {
+        let mut carousel = Self::new();
+        let mut slots = carousel.items.iter_mut();
+        for entry in sequence.split(',').filter(|entry| !entry.is_empty()) {
+            let (name, seconds) = entry.split_once(':').ok_or(Error::CarouselFormat)?;
+            let item = match name.trim() {
+                "time" => CarouselItem::Time,
+                "date" => CarouselItem::Date,
+                "temp" => CarouselItem::Temperature,
+                "msg" => CarouselItem::Message,
+                _ => return Err(Error::CarouselFormat),
+            };
+            let seconds = seconds
+                .trim()
+                .parse::<u16>()
+                .ok()
+                .filter(|seconds| (1..=3600).contains(seconds))
+                .ok_or(Error::CarouselFormat)?;
+            *slots.next().ok_or(Error::CarouselFormat)? = Some((item, seconds));
+        }
+        carousel.message = Text::try_from(message).map_err(|_| Error::CarouselFormat)?;
+        Ok(carousel)
+    }
+
+    /// Whether the sequence has no items.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.items.iter().all(Option::is_none)
+    }
+
+    /// The message `CarouselItem::Message` scrolls.
+    #[must_use]
+    pub fn message(&self) -> &[char] {
+        self.message.chars()
+    }
+
+    /// The item showing `elapsed` after the sequence first started, how long it has been
+    /// showing, and how long until the next item. `None` if the sequence is empty.
+    #[must_use]
+    #[expect(
+        clippy::arithmetic_side_effects,
+        clippy::integer_division_remainder_used,
+        reason = "The cycle is non-zero, and the position stays within it."
+    )]
+    pub fn item_at(&self, elapsed: Duration) -> Option<(CarouselItem, Duration, Duration)> {
+        let items = || self.items.iter().flatten();
+        let cycle: u64 = items().map(|&(_, seconds)| u64::from(seconds)).sum();
+        if cycle == 0 {
+            return None;
+        }
+        let mut position = elapsed.as_ticks() % Duration::from_secs(cycle).as_ticks();
+        for &(item, seconds) in items() {
+            let dwell = Duration::from_secs(u64::from(seconds)).as_ticks();
+            if position < dwell {
+                return Some((
+                    item,
+                    Duration::from_ticks(position),
+                    Duration::from_ticks(dwell - position),
+                ));
+            }
+            position -= dwell;
+        }
+        None
+    }
+}
+
+impl Default for Carousel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
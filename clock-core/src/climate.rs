@@ -0,0 +1,116 @@
+use core::fmt::Write;
+
+use crate::{Temperature, UnixSeconds};
+
+/// Bytes used by `ClimateSample::to_bytes`.
+pub const CLIMATE_SAMPLE_BYTES: usize = 8;
+
+/// The first line of `GET /climate.csv`, naming its columns.
+pub const CLIMATE_CSV_HEADER: &str = "unix_seconds,temperature,humidity_percent\n";
+
+/// Stored for a sample without a humidity reading.
+const NO_HUMIDITY: u8 = 0xFF;
+
+/// Mixed into each record's check byte, so an erased slot (all `0xFF`) never reads as a sample.
+const CHECK_SEED: u8 = 0xA5;
+
+/// One logged room-climate reading.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClimateSample {
+    /// When the reading was taken.
+    pub unix_seconds: UnixSeconds,
+    /// The temperature, in whatever unit its source reports.
+    pub temperature: Temperature,
+    /// The relative humidity, 0 to 100, if a source reports it.
+    pub humidity_percent: Option<u8>,
+}
+
+impl ClimateSample {
+    /// Packs the sample as big-endian Unix seconds (as a `u32`), the temperature as an `i16`,
+    /// the humidity (`0xFF` for none), and a check byte. Returns `None` if the time does not
+    /// fit in a `u32`.
+    #[must_use]
+    pub fn to_bytes(&self) -> Option<[u8; CLIMATE_SAMPLE_BYTES]> {
+        let [s0, s1, s2, s3] = u32::try_from(self.unix_seconds.as_i64()).ok()?.to_be_bytes();
+        let [t0, t1] = self.temperature.0.to_be_bytes();
+        let humidity = self.humidity_percent.unwrap_or(NO_HUMIDITY);
+        let data = [s0, s1, s2, s3, t0, t1, humidity];
+        Some([s0, s1, s2, s3, t0, t1, humidity, check_byte(&data)])
+    }
+
+    /// Unpacks a sample written by `to_bytes`, or `None` for an empty (or damaged) slot.
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8; CLIMATE_SAMPLE_BYTES]) -> Option<Self> {
+        let [s0, s1, s2, s3, t0, t1, humidity, check] = *bytes;
+        if check != check_byte(&[s0, s1, s2, s3, t0, t1, humidity]) {
+            return None;
+        }
+        Some(Self {
+            unix_seconds: UnixSeconds(i64::from(u32::from_be_bytes([s0, s1, s2, s3]))),
+            temperature: Temperature(i16::from_be_bytes([t0, t1])),
+            humidity_percent: (humidity <= 100).then_some(humidity),
+        })
+    }
+
+    /// Writes the sample as a line under `CLIMATE_CSV_HEADER`, leaving the humidity empty if
+    /// there is none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `out` is full.
+    pub fn write_csv_row(&self, out: &mut impl Write) -> core::fmt::Result {
+        write!(out, "{},{},", self.unix_seconds.as_i64(), self.temperature.0)?;
+        if let Some(humidity_percent) = self.humidity_percent {
+            write!(out, "{humidity_percent}")?;
+        }
+        out.write_char('\n')
+    }
+}
+
+/// Where the next sample goes in a ring log of `slots` slots: the first erased slot after the
+/// last written one.
+///
+/// The log is written in order and erased a sector ahead of the writes. `erased(slot)` says
+/// whether a slot still reads as all `0xFF`. A slot holding anything else, even a sample cut
+/// short by a power failure, counts as written.
+///
+/// Returns `Ok(None)` if the slots do not look like such a log (such as flash left over from
+/// other firmware): none erased, or more than one run of written slots. The log should then be
+/// erased as a whole. An entirely erased log starts at slot 0.
+///
+/// # Errors
+///
+/// Returns the first error `erased` returns.
+pub fn climate_log_head<E>(
+    slots: usize,
+    mut erased: impl FnMut(usize) -> core::result::Result<bool, E>,
+) -> core::result::Result<Option<usize>, E> {
+    let Some(last) = slots.checked_sub(1) else {
+        return Ok(None);
+    };
+    let mut previous_erased = erased(last)?;
+    let mut head = None;
+    let mut any_erased = false;
+    for slot in 0..slots {
+        let slot_erased = erased(slot)?;
+        if slot_erased && !previous_erased {
+            if head.is_some() {
+                return Ok(None);
+            }
+            head = Some(slot);
+        }
+        any_erased |= slot_erased;
+        previous_erased = slot_erased;
+    }
+    Ok(match head {
+        Some(slot) => Some(slot),
+        None if any_erased => Some(0),
+        None => None,
+    })
+}
+
+/// The XOR of `data` and `CHECK_SEED`.
+fn check_byte(data: &[u8]) -> u8 {
+    data.iter().fold(CHECK_SEED, |check, byte| check ^ byte)
+}
@@ -0,0 +1,125 @@
+use embassy_time::{Duration, Instant};
+
+use crate::{
+    BitMatrix, BlinkState, ClockState, ClockTime, DisplayContent, Message, PendingSettings,
+    ONE_DAY,
+};
+
+/// Everything the clock task draws a frame from: the time, the state, the content, and what
+/// shows in their place for now (a hold, a pushed message, or an edit's preview).
+///
+/// The firmware's clock task and the host `Scenario` both render through `render`, so the
+/// frames host tests check are the ones the display shows.
+#[derive(Clone)]
+pub struct ClockFace {
+    /// The clock's time.
+    pub clock_time: ClockTime,
+    /// The state the state machine last entered.
+    pub state: ClockState,
+    /// Everything besides the time that the display modes draw from.
+    pub content: DisplayContent,
+    /// A pushed message scrolling in place of the current mode, and when it started.
+    pub message: Option<(Message, Instant)>,
+    /// The frame a hold froze, blinking in place of everything else until the hold ends.
+    pub held: Option<BitMatrix>,
+    /// An edit in progress, shown in place of the live settings until committed or dropped.
+    pub preview: Option<PendingSettings>,
+}
+
+impl ClockFace {
+    /// A face showing `clock_time` in `ClockState::HoursMinutes`, with nothing in its place.
+    #[must_use]
+    pub const fn new(clock_time: ClockTime, content: DisplayContent) -> Self {
+        Self {
+            clock_time,
+            state: ClockState::HoursMinutes,
+            content,
+            message: None,
+            held: None,
+            preview: None,
+        }
+    }
+
+    /// The frame to show now: how it blinks, its segments (turned if `Settings::rotate_180`
+    /// says so), and how long until it next changes.
+    ///
+    /// A held frame comes first, then a pushed message (dropped once it has scrolled past),
+    /// then the current state, previewing any edit in progress. Midnight always ends the
+    /// wait, so the clock task can announce the new day.
+    pub fn render(&mut self) -> (BlinkState, BitMatrix, Duration) {
+        let till_midnight = ClockTime::till_next(self.clock_time.now().since_midnight(), ONE_DAY);
+        let pushed = self
+            .message
+            .and_then(|(message, started)| message.render(started.elapsed()));
+        if pushed.is_none() {
+            self.message = None;
+        }
+        // The `Blinker` blinks a held frame, which stays until the hold ends
+        let (blink_state, upright, sleep_duration) = self.held.map_or_else(
+            || pushed.unwrap_or_else(|| self.render_state()),
+            |held| (BlinkState::BlinkingAndOn, held, Duration::MAX),
+        );
+        (
+            blink_state,
+            self.turned(upright),
+            sleep_duration.min(till_midnight),
+        )
+    }
+
+    /// The current state's frame, upright, with any edit in progress in place of the live
+    /// UTC offset and settings.
+    fn render_state(&self) -> (BlinkState, BitMatrix, Duration) {
+        self.preview.map_or_else(
+            || self.state.render(&self.clock_time, &self.content),
+            |pending| {
+                self.state.render(
+                    &self
+                        .clock_time
+                        .with_utc_offset_minutes(pending.utc_offset_minutes),
+                    &DisplayContent {
+                        settings: pending.settings,
+                        ..self.content
+                    },
+                )
+            },
+        )
+    }
+
+    /// What the sensor page (see `Page`) shows, turned as `render` turns the time.
+    #[must_use]
+    pub fn render_sensor_page(&self) -> BitMatrix {
+        self.turned(ClockState::render_sensor_page(&self.content))
+    }
+
+    /// Whether the time shows undisturbed: `HoursMinutes` with nothing in its place.
+    #[must_use]
+    pub fn undisturbed(&self) -> bool {
+        self.held.is_none()
+            && self.message.is_none()
+            && self.preview.is_none()
+            && self.state == ClockState::HoursMinutes
+    }
+
+    /// Freezes the display on the current state's frame. The time keeps running underneath.
+    pub fn hold(&mut self) {
+        let (_, bit_matrix, _) = self.state.render(&self.clock_time, &self.content);
+        self.held = Some(bit_matrix);
+    }
+
+    /// Makes `pending` the live UTC offset and settings, ending any preview.
+    pub fn commit(&mut self, pending: PendingSettings) {
+        self.clock_time
+            .set_utc_offset_minutes(pending.utc_offset_minutes);
+        self.content.settings = pending.settings;
+        self.preview = None;
+    }
+
+    /// `upright` as the display shows it: turned half a turn if the settings say so.
+    fn turned(&self, upright: BitMatrix) -> BitMatrix {
+        if self.content.settings.rotate_180 {
+            upright.rotated_180()
+        } else {
+            upright
+        }
+    }
+}
@@ -0,0 +1,708 @@
+use crate::{
+    bit_matrix::BitMatrix,
+    carousel::CarouselItem,
+    display_content::DisplayContent,
+    reminders::Reminders,
+    render_util::{centered, centered_index, hour_digits, join, right_align, two_digits},
+    scroll::{scroll_frame, scroll_frame_count, SCROLL_STEP},
+    settings::Settings,
+    temperature::Temperature,
+    time_source::TimeSource,
+    time_sync::{SyncError, TimeSyncEvent},
+    battery_glyph_level, BlinkState, ClockTime, Leds, Segments, UnixSeconds, ONE_DAY,
+    ONE_MINUTE, ONE_SECOND,
+};
+
+use core::ops::Range;
+
+use embassy_time::{Duration, Instant};
+
+/// The most characters of the version that `Version` scrolls.
+const MAX_VERSION_LEN: usize = 40;
+
+/// How long `Syncing` lights each segment of its spinner.
+const SPINNER_STEP: Duration = Duration::from_millis(100);
+
+/// How long `Weather` shows the time, and then the temperature, before switching.
+const WEATHER_ALTERNATION: Duration = Duration::from_secs(10);
+
+/// Represents the different states the clock can operate in.
+///
+/// The display modes, such as `HoursMinutes` (HH:MM) and `MinutesSeconds` (MM:SS), are listed
+/// in `MODES`, and a short press steps through those that are enabled. Long press enters UTC
+/// offset edit mode.
+/// Double press in `HoursMinutes` syncs the time now, showing `Syncing` and then the outcome.
+/// Holding the button for ten seconds in any state shows `FactoryReset` before rebooting.
+#[expect(missing_docs, reason = "The variants are self-explanatory.")]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ClockState {
+    #[default]
+    HoursMinutes,
+    MinutesSeconds,
+    EditUtcOffset,
+    FactoryReset,
+    /// The time and the outdoor temperature, alternating every ten seconds.
+    Weather,
+    /// Days elapsed since `Settings::days_since`.
+    DaysSince,
+    /// Rotates through `DisplayContent::carousel`, showing each item for its dwell time.
+    Carousel,
+    /// The battery's estimated charge in percent, with a glyph of lit decimal points.
+    Battery,
+    /// Diagnostic: the correction (ms) applied at the latest sync.
+    SyncSkew,
+    /// Diagnostic: the source that last set the time, such as `ntP` or `GPS`.
+    TimeSource,
+    /// Diagnostic: the firmware version and build, scrolling.
+    Version,
+    /// Diagnostic: the signal strength (dBm) of the configured network, measured over and
+    /// over, for finding a spot with good reception.
+    Survey,
+    /// A spinner while a requested time sync is under way.
+    Syncing,
+    /// `Sync` after a requested time sync succeeded.
+    SyncSucceeded,
+    /// Why a requested time sync failed (see `SyncError::chars`), or `FAIL` if it is unknown.
+    SyncFailed,
+    /// Display blank and `WiFi` powered down until the button is pressed.
+    Off,
+    /// The time, blinking, while the button moves it forward.
+    SetTime,
+}
+
+/// The resume code of `Off`, the one state outside `MODES` that survives a soft reboot.
+const OFF_RESUME_CODE: u8 = 8;
+
+/// What the clock's configuration enables, for `ModeDescriptor::enabled`.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[expect(clippy::struct_excessive_bools, reason = "Each is an independent feature.")]
+pub struct ModeConditions {
+    /// A weather URL is configured.
+    pub weather: bool,
+    /// A date to count from is configured.
+    pub days_since: bool,
+    /// A carousel sequence is configured.
+    pub carousel: bool,
+    /// The supply is monitored.
+    pub battery: bool,
+    /// The firmware was built with its diagnostic modes.
+    pub diagnostics: bool,
+}
+
+/// A display mode: how it draws, how it answers the button, and when it is in the cycle.
+///
+/// A new mode needs a `ClockState` variant and an entry in `MODES`. The state machine and
+/// `ClockState::render` find everything else here.
+#[derive(Clone, Copy)]
+pub struct ModeDescriptor {
+    /// The state this describes.
+    pub state: ClockState,
+    /// Draws the mode, as `ClockState::render` describes.
+    pub render: fn(&ClockTime, &DisplayContent) -> (BlinkState, BitMatrix, Duration),
+    /// Where a long press goes.
+    pub long_press: ClockState,
+    /// Where a double press goes, or `None` to take each short press on its own.
+    pub double_press: Option<ClockState>,
+    /// Whether the mode goes back to `HoursMinutes` after the configured auto-return time.
+    pub auto_return: bool,
+    /// Whether the short-press cycle stops at this mode.
+    pub enabled: fn(&ModeConditions) -> bool,
+    /// A number that stands for this mode across a soft reboot. Never reuse one.
+    pub resume_code: u8,
+}
+
+/// A press of the button, as the state machine tells them apart.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Press {
+    /// A quick press and release.
+    Short,
+    /// A press held past the long-press threshold.
+    Long,
+    /// A press held on past a long press, to the factory-reset threshold.
+    VeryLong,
+    /// Two quick presses.
+    Double,
+}
+
+impl ModeDescriptor {
+    /// The state `press` leads to from this mode: a short press moves on through the cycle, a
+    /// double press goes where the entry says (or counts as a short press), a long press goes
+    /// where the entry says, and a very long press resets the clock.
+    #[must_use]
+    pub fn after_press(&self, press: Press, conditions: &ModeConditions) -> ClockState {
+        match press {
+            Press::Short => self.state.next_mode(conditions),
+            Press::Double => self
+                .double_press
+                .unwrap_or_else(|| self.state.next_mode(conditions)),
+            Press::Long => self.long_press,
+            Press::VeryLong => ClockState::FactoryReset,
+        }
+    }
+}
+
+/// The display modes, in short-press order. The cycle wraps from the last back to the first.
+pub const MODES: &[ModeDescriptor] = &[
+    ModeDescriptor {
+        state: ClockState::HoursMinutes,
+        render: |clock_time, content| {
+            render_reminder(clock_time, &content.reminders).unwrap_or_else(|| {
+                ClockState::render_hours_minutes(clock_time, &content.settings)
+            })
+        },
+        long_press: ClockState::EditUtcOffset,
+        double_press: Some(ClockState::Syncing),
+        auto_return: false,
+        enabled: |_| true,
+        resume_code: 1,
+    },
+    ModeDescriptor {
+        state: ClockState::MinutesSeconds,
+        render: |clock_time, _| ClockState::render_minutes_seconds(clock_time),
+        long_press: ClockState::SetTime,
+        double_press: None,
+        auto_return: true,
+        enabled: |_| true,
+        resume_code: 2,
+    },
+    ModeDescriptor {
+        state: ClockState::Weather,
+        render: |clock_time, content| {
+            ClockState::render_weather(clock_time, &content.settings, content.temperature)
+        },
+        long_press: ClockState::EditUtcOffset,
+        double_press: None,
+        auto_return: false,
+        enabled: |conditions| conditions.weather,
+        resume_code: 3,
+    },
+    ModeDescriptor {
+        state: ClockState::DaysSince,
+        render: |clock_time, content| {
+            ClockState::render_days_since(clock_time, &content.settings)
+        },
+        long_press: ClockState::EditUtcOffset,
+        double_press: None,
+        auto_return: false,
+        enabled: |conditions| conditions.days_since,
+        resume_code: 4,
+    },
+    ModeDescriptor {
+        state: ClockState::Carousel,
+        render: ClockState::render_carousel,
+        long_press: ClockState::EditUtcOffset,
+        double_press: None,
+        auto_return: false,
+        enabled: |conditions| conditions.carousel,
+        resume_code: 5,
+    },
+    ModeDescriptor {
+        state: ClockState::Battery,
+        render: |_, content| ClockState::render_battery(content.battery_percent),
+        long_press: ClockState::EditUtcOffset,
+        double_press: None,
+        auto_return: false,
+        enabled: |conditions| conditions.battery,
+        resume_code: 9,
+    },
+    ModeDescriptor {
+        state: ClockState::SyncSkew,
+        render: |clock_time, _| ClockState::render_sync_skew(clock_time),
+        long_press: ClockState::EditUtcOffset,
+        double_press: None,
+        auto_return: true,
+        enabled: |conditions| conditions.diagnostics,
+        resume_code: 6,
+    },
+    ModeDescriptor {
+        state: ClockState::TimeSource,
+        render: |_, content| ClockState::render_time_source(content.time_source),
+        long_press: ClockState::EditUtcOffset,
+        double_press: None,
+        auto_return: true,
+        enabled: |conditions| conditions.diagnostics,
+        resume_code: 10,
+    },
+    ModeDescriptor {
+        state: ClockState::Version,
+        render: |_, content| ClockState::render_version(content.version),
+        long_press: ClockState::EditUtcOffset,
+        double_press: None,
+        auto_return: true,
+        enabled: |conditions| conditions.diagnostics,
+        resume_code: 7,
+    },
+    ModeDescriptor {
+        state: ClockState::Survey,
+        render: |_, content| ClockState::render_survey(content.signal_dbm),
+        long_press: ClockState::EditUtcOffset,
+        double_press: None,
+        // Carried around the house, it stays until the button moves it on
+        auto_return: false,
+        enabled: |conditions| conditions.diagnostics,
+        resume_code: 11,
+    },
+];
+
+impl ClockState {
+    /// This state's entry in `MODES`, or `None` if it is not a display mode.
+    #[must_use]
+    pub fn mode(self) -> Option<&'static ModeDescriptor> {
+        MODES.iter().find(|mode| mode.state == self)
+    }
+
+    /// The display mode a short press moves to from this one: the next enabled entry in
+    /// `MODES`, wrapping around. `HoursMinutes` for a state that is not a display mode.
+    #[must_use]
+    pub fn next_mode(self, conditions: &ModeConditions) -> Self {
+        let start = MODES
+            .iter()
+            .position(|mode| mode.state == self)
+            .map_or(0, |index| index.saturating_add(1));
+        MODES
+            .iter()
+            .skip(start)
+            .chain(MODES.iter().take(start))
+            .find(|mode| (mode.enabled)(conditions))
+            .map_or(Self::HoursMinutes, |mode| mode.state)
+    }
+
+    /// Where `Syncing` goes once the sync it requested ends with `outcome`, or with `None` if
+    /// none arrived in time.
+    #[must_use]
+    pub const fn after_sync(outcome: Option<&TimeSyncEvent>) -> Self {
+        match outcome {
+            Some(TimeSyncEvent::Success { .. }) => Self::SyncSucceeded,
+            Some(TimeSyncEvent::Failed(_) | TimeSyncEvent::Rejected(_)) | None => {
+                Self::SyncFailed
+            }
+        }
+    }
+
+    /// Where this state goes when its timer runs out: a sync result, an edit (dropping its
+    /// changes), setting the time, and a display mode that returns on its own all go back to
+    /// `HoursMinutes`. Any other state has no timer and stays.
+    #[must_use]
+    pub fn after_timeout(self) -> Self {
+        match self {
+            Self::SyncSucceeded | Self::SyncFailed | Self::EditUtcOffset | Self::SetTime => {
+                Self::HoursMinutes
+            }
+            _ if self.mode().is_some_and(|mode| mode.auto_return) => Self::HoursMinutes,
+            _ => self,
+        }
+    }
+
+    /// A number that stands for this state across a soft reboot, or `None` for a state that
+    /// should not outlive one: an edit (whose staged changes are lost), a sync in progress or
+    /// its result, or a factory reset.
+    #[must_use]
+    pub fn resume_code(self) -> Option<u8> {
+        if self == Self::Off {
+            return Some(OFF_RESUME_CODE);
+        }
+        self.mode().map(|mode| mode.resume_code)
+    }
+
+    /// The state `resume_code` gave `code`, if any.
+    #[must_use]
+    pub fn from_resume_code(code: u8) -> Option<Self> {
+        if code == OFF_RESUME_CODE {
+            return Some(Self::Off);
+        }
+        MODES
+            .iter()
+            .find(|mode| mode.resume_code == code)
+            .map(|mode| mode.state)
+    }
+
+    /// Given the current `ClockMode`, `ClockTime`, and `DisplayContent`, generates the
+    /// information the `Clock` abstraction should display.
+    ///
+    /// The returned duration runs from now to the next time the display changes (a minute
+    /// or second boundary, a scroll step, or an alternation), not a fixed refresh period.
+    ///
+    /// On a day with a reminder, `HoursMinutes` scrolls its label at the top of each minute.
+    ///
+    /// # Example
+    ///
+    /// If the `ClockState` is `HoursMinutes` and the `ClockTime` is 1:23:45, the function will return:
+    /// - Bit Matrix: the segments for `[' ', '1', '2', '3']`
+    /// - Blink Mode: `BlinkState::Solid`
+    /// - Sleep Duration: `Duration::from_secs(15)`
+    #[must_use]
+    pub fn render(
+        self,
+        clock_time: &ClockTime,
+        content: &DisplayContent,
+    ) -> (BlinkState, BitMatrix, Duration) {
+        match self {
+            // The UTC offset editor steps the hours and set time the minutes, so the part
+            // being edited blinks and the other part shows dimmed
+            Self::EditUtcOffset => Self::render_edit_time(clock_time, &content.settings, 2..4),
+            Self::SetTime => Self::render_edit_time(clock_time, &content.settings, 0..2),
+            Self::FactoryReset => Self::render_factory_reset(),
+            Self::Syncing => Self::render_syncing(),
+            Self::SyncSucceeded => Self::render_sync_result(BlinkState::Solid, ['S', 'y', 'n', 'c']),
+            Self::SyncFailed => Self::render_sync_result(
+                BlinkState::Fast,
+                content.sync_error.map_or(['F', 'A', 'I', 'L'], SyncError::chars),
+            ),
+            // The display modes draw as their `MODES` entries say
+            _ => self.mode().map_or(
+                (BlinkState::Solid, BitMatrix::default(), ONE_DAY),
+                |mode| (mode.render)(clock_time, content),
+            ),
+        }
+    }
+
+    /// What the sensor page (see `Page`) shows: the latest temperature, or dashes until one is
+    /// known.
+    #[must_use]
+    pub fn render_sensor_page(content: &DisplayContent) -> BitMatrix {
+        content.temperature.map_or_else(
+            || BitMatrix::from_text(&centered(['-'; 4])),
+            temperature_bit_matrix,
+        )
+    }
+
+    #[cfg(not(feature = "eight-digits"))]
+    fn render_hours_minutes(
+        clock_time: &ClockTime,
+        settings: &Settings,
+    ) -> (BlinkState, BitMatrix, Duration) {
+        let (hours, minutes, _, sleep_duration) = clock_time.h_m_s_sleep_duration(ONE_MINUTE);
+        let mut bit_matrix = BitMatrix::from_text(&centered(join(
+            hour_digits(hours, settings.leading_zero_hours),
+            two_digits(minutes),
+        )));
+        // The clock shows 12-hour time, so optionally mark the afternoon and evening
+        if settings.pm_indicator && clock_time.is_pm() {
+            bit_matrix.set_dot(crate::CELL_COUNT - 1);
+        }
+        (BlinkState::Solid, bit_matrix, sleep_duration)
+    }
+
+    /// With eight cells there is room for seconds, so show ` HH.MM.SS `.
+    #[cfg(feature = "eight-digits")]
+    fn render_hours_minutes(
+        clock_time: &ClockTime,
+        settings: &Settings,
+    ) -> (BlinkState, BitMatrix, Duration) {
+        let (hours, minutes, seconds, sleep_duration) =
+            clock_time.h_m_s_sleep_duration(ONE_SECOND);
+        let [hours_tens, hours_ones] = hour_digits(hours, settings.leading_zero_hours);
+        let [minutes_tens, minutes_ones] = two_digits(minutes);
+        let [seconds_tens, seconds_ones] = two_digits(seconds);
+        let mut bit_matrix = BitMatrix::from_text(&[
+            ' ',
+            hours_tens,
+            hours_ones,
+            minutes_tens,
+            minutes_ones,
+            seconds_tens,
+            seconds_ones,
+            ' ',
+        ]);
+        bit_matrix.set_dot(2);
+        bit_matrix.set_dot(4);
+        // The clock shows 12-hour time, so optionally mark the afternoon and evening
+        if settings.pm_indicator && clock_time.is_pm() {
+            bit_matrix.set_dot(6);
+        }
+        (BlinkState::Solid, bit_matrix, sleep_duration)
+    }
+
+    fn render_minutes_seconds(clock_time: &ClockTime) -> (BlinkState, BitMatrix, Duration) {
+        let (_, minutes, seconds, sleep_duration) = clock_time.h_m_s_sleep_duration(ONE_SECOND);
+        (
+            BlinkState::Solid,
+            BitMatrix::from_text(&centered(join(two_digits(minutes), two_digits(seconds)))),
+            sleep_duration,
+        )
+    }
+
+    /// Shows the time as `HHMM`, blinking, with the cells in `dimmed` (of the four) held steady
+    /// at reduced brightness.
+    fn render_edit_time(
+        clock_time: &ClockTime,
+        settings: &Settings,
+        dimmed: Range<usize>,
+    ) -> (BlinkState, BitMatrix, Duration) {
+        // Display the current time in HH:MM format while blinking
+        // This shows what the time looks like with the edit so far. The `Blinker`
+        // does the blinking, so only the minute flip needs a redraw.
+        let (hours, minutes, _, sleep_duration) = clock_time.h_m_s_sleep_duration(ONE_MINUTE);
+        let mut bit_matrix = BitMatrix::from_text(&centered(join(
+            hour_digits(hours, settings.leading_zero_hours),
+            two_digits(minutes),
+        )));
+        for index in dimmed {
+            bit_matrix.dim(centered_index(index));
+        }
+        (BlinkState::BlinkingAndOn, bit_matrix, sleep_duration)
+    }
+
+    /// Shows the time, then the temperature, for `WEATHER_ALTERNATION` each. Shows only the
+    /// time until a temperature is known, and `OFL ` if it does not fit.
+    #[expect(
+        clippy::arithmetic_side_effects,
+        clippy::integer_division_remainder_used,
+        reason = "The alternation length is non-zero."
+    )]
+    fn render_weather(
+        clock_time: &ClockTime,
+        settings: &Settings,
+        temperature: Option<Temperature>,
+    ) -> (BlinkState, BitMatrix, Duration) {
+        let now = clock_time.now().since_midnight();
+        let till_switch = ClockTime::till_next(now, WEATHER_ALTERNATION);
+        let showing_temperature = (now.as_ticks() / WEATHER_ALTERNATION.as_ticks()) % 2 == 1;
+        match temperature {
+            Some(temperature) if showing_temperature => {
+                (BlinkState::Solid, temperature_bit_matrix(temperature), till_switch)
+            }
+            _ => {
+                let (blink_state, bit_matrix, sleep_duration) =
+                    Self::render_hours_minutes(clock_time, settings);
+                (blink_state, bit_matrix, sleep_duration.min(till_switch))
+            }
+        }
+    }
+
+    /// Shows the current carousel item, switching to the next when its dwell time is up.
+    /// The sequence runs from power-on, so it keeps its place across mode changes.
+    fn render_carousel(
+        clock_time: &ClockTime,
+        content: &DisplayContent,
+    ) -> (BlinkState, BitMatrix, Duration) {
+        let since_power_on = Duration::from_ticks(Instant::now().as_ticks());
+        let Some((item, shown_for, till_next_item)) = content.carousel.item_at(since_power_on)
+        else {
+            return Self::render_hours_minutes(clock_time, &content.settings);
+        };
+        let (blink_state, bit_matrix, sleep_duration) = match item {
+            CarouselItem::Time => Self::render_hours_minutes(clock_time, &content.settings),
+            CarouselItem::Date => Self::render_date(clock_time),
+            CarouselItem::Temperature => (
+                BlinkState::Solid,
+                Self::render_sensor_page(content),
+                till_next_item,
+            ),
+            CarouselItem::Message => render_scroll(content.carousel.message(), shown_for),
+        };
+        (blink_state, bit_matrix, sleep_duration.min(till_next_item))
+    }
+
+    /// Shows the local date as month and day, such as `10.16`, or dashes before the first sync.
+    fn render_date(clock_time: &ClockTime) -> (BlinkState, BitMatrix, Duration) {
+        // The date changes at midnight, and a first sync redraws it as it arrives
+        let sleep_duration = ClockTime::till_next(clock_time.now().since_midnight(), ONE_DAY);
+        let chars = clock_time.local_date().map_or(['-'; 4], |date| {
+            join(hour_digits(u8::from(date.month()), false), two_digits(date.day()))
+        });
+        let mut bit_matrix = BitMatrix::from_text(&centered(chars));
+        bit_matrix.set_dot(centered_index(1));
+        (BlinkState::Solid, bit_matrix, sleep_duration)
+    }
+
+    /// Shows the days since `settings.days_since`, up to 9999, right-aligned. Shows `----`
+    /// before the first sync, with no date set, or while the date is still in the future.
+    fn render_days_since(
+        clock_time: &ClockTime,
+        settings: &Settings,
+    ) -> (BlinkState, BitMatrix, Duration) {
+        // The count changes at midnight, and a first sync or a new date redraws it as it
+        // arrives
+        let sleep_duration = ClockTime::till_next(clock_time.now().since_midnight(), ONE_DAY);
+        let chars = settings
+            .days_since
+            .zip(clock_time.local_date())
+            .map(|(anchor, today)| UnixSeconds::from_date(today).days_since(anchor))
+            .filter(|days| *days >= 0)
+            .and_then(|days| i16::try_from(days.min(9999)).ok())
+            .and_then(right_align)
+            .unwrap_or(['-'; 4]);
+        (
+            BlinkState::Solid,
+            BitMatrix::from_text(&centered(chars)),
+            sleep_duration,
+        )
+    }
+
+    /// Shows `b` and the battery's charge in percent, right-aligned, such as `b 87`, or `b---`
+    /// before the first measurement. One decimal point lights for each quarter of charge.
+    ///
+    /// A new measurement redraws it as it arrives, so it needs no redraw of its own.
+    fn render_battery(battery_percent: Option<u8>) -> (BlinkState, BitMatrix, Duration) {
+        let Some(percent) = battery_percent else {
+            return (
+                BlinkState::Solid,
+                BitMatrix::from_text(&centered(['b', '-', '-', '-'])),
+                ONE_DAY,
+            );
+        };
+        let [_, hundreds, tens, ones] = right_align(i16::from(percent)).unwrap_or(['-'; 4]);
+        let mut bit_matrix = BitMatrix::from_text(&centered(['b', hundreds, tens, ones]));
+        for index in 0..usize::from(battery_glyph_level(percent)) {
+            bit_matrix.set_dot(centered_index(index));
+        }
+        (BlinkState::Solid, bit_matrix, ONE_DAY)
+    }
+
+    /// Shows the source that last set the time, or `----` before any has. A new source redraws
+    /// it as it sets the time.
+    fn render_time_source(time_source: Option<TimeSource>) -> (BlinkState, BitMatrix, Duration) {
+        let chars = time_source.map_or(['-'; 4], TimeSource::chars);
+        (
+            BlinkState::Solid,
+            BitMatrix::from_text(&centered(chars)),
+            ONE_DAY,
+        )
+    }
+
+    /// Shows the latest sync correction in milliseconds, `----` before there is one, or
+    /// `OFL ` if it does not fit. The next sync redraws it as it sets the time.
+    fn render_sync_skew(clock_time: &ClockTime) -> (BlinkState, BitMatrix, Duration) {
+        let chars = clock_time
+            .skew_history()
+            .latest()
+            .map_or(['-'; 4], |correction_millis| {
+                i16::try_from(correction_millis)
+                    .ok()
+                    .and_then(right_align)
+                    .unwrap_or(['O', 'F', 'L', ' '])
+            });
+        (
+            BlinkState::Solid,
+            BitMatrix::from_text(&centered(chars)),
+            ONE_DAY,
+        )
+    }
+
+    /// Shows the configured network's signal strength in dBm, right-aligned, such as ` -52`,
+    /// or `----` until it has been measured or while the network is out of range. Each
+    /// measurement redraws it as it arrives.
+    fn render_survey(signal_dbm: Option<i16>) -> (BlinkState, BitMatrix, Duration) {
+        let chars = signal_dbm.and_then(right_align).unwrap_or(['-'; 4]);
+        (
+            BlinkState::Solid,
+            BitMatrix::from_text(&centered(chars)),
+            ONE_DAY,
+        )
+    }
+
+    /// Scrolls `version` across the display, over and over.
+    fn render_version(version: &str) -> (BlinkState, BitMatrix, Duration) {
+        let mut chars = [' '; MAX_VERSION_LEN];
+        let mut len = 0_usize;
+        for (slot, char) in chars.iter_mut().zip(version.chars()) {
+            *slot = char;
+            len = len.saturating_add(1);
+        }
+        let since_power_on = Duration::from_ticks(Instant::now().as_ticks());
+        render_scroll(chars.get(..len).unwrap_or_default(), since_power_on)
+    }
+
+    /// Chases a lit segment around the outside of every cell.
+    #[expect(
+        clippy::arithmetic_side_effects,
+        clippy::integer_division_remainder_used,
+        clippy::cast_possible_truncation,
+        reason = "The step length and ring length are non-zero, so the index is in range."
+    )]
+    fn render_syncing() -> (BlinkState, BitMatrix, Duration) {
+        const RING: [Segments; 6] = [
+            Leds::SEG_A,
+            Leds::SEG_B,
+            Leds::SEG_C,
+            Leds::SEG_D,
+            Leds::SEG_E,
+            Leds::SEG_F,
+        ];
+        let since_power_on = Duration::from_ticks(Instant::now().as_ticks());
+        let step = (since_power_on.as_ticks() / SPINNER_STEP.as_ticks()) as usize % RING.len();
+        (
+            BlinkState::Solid,
+            BitMatrix::from_bits(RING.get(step).copied().unwrap_or(Leds::SEG_A)),
+            ClockTime::till_next(since_power_on, SPINNER_STEP),
+        )
+    }
+
+    /// Shows the outcome of a requested sync. The state machine moves on before it needs
+    /// redrawing.
+    fn render_sync_result(
+        blink_state: BlinkState,
+        chars: [char; 4],
+    ) -> (BlinkState, BitMatrix, Duration) {
+        (
+            blink_state,
+            BitMatrix::from_text(&centered(chars)),
+            ONE_DAY,
+        )
+    }
+
+    /// Shows `rSt` while saved settings are erased. The clock reboots before it needs redrawing.
+    fn render_factory_reset() -> (BlinkState, BitMatrix, Duration) {
+        (
+            BlinkState::Solid,
+            BitMatrix::from_text(&centered(['r', 'S', 't', ' '])),
+            ONE_DAY,
+        )
+    }
+}
+
+/// If today has a reminder and the minute has just started, the current step of its label
+/// scrolling across the display.
+#[expect(
+    clippy::arithmetic_side_effects,
+    clippy::integer_division_remainder_used,
+    clippy::cast_possible_truncation,
+    reason = "The step length is non-zero, and steps within a minute fit in a usize."
+)]
+fn render_reminder(
+    clock_time: &ClockTime,
+    reminders: &Reminders,
+) -> Option<(BlinkState, BitMatrix, Duration)> {
+    let reminder = reminders.for_date(clock_time.local_date()?)?;
+    let into_minute = clock_time.now().since_midnight().as_ticks() % ONE_MINUTE.as_ticks();
+    let step = (into_minute / SCROLL_STEP.as_ticks()) as usize;
+    if step >= scroll_frame_count(reminder.label.len()) {
+        return None;
+    }
+    let frame = scroll_frame(&reminder.label, step);
+    let sleep_duration = ClockTime::till_next(Duration::from_ticks(into_minute), SCROLL_STEP);
+    Some((
+        BlinkState::Solid,
+        BitMatrix::from_text(&frame),
+        sleep_duration,
+    ))
+}
+
+/// The step of `text` scrolling across the display, `shown_for` after it started, repeating
+/// once it has scrolled off.
+#[expect(
+    clippy::arithmetic_side_effects,
+    clippy::integer_division_remainder_used,
+    clippy::cast_possible_truncation,
+    reason = "The step length and frame count are non-zero, and steps fit in a usize."
+)]
+fn render_scroll(text: &[char], shown_for: Duration) -> (BlinkState, BitMatrix, Duration) {
+    let step = (shown_for.as_ticks() / SCROLL_STEP.as_ticks()) as usize;
+    let frame = scroll_frame(text, step % scroll_frame_count(text.len()));
+    (
+        BlinkState::Solid,
+        BitMatrix::from_text(&frame),
+        ClockTime::till_next(shown_for, SCROLL_STEP),
+    )
+}
+
+/// A temperature with its degree sign, or `OFL ` if it does not fit.
+fn temperature_bit_matrix(temperature: Temperature) -> BitMatrix {
+    BitMatrix::from_text(&centered(
+        temperature.chars().unwrap_or(['O', 'F', 'L', ' ']),
+    ))
+}
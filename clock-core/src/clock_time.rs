@@ -0,0 +1,636 @@
+use core::ops::AddAssign;
+
+use embassy_time::{Duration, Instant, TICK_HZ};
+use time::{Date, Month, OffsetDateTime, UtcOffset, Weekday};
+
+use crate::{
+    pending_settings::{round_to_hours, wrap_utc_offset_hours},
+    LeapIndicator, SkewHistory, UnixSeconds, ONE_DAY, ONE_HOUR, ONE_MINUTE, TICKS_IN_ONE_DAY,
+};
+
+/// How long a smeared leap second takes to spread into the displayed time.
+const LEAP_SMEAR_DURATION: Duration = ONE_HOUR;
+
+/// How many ticks pass for each tick a slew works off: the displayed time runs 1% fast or
+/// slow until it has caught up, so a 2 s correction takes a little over 3 minutes.
+const SLEW_RATE_DIVISOR: i64 = 100;
+
+/// The system time along with an offset to represent time
+/// to display on the clock.
+#[derive(Clone)]
+pub struct ClockTime {
+    /// Added to `Instant::now()`'s ticks, the local time in ticks since 1970-01-01 00:00
+    /// local. Before the first sync, the date part is only a placeholder.
+    local_epoch_ticks: i64,
+    /// UTC offset in minutes
+    utc_offset_minutes: i32,
+    /// A leap second announced by the time source, if one is due or in progress
+    leap_second: Option<LeapSecond>,
+    /// The last time set from a time source, and when it was set
+    synced_at: Option<(UnixSeconds, Instant)>,
+    /// The corrections applied by syncs after the first
+    skew_history: SkewHistory,
+    /// A sync correction still being worked into the displayed time
+    slew: Option<Slew>,
+    /// The largest sync correction slewed rather than stepped (zero to always step)
+    max_slew: Duration,
+    /// Where a backward step left the displayed time, in local ticks: it stays there until
+    /// the corrected time passes it
+    hold: Option<i64>,
+    /// The longest backward step held rather than shown (zero to always show it)
+    max_hold: Duration,
+}
+
+/// A sync correction worked into the displayed time gradually, so the seconds never jump.
+#[derive(Debug, Clone, Copy)]
+struct Slew {
+    /// When the correction was made.
+    from: Instant,
+    /// How far the displayed time was from the corrected time at `from`, in ticks.
+    ticks: i64,
+}
+
+impl Slew {
+    /// How far, in ticks, the displayed time is still from the corrected time at `instant`:
+    /// `ticks`, shrinking by one for every `SLEW_RATE_DIVISOR` ticks that pass, to zero.
+    #[expect(
+        clippy::arithmetic_side_effects,
+        clippy::integer_division_remainder_used,
+        reason = "The divisor is non-zero, and what is worked off is capped at `ticks`."
+    )]
+    fn remaining_ticks(self, instant: Instant) -> i64 {
+        let elapsed = instant.saturating_duration_since(self.from).as_ticks();
+        let worked_off = i64::try_from(elapsed).unwrap_or(i64::MAX) / SLEW_RATE_DIVISOR;
+        self.ticks - self.ticks.signum() * worked_off.min(self.ticks.abs())
+    }
+}
+
+/// A leap second scheduled for a UTC midnight.
+#[derive(Debug, Clone, Copy)]
+struct LeapSecond {
+    /// When the leap second happens.
+    at: Instant,
+    /// `-1000` to repeat a second (insert) or `1000` to skip one (delete).
+    step_millis: i64,
+    /// Spread the step over `LEAP_SMEAR_DURATION` instead of taking it all at once.
+    smear: bool,
+    /// The time was set after `at`, so `local_epoch_ticks` already includes the whole step.
+    in_offset: bool,
+}
+
+impl LeapSecond {
+    /// When the displayed time has fully taken the step.
+    fn done_at(self) -> Instant {
+        let span = if self.smear {
+            LEAP_SMEAR_DURATION
+        } else {
+            Duration::from_ticks(0)
+        };
+        self.at.checked_add(span).unwrap_or(Instant::MAX)
+    }
+
+    /// How far, in milliseconds, the displayed time should be moved at `instant`.
+    #[expect(
+        clippy::arithmetic_side_effects,
+        clippy::integer_division_remainder_used,
+        clippy::cast_possible_wrap,
+        reason = "elapsed is at most one hour of milliseconds and step_millis is ±1000."
+    )]
+    fn correction_millis(self, instant: Instant) -> i64 {
+        let applied = if instant < self.at {
+            0
+        } else if instant >= self.done_at() {
+            self.step_millis
+        } else {
+            let smear_millis = LEAP_SMEAR_DURATION.as_millis() as i64;
+            let elapsed = instant.saturating_duration_since(self.at).as_millis() as i64;
+            self.step_millis * elapsed / smear_millis
+        };
+        if self.in_offset {
+            applied - self.step_millis
+        } else {
+            applied
+        }
+    }
+
+    /// What remains of this leap second after the time is set from the source at `instant`.
+    ///
+    /// Once the leap second has passed, the source's time includes it. If a smear is still
+    /// running, it carries on from the new offset; otherwise there is nothing left to do.
+    fn after_sync(self, instant: Instant) -> Option<Self> {
+        if instant >= self.done_at() {
+            None
+        } else if instant >= self.at {
+            Some(Self {
+                in_offset: true,
+                ..self
+            })
+        } else {
+            Some(self)
+        }
+    }
+}
+
+impl Default for ClockTime {
+    /// By default, `ClockTime` starts at 12:00:00 UTC.
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl ClockTime {
+    /// Creates a `ClockTime` that starts at 12:00:00 in the time zone `utc_offset_minutes`
+    /// from UTC.
+    #[must_use]
+    #[expect(
+        clippy::cast_possible_wrap,
+        reason = "Twelve hours of ticks fit easily in an i64."
+    )]
+    pub const fn new(utc_offset_minutes: i32) -> Self {
+        // Start at 12:00:00 (12 hours * 3600 seconds/hour * 1000 milliseconds/second)
+        Self {
+            local_epoch_ticks: Duration::from_millis(12 * 3600 * 1000).as_ticks() as i64,
+            utc_offset_minutes,
+            leap_second: None,
+            synced_at: None,
+            skew_history: SkewHistory::new(),
+            slew: None,
+            max_slew: Duration::from_ticks(0),
+            hold: None,
+            max_hold: Duration::from_ticks(0),
+        }
+    }
+
+    /// Sync corrections up to `max_slew` are worked in gradually, by running the displayed
+    /// time 1% fast or slow, instead of as a jump, so the seconds never go backwards. Zero (the
+    /// default) steps every correction. The first sync always steps.
+    pub const fn set_max_slew(&mut self, max_slew: Duration) {
+        self.max_slew = max_slew;
+    }
+
+    /// A sync correction too large to slew that moves the time back by at most `max_hold`
+    /// holds the displayed time where it was until the corrected time catches up, so the
+    /// display never counts backwards (as matters when `MinutesSeconds` is used as a timer).
+    /// Zero (the default) shows every backward step at once.
+    pub const fn set_max_hold(&mut self, max_hold: Duration) {
+        self.max_hold = max_hold;
+    }
+
+    /// Sets the time (and the date) from a Unix timestamp with UTC offset applied.
+    ///
+    /// Uses the current UTC offset stored in the struct. A correction of at most the
+    /// `set_max_slew` limit is slewed into the displayed time rather than stepped, and a
+    /// backward step of at most the `set_max_hold` limit is held.
+    #[expect(
+        clippy::arithmetic_side_effects,
+        clippy::cast_possible_wrap,
+        reason = "Local ticks since 1970 fit easily in an i64."
+    )]
+    pub fn set_from_unix(&mut self, unix_seconds: UnixSeconds) {
+        let before = self.now();
+        self.slew = None;
+        self.hold = None;
+
+        // Convert to local time
+        let local_seconds = unix_seconds.as_i64() + i64::from(self.utc_offset_minutes) * 60;
+
+        // Calculate the epoch needed to make now() return the target time
+        self.local_epoch_ticks = local_seconds
+            .saturating_mul(TICK_HZ as i64)
+            .saturating_sub(Instant::now().as_ticks() as i64);
+        self.leap_second = self
+            .leap_second
+            .and_then(|leap_second| leap_second.after_sync(Instant::now()));
+
+        // The first sync only replaces the power-on guess, so it says nothing about drift
+        if self.synced_at.is_some() {
+            let after = self.now();
+            let correction_millis =
+                signed_millis_between(before.since_midnight(), after.since_midnight());
+            self.skew_history.record(correction_millis);
+            #[cfg(feature = "defmt")]
+            defmt::info!(
+                "Sync correction: {} ms ({} recorded)",
+                correction_millis,
+                self.skew_history.len()
+            );
+            let back_ticks = before.ticks - after.ticks;
+            if u64::from(correction_millis.unsigned_abs()) <= self.max_slew.as_millis() {
+                self.slew = Some(Slew {
+                    from: Instant::now(),
+                    ticks: back_ticks,
+                });
+            } else if back_ticks > 0 && back_ticks <= self.max_hold.as_ticks() as i64 {
+                self.hold = Some(before.ticks);
+            }
+        }
+        self.synced_at = Some((unix_seconds, Instant::now()));
+        #[cfg(feature = "defmt")]
+        defmt::info!(
+            "Set time from Unix: {} -> local epoch: {} ticks",
+            unix_seconds.as_i64(),
+            self.local_epoch_ticks
+        );
+    }
+
+    /// Sets the local time of day to `hours`:`minutes`:`seconds` (24-hour), keeping the date
+    /// and the UTC offset, as a time set by hand does. The time moves whichever way is needed,
+    /// so it never crosses midnight. Values past the end of the day wrap.
+    #[expect(
+        clippy::arithmetic_side_effects,
+        clippy::cast_possible_wrap,
+        reason = "Both times are under one day, so the step fits easily."
+    )]
+    pub fn set_time_of_day(&mut self, hours: u8, minutes: u8, seconds: u8) {
+        // A time set by hand is a step, so any slew under way is done at once, and a hold
+        // let go
+        if let Some(slew) = self.slew.take() {
+            self.local_epoch_ticks += slew.remaining_ticks(Instant::now());
+        }
+        self.hold = None;
+        let target = (u64::from(hours) * 3600 + u64::from(minutes) * 60 + u64::from(seconds))
+            % ONE_DAY.as_secs();
+        let target_ticks = Duration::from_secs(target).as_ticks() as i64;
+        let now_ticks = self.now().since_midnight().as_ticks() as i64;
+        self.local_epoch_ticks = self
+            .local_epoch_ticks
+            .saturating_add(target_ticks - now_ticks);
+        #[cfg(feature = "defmt")]
+        defmt::info!(
+            "Set time of day to {:02}:{:02}:{:02} -> local epoch: {} ticks",
+            hours,
+            minutes,
+            seconds,
+            self.local_epoch_ticks
+        );
+    }
+
+    /// The current Unix time, once the time has been set from a time source.
+    #[must_use]
+    pub fn unix_seconds(&self) -> Option<UnixSeconds> {
+        self.to_wall_clock(Instant::now())
+    }
+
+    /// The Unix time at `instant` (truncated to the second), once the time has been set from
+    /// a time source, for reporting when something happened rather than how long after boot.
+    /// Instants before the sync count back from it.
+    #[must_use]
+    #[expect(
+        clippy::arithmetic_side_effects,
+        clippy::integer_division_remainder_used,
+        clippy::cast_possible_wrap,
+        reason = "Ticks since boot fit easily in an i64, and TICK_HZ is non-zero."
+    )]
+    pub fn to_wall_clock(&self, instant: Instant) -> Option<UnixSeconds> {
+        let (unix_seconds, synced_at) = self.synced_at?;
+        let since_sync = instant.as_ticks() as i64 - synced_at.as_ticks() as i64;
+        Some(UnixSeconds(
+            unix_seconds
+                .as_i64()
+                .saturating_add(since_sync.div_euclid(TICK_HZ as i64)),
+        ))
+    }
+
+    /// The instant at which the Unix second `unix_seconds` starts, the inverse of
+    /// `to_wall_clock`, for waiting until a wall-clock time. `None` before the time has been
+    /// set from a time source, or for a time before boot.
+    #[must_use]
+    #[expect(
+        clippy::cast_possible_wrap,
+        reason = "Ticks since boot fit easily in an i64."
+    )]
+    pub fn to_instant(&self, unix_seconds: UnixSeconds) -> Option<Instant> {
+        let (synced_unix_seconds, synced_at) = self.synced_at?;
+        let ticks = unix_seconds
+            .as_i64()
+            .checked_sub(synced_unix_seconds.as_i64())?
+            .checked_mul(TICK_HZ as i64)?
+            .checked_add(synced_at.as_ticks() as i64)?;
+        u64::try_from(ticks).ok().map(Instant::from_ticks)
+    }
+
+    /// Today's local date, once the time has been set from a time source.
+    #[must_use]
+    pub fn local_date(&self) -> Option<Date> {
+        self.now().date()
+    }
+
+    /// When today began: the local date and the Unix time of its local midnight, once the
+    /// time has been set from a time source.
+    #[must_use]
+    pub fn today(&self) -> Option<MidnightTick> {
+        let date = self.local_date()?;
+        let offset_seconds = i64::from(self.utc_offset_minutes).saturating_mul(60);
+        Some(MidnightTick {
+            date,
+            unix_seconds: UnixSeconds(
+                UnixSeconds::from_date(date)
+                    .as_i64()
+                    .saturating_sub(offset_seconds),
+            ),
+        })
+    }
+
+    /// The corrections applied by syncs after the first, for measuring crystal drift.
+    #[must_use]
+    pub const fn skew_history(&self) -> &SkewHistory {
+        &self.skew_history
+    }
+
+    /// Returns the current local time: the time of day, and the date once it is known.
+    #[expect(
+        clippy::cast_possible_wrap,
+        reason = "Ticks since boot fit easily in an i64."
+    )]
+    #[inline]
+    #[must_use]
+    pub fn now(&self) -> LocalTime {
+        let instant = Instant::now();
+        let ticks = (instant.as_ticks() as i64)
+            .saturating_add(self.local_epoch_ticks)
+            .saturating_add(self.leap_correction_ticks(instant))
+            .saturating_add(self.slew.map_or(0, |slew| slew.remaining_ticks(instant)));
+        LocalTime {
+            ticks: self.hold.map_or(ticks, |hold| ticks.max(hold)),
+            date_known: self.synced_at.is_some(),
+        }
+    }
+
+    /// The leap-second correction at `instant`, in ticks.
+    #[expect(
+        clippy::arithmetic_side_effects,
+        clippy::integer_division_remainder_used,
+        clippy::cast_possible_wrap,
+        reason = "The correction is at most a second's worth of ticks."
+    )]
+    fn leap_correction_ticks(&self, instant: Instant) -> i64 {
+        self.leap_second.map_or(0, |leap_second| {
+            leap_second.correction_millis(instant) * TICK_HZ as i64 / 1000
+        })
+    }
+
+    /// Schedules a leap second announced by the time source for the coming UTC midnight.
+    ///
+    /// `unix_seconds` is the time the announcement came with. Leap seconds only happen at
+    /// the end of June or December, so announcements for any other midnight are ignored.
+    /// With `smear`, the second is spread over the following hour rather than taken as a jump.
+    #[expect(
+        clippy::arithmetic_side_effects,
+        clippy::cast_sign_loss,
+        reason = "rem_euclid keeps the seconds to midnight within 1..=86400."
+    )]
+    pub fn schedule_leap_second(
+        &mut self,
+        unix_seconds: UnixSeconds,
+        leap: LeapIndicator,
+        smear: bool,
+    ) {
+        let step_millis = match leap {
+            LeapIndicator::InsertSecond => -1000,
+            LeapIndicator::DeleteSecond => 1000,
+            LeapIndicator::NoWarning | LeapIndicator::Unsynchronized => return,
+        };
+        if self.leap_second.is_some() {
+            return;
+        }
+        let seconds_to_midnight = 86400 - unix_seconds.as_i64().rem_euclid(86400);
+        let midnight = UnixSeconds(unix_seconds.as_i64() + seconds_to_midnight);
+        let is_leap_midnight = midnight
+            .to_offset_datetime(UtcOffset::UTC)
+            .is_some_and(|date_time| {
+                date_time.day() == 1 && matches!(date_time.month(), Month::January | Month::July)
+            });
+        if !is_leap_midnight {
+            #[cfg(feature = "defmt")]
+            defmt::warn!("Ignoring leap second announced for {}", midnight.as_i64());
+            return;
+        }
+        let at = Instant::now()
+            .checked_add(Duration::from_secs(seconds_to_midnight as u64))
+            .unwrap_or(Instant::MAX);
+        self.leap_second = Some(LeapSecond {
+            at,
+            step_millis,
+            smear,
+            in_offset: false,
+        });
+        #[cfg(feature = "defmt")]
+        defmt::warn!(
+            "Leap second ({}) in {} s, smear: {}",
+            leap,
+            seconds_to_midnight,
+            smear
+        );
+    }
+
+    /// Returns `true` from noon until midnight.
+    #[must_use]
+    pub fn is_pm(&self) -> bool {
+        const NOON: Duration = Duration::from_secs(12 * 60 * 60);
+        self.now().since_midnight() >= NOON
+    }
+
+    /// Returns the current hours, minutes, seconds, and wait duration until the next unit of time.
+    ///
+    /// For example, if `unit` is `ONE_MINUTE`, this function will tell how long to wait
+    /// until the top of the next minute. This is used to put the microcontroller to sleep
+    /// until the next time the display needs to be updated.
+    ///
+    /// The function is in-line so that the compiler can optimize return values that
+    /// are not used.
+    #[expect(
+        clippy::cast_possible_truncation,
+        clippy::integer_division_remainder_used,
+        clippy::arithmetic_side_effects,
+        reason = "The modulo operations prevent overflow."
+    )]
+    #[must_use]
+    #[inline]
+    pub fn h_m_s_sleep_duration(&self, unit: Duration) -> (u8, u8, u8, Duration) {
+        let now = self.now().since_midnight();
+        let sleep_duration = Self::till_next(now, unit);
+        let elapsed_seconds = now.as_secs();
+        let hours = ((elapsed_seconds / 3600) + 11) % 12 + 1; // 1-12 instead of 0-11
+        let minutes = (elapsed_seconds % 3600) / 60;
+        let seconds = elapsed_seconds % 60;
+        (hours as u8, minutes as u8, seconds as u8, sleep_duration)
+    }
+
+    #[inline]
+    #[must_use]
+    #[expect(
+        clippy::integer_division_remainder_used,
+        clippy::arithmetic_side_effects,
+        reason = "The modulo operations prevent overflow."
+    )]
+    /// Returns the duration until the next unit of time.
+    ///
+    /// For example, if `a` is 1:02:03 and `unit` is `ONE_HOUR`, this function will return
+    /// the duration until 2:00:00 which is 57 minutes and 57 seconds.
+    pub const fn till_next(time: Duration, unit: Duration) -> Duration {
+        let unit_ticks = unit.as_ticks();
+        Duration::from_ticks(unit_ticks - time.as_ticks() % unit_ticks)
+    }
+
+    /// Returns the current UTC offset in minutes.
+    #[must_use]
+    pub const fn utc_offset_minutes(&self) -> i32 {
+        self.utc_offset_minutes
+    }
+
+    /// Returns the current UTC offset in hours (rounded to nearest hour).
+    #[must_use]
+    pub const fn utc_offset_hours(&self) -> i32 {
+        round_to_hours(self.utc_offset_minutes)
+    }
+
+    /// Adjusts the UTC offset by the given number of hours.
+    /// The offset wraps around from +14 to -12 (27 total values: -12 to +14).
+    pub fn adjust_utc_offset_hours(&mut self, hours: i32) {
+        let wrapped = wrap_utc_offset_hours(self.utc_offset_hours().saturating_add(hours));
+        self.set_utc_offset_minutes(wrapped.saturating_mul(60));
+    }
+
+    /// Moves to another time zone, keeping the same instant: when the UTC offset increases
+    /// by an hour, the display shows an hour later (and the date follows across midnight).
+    #[expect(
+        clippy::arithmetic_side_effects,
+        clippy::cast_possible_wrap,
+        reason = "Offsets are within a couple of days, so the step fits easily."
+    )]
+    pub fn set_utc_offset_minutes(&mut self, utc_offset_minutes: i32) {
+        let delta_minutes = i64::from(utc_offset_minutes) - i64::from(self.utc_offset_minutes);
+        let delta_ticks = delta_minutes * ONE_MINUTE.as_ticks() as i64;
+        self.local_epoch_ticks = self.local_epoch_ticks.saturating_add(delta_ticks);
+        self.hold = self.hold.map(|hold| hold.saturating_add(delta_ticks));
+        #[cfg(feature = "defmt")]
+        defmt::info!(
+            "Adjusted UTC offset from {} to {} minutes",
+            self.utc_offset_minutes,
+            utc_offset_minutes
+        );
+        self.utc_offset_minutes = utc_offset_minutes;
+    }
+
+    /// A copy of this time moved to another time zone, for previewing an edit.
+    #[must_use]
+    pub fn with_utc_offset_minutes(&self, utc_offset_minutes: i32) -> Self {
+        let mut moved = self.clone();
+        moved.set_utc_offset_minutes(utc_offset_minutes);
+        moved
+    }
+}
+
+/// Parses a 24-hour time of day, `HH:MM` or `HH:MM:SS`, into hours, minutes, and seconds.
+#[must_use]
+pub fn parse_time_of_day(text: &str) -> Option<(u8, u8, u8)> {
+    let mut parts = text.trim().split(':');
+    let mut part = |limit: u8| {
+        parts
+            .next()
+            .and_then(|part| part.parse::<u8>().ok())
+            .filter(|&value| value < limit)
+    };
+    let hours = part(24)?;
+    let minutes = part(60)?;
+    let seconds = match parts.next() {
+        Some(seconds) => seconds.parse::<u8>().ok().filter(|&value| value < 60)?,
+        None => 0,
+    };
+    parts.next().is_none().then_some((hours, minutes, seconds))
+}
+
+/// How far `after` is from `before` in milliseconds, taking the shorter way around the day.
+#[expect(
+    clippy::arithmetic_side_effects,
+    clippy::integer_division_remainder_used,
+    clippy::cast_possible_wrap,
+    reason = "Both times are under one day, so the difference fits and rem_euclid keeps it there."
+)]
+fn signed_millis_between(before: Duration, after: Duration) -> i32 {
+    let day_millis = ONE_DAY.as_millis() as i64;
+    let forward = (after.as_millis() as i64 - before.as_millis() as i64).rem_euclid(day_millis);
+    let shortest = if forward > day_millis / 2 {
+        forward - day_millis
+    } else {
+        forward
+    };
+    i32::try_from(shortest).unwrap_or(i32::MAX)
+}
+
+impl AddAssign<Duration> for ClockTime {
+    /// Moves the time forward by the given duration, into the next day if it gets there.
+    fn add_assign(&mut self, duration: Duration) {
+        let ticks = i64::try_from(duration.as_ticks()).unwrap_or(i64::MAX);
+        self.local_epoch_ticks = self.local_epoch_ticks.saturating_add(ticks);
+        self.hold = self.hold.map(|hold| hold.saturating_add(ticks));
+        #[cfg(feature = "defmt")]
+        defmt::info!(
+            "Now: {:?}, local epoch: {} ticks",
+            Instant::now().as_millis(),
+            self.local_epoch_ticks
+        );
+    }
+}
+
+/// The start of a local day, which the firmware's clock task announces at 00:00 local.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MidnightTick {
+    /// The day's local date.
+    pub date: Date,
+    /// Its local midnight, in Unix time.
+    pub unix_seconds: UnixSeconds,
+}
+
+/// The local time at one moment, as `ClockTime::now` reads it.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalTime {
+    /// Ticks since 1970-01-01 00:00 local time.
+    ticks: i64,
+    /// Whether a time source has set the date, which is only a placeholder before.
+    date_known: bool,
+}
+
+impl LocalTime {
+    /// The time of day: how long it has been since local midnight.
+    #[must_use]
+    #[expect(
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss,
+        reason = "One day's ticks fit in an i64, and rem_euclid is non-negative."
+    )]
+    pub const fn since_midnight(self) -> Duration {
+        Duration::from_ticks(self.ticks.rem_euclid(TICKS_IN_ONE_DAY as i64) as u64)
+    }
+
+    /// Seconds since 1970-01-01 00:00 local time, once a time source has set the date.
+    #[must_use]
+    #[expect(
+        clippy::arithmetic_side_effects,
+        clippy::integer_division_remainder_used,
+        clippy::cast_possible_wrap,
+        reason = "TICK_HZ is a non-zero constant that fits in an i64."
+    )]
+    pub const fn local_seconds(self) -> Option<i64> {
+        if self.date_known {
+            Some(self.ticks.div_euclid(TICK_HZ as i64))
+        } else {
+            None
+        }
+    }
+
+    /// Today's date, once a time source has set it.
+    #[must_use]
+    pub fn date(self) -> Option<Date> {
+        OffsetDateTime::from_unix_timestamp(self.local_seconds()?)
+            .ok()
+            .map(OffsetDateTime::date)
+    }
+
+    /// Today's day of the week, once a time source has set the date.
+    #[must_use]
+    pub fn weekday(self) -> Option<Weekday> {
+        self.date().map(Date::weekday)
+    }
+}
@@ -0,0 +1,279 @@
+use core::fmt::Write;
+
+use embassy_time::Duration;
+
+use crate::{AlarmSound, Error, Result, Segments, SEGMENT_COUNT};
+
+/// Bytes used by `Config::to_bytes`: each value (and the alarm sound's index) as a big-endian
+/// `u16`.
+pub const CONFIG_BYTES: usize = 16;
+
+/// The shortest a blinking display stays lit, or dark, in milliseconds. Shorter and the blink
+/// is a flicker, and at 0 the blinker would spin redrawing.
+pub const MIN_BLINK_MS: u16 = 20;
+
+/// How long a blinking display stays lit, and then dark, in milliseconds, at least and at most.
+const BLINK_MS: (u16, u16) = (MIN_BLINK_MS, 2_000);
+
+/// How long each cell is lit in turn as the display is multiplexed, in microseconds. Much
+/// longer and a full scan flickers.
+const MULTIPLEX_US: (u16, u16) = (500, 5_000);
+
+/// How long every cell stays dark between one cell and the next, in microseconds, so a cell
+/// still turning off does not faintly show the next cell's segments. It comes out of each
+/// multiplex slot, so it is always shorter than one.
+const BLANKING_US: (u16, u16) = (0, 250);
+
+/// How much shorter a cell with one segment lit is lit than one with every segment lit, in
+/// percent. 0 is off.
+const SEGMENT_COMPENSATION_PERCENT: (u16, u16) = (0, 50);
+
+/// How long the clock waits between time syncs, in minutes.
+const SYNC_MINUTES: (u16, u16) = (5, 1_440);
+
+/// How loud the buzzer ticks each second, in percent. 0 is off.
+const TICK_VOLUME_PERCENT: (u16, u16) = (0, 100);
+
+/// The clock's tuning: how it blinks, how it scans the display, how often it syncs, how
+/// loud it ticks, and what its alarm sounds like.
+///
+/// `Config::DEFAULT` holds the values the clock is built with. A saved `Config` overrides
+/// them at boot, so tuning does not need a rebuild. The button's timings are tuned the same
+/// way with `GestureTimings`.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    /// How long a blinking display stays lit.
+    pub blink_on_ms: u16,
+    /// How long a blinking display stays dark.
+    pub blink_off_ms: u16,
+    /// How long each cell is lit in turn as the display is multiplexed.
+    pub multiplex_us: u16,
+    /// How long the display is dark between cells, within each multiplex slot.
+    pub blanking_us: u16,
+    /// How much less of its slot a cell with one segment lit is lit than a cell with every
+    /// segment lit, in percent (see `Config::compensated_lit`). 0 lights every cell alike.
+    pub segment_compensation_percent: u16,
+    /// How long the clock waits after a successful time sync before the next.
+    pub sync_interval_minutes: u16,
+    /// How loud the buzzer ticks each second, in percent. 0 turns the tick off.
+    pub tick_volume_percent: u16,
+    /// What the buzzer plays when an alarm goes off.
+    pub alarm_sound: AlarmSound,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl Config {
+    /// A 150 ms blink with 50 ms dark, a 3 ms multiplex slot (2 ms with eight cells, so a full
+    /// scan still refreshes faster than the eye can see) with 50 µs of blanking and no segment
+    /// compensation, an hourly time sync, no tick, and a beeping alarm.
+    pub const DEFAULT: Self = Self {
+        blink_on_ms: 150,
+        blink_off_ms: 50,
+        multiplex_us: if cfg!(feature = "eight-digits") {
+            2_000
+        } else {
+            3_000
+        },
+        blanking_us: 50,
+        segment_compensation_percent: 0,
+        sync_interval_minutes: 60,
+        tick_volume_percent: 0,
+        alarm_sound: AlarmSound::Beep,
+    };
+
+    /// How long a blinking display stays lit.
+    #[must_use]
+    pub fn blink_on(&self) -> Duration {
+        Duration::from_millis(u64::from(self.blink_on_ms))
+    }
+
+    /// How long a blinking display stays dark.
+    #[must_use]
+    pub fn blink_off(&self) -> Duration {
+        Duration::from_millis(u64::from(self.blink_off_ms))
+    }
+
+    /// How long each cell is lit in turn.
+    #[must_use]
+    pub fn multiplex_sleep(&self) -> Duration {
+        Duration::from_micros(u64::from(self.multiplex_us))
+    }
+
+    /// How long the display is dark between cells.
+    #[must_use]
+    pub fn blanking(&self) -> Duration {
+        Duration::from_micros(u64::from(self.blanking_us))
+    }
+
+    /// How long a cell showing `segments` stays lit, out of the `lit` time its slot gives.
+    ///
+    /// A cell lighting many segments shares the supply among them and looks dimmer than a
+    /// cell lighting few, so the fewer segments a cell lights the more its lit time is
+    /// trimmed: by `segment_compensation_percent` with one segment lit, by nothing with every
+    /// segment lit, and evenly in between. The trimmed time is left dark, so a scan takes as
+    /// long as before.
+    #[must_use]
+    #[expect(
+        clippy::arithmetic_side_effects,
+        clippy::integer_division_remainder_used,
+        reason = "Counts are at most `SEGMENT_COUNT` and the trim at most half the lit time."
+    )]
+    pub fn compensated_lit(&self, lit: Duration, segments: Segments) -> Duration {
+        let segment_count = u64::try_from(SEGMENT_COUNT).unwrap_or(u64::MAX);
+        let unlit = segment_count - u64::from(segments.count_ones()).clamp(1, segment_count);
+        let percent =
+            u64::from(self.segment_compensation_percent.min(SEGMENT_COMPENSATION_PERCENT.1));
+        // Out of `100 * (segment_count - 1)`, so one lit segment trims `percent` percent
+        let whole = 100 * (segment_count - 1);
+        Duration::from_ticks(lit.as_ticks() * (whole - percent * unlit) / whole)
+    }
+
+    /// How long to wait between successful time syncs.
+    #[must_use]
+    pub fn sync_interval(&self) -> Duration {
+        Duration::from_secs(u64::from(self.sync_interval_minutes).saturating_mul(60))
+    }
+
+    /// Whether each value is in its range.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        let in_range = |val: u16, (min, max): (u16, u16)| (min..=max).contains(&val);
+        in_range(self.blink_on_ms, BLINK_MS)
+            && in_range(self.blink_off_ms, BLINK_MS)
+            && in_range(self.multiplex_us, MULTIPLEX_US)
+            && in_range(self.blanking_us, BLANKING_US)
+            && in_range(self.segment_compensation_percent, SEGMENT_COMPENSATION_PERCENT)
+            && in_range(self.sync_interval_minutes, SYNC_MINUTES)
+            && in_range(self.tick_volume_percent, TICK_VOLUME_PERCENT)
+    }
+
+    /// This config with the `name=value` changes in `changes` applied, where `name` is
+    /// `blink_on`, `blink_off`, `multiplex_us`, `blanking_us`, `segment_compensation`,
+    /// `sync_minutes`, `tick_volume`, or `alarm_sound` (named as `AlarmSound::parse` reads it). Changes are separated by `,`,
+    /// `&`, or spaces, and values not named are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a change is malformed or the result is not `is_valid`.
+    pub fn with_changes(self, changes: &str) -> Result<Self> {
+        let mut config = self;
+        for change in changes
+            .split([',', '&', ' '])
+            .filter(|change| !change.is_empty())
+        {
+            let (name, value) = change.split_once('=').ok_or(Error::ConfigFormat)?;
+            let number = || value.parse().map_err(|_| Error::ConfigFormat);
+            match name {
+                "blink_on" => config.blink_on_ms = number()?,
+                "blink_off" => config.blink_off_ms = number()?,
+                "multiplex_us" => config.multiplex_us = number()?,
+                "blanking_us" => config.blanking_us = number()?,
+                "segment_compensation" => config.segment_compensation_percent = number()?,
+                "sync_minutes" => config.sync_interval_minutes = number()?,
+                "tick_volume" => config.tick_volume_percent = number()?,
+                "alarm_sound" => {
+                    config.alarm_sound = AlarmSound::parse(value).ok_or(Error::ConfigFormat)?;
+                }
+                _ => return Err(Error::ConfigFormat),
+            }
+        }
+        if config.is_valid() {
+            Ok(config)
+        } else {
+            Err(Error::ConfigFormat)
+        }
+    }
+
+    /// Writes the config in the format `with_changes` reads.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `out` is full.
+    pub fn write_list(&self, out: &mut impl Write) -> core::fmt::Result {
+        write!(
+            out,
+            "blink_on={},blink_off={},multiplex_us={},blanking_us={},segment_compensation={},\
+             sync_minutes={},tick_volume={},alarm_sound={}",
+            self.blink_on_ms,
+            self.blink_off_ms,
+            self.multiplex_us,
+            self.blanking_us,
+            self.segment_compensation_percent,
+            self.sync_interval_minutes,
+            self.tick_volume_percent,
+            self.alarm_sound.name()
+        )
+    }
+
+    /// Packs the config for storage.
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; CONFIG_BYTES] {
+        let [on_0, on_1] = self.blink_on_ms.to_be_bytes();
+        let [off_0, off_1] = self.blink_off_ms.to_be_bytes();
+        let [multiplex_0, multiplex_1] = self.multiplex_us.to_be_bytes();
+        let [blanking_0, blanking_1] = self.blanking_us.to_be_bytes();
+        let [compensation_0, compensation_1] = self.segment_compensation_percent.to_be_bytes();
+        let [sync_0, sync_1] = self.sync_interval_minutes.to_be_bytes();
+        let [tick_0, tick_1] = self.tick_volume_percent.to_be_bytes();
+        let [sound_0, sound_1] = self.alarm_sound.index().to_be_bytes();
+        [
+            on_0,
+            on_1,
+            off_0,
+            off_1,
+            multiplex_0,
+            multiplex_1,
+            blanking_0,
+            blanking_1,
+            compensation_0,
+            compensation_1,
+            sync_0,
+            sync_1,
+            tick_0,
+            tick_1,
+            sound_0,
+            sound_1,
+        ]
+    }
+
+    /// Unpacks a config stored with `to_bytes`, or `None` if it is not `is_valid` or names no
+    /// alarm sound (such as erased flash, which reads as all `0xFF`).
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8; CONFIG_BYTES]) -> Option<Self> {
+        let [
+            on_0,
+            on_1,
+            off_0,
+            off_1,
+            multiplex_0,
+            multiplex_1,
+            blanking_0,
+            blanking_1,
+            compensation_0,
+            compensation_1,
+            sync_0,
+            sync_1,
+            tick_0,
+            tick_1,
+            sound_0,
+            sound_1,
+        ] = *bytes;
+        let config = Self {
+            blink_on_ms: u16::from_be_bytes([on_0, on_1]),
+            blink_off_ms: u16::from_be_bytes([off_0, off_1]),
+            multiplex_us: u16::from_be_bytes([multiplex_0, multiplex_1]),
+            blanking_us: u16::from_be_bytes([blanking_0, blanking_1]),
+            segment_compensation_percent: u16::from_be_bytes([compensation_0, compensation_1]),
+            sync_interval_minutes: u16::from_be_bytes([sync_0, sync_1]),
+            tick_volume_percent: u16::from_be_bytes([tick_0, tick_1]),
+            alarm_sound: AlarmSound::from_index(u16::from_be_bytes([sound_0, sound_1]))?,
+        };
+        config.is_valid().then_some(config)
+    }
+}
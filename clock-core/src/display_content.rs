@@ -0,0 +1,27 @@
+use crate::{Carousel, Reminders, Settings, SyncError, Temperature, TimeSource};
+
+/// Everything besides the time that the display modes draw from, kept by the clock task.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DisplayContent {
+    /// How to show the time.
+    pub settings: Settings,
+    /// Yearly labels that `ClockState::HoursMinutes` scrolls on their dates.
+    pub reminders: Reminders,
+    /// The outdoor temperature, once fetched.
+    pub temperature: Option<Temperature>,
+    /// The sequence `ClockState::Carousel` rotates through.
+    pub carousel: Carousel,
+    /// The battery's estimated charge in percent, once measured, shown by
+    /// `ClockState::Battery`.
+    pub battery_percent: Option<u8>,
+    /// The source that last set the time, shown by `ClockState::TimeSource`.
+    pub time_source: Option<TimeSource>,
+    /// Why the latest sync failed, shown by `ClockState::SyncFailed`.
+    pub sync_error: Option<SyncError>,
+    /// The configured network's latest signal strength in dBm, shown by `ClockState::Survey`,
+    /// or `None` if it did not answer.
+    pub signal_dbm: Option<i16>,
+    /// The firmware version and build, scrolled by `ClockState::Version`.
+    pub version: &'static str,
+}
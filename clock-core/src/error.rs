@@ -0,0 +1,51 @@
+use derive_more::derive::{Display, Error};
+
+/// A specialized `Result` where the error is this crate's `Error` type.
+pub type Result<T, E = Error> = core::result::Result<T, E>;
+
+/// Errors from the hardware-free clock logic.
+#[expect(missing_docs, reason = "The variants are self-explanatory.")]
+#[derive(Debug, Display, Error)]
+pub enum Error {
+    #[display("bits_to_indexes does not have enough preallocated space")]
+    BitsToIndexesNotEnoughSpace,
+
+    #[display("Time response is too short to carry an authentication code")]
+    TimeAuthTooShort,
+
+    #[display("Time response does not answer this request")]
+    TimeAuthNonceMismatch,
+
+    #[display("Time response authentication code does not match")]
+    TimeAuthBadMac,
+
+    #[display("Reminders must be a comma-separated list of up to 8 MMDDLABL entries")]
+    ReminderFormat,
+
+    #[display("Carousel must be a comma-separated list of up to 8 name:seconds items, with a message of up to 32 characters")]
+    CarouselFormat,
+
+    #[display("Config must be blink_on=20..2000, blink_off=20..2000, multiplex_us=500..5000, sync_minutes=5..1440, tick_volume=0..100, and alarm_sound=beep|beeps|chime|tune|custom")]
+    ConfigFormat,
+
+    #[display("Gesture timings must be long=200..3000, double=100..1500, and very_long=3000..60000 (at least twice long) milliseconds")]
+    GestureTimingsFormat,
+
+    #[display("Melody must be optional d=, o=, and b= defaults, a colon, and notes such as 8c#6.")]
+    MelodyFormat,
+
+    #[display("Voice file must be VOX1 with 26 clips of 8 to 48 kHz audio, all within the file")]
+    VoiceFormat,
+
+    #[display("Message must be 1 to 32 characters, repeated 1 to 20 times")]
+    MessageFormat,
+
+    #[display("Text is longer than it has room for")]
+    TextTooLong,
+
+    #[display("MQTT packet is malformed")]
+    MqttMalformed,
+
+    #[display("Provisioning needs an SSID of 1 to 32 bytes, a password of up to 64, and a UTC offset of -720..=840 minutes")]
+    ProvisioningFormat,
+}
@@ -0,0 +1,66 @@
+//! Commands for exixe Nixie tube driver boards, one board (and SPI chip select) per tube.
+
+use crate::{Brightness, Leds, Segments};
+
+/// Bytes in one exixe command: a header, the ten cathodes (1 to 9, then 0), the left and
+/// right decimal points, and the red, green, and blue backlight.
+pub const EXIXE_COMMAND_LEN: usize = 16;
+
+/// The byte that starts every command.
+const HEADER: u8 = 0xAA;
+
+/// Set on each value byte so the board takes it (otherwise it keeps the old value).
+const UPDATE: u8 = 0x80;
+
+/// The brightest a cathode's PWM duty can be.
+const MAX_DUTY: u8 = 127;
+
+/// Where the right decimal point's byte is. The left one is just before it.
+const RIGHT_DOT: usize = 12;
+
+/// The digit `segments` shows on a tube, or `None` for anything a tube cannot show (such as
+/// a letter), which leaves the tube dark. A lit decimal point is ignored.
+#[must_use]
+pub fn nixie_digit(segments: Segments) -> Option<u8> {
+    let digit = Leds::DIGITS
+        .iter()
+        .position(|&digit_segments| digit_segments == segments & !Leds::DECIMAL)?;
+    u8::try_from(digit).ok()
+}
+
+/// The command that lights `digit` (0 to 9, or `None` for none) and the right decimal point
+/// if `dot`, at `brightness`. Every cathode is written, so the previous digit goes dark.
+#[must_use]
+pub fn exixe_command(
+    digit: Option<u8>,
+    dot: bool,
+    brightness: Brightness,
+) -> [u8; EXIXE_COMMAND_LEN] {
+    let lit = UPDATE | duty(brightness);
+    let mut command = [UPDATE; EXIXE_COMMAND_LEN];
+    if let Some(header) = command.first_mut() {
+        *header = HEADER;
+    }
+    // Cathode 0 comes after 9
+    let cathode = digit.map(|number| if number == 0 { 10 } else { usize::from(number) });
+    if let Some(byte) = cathode.and_then(|index| command.get_mut(index)) {
+        *byte = lit;
+    }
+    if let Some(byte) = command.get_mut(RIGHT_DOT).filter(|_| dot) {
+        *byte = lit;
+    }
+    command
+}
+
+/// `brightness` as a PWM duty, 0 to `MAX_DUTY`, on its gamma curve. Anything lit gets a duty
+/// of at least 1.
+#[expect(
+    clippy::arithmetic_side_effects,
+    clippy::integer_division_remainder_used,
+    reason = "Duties are at most `FULL_DUTY`, so the product fits and the divisor is not zero."
+)]
+fn duty(brightness: Brightness) -> u8 {
+    let gamma_duty = u32::from(brightness.duty().min(Brightness::FULL_DUTY));
+    let duty = gamma_duty * u32::from(MAX_DUTY) / u32::from(Brightness::FULL_DUTY);
+    u8::try_from(duty.max(u32::from(gamma_duty != 0))).unwrap_or(MAX_DUTY)
+}
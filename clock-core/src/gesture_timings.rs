@@ -0,0 +1,138 @@
+use core::fmt::Write;
+
+use embassy_time::Duration;
+
+use crate::{Error, Result};
+
+/// Bytes used by `GestureTimings::to_bytes`: each time in milliseconds as a big-endian `u16`.
+pub const GESTURE_TIMINGS_BYTES: usize = 6;
+
+/// How long the button must be held for a long press, in milliseconds, at least and at most.
+const LONG_PRESS_MS: (u16, u16) = (200, 3_000);
+
+/// How long the double-press window may be, in milliseconds.
+const DOUBLE_PRESS_MS: (u16, u16) = (100, 1_500);
+
+/// How long the button must be held for a very long press (a factory reset), in milliseconds.
+/// It must also be well past the long press, so the two cannot be confused.
+const VERY_LONG_PRESS_MS: (u16, u16) = (3_000, 60_000);
+
+/// How the button's presses are told apart.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GestureTimings {
+    /// How long the button must be held for a press to count as long.
+    pub long_press_ms: u16,
+    /// How soon a second short press must follow the first to count as a double press.
+    pub double_press_ms: u16,
+    /// How long the button must be held for a press to count as very long.
+    pub very_long_press_ms: u16,
+}
+
+impl Default for GestureTimings {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl GestureTimings {
+    /// Half a second for a long press, 300 ms between the presses of a double press, and ten
+    /// seconds for a very long press.
+    pub const DEFAULT: Self = Self {
+        long_press_ms: 500,
+        double_press_ms: 300,
+        very_long_press_ms: 10_000,
+    };
+
+    /// The long press threshold.
+    #[must_use]
+    pub fn long_press(&self) -> Duration {
+        Duration::from_millis(u64::from(self.long_press_ms))
+    }
+
+    /// The double-press window.
+    #[must_use]
+    pub fn double_press(&self) -> Duration {
+        Duration::from_millis(u64::from(self.double_press_ms))
+    }
+
+    /// The very long press threshold.
+    #[must_use]
+    pub fn very_long_press(&self) -> Duration {
+        Duration::from_millis(u64::from(self.very_long_press_ms))
+    }
+
+    /// Whether each time is in its range, and a very long press is at least twice a long one.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        let in_range = |ms: u16, (min, max): (u16, u16)| (min..=max).contains(&ms);
+        in_range(self.long_press_ms, LONG_PRESS_MS)
+            && in_range(self.double_press_ms, DOUBLE_PRESS_MS)
+            && in_range(self.very_long_press_ms, VERY_LONG_PRESS_MS)
+            && self.very_long_press_ms >= self.long_press_ms.saturating_mul(2)
+    }
+
+    /// These timings with the `name=milliseconds` changes in `changes` applied, where `name` is
+    /// `long`, `double`, or `very_long`. Changes are separated by `,`, `&`, or spaces, and
+    /// times not named are kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a change is malformed or the result is not `is_valid`.
+    pub fn with_changes(self, changes: &str) -> Result<Self> {
+        let mut timings = self;
+        for change in changes
+            .split([',', '&', ' '])
+            .filter(|change| !change.is_empty())
+        {
+            let (name, value) = change.split_once('=').ok_or(Error::GestureTimingsFormat)?;
+            let ms = value.parse().map_err(|_| Error::GestureTimingsFormat)?;
+            match name {
+                "long" => timings.long_press_ms = ms,
+                "double" => timings.double_press_ms = ms,
+                "very_long" => timings.very_long_press_ms = ms,
+                _ => return Err(Error::GestureTimingsFormat),
+            }
+        }
+        if timings.is_valid() {
+            Ok(timings)
+        } else {
+            Err(Error::GestureTimingsFormat)
+        }
+    }
+
+    /// Writes the timings in the format `with_changes` reads.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `out` is full.
+    pub fn write_list(&self, out: &mut impl Write) -> core::fmt::Result {
+        write!(
+            out,
+            "long={},double={},very_long={}",
+            self.long_press_ms, self.double_press_ms, self.very_long_press_ms
+        )
+    }
+
+    /// Packs the timings for storage.
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; GESTURE_TIMINGS_BYTES] {
+        let [long_0, long_1] = self.long_press_ms.to_be_bytes();
+        let [double_0, double_1] = self.double_press_ms.to_be_bytes();
+        let [very_long_0, very_long_1] = self.very_long_press_ms.to_be_bytes();
+        [long_0, long_1, double_0, double_1, very_long_0, very_long_1]
+    }
+
+    /// Unpacks timings stored with `to_bytes`, or `None` if they are not `is_valid` (such as
+    /// erased flash, which reads as all `0xFF`).
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8; GESTURE_TIMINGS_BYTES]) -> Option<Self> {
+        let [long_0, long_1, double_0, double_1, very_long_0, very_long_1] = *bytes;
+        let timings = Self {
+            long_press_ms: u16::from_be_bytes([long_0, long_1]),
+            double_press_ms: u16::from_be_bytes([double_0, double_1]),
+            very_long_press_ms: u16::from_be_bytes([very_long_0, very_long_1]),
+        };
+        timings.is_valid().then_some(timings)
+    }
+}
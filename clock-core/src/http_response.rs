@@ -0,0 +1,165 @@
+//! Just enough HTTP/1.1 to read one response from a fixed buffer: the status line, headers,
+//! and a plain, `Content-Length`, or chunked body.
+
+/// The blank line that ends the headers.
+const HEADER_END: &[u8] = b"\r\n\r\n";
+
+/// The last chunk of a chunked body, with no trailers.
+const LAST_CHUNK: &[u8] = b"0\r\n\r\n";
+
+/// A response read into a buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HttpResponse<'a> {
+    status: u16,
+    headers: &'a str,
+    body: &'a [u8],
+}
+
+impl<'a> HttpResponse<'a> {
+    /// Parses a response from the bytes read off a connection, decoding a chunked body in
+    /// place. A body cut short (by a full buffer) keeps what arrived.
+    ///
+    /// Returns `None` if the status line or headers are incomplete or malformed.
+    #[must_use]
+    pub fn parse(bytes: &'a mut [u8]) -> Option<Self> {
+        let header_end = find(bytes, HEADER_END)?;
+        let (head_bytes, rest) = bytes.split_at_mut(header_end);
+        let head = core::str::from_utf8(head_bytes).ok()?;
+        let (status_line, headers) = head.split_once("\r\n").unwrap_or((head, ""));
+        let status = parse_status(status_line)?;
+        let raw_body = rest.get_mut(HEADER_END.len()..)?;
+        let body_len = if is_chunked(headers) {
+            dechunk(raw_body)
+        } else {
+            content_length(headers).map_or(raw_body.len(), |len| len.min(raw_body.len()))
+        };
+        let body: &'a [u8] = raw_body;
+        Some(Self {
+            status,
+            headers,
+            body: body.get(..body_len)?,
+        })
+    }
+
+    /// Whether `bytes` hold a whole response, so reading can stop before the server closes
+    /// the connection: the headers alone for a `HEAD` request (`head_only`), otherwise a body
+    /// as long as its `Content-Length` or ending in the last chunk.
+    #[must_use]
+    pub fn is_complete(bytes: &[u8], head_only: bool) -> bool {
+        let Some(header_end) = find(bytes, HEADER_END) else {
+            return false;
+        };
+        if head_only {
+            return true;
+        }
+        let head = bytes.get(..header_end).unwrap_or_default();
+        let body = bytes
+            .get(header_end.saturating_add(HEADER_END.len())..)
+            .unwrap_or_default();
+        let headers = core::str::from_utf8(head)
+            .ok()
+            .and_then(|text| text.split_once("\r\n"))
+            .map_or("", |(_, headers)| headers);
+        if is_chunked(headers) {
+            body.ends_with(LAST_CHUNK)
+        } else {
+            content_length(headers).is_some_and(|len| body.len() >= len)
+        }
+    }
+
+    /// The status code, such as 200.
+    #[must_use]
+    pub const fn status(&self) -> u16 {
+        self.status
+    }
+
+    /// Whether the status is 2xx.
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// The value of the first header called `name` (in any case), trimmed.
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&'a str> {
+        header(self.headers, name)
+    }
+
+    /// The body, with any chunking removed.
+    #[must_use]
+    pub const fn body(&self) -> &'a [u8] {
+        self.body
+    }
+
+    /// The body as text, up to its first byte that is not UTF-8 (such as a character cut in
+    /// two by a full buffer).
+    #[must_use]
+    pub fn text(&self) -> &'a str {
+        core::str::from_utf8(self.body).unwrap_or_else(|err| {
+            core::str::from_utf8(self.body.get(..err.valid_up_to()).unwrap_or_default())
+                .unwrap_or_default()
+        })
+    }
+}
+
+/// The code from a status line such as `HTTP/1.1 200 OK`.
+fn parse_status(status_line: &str) -> Option<u16> {
+    let mut words = status_line.split_ascii_whitespace();
+    words.next().filter(|version| version.starts_with("HTTP/"))?;
+    words.next()?.parse().ok()
+}
+
+/// The value of the first header line called `name` (in any case), trimmed.
+fn header<'a>(headers: &'a str, name: &str) -> Option<&'a str> {
+    headers.split("\r\n").find_map(|line| {
+        let (line_name, value) = line.split_once(':')?;
+        line_name.trim().eq_ignore_ascii_case(name).then_some(value.trim())
+    })
+}
+
+fn is_chunked(headers: &str) -> bool {
+    header(headers, "transfer-encoding")
+        .is_some_and(|value| value.eq_ignore_ascii_case("chunked"))
+}
+
+fn content_length(headers: &str) -> Option<usize> {
+    header(headers, "content-length")?.parse().ok()
+}
+
+/// Where `needle` first starts in `haystack`.
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+/// Removes the chunk-size lines from a chunked body in place and returns the length of the
+/// data left at its start. Stops at the last chunk, or at the end of what arrived.
+fn dechunk(body: &mut [u8]) -> usize {
+    let mut read = 0_usize;
+    let mut written = 0_usize;
+    while let Some(line_len) = body.get(read..).and_then(|rest| find(rest, b"\r\n")) {
+        let parsed = body
+            .get(read..read.saturating_add(line_len))
+            .and_then(|line| core::str::from_utf8(line).ok())
+            .and_then(|line| {
+                // Chunk extensions (after `;`) are allowed and ignored
+                let digits = line.split(';').next().unwrap_or_default().trim();
+                usize::from_str_radix(digits, 16).ok()
+            });
+        let Some(size) = parsed.filter(|&len| len > 0) else {
+            break;
+        };
+        read = read.saturating_add(line_len).saturating_add(2);
+        let available = size.min(body.len().saturating_sub(read));
+        if available == 0 {
+            break;
+        }
+        body.copy_within(read..read.saturating_add(available), written);
+        written = written.saturating_add(available);
+        if available < size {
+            break;
+        }
+        // Skip the data and the line break after it
+        read = read.saturating_add(available).saturating_add(2);
+    }
+    written
+}
@@ -0,0 +1,62 @@
+//! Just enough JSON reading to pull one number out of a small API response.
+
+/// Finds the first `"key": <number>` in `json` and returns the number rounded to the nearest
+/// whole value (halves away from zero).
+///
+/// Occurrences whose value is not a number (such as `"temperature_2m":"°C"` in a units
+/// section) are skipped. Nesting is ignored, so the key should be unique enough on its own.
+#[must_use]
+pub fn find_number(json: &str, key: &str) -> Option<i32> {
+    let mut rest = json;
+    while let Some(start) = rest.find('"') {
+        let after_quote = rest.get(start.saturating_add(1)..)?;
+        let (name, after_name) = after_quote.split_once('"')?;
+        rest = after_name;
+        if name != key {
+            continue;
+        }
+        let Some(value) = after_name.trim_start().strip_prefix(':') else {
+            continue;
+        };
+        if let Some(number) = parse_rounded(value.trim_start()) {
+            return Some(number);
+        }
+    }
+    None
+}
+
+/// Parses the number at the start of `text`, such as `-3.46` or `12`, rounded to a whole
+/// value. Exponents are not supported.
+#[expect(
+    clippy::arithmetic_side_effects,
+    reason = "Digits are checked, and the whole part is limited to six digits."
+)]
+fn parse_rounded(text: &str) -> Option<i32> {
+    let (negative, unsigned) = text
+        .strip_prefix('-')
+        .map_or((false, text), |rest| (true, rest));
+    let mut chars = unsigned.chars().peekable();
+    let mut whole = 0_i32;
+    let mut digit_count = 0_u8;
+    while let Some(digit) = chars.peek().and_then(|char| char.to_digit(10)) {
+        chars.next();
+        digit_count = digit_count.saturating_add(1);
+        if digit_count > 6 {
+            return None;
+        }
+        whole = whole * 10 + i32::try_from(digit).ok()?;
+    }
+    if digit_count == 0 {
+        return None;
+    }
+    // Only the first decimal place matters for rounding
+    if chars.next() == Some('.')
+        && chars
+            .next()
+            .and_then(|char| char.to_digit(10))
+            .is_some_and(|tenths| tenths >= 5)
+    {
+        whole += 1;
+    }
+    Some(if negative { -whole } else { whole })
+}
@@ -0,0 +1,162 @@
+use time::OffsetDateTime;
+
+use crate::{Brightness, UnixSeconds, CELL_COUNT};
+
+/// The colors the day moves through, by local minute of the day: blue at night, orange at
+/// dawn and dusk, and warm white to white by day. Colors in between are blended.
+const DAY_COLORS: [(u16, Rgb); 7] = [
+    (0, Rgb::new(0x28, 0x28, 0xFF)),
+    (6 * 60, Rgb::new(0xFF, 0x60, 0x10)),
+    (9 * 60, Rgb::new(0xFF, 0xE0, 0xB0)),
+    (17 * 60, Rgb::new(0xFF, 0xFF, 0xFF)),
+    (19 * 60, Rgb::new(0xFF, 0x50, 0x20)),
+    (22 * 60, Rgb::new(0x60, 0x30, 0xFF)),
+    (24 * 60, Rgb::new(0x28, 0x28, 0xFF)),
+];
+
+/// A color for an RGB LED, such as a WS2812.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Rgb {
+    /// Every channel full.
+    pub const WHITE: Self = Self::new(0xFF, 0xFF, 0xFF);
+    /// Every channel off.
+    pub const OFF: Self = Self::new(0, 0, 0);
+
+    #[must_use]
+    pub const fn new(red: u8, green: u8, blue: u8) -> Self {
+        Self { red, green, blue }
+    }
+
+    /// Parses six hex digits, such as `ff8800`, with or without a leading `#`.
+    #[must_use]
+    pub fn parse_hex(text: &str) -> Option<Self> {
+        let digits = text.strip_prefix('#').unwrap_or(text);
+        if digits.len() != 6 {
+            return None;
+        }
+        let channel = |range| u8::from_str_radix(digits.get(range)?, 16).ok();
+        Some(Self::new(channel(0..2)?, channel(2..4)?, channel(4..6)?))
+    }
+
+    /// This color dimmed to `brightness`, each channel in proportion to its gamma-corrected
+    /// duty. A channel that is lit stays at least 1, so the dimmest levels still show.
+    #[must_use]
+    pub fn at_brightness(self, brightness: Brightness) -> Self {
+        self.scaled(brightness.duty(), Brightness::FULL_DUTY)
+    }
+
+    /// Each channel times `part / whole`, where `part` is at most `whole`. A lit channel
+    /// scaled by a non-zero `part` stays lit.
+    #[expect(
+        clippy::arithmetic_side_effects,
+        clippy::integer_division_remainder_used,
+        reason = "Channels are at most 255 and `part` at most `whole`, so nothing overflows."
+    )]
+    fn scaled(self, part: u16, whole: u16) -> Self {
+        let part = u32::from(part.min(whole));
+        let scale = |channel: u8| {
+            let scaled = u32::from(channel) * part / u32::from(whole.max(1));
+            let floor = u32::from(channel != 0 && part != 0);
+            u8::try_from(scaled.max(floor)).unwrap_or(u8::MAX)
+        };
+        Self::new(scale(self.red), scale(self.green), scale(self.blue))
+    }
+
+    /// The color `part / whole` of the way from this one to `other`.
+    #[expect(
+        clippy::arithmetic_side_effects,
+        clippy::integer_division_remainder_used,
+        reason = "Channels are at most 255 and `part` at most `whole`, so nothing overflows."
+    )]
+    fn blend(self, other: Self, part: u16, whole: u16) -> Self {
+        let (part, whole) = (i32::from(part.min(whole)), i32::from(whole.max(1)));
+        let mix = |from: u8, to: u8| {
+            let (from, to) = (i32::from(from), i32::from(to));
+            u8::try_from(from + (to - from) * part / whole).unwrap_or(u8::MAX)
+        };
+        Self::new(
+            mix(self.red, other.red),
+            mix(self.green, other.green),
+            mix(self.blue, other.blue),
+        )
+    }
+
+    /// The color for `minute_of_day` (0 to 1439) on the `DAY_COLORS` wheel.
+    #[must_use]
+    pub fn time_of_day(minute_of_day: u16) -> Self {
+        DAY_COLORS
+            .windows(2)
+            .find_map(|pair| match *pair {
+                [(start, from), (end, to)] if (start..end).contains(&minute_of_day) => Some(
+                    from.blend(to, minute_of_day.saturating_sub(start), end.saturating_sub(start)),
+                ),
+                _ => None,
+            })
+            .unwrap_or(Self::WHITE)
+    }
+}
+
+/// How an RGB display colors its digits.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigitColors {
+    /// A fixed color for each digit, from the left.
+    PerDigit([Rgb; CELL_COUNT]),
+    /// Every digit in the color of the local time of day (see `Rgb::time_of_day`).
+    TimeOfDay,
+}
+
+impl Default for DigitColors {
+    fn default() -> Self {
+        Self::PerDigit([Rgb::WHITE; CELL_COUNT])
+    }
+}
+
+impl DigitColors {
+    /// Parses `time` for `TimeOfDay`, or hex colors separated by commas, one per digit from
+    /// the left, such as `ff0000,ff0000,00ff00,00ff00`. A shorter list repeats its last color.
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        if text.trim() == "time" {
+            return Some(Self::TimeOfDay);
+        }
+        let mut colors = [Rgb::WHITE; CELL_COUNT];
+        let mut parts = text.split(',').map(str::trim);
+        let mut last = Rgb::parse_hex(parts.next()?)?;
+        for color in &mut colors {
+            *color = last;
+            if let Some(part) = parts.next() {
+                last = Rgb::parse_hex(part)?;
+            }
+        }
+        // Colors beyond the last digit are a mistake, not something to ignore
+        parts.next().is_none().then_some(Self::PerDigit(colors))
+    }
+
+    /// The color of the digit at `index`. `TimeOfDay` needs the local `minute_of_day`, and
+    /// shows white until the clock knows the time.
+    #[must_use]
+    pub fn color(&self, index: usize, minute_of_day: Option<u16>) -> Rgb {
+        match self {
+            Self::PerDigit(colors) => colors.get(index).copied().unwrap_or(Rgb::WHITE),
+            Self::TimeOfDay => minute_of_day.map_or(Rgb::WHITE, Rgb::time_of_day),
+        }
+    }
+}
+
+/// The local minute of the day (0 to 1439) at `unix_seconds`, `utc_offset_minutes` from UTC.
+#[must_use]
+pub fn local_minute_of_day(unix_seconds: UnixSeconds, utc_offset_minutes: i32) -> Option<u16> {
+    let local_seconds = unix_seconds
+        .as_i64()
+        .saturating_add(i64::from(utc_offset_minutes).saturating_mul(60));
+    let local = OffsetDateTime::from_unix_timestamp(local_seconds).ok()?;
+    Some(u16::from(local.hour()).saturating_mul(60).saturating_add(u16::from(local.minute())))
+}
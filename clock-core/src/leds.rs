@@ -0,0 +1,441 @@
+use crate::Segments;
+
+/// An empty struct useful for defining constants related to 7-segment (or, with the
+/// `fourteen-segment` feature, 14-segment) LED displays.
+pub struct Leds;
+
+// A font mistake fails the build rather than showing up as a garbled display.
+const _: () = assert!(Leds::font_is_consistent(), "segment font is inconsistent");
+
+/// Constants of interest for a 7-segment LED display. A 14-segment display keeps the same
+/// bits for A to F, G (its left middle bar), and the decimal point, so code that draws with
+/// them works on both.
+impl Leds {
+    /// Segment A of the 7-segment display.
+    pub const SEG_A: Segments = 0b_0000_0001;
+    /// Segment B of the 7-segment display.
+    pub const SEG_B: Segments = 0b_0000_0010;
+    /// Segment C of the 7-segment display.
+    pub const SEG_C: Segments = 0b_0000_0100;
+    /// Segment D of the 7-segment display.
+    pub const SEG_D: Segments = 0b_0000_1000;
+    /// Segment E of the 7-segment display.
+    pub const SEG_E: Segments = 0b_0001_0000;
+    /// Segment F of the 7-segment display.
+    pub const SEG_F: Segments = 0b_0010_0000;
+    /// Segment G of the 7-segment display.
+    pub const SEG_G: Segments = 0b_0100_0000;
+    /// Decimal point of the 7-segment display.
+    pub const DECIMAL: Segments = 0b_1000_0000;
+
+    /// Segment G2 of a 14-segment display: the right half of the middle bar.
+    #[cfg(feature = "fourteen-segment")]
+    pub const SEG_G2: Segments = 0b_0000_0001_0000_0000;
+    /// Segment H of a 14-segment display: the upper left diagonal.
+    #[cfg(feature = "fourteen-segment")]
+    pub const SEG_H: Segments = 0b_0000_0010_0000_0000;
+    /// Segment I of a 14-segment display: the upper vertical.
+    #[cfg(feature = "fourteen-segment")]
+    pub const SEG_I: Segments = 0b_0000_0100_0000_0000;
+    /// Segment J of a 14-segment display: the upper right diagonal.
+    #[cfg(feature = "fourteen-segment")]
+    pub const SEG_J: Segments = 0b_0000_1000_0000_0000;
+    /// Segment K of a 14-segment display: the lower left diagonal.
+    #[cfg(feature = "fourteen-segment")]
+    pub const SEG_K: Segments = 0b_0001_0000_0000_0000;
+    /// Segment L of a 14-segment display: the lower vertical.
+    #[cfg(feature = "fourteen-segment")]
+    pub const SEG_L: Segments = 0b_0010_0000_0000_0000;
+    /// Segment M of a 14-segment display: the lower right diagonal.
+    #[cfg(feature = "fourteen-segment")]
+    pub const SEG_M: Segments = 0b_0100_0000_0000_0000;
+
+    /// Array representing the segments for digits 0-9 on a 7-segment display.
+    #[cfg(not(feature = "fourteen-segment"))]
+    pub const DIGITS: [Segments; 10] = [
+        0b_0011_1111, // Digit 0
+        0b_0000_0110, // Digit 1
+        0b_0101_1011, // Digit 2
+        0b_0100_1111, // Digit 3
+        0b_0110_0110, // Digit 4
+        0b_0110_1101, // Digit 5
+        0b_0111_1101, // Digit 6
+        0b_0000_0111, // Digit 7
+        0b_0111_1111, // Digit 8
+        0b_0110_1111, // Digit 9
+    ];
+
+    /// Array representing the segments for digits 0-9 on a 14-segment display. Zero has a
+    /// slash, so it is not mistaken for `O`.
+    #[cfg(feature = "fourteen-segment")]
+    pub const DIGITS: [Segments; 10] = [
+        0b_0001_1000_0011_1111, // Digit 0
+        0b_0000_0000_0000_0110, // Digit 1
+        0b_0000_0001_0101_1011, // Digit 2
+        0b_0000_0001_0000_1111, // Digit 3
+        0b_0000_0001_0110_0110, // Digit 4
+        0b_0000_0001_0110_1101, // Digit 5
+        0b_0000_0001_0111_1101, // Digit 6
+        0b_0000_0000_0000_0111, // Digit 7
+        0b_0000_0001_0111_1111, // Digit 8
+        0b_0000_0001_0110_1111, // Digit 9
+    ];
+
+    /// Representation of a blank space.
+    pub const SPACE: Segments = 0b_0000_0000;
+
+    /// A degree sign (`°`): the top four segments, as a small raised square.
+    #[cfg(not(feature = "fourteen-segment"))]
+    pub const DEGREE: Segments = 0b_0110_0011;
+
+    /// A degree sign (`°`): the top box, closed by both halves of the middle bar.
+    #[cfg(feature = "fourteen-segment")]
+    pub const DEGREE: Segments = 0b_0000_0001_0110_0011;
+
+    /// The segments for `char`: `ASCII_TABLE` plus `°`. Other characters are blank.
+    #[must_use]
+    pub fn segments(char: char) -> Segments {
+        if char == '°' {
+            Self::DEGREE
+        } else {
+            Self::ASCII_TABLE
+                .get(char as usize)
+                .copied()
+                .unwrap_or(Self::SPACE)
+        }
+    }
+
+    /// The character whose segments are exactly `segments`, for debug logs and simulators.
+    /// Where characters share a pattern, the earliest in ASCII wins (so `0`, not `O`).
+    /// `None` if no character matches.
+    #[must_use]
+    pub fn char_for(segments: Segments) -> Option<char> {
+        if segments == Self::DEGREE {
+            return Some('°');
+        }
+        (b' '..=b'~')
+            .find(|&code| Self::ASCII_TABLE.get(usize::from(code)) == Some(&segments))
+            .map(char::from)
+    }
+
+    /// Whether the font is self-consistent: `DIGITS` matches the digits in `ASCII_TABLE`,
+    /// no two digits look alike, and only `!` and `.` light the decimal point, so
+    /// `BitMatrix::set_dot` never turns one glyph into another. Checked at compile time.
+    #[must_use]
+    pub const fn font_is_consistent() -> bool {
+        let mut digits: &[Segments] = &Self::DIGITS;
+        let mut rest_of_table: &[Segments] = &Self::ASCII_TABLE;
+        let mut code = 0_u8;
+        while let Some((&segments, rest)) = rest_of_table.split_first() {
+            if segments & Self::DECIMAL != 0 && code != b'!' && code != b'.' {
+                return false;
+            }
+            if code.is_ascii_digit() {
+                let Some((&digit, rest_of_digits)) = digits.split_first() else {
+                    return false;
+                };
+                if digit != segments {
+                    return false;
+                }
+                digits = rest_of_digits;
+            }
+            code = code.wrapping_add(1);
+            rest_of_table = rest;
+        }
+        digits.is_empty() && Self::digits_are_distinct()
+    }
+
+    /// Whether every entry of `DIGITS` differs from the others.
+    const fn digits_are_distinct() -> bool {
+        let mut outer: &[Segments] = &Self::DIGITS;
+        while let Some((&digit, rest)) = outer.split_first() {
+            let mut inner = rest;
+            while let Some((&other, rest_of_inner)) = inner.split_first() {
+                if digit == other {
+                    return false;
+                }
+                inner = rest_of_inner;
+            }
+            outer = rest;
+        }
+        true
+    }
+
+    /// ASCII table mapping characters to their 7-segment display representations.
+    /// Control characters (0-31) and delete (127) are represented as blank spaces.
+    /// Uppercase and lowercase letters are mostly mapped to the same segments for simplicity.
+    #[cfg(not(feature = "fourteen-segment"))]
+    pub const ASCII_TABLE: [Segments; 128] = [
+        // Control characters (0-31) + space (32)
+        0b_0000_0000,
+        0b_0000_0000,
+        0b_0000_0000,
+        0b_0000_0000,
+        0b_0000_0000, // 0-4
+        0b_0000_0000,
+        0b_0000_0000,
+        0b_0000_0000,
+        0b_0000_0000,
+        0b_0000_0000, // 5-9
+        0b_0000_0000,
+        0b_0000_0000,
+        0b_0000_0000,
+        0b_0000_0000,
+        0b_0000_0000, // 10-14
+        0b_0000_0000,
+        0b_0000_0000,
+        0b_0000_0000,
+        0b_0000_0000,
+        0b_0000_0000, // 15-19
+        0b_0000_0000,
+        0b_0000_0000,
+        0b_0000_0000,
+        0b_0000_0000,
+        0b_0000_0000, //  20-24
+        0b_0000_0000,
+        0b_0000_0000,
+        0b_0000_0000,
+        0b_0000_0000,
+        0b_0000_0000, //  25-29
+        0b_0000_0000,
+        0b_0000_0000,
+        0b_0000_0000, // 30-32
+        // Symbols (33-47)
+        0b_1000_0110, // !
+        0b_0000_0000, // "
+        0b_0000_0000, // #
+        0b_0000_0000, // $
+        0b_0000_0000, // %
+        0b_0000_0000, // &
+        0b_0000_0000, // '
+        0b_0000_0000, // (
+        0b_0000_0000, // )
+        0b_0000_0000, // *
+        0b_0000_0000, // +
+        0b_0000_0000, // ,
+        0b_0100_0000, // -
+        0b_1000_0000, // .
+        0b_0000_0000, // /
+        // Numbers (48-57)
+        0b_0011_1111, // 0
+        0b_0000_0110, // 1
+        0b_0101_1011, // 2
+        0b_0100_1111, // 3
+        0b_0110_0110, // 4
+        0b_0110_1101, // 5
+        0b_0111_1101, // 6
+        0b_0000_0111, // 7
+        0b_0111_1111, // 8
+        0b_0110_1111, // 9
+        // Symbols (58-64)
+        0b_0000_0000, // :
+        0b_0000_0000, // ;
+        0b_0000_0000, // <
+        0b_0000_0000, // =
+        0b_0000_0000, // >
+        0b_0000_0000, // ?
+        0b_0000_0000, // @
+        // Uppercase letters (65-90)
+        0b_0111_0111, // A
+        0b_0111_1100, // B (same as b)
+        0b_0011_1001, // C
+        0b_0101_1110, // D (same as d)
+        0b_0111_1001, // E
+        0b_0111_0001, // F
+        0b_0011_1101, // G (same as 9)
+        0b_0111_0110, // H
+        0b_0000_0110, // I (same as 1)
+        0b_0001_1110, // J
+        0b_0111_0110, // K (approximation)
+        0b_0011_1000, // L
+        0b_0001_0101, // M (arbitrary, no good match)
+        0b_0101_0100, // N
+        0b_0011_1111, // O (same as 0)
+        0b_0111_0011, // P
+        0b_0110_0111, // Q
+        0b_0101_0000, // R
+        0b_0110_1101, // S (same as 5)
+        0b_0111_1000, // T
+        0b_0011_1110, // U
+        0b_0010_1010, // V (arbitrary, no good match)
+        0b_0001_1101, // W (arbitrary, no good match)
+        0b_0111_0110, // X (same as H)
+        0b_0110_1110, // Y
+        0b_0101_1011, // Z (same as 2)
+        // Symbols (91-96)
+        0b_0011_1001, // [
+        0b_0000_0000, // \
+        0b_0000_1111, // ]
+        0b_0000_0000, // ^
+        0b_0000_1000, // _
+        0b_0000_0000, // `
+        // Lowercase letters (97-122), mostly reusing uppercase for simplicity
+        0b_0111_0111, // A
+        0b_0111_1100, // B (same as b)
+        0b_0011_1001, // C
+        0b_0101_1110, // D (same as d)
+        0b_0111_1001, // E
+        0b_0111_0001, // F
+        0b_0011_1101, // G (same as 9)
+        0b_0111_0100, // H
+        0b_0000_0110, // I (same as 1)
+        0b_0001_1110, // J
+        0b_0111_0110, // K (approximation)
+        0b_0011_1000, // L
+        0b_0001_0101, // M (arbitrary, no good match)
+        0b_0101_0100, // N
+        0b_0011_1111, // O (same as 0)
+        0b_0111_0011, // P
+        0b_0110_0111, // Q
+        0b_0101_0000, // R
+        0b_0110_1101, // S (same as 5)
+        0b_0111_1000, // T
+        0b_0011_1110, // U
+        0b_0010_1010, // V (arbitrary, no good match)
+        0b_0001_1101, // W (arbitrary, no good match)
+        0b_0111_0110, // X (same as H)
+        0b_0110_1110, // Y
+        0b_0101_1011, // Z (same as 2)
+        // Placeholder for simplicity
+        0b_0011_1001, // '{' (123)
+        0b_0000_0110, // '|' (124)
+        0b_0000_1111, // '}' (125)
+        0b_0100_0000, // '~' (126)
+        0b_0000_0000, // delete (127)
+    ];
+    /// ASCII table mapping characters to their 14-segment display representations.
+    /// Control characters (0-31) and delete (127) are represented as blank spaces.
+    /// Every letter has its own shape, so scrolled messages read as words; lowercase letters
+    /// use the uppercase shapes.
+    #[cfg(feature = "fourteen-segment")]
+    pub const ASCII_TABLE: [Segments; 128] = [
+        0b_0000_0000_0000_0000, // control (0)
+        0b_0000_0000_0000_0000, // control (1)
+        0b_0000_0000_0000_0000, // control (2)
+        0b_0000_0000_0000_0000, // control (3)
+        0b_0000_0000_0000_0000, // control (4)
+        0b_0000_0000_0000_0000, // control (5)
+        0b_0000_0000_0000_0000, // control (6)
+        0b_0000_0000_0000_0000, // control (7)
+        0b_0000_0000_0000_0000, // control (8)
+        0b_0000_0000_0000_0000, // control (9)
+        0b_0000_0000_0000_0000, // control (10)
+        0b_0000_0000_0000_0000, // control (11)
+        0b_0000_0000_0000_0000, // control (12)
+        0b_0000_0000_0000_0000, // control (13)
+        0b_0000_0000_0000_0000, // control (14)
+        0b_0000_0000_0000_0000, // control (15)
+        0b_0000_0000_0000_0000, // control (16)
+        0b_0000_0000_0000_0000, // control (17)
+        0b_0000_0000_0000_0000, // control (18)
+        0b_0000_0000_0000_0000, // control (19)
+        0b_0000_0000_0000_0000, // control (20)
+        0b_0000_0000_0000_0000, // control (21)
+        0b_0000_0000_0000_0000, // control (22)
+        0b_0000_0000_0000_0000, // control (23)
+        0b_0000_0000_0000_0000, // control (24)
+        0b_0000_0000_0000_0000, // control (25)
+        0b_0000_0000_0000_0000, // control (26)
+        0b_0000_0000_0000_0000, // control (27)
+        0b_0000_0000_0000_0000, // control (28)
+        0b_0000_0000_0000_0000, // control (29)
+        0b_0000_0000_0000_0000, // control (30)
+        0b_0000_0000_0000_0000, // control (31)
+        0b_0000_0000_0000_0000, // space (32)
+        0b_0000_0000_1000_0110, // !
+        0b_0000_0100_0000_0010, // "
+        0b_0010_0101_0100_1110, // #
+        0b_0010_0101_0110_1101, // $
+        0b_0001_1000_0010_0100, // %
+        0b_0100_0110_0101_1001, // &
+        0b_0000_0100_0000_0000, // '
+        0b_0100_1000_0000_0000, // (
+        0b_0001_0010_0000_0000, // )
+        0b_0111_1111_0100_0000, // *
+        0b_0010_0101_0100_0000, // +
+        0b_0001_0000_0000_0000, // ,
+        0b_0000_0001_0100_0000, // -
+        0b_0000_0000_1000_0000, // .
+        0b_0001_1000_0000_0000, // /
+        0b_0001_1000_0011_1111, // 0
+        0b_0000_0000_0000_0110, // 1
+        0b_0000_0001_0101_1011, // 2
+        0b_0000_0001_0000_1111, // 3
+        0b_0000_0001_0110_0110, // 4
+        0b_0000_0001_0110_1101, // 5
+        0b_0000_0001_0111_1101, // 6
+        0b_0000_0000_0000_0111, // 7
+        0b_0000_0001_0111_1111, // 8
+        0b_0000_0001_0110_1111, // 9
+        0b_0010_0100_0000_0000, // :
+        0b_0001_0100_0000_0000, // ;
+        0b_0100_1000_0000_0000, // <
+        0b_0000_0001_0100_1000, // =
+        0b_0001_0010_0000_0000, // >
+        0b_0010_0001_0000_0011, // ?
+        0b_0000_0101_0011_1011, // @
+        0b_0000_0001_0111_0111, // A
+        0b_0010_0101_0000_1111, // B
+        0b_0000_0000_0011_1001, // C
+        0b_0010_0100_0000_1111, // D
+        0b_0000_0000_0111_1001, // E
+        0b_0000_0000_0111_0001, // F
+        0b_0000_0001_0011_1101, // G
+        0b_0000_0001_0111_0110, // H
+        0b_0010_0100_0000_1001, // I
+        0b_0000_0000_0001_1110, // J
+        0b_0100_1000_0111_0000, // K
+        0b_0000_0000_0011_1000, // L
+        0b_0000_1010_0011_0110, // M
+        0b_0100_0010_0011_0110, // N
+        0b_0000_0000_0011_1111, // O
+        0b_0000_0001_0111_0011, // P
+        0b_0100_0000_0011_1111, // Q
+        0b_0100_0001_0111_0011, // R
+        0b_0000_0011_0000_1101, // S
+        0b_0010_0100_0000_0001, // T
+        0b_0000_0000_0011_1110, // U
+        0b_0001_1000_0011_0000, // V
+        0b_0101_0000_0011_0110, // W
+        0b_0101_1010_0000_0000, // X
+        0b_0010_1010_0000_0000, // Y
+        0b_0001_1000_0000_1001, // Z
+        0b_0000_0000_0011_1001, // [
+        0b_0100_0010_0000_0000, // \
+        0b_0000_0000_0000_1111, // ]
+        0b_0101_0000_0000_0000, // ^
+        0b_0000_0000_0000_1000, // _
+        0b_0000_0010_0000_0000, // `
+        0b_0000_0001_0111_0111, // a (same as A)
+        0b_0010_0101_0000_1111, // b (same as B)
+        0b_0000_0000_0011_1001, // c (same as C)
+        0b_0010_0100_0000_1111, // d (same as D)
+        0b_0000_0000_0111_1001, // e (same as E)
+        0b_0000_0000_0111_0001, // f (same as F)
+        0b_0000_0001_0011_1101, // g (same as G)
+        0b_0000_0001_0111_0110, // h (same as H)
+        0b_0010_0100_0000_1001, // i (same as I)
+        0b_0000_0000_0001_1110, // j (same as J)
+        0b_0100_1000_0111_0000, // k (same as K)
+        0b_0000_0000_0011_1000, // l (same as L)
+        0b_0000_1010_0011_0110, // m (same as M)
+        0b_0100_0010_0011_0110, // n (same as N)
+        0b_0000_0000_0011_1111, // o (same as O)
+        0b_0000_0001_0111_0011, // p (same as P)
+        0b_0100_0000_0011_1111, // q (same as Q)
+        0b_0100_0001_0111_0011, // r (same as R)
+        0b_0000_0011_0000_1101, // s (same as S)
+        0b_0010_0100_0000_0001, // t (same as T)
+        0b_0000_0000_0011_1110, // u (same as U)
+        0b_0001_1000_0011_0000, // v (same as V)
+        0b_0101_0000_0011_0110, // w (same as W)
+        0b_0101_1010_0000_0000, // x (same as X)
+        0b_0010_1010_0000_0000, // y (same as Y)
+        0b_0001_1000_0000_1001, // z (same as Z)
+        0b_0001_0010_0100_1001, // {
+        0b_0010_0100_0000_0000, // |
+        0b_0100_1001_0000_1001, // }
+        0b_0000_0001_0100_0000, // ~
+        0b_0000_0000_0000_0000, // delete (127)
+    ];
+}
@@ -0,0 +1,125 @@
+//! Hardware-free clock logic: time keeping, display states, and the 7-segment (or 14-segment)
+//! font.
+//!
+//! Nothing here touches RP2040 peripherals or the network, so other boards can reuse it and
+//! it can be tested on the host. Enable the `defmt` feature to derive `defmt::Format` and log,
+//! and the `host` feature for the mock time sync and `WiFi` that the scenario tests drive (with
+//! the `sim` feature's `MockInstant` time driver).
+#![cfg_attr(not(test), no_std)]
+
+mod battery;
+mod bit_matrix;
+mod blink_state;
+mod brightness;
+mod carousel;
+mod climate;
+mod clock_face;
+mod clock_state;
+mod clock_time;
+mod config;
+mod display_content;
+mod error;
+mod exixe;
+mod gesture_timings;
+mod http_response;
+mod json_lite;
+mod led_colors;
+mod leds;
+mod melody;
+mod message;
+#[cfg(feature = "sim")]
+mod mock_instant;
+mod mqtt;
+mod nec;
+mod nmea;
+mod ntp_auth;
+mod page;
+mod pending_settings;
+mod provisioning;
+mod quiet_hours;
+mod radio_time;
+mod reminders;
+mod render_util;
+mod schedule;
+mod scroll;
+mod settings;
+mod shared_constants;
+#[cfg(feature = "host")]
+mod sim;
+mod skew_history;
+mod solar;
+mod speech;
+mod supply;
+mod temperature;
+mod text;
+mod time_sanity;
+mod time_source;
+mod time_sync;
+mod tls_pin;
+mod touch;
+mod unix_seconds;
+
+pub use battery::{battery_glyph_level, BatteryChemistry, BATTERY_GLYPH_LEVELS};
+pub use bit_matrix::{BitMatrix, CellText};
+pub use blink_state::BlinkState;
+pub use brightness::Brightness;
+pub use carousel::{Carousel, CarouselItem, MAX_CAROUSEL_ITEMS, MAX_MESSAGE_LEN};
+pub use climate::{climate_log_head, ClimateSample, CLIMATE_CSV_HEADER, CLIMATE_SAMPLE_BYTES};
+pub use clock_face::ClockFace;
+pub use clock_state::{ClockState, ModeConditions, ModeDescriptor, Press, MODES};
+pub use clock_time::{parse_time_of_day, ClockTime, LocalTime, MidnightTick};
+pub use config::{Config, CONFIG_BYTES, MIN_BLINK_MS};
+pub use display_content::DisplayContent;
+pub use error::{Error, Result};
+pub use exixe::{exixe_command, nixie_digit, EXIXE_COMMAND_LEN};
+pub use gesture_timings::{GestureTimings, GESTURE_TIMINGS_BYTES};
+pub use http_response::HttpResponse;
+pub use json_lite::find_number;
+pub use led_colors::{local_minute_of_day, DigitColors, Rgb};
+pub use leds::Leds;
+pub use melody::{AlarmSound, Melody, Note};
+pub use message::{Message, MAX_MESSAGE_REPEATS, MAX_PUSHED_MESSAGE_LEN};
+#[cfg(feature = "sim")]
+pub use mock_instant::MockInstant;
+pub use mqtt::{
+    mqtt_connect, mqtt_packet_len, mqtt_publish, mqtt_subscribe, MqttCredentials, MqttPacket,
+    MQTT_DISCONNECT, MQTT_PINGREQ, MQTT_PORT,
+};
+pub use nec::{NecDecoder, NecEvent};
+pub use nmea::{NmeaFix, MAX_NMEA_SENTENCE_LEN};
+pub use ntp_auth::{ntp_request, verify_ntp_auth, NTP_AUTH_CODE_LEN, NTP_PACKET_LEN};
+pub use page::{Page, PageRotation};
+pub use pending_settings::{
+    EditStep, PendingSettings, MAX_UTC_OFFSET_HOURS, MIN_UTC_OFFSET_HOURS,
+};
+pub use provisioning::{
+    Bssid, Provisioning, WifiCountry, MAX_SSID_LEN, MAX_WIFI_PASSWORD_LEN, PROVISIONING_BYTES,
+    PROVISIONING_OFFSET, PROVISIONING_VERSION,
+};
+pub use quiet_hours::QuietHours;
+pub use radio_time::{RadioStation, RadioTimeDecoder};
+pub use reminders::{Reminder, Reminders, MAX_REMINDERS, REMINDERS_BYTES, REMINDER_LABEL_LEN};
+pub use render_util::{
+    centered, centered_index, hour_digits, join, right_align, temperature_chars, two_digits,
+};
+pub use schedule::{Schedule, When, MAX_SCHEDULED_JOBS, SCHEDULE_RECHECK};
+pub use scroll::{scroll_frame, scroll_frame_count};
+pub use settings::Settings;
+pub use shared_constants::*;
+#[cfg(feature = "host")]
+pub use sim::{MockWifi, Scenario, ScriptedTimeSync, ShownFrame, MAX_SCRIPTED_EVENTS};
+pub use skew_history::{SkewHistory, SKEW_HISTORY_LEN};
+pub use solar::{sun_times, Location, SolarDimming, SunTimes};
+pub use speech::{spoken_time, VoiceBank, Word, MAX_SPOKEN_WORDS, VOICE_CLIPS};
+pub use supply::{SupplyConfig, SupplyLevel, SUPPLY_HYSTERESIS_MILLIVOLTS};
+pub use temperature::Temperature;
+pub use text::Text;
+pub use time_sanity::{SyncRejection, TimeSanity, EARLIEST_PLAUSIBLE};
+pub use time_source::{Arbitration, TimeArbiter, TimeSource, SOURCE_STALE_AFTER};
+pub use time_sync::{SyncAccuracy, SyncError, TimeSyncEvent, TimeSyncPort, WifiPort};
+pub use tls_pin::{
+    certificate_fingerprint, certificate_verify_message, find_tls_pin, p256_public_key,
+    parse_fingerprint, CERTIFICATE_VERIFY_MESSAGE_LEN, CERT_FINGERPRINT_LEN,
+};
+pub use touch::TouchDetector;
+pub use unix_seconds::{LeapIndicator, UnixSeconds};
@@ -0,0 +1,280 @@
+//! Melodies in a compact, RTTTL-like text format, and the alarm sounds built from them.
+//!
+//! A melody is optional defaults, a `:`, then comma-separated notes, such as
+//! `b=160,o=5:e,e,f,g,2p`. The defaults are `d` (the note length, default 4 for a quarter
+//! note), `o` (the octave, 4 to 7, default 6), and `b` (quarter notes a minute, default 63).
+//! Each note is an optional length (1, 2, 4, 8, 16, or 32), a letter `a` to `g` or `p` for a
+//! rest, an optional `#` for a sharp, an optional octave, and an optional `.` that makes it
+//! half again as long, such as `8c#6.`. A leading name (`name:defaults:notes`), as in RTTTL
+//! ringtones, is ignored.
+
+use embassy_time::Duration;
+
+use crate::{Error, Result};
+
+/// The frequencies, in hertz, of the semitones from C4 up to C5.
+const OCTAVE_4_HZ: [u16; 13] = [262, 277, 294, 311, 330, 349, 370, 392, 415, 440, 466, 494, 523];
+
+/// The octaves a note may name. Octave 7 tops out near a piezo's loudest pitch.
+const OCTAVES: (u8, u8) = (4, 7);
+
+/// How many quarter notes a minute a melody may play, at least and at most.
+const BEATS_PER_MINUTE: (u16, u16) = (25, 900);
+
+/// A whole note's length at one beat a minute: four beats of a minute each, in milliseconds.
+const WHOLE_NOTE_MS_AT_ONE_BPM: u32 = 240_000;
+
+/// One note (or rest) of a melody.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Note {
+    /// The pitch, or `None` for a rest.
+    pub pitch_hz: Option<u16>,
+    /// How long the note lasts.
+    pub duration: Duration,
+}
+
+/// A melody checked by `Melody::parse`, whose notes can be played back any number of times.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy)]
+pub struct Melody<'a> {
+    notes: &'a str,
+    defaults: Defaults,
+}
+
+impl<'a> Melody<'a> {
+    /// Parses a melody in the format described in this module.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a default or a note is malformed, or there are no notes.
+    pub fn parse(text: &'a str) -> Result<Self> {
+        let (head, notes) = text.rsplit_once(':').unwrap_or(("", text));
+        let settings = head.rsplit_once(':').map_or(head, |(_, settings)| settings);
+        let melody = Self {
+            notes,
+            defaults: Defaults::parse(settings).ok_or(Error::MelodyFormat)?,
+        };
+        if note_texts(notes).next().is_none()
+            || note_texts(notes).any(|note| Note::parse(note, melody.defaults).is_none())
+        {
+            return Err(Error::MelodyFormat);
+        }
+        Ok(melody)
+    }
+
+    /// The notes, in order.
+    pub fn notes(self) -> impl Iterator<Item = Note> + 'a {
+        note_texts(self.notes).filter_map(move |note| Note::parse(note, self.defaults))
+    }
+}
+
+/// Each note's text in a comma-separated list, without surrounding spaces.
+fn note_texts(notes: &str) -> impl Iterator<Item = &str> {
+    notes
+        .split(',')
+        .map(str::trim)
+        .filter(|note| !note.is_empty())
+}
+
+/// The length, octave, and tempo a note uses when it does not give its own.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy)]
+struct Defaults {
+    divisor: u16,
+    octave: u8,
+    beats_per_minute: u16,
+}
+
+impl Defaults {
+    /// Parses `d=4,o=6,b=63` (in any order, any of them left out).
+    fn parse(settings: &str) -> Option<Self> {
+        let mut defaults = Self {
+            divisor: 4,
+            octave: 6,
+            beats_per_minute: 63,
+        };
+        for setting in settings
+            .split(',')
+            .map(str::trim)
+            .filter(|setting| !setting.is_empty())
+        {
+            let (name, value) = setting.split_once('=')?;
+            match name.trim() {
+                "d" => defaults.divisor = parse_divisor(value.trim())?,
+                "o" => defaults.octave = parse_octave(value.trim())?,
+                "b" => {
+                    let (min, max) = BEATS_PER_MINUTE;
+                    defaults.beats_per_minute = value
+                        .trim()
+                        .parse()
+                        .ok()
+                        .filter(|bpm| (min..=max).contains(bpm))?;
+                }
+                _ => return None,
+            }
+        }
+        Some(defaults)
+    }
+}
+
+impl Note {
+    /// Parses one note such as `8c#6.`, filling in what it leaves out from `defaults`.
+    fn parse(text: &str, defaults: Defaults) -> Option<Self> {
+        let letter_at = text.find(|char: char| char.is_ascii_alphabetic())?;
+        let (length, after_length) = text.split_at(letter_at);
+        let divisor = if length.is_empty() {
+            defaults.divisor
+        } else {
+            parse_divisor(length)?
+        };
+        let mut chars = after_length.chars();
+        let letter = chars.next()?.to_ascii_lowercase();
+        let after_letter = chars.as_str();
+        let (sharp, after_sharp) = after_letter
+            .strip_prefix('#')
+            .map_or((false, after_letter), |rest| (true, rest));
+        let (dot_before, after_dot) = after_sharp
+            .strip_prefix('.')
+            .map_or((false, after_sharp), |rest| (true, rest));
+        let (octave_text, dot_after) = after_dot
+            .strip_suffix('.')
+            .map_or((after_dot, false), |rest| (rest, true));
+        if dot_before && dot_after {
+            return None;
+        }
+        let octave = if octave_text.is_empty() {
+            defaults.octave
+        } else {
+            parse_octave(octave_text)?
+        };
+        let pitch_hz = match (letter, sharp) {
+            ('p', false) => None,
+            ('p', true) => return None,
+            _ => Some(pitch_hz(letter, sharp, octave)?),
+        };
+        Some(Self {
+            pitch_hz,
+            duration: note_duration(divisor, defaults.beats_per_minute, dot_before || dot_after),
+        })
+    }
+}
+
+/// Parses a note length: 1 (a whole note), 2, 4, 8, 16, or 32.
+fn parse_divisor(text: &str) -> Option<u16> {
+    text.parse()
+        .ok()
+        .filter(|divisor| [1, 2, 4, 8, 16, 32].contains(divisor))
+}
+
+/// Parses an octave from 4 to 7.
+fn parse_octave(text: &str) -> Option<u8> {
+    let (min, max) = OCTAVES;
+    text.parse()
+        .ok()
+        .filter(|octave| (min..=max).contains(octave))
+}
+
+/// The frequency of `letter` (`a` to `g`), raised a semitone if `sharp`, in `octave`.
+fn pitch_hz(letter: char, sharp: bool, octave: u8) -> Option<u16> {
+    let semitone: usize = match letter {
+        'c' => 0,
+        'd' => 2,
+        'e' => 4,
+        'f' => 5,
+        'g' => 7,
+        'a' => 9,
+        'b' => 11,
+        _ => return None,
+    };
+    let hz = *OCTAVE_4_HZ.get(semitone.saturating_add(usize::from(sharp)))?;
+    // Each octave above the fourth doubles the frequency; C8 (the highest, from `b#7`) is
+    // still well within a `u16`.
+    hz.checked_shl(u32::from(octave.saturating_sub(OCTAVES.0)))
+}
+
+/// How long a note of `divisor` (4 for a quarter note) lasts at `beats_per_minute`, half
+/// again as long if `dotted`.
+fn note_duration(divisor: u16, beats_per_minute: u16, dotted: bool) -> Duration {
+    let (numerator, denominator) = if dotted { (3, 2) } else { (1, 1) };
+    let millis = WHOLE_NOTE_MS_AT_ONE_BPM
+        .saturating_mul(numerator)
+        .checked_div(
+            u32::from(divisor)
+                .saturating_mul(u32::from(beats_per_minute))
+                .saturating_mul(denominator),
+        )
+        .unwrap_or(0);
+    Duration::from_millis(u64::from(millis))
+}
+
+/// What the buzzer plays when an alarm goes off, repeated until it is silenced.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlarmSound {
+    /// One short beep a second.
+    Beep,
+    /// Four quick beeps every two seconds.
+    Beeps,
+    /// The first two changes of the Westminster chimes.
+    Chime,
+    /// The opening of "Ode to Joy".
+    Tune,
+    /// The melody in the firmware's `ALARM_MELODY` setting.
+    Custom,
+}
+
+impl AlarmSound {
+    /// Every sound, in the order of its saved index.
+    const ALL: [Self; 5] = [Self::Beep, Self::Beeps, Self::Chime, Self::Tune, Self::Custom];
+
+    /// Parses `beep`, `beeps`, `chime`, `tune`, or `custom` (in any case).
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|sound| sound.name().eq_ignore_ascii_case(text))
+    }
+
+    /// The name `parse` reads.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Beep => "beep",
+            Self::Beeps => "beeps",
+            Self::Chime => "chime",
+            Self::Tune => "tune",
+            Self::Custom => "custom",
+        }
+    }
+
+    /// The built-in melody, or `None` for `Custom`, whose melody the firmware supplies.
+    #[must_use]
+    pub const fn built_in_melody(self) -> Option<&'static str> {
+        match self {
+            Self::Beep => Some("b=120,o=7:8a,4p."),
+            Self::Beeps => Some("b=120,o=7:16a,16p,16a,16p,16a,16p,16a,16p,2p"),
+            Self::Chime => Some("b=80,o=5:g#,f#,e,2b4,e,g#,f#,2e,1p"),
+            Self::Tune => Some("b=160,o=5:e,e,f,g,g,f,e,d,c,c,d,e,e.,8d,2d,2p"),
+            Self::Custom => None,
+        }
+    }
+
+    /// The index `from_index` reads, for saving the sound.
+    #[must_use]
+    pub const fn index(self) -> u16 {
+        match self {
+            Self::Beep => 0,
+            Self::Beeps => 1,
+            Self::Chime => 2,
+            Self::Tune => 3,
+            Self::Custom => 4,
+        }
+    }
+
+    /// The sound saved as `index`, or `None` if there is no such sound.
+    #[must_use]
+    pub fn from_index(index: u16) -> Option<Self> {
+        Self::ALL.get(usize::from(index)).copied()
+    }
+}
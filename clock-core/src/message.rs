@@ -0,0 +1,95 @@
+use embassy_time::Duration;
+
+use crate::{
+    scroll::{scroll_frame, scroll_frame_count, SCROLL_STEP},
+    BitMatrix, BlinkState, ClockTime, Error, Result, Text,
+};
+
+/// The longest text a pushed `Message` may hold.
+pub const MAX_PUSHED_MESSAGE_LEN: usize = 32;
+
+/// The most times a `Message` may scroll past.
+pub const MAX_MESSAGE_REPEATS: u8 = 20;
+
+/// Text pushed over the network to scroll across the display a set number of times, in
+/// place of whatever the clock was showing.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy)]
+pub struct Message {
+    text: Text<MAX_PUSHED_MESSAGE_LEN>,
+    repeats: u8,
+    blink_state: BlinkState,
+}
+
+impl Message {
+    /// Creates a message that scrolls `text` past `repeats` times, blinking if `blink_state`
+    /// says so.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `text` is empty or longer than `MAX_PUSHED_MESSAGE_LEN`, or
+    /// `repeats` is not 1 to `MAX_MESSAGE_REPEATS`.
+    pub fn new(text: &str, repeats: u8, blink_state: BlinkState) -> Result<Self> {
+        let text = Text::try_from(text).map_err(|_| Error::MessageFormat)?;
+        Self::from_text(text, repeats, blink_state)
+    }
+
+    /// Creates a message from text already built, such as with `write!`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `text` is empty or `repeats` is not 1 to `MAX_MESSAGE_REPEATS`.
+    pub fn from_text(
+        text: Text<MAX_PUSHED_MESSAGE_LEN>,
+        repeats: u8,
+        blink_state: BlinkState,
+    ) -> Result<Self> {
+        if text.is_empty() || !(1..=MAX_MESSAGE_REPEATS).contains(&repeats) {
+            return Err(Error::MessageFormat);
+        }
+        Ok(Self {
+            text,
+            repeats,
+            blink_state,
+        })
+    }
+
+    /// The text that scrolls.
+    #[must_use]
+    pub fn text(&self) -> &[char] {
+        self.text.chars()
+    }
+
+    /// How long all the repeats take to scroll past.
+    #[must_use]
+    #[expect(
+        clippy::arithmetic_side_effects,
+        reason = "At most 40 steps, 20 times, so far from overflowing."
+    )]
+    pub const fn duration(&self) -> Duration {
+        let steps = scroll_frame_count(self.text.len()) as u64 * self.repeats as u64;
+        Duration::from_ticks(SCROLL_STEP.as_ticks() * steps)
+    }
+
+    /// The display `shown_for` after the message started, and how long until it changes.
+    /// `None` once the last repeat has scrolled off.
+    #[must_use]
+    #[expect(
+        clippy::arithmetic_side_effects,
+        clippy::integer_division_remainder_used,
+        clippy::cast_possible_truncation,
+        reason = "The step length and frame count are non-zero, and steps fit in a usize."
+    )]
+    pub fn render(&self, shown_for: Duration) -> Option<(BlinkState, BitMatrix, Duration)> {
+        if shown_for >= self.duration() {
+            return None;
+        }
+        let step = (shown_for.as_ticks() / SCROLL_STEP.as_ticks()) as usize;
+        let frame = scroll_frame(self.text(), step % scroll_frame_count(self.text.len()));
+        Some((
+            self.blink_state,
+            BitMatrix::from_text(&frame),
+            ClockTime::till_next(shown_for, SCROLL_STEP),
+        ))
+    }
+}
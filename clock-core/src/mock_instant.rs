@@ -0,0 +1,110 @@
+//! A time driver for host tests whose time moves only when a test moves it, so a test can
+//! jump hours ahead in one step and check exactly what the clock shows on each side of a
+//! boundary (the midnight wrap, 12:59 to 1:00).
+//!
+//! It replaces `embassy-time`'s `std` driver: `Instant::now()` starts at zero and stays there
+//! until `MockInstant::advance`. Timers wake once the time is moved past their deadline.
+
+extern crate alloc;
+#[cfg(not(test))]
+extern crate std;
+
+use alloc::vec::Vec;
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::task::Waker;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use embassy_time::{Duration, Instant};
+use embassy_time_driver::Driver;
+
+/// Held by the test that owns the time. Every test in a binary shares the one driver, and
+/// tests run on several threads, so they take turns.
+static OWNER: Mutex<()> = Mutex::new(());
+
+/// Ticks since the start, and the wakers of timers waiting for a later tick.
+struct MockDriver {
+    ticks: AtomicU64,
+    wakers: Mutex<Vec<(u64, Waker)>>,
+}
+
+embassy_time_driver::time_driver_impl!(static DRIVER: MockDriver = MockDriver {
+    ticks: AtomicU64::new(0),
+    wakers: Mutex::new(Vec::new()),
+});
+
+impl Driver for MockDriver {
+    fn now(&self) -> u64 {
+        self.ticks.load(Ordering::SeqCst)
+    }
+
+    fn schedule_wake(&self, at: u64, waker: &Waker) {
+        if at <= self.now() {
+            waker.wake_by_ref();
+        } else {
+            self.wakers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push((at, waker.clone()));
+        }
+    }
+}
+
+impl MockDriver {
+    /// Moves the time to `ticks` and wakes the timers that are now due.
+    fn set(&self, ticks: u64) {
+        self.ticks.store(ticks, Ordering::SeqCst);
+        let wakers =
+            core::mem::take(&mut *self.wakers.lock().unwrap_or_else(PoisonError::into_inner));
+        let (due, waiting): (Vec<_>, Vec<_>) =
+            wakers.into_iter().partition(|(at, _)| *at <= ticks);
+        self.wakers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .extend(waiting);
+        for (_, waker) in due {
+            waker.wake();
+        }
+    }
+}
+
+/// The time the `sim` driver keeps, owned by one test at a time.
+pub struct MockInstant {
+    _owner: MutexGuard<'static, ()>,
+}
+
+// Taking `&self` means only the test holding the time can move it
+impl MockInstant {
+    /// Takes the time for this test, waiting for any other test that holds it, and resets it
+    /// to zero (forgetting the timers of earlier tests). It stays where this test leaves it
+    /// until the `MockInstant` is dropped.
+    #[must_use]
+    pub fn take() -> Self {
+        let owner = OWNER.lock().unwrap_or_else(PoisonError::into_inner);
+        DRIVER
+            .wakers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+        DRIVER.set(0);
+        Self { _owner: owner }
+    }
+
+    /// The current time, as `Instant::now()` reports it.
+    #[must_use]
+    pub fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    /// Moves the time on by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        self.advance_to(Instant::now().checked_add(duration).unwrap_or(Instant::MAX));
+    }
+
+    /// Moves the time on to `instant`. An instant before now is ignored: time never runs
+    /// backward, as `embassy-time` expects.
+    pub fn advance_to(&self, instant: Instant) {
+        if instant > Instant::now() {
+            DRIVER.set(instant.as_ticks());
+        }
+    }
+}
@@ -0,0 +1,266 @@
+//! Just enough MQTT 3.1.1 for a client that publishes and subscribes at quality of service 0:
+//! the packets it sends, written into a caller's buffer, and the ones a broker sends back,
+//! read from one.
+
+use crate::{Error, Result};
+
+/// The port a broker listens on without TLS.
+pub const MQTT_PORT: u16 = 1883;
+
+/// A `PINGREQ`, which keeps the connection open while there is nothing else to send.
+pub const MQTT_PINGREQ: [u8; 2] = [0xC0, 0x00];
+
+/// A `DISCONNECT`, sent before closing the connection.
+pub const MQTT_DISCONNECT: [u8; 2] = [0xE0, 0x00];
+
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+/// `SUBSCRIBE` has its reserved flags set to 0b0010.
+const SUBSCRIBE: u8 = 0x82;
+const SUBACK: u8 = 0x90;
+const PINGRESP: u8 = 0xD0;
+
+/// `CONNECT` flags: a clean session, and whether a user name and password follow.
+const CLEAN_SESSION: u8 = 0x02;
+const PASSWORD_FLAG: u8 = 0x40;
+const USER_NAME_FLAG: u8 = 0x80;
+
+/// `PUBLISH` flag: the broker keeps the message for clients that subscribe later.
+const RETAIN: u8 = 0x01;
+
+/// The largest remaining length four length bytes can encode.
+const MAX_REMAINING_LEN: usize = 268_435_455;
+
+/// The user name and password a broker may ask for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MqttCredentials<'a> {
+    /// The user name.
+    pub user: &'a str,
+    /// The password.
+    pub password: &'a str,
+}
+
+/// Writes a `CONNECT` for a clean session as `client_id` into `buffer`. Returns its length,
+/// or `None` if it does not fit.
+///
+/// The broker drops the connection if nothing arrives for one and a half times
+/// `keep_alive_seconds`.
+#[must_use]
+pub fn mqtt_connect(
+    buffer: &mut [u8],
+    client_id: &str,
+    credentials: Option<MqttCredentials<'_>>,
+    keep_alive_seconds: u16,
+) -> Option<usize> {
+    let flags = credentials.map_or(CLEAN_SESSION, |_| {
+        CLEAN_SESSION | USER_NAME_FLAG | PASSWORD_FLAG
+    });
+    let credentials_len = credentials.map_or(0, |given| {
+        string_len(given.user).saturating_add(string_len(given.password))
+    });
+    // Protocol name, level, flags, and keep alive, then the payload
+    let remaining = 10_usize
+        .saturating_add(string_len(client_id))
+        .saturating_add(credentials_len);
+    let mut writer = Writer::start(buffer, CONNECT, remaining)?;
+    writer.string("MQTT")?;
+    writer.bytes(&[4, flags])?;
+    writer.bytes(&keep_alive_seconds.to_be_bytes())?;
+    writer.string(client_id)?;
+    if let Some(given) = credentials {
+        writer.string(given.user)?;
+        writer.string(given.password)?;
+    }
+    Some(writer.len)
+}
+
+/// Writes a `PUBLISH` of `payload` to `topic` at quality of service 0 into `buffer`. Returns
+/// its length, or `None` if it does not fit.
+///
+/// If `retain` is set, the broker keeps the message for clients that subscribe later.
+#[must_use]
+pub fn mqtt_publish(buffer: &mut [u8], topic: &str, payload: &[u8], retain: bool) -> Option<usize> {
+    let header = if retain { PUBLISH | RETAIN } else { PUBLISH };
+    let remaining = string_len(topic).saturating_add(payload.len());
+    let mut writer = Writer::start(buffer, header, remaining)?;
+    writer.string(topic)?;
+    writer.bytes(payload)?;
+    Some(writer.len)
+}
+
+/// Writes a `SUBSCRIBE` to `topic_filter` at quality of service 0, as request `packet_id`
+/// (not 0), into `buffer`. Returns its length, or `None` if it does not fit.
+#[must_use]
+pub fn mqtt_subscribe(buffer: &mut [u8], packet_id: u16, topic_filter: &str) -> Option<usize> {
+    let remaining = string_len(topic_filter).saturating_add(3);
+    let mut writer = Writer::start(buffer, SUBSCRIBE, remaining)?;
+    writer.bytes(&packet_id.to_be_bytes())?;
+    writer.string(topic_filter)?;
+    writer.bytes(&[0])?;
+    Some(writer.len)
+}
+
+/// A packet a broker sent.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MqttPacket<'a> {
+    /// The answer to `CONNECT`: 0 accepts the connection, anything else refuses it (such as
+    /// 5, not authorized).
+    ConnAck {
+        /// The broker's return code.
+        return_code: u8,
+    },
+    /// A message on a topic the client subscribed to.
+    Publish {
+        /// The topic it was published to.
+        topic: &'a str,
+        /// Its bytes.
+        payload: &'a [u8],
+    },
+    /// The answer to `SUBSCRIBE`.
+    SubAck {
+        /// Whether the broker granted the subscription.
+        granted: bool,
+    },
+    /// The answer to `PINGREQ`.
+    PingResp,
+    /// Any other packet, by its type (the high nibble of its first byte).
+    Other(u8),
+}
+
+impl<'a> MqttPacket<'a> {
+    /// Reads the packet at the start of `bytes`, returning it and how many bytes it took, so
+    /// the caller can drop it from its buffer. Returns `Ok(None)` until all of it has arrived.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::MqttMalformed` if the bytes are not a packet this client can read,
+    /// after which the connection cannot be trusted to stay in step.
+    pub fn parse(bytes: &'a [u8]) -> Result<Option<(Self, usize)>> {
+        let Some(&first) = bytes.first() else {
+            return Ok(None);
+        };
+        let Some((remaining, length_bytes)) =
+            read_remaining_len(bytes.get(1..).unwrap_or_default())?
+        else {
+            return Ok(None);
+        };
+        let body_start = length_bytes.saturating_add(1);
+        let packet_len = body_start.saturating_add(remaining);
+        let Some(body) = bytes.get(body_start..packet_len) else {
+            return Ok(None);
+        };
+        let packet = match first & 0xF0 {
+            CONNACK => match *body {
+                [_session_present, return_code] => Self::ConnAck { return_code },
+                _ => return Err(Error::MqttMalformed),
+            },
+            PUBLISH => Self::parse_publish(first, body)?,
+            SUBACK => match *body {
+                [_, _, granted_qos] => Self::SubAck {
+                    granted: granted_qos & 0x80 == 0,
+                },
+                _ => return Err(Error::MqttMalformed),
+            },
+            PINGRESP => Self::PingResp,
+            other => Self::Other(other >> 4),
+        };
+        Ok(Some((packet, packet_len)))
+    }
+
+    fn parse_publish(first: u8, body: &'a [u8]) -> Result<Self> {
+        let (topic_len, rest) = body.split_first_chunk::<2>().ok_or(Error::MqttMalformed)?;
+        let (topic_bytes, after_topic) = rest
+            .split_at_checked(usize::from(u16::from_be_bytes(*topic_len)))
+            .ok_or(Error::MqttMalformed)?;
+        let topic = core::str::from_utf8(topic_bytes).map_err(|_| Error::MqttMalformed)?;
+        // Quality of service 1 and 2 carry a packet identifier before the payload
+        let payload = if first & 0x06 == 0 {
+            after_topic
+        } else {
+            after_topic.get(2..).ok_or(Error::MqttMalformed)?
+        };
+        Ok(Self::Publish { topic, payload })
+    }
+}
+
+/// How long the packet at the start of `bytes` is, once its fixed header has arrived, so a reader
+/// can skip one too big for its buffer. Returns `Ok(None)` until then.
+///
+/// # Errors
+///
+/// Returns `Error::MqttMalformed` if the length takes more than four bytes.
+pub fn mqtt_packet_len(bytes: &[u8]) -> Result<Option<usize>> {
+    if bytes.is_empty() {
+        return Ok(None);
+    }
+    let remaining = read_remaining_len(bytes.get(1..).unwrap_or_default())?;
+    Ok(remaining
+        .map(|(remaining, length_bytes)| remaining.saturating_add(length_bytes).saturating_add(1)))
+}
+
+/// The remaining length at the start of `bytes` and how many bytes encoded it, or `None` if
+/// they have not all arrived.
+fn read_remaining_len(bytes: &[u8]) -> Result<Option<(usize, usize)>> {
+    let mut value = 0_usize;
+    for (index, &byte) in bytes.iter().take(4).enumerate() {
+        let shift = index.saturating_mul(7);
+        value |= usize::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(Some((value, index.saturating_add(1))));
+        }
+    }
+    if bytes.len() >= 4 {
+        Err(Error::MqttMalformed)
+    } else {
+        Ok(None)
+    }
+}
+
+/// The length of `text` as an MQTT string: two length bytes, then its UTF-8.
+const fn string_len(text: &str) -> usize {
+    text.len().saturating_add(2)
+}
+
+/// Writes a packet into a buffer, refusing anything that does not fit.
+struct Writer<'a> {
+    buffer: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> Writer<'a> {
+    /// Writes the fixed header: the packet type and flags, then `remaining` in one to four
+    /// bytes.
+    fn start(buffer: &'a mut [u8], header: u8, remaining: usize) -> Option<Self> {
+        if remaining > MAX_REMAINING_LEN {
+            return None;
+        }
+        let mut writer = Self { buffer, len: 0 };
+        writer.bytes(&[header])?;
+        let mut left = remaining;
+        loop {
+            let low_bits = u8::try_from(left & 0x7F).ok()?;
+            left >>= 7;
+            if left == 0 {
+                writer.bytes(&[low_bits])?;
+                return Some(writer);
+            }
+            writer.bytes(&[low_bits | 0x80])?;
+        }
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) -> Option<()> {
+        let end = self.len.checked_add(bytes.len())?;
+        self.buffer.get_mut(self.len..end)?.copy_from_slice(bytes);
+        self.len = end;
+        Some(())
+    }
+
+    /// Writes `text` as an MQTT string, which holds at most 65,535 bytes.
+    fn string(&mut self, text: &str) -> Option<()> {
+        let len = u16::try_from(text.len()).ok()?;
+        self.bytes(&len.to_be_bytes())?;
+        self.bytes(text.as_bytes())
+    }
+}
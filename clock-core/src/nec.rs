@@ -0,0 +1,110 @@
+//! Decoding of the NEC infrared remote protocol from measured pulse lengths.
+//!
+//! An IR receiver module (such as a VS1838B) pulls its output low while it sees the 38 kHz
+//! carrier (a mark) and lets it float high otherwise (a space). A frame is a 9 ms mark and a
+//! 4.5 ms space, then 32 bits, least significant first: address, inverted address (or the
+//! high byte of a 16-bit address), command, inverted command. Each bit is a 562.5 µs mark
+//! followed by a 562.5 µs space (0) or a 1687.5 µs space (1). A key held down sends a repeat
+//! code instead: a 9 ms mark and a 2.25 ms space.
+
+/// The leader mark that starts a frame or a repeat code.
+const LEADER_MARK_MICROS: u32 = 9_000;
+/// The space after the leader mark in a frame.
+const FRAME_SPACE_MICROS: u32 = 4_500;
+/// The space after the leader mark in a repeat code.
+const REPEAT_SPACE_MICROS: u32 = 2_250;
+/// The mark that starts each bit.
+const BIT_MARK_MICROS: u32 = 562;
+/// The space that ends a 0 bit.
+const ZERO_SPACE_MICROS: u32 = 562;
+/// The space that ends a 1 bit.
+const ONE_SPACE_MICROS: u32 = 1_687;
+/// The number of bits in a frame.
+const FRAME_BITS: u8 = 32;
+
+/// What a `NecDecoder` recognized.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NecEvent {
+    /// A key press: the remote's address and the key's command.
+    Command { address: u16, command: u8 },
+    /// The last key is still held down.
+    Repeat,
+}
+
+/// Turns a stream of mark and space lengths into `NecEvent`s.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NecDecoder {
+    /// The bits so far, or `None` while waiting for a leader.
+    frame: Option<(u32, u8)>,
+}
+
+impl NecDecoder {
+    /// Creates a decoder waiting for a leader.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { frame: None }
+    }
+
+    /// Takes the length of a mark and of the space after it, in microseconds. Returns an
+    /// event when they complete a frame or a repeat code.
+    ///
+    /// A pulse that fits nowhere drops the partial frame, so a glitch costs at most one key
+    /// press.
+    pub fn push(&mut self, mark_micros: u32, space_micros: u32) -> Option<NecEvent> {
+        if let Some((bits, count)) = self.frame.take() {
+            if is_near(mark_micros, BIT_MARK_MICROS) {
+                let bit = if is_near(space_micros, ONE_SPACE_MICROS) {
+                    1_u32.checked_shl(u32::from(count)).unwrap_or_default()
+                } else if is_near(space_micros, ZERO_SPACE_MICROS) {
+                    0
+                } else {
+                    return None;
+                };
+                let next_bits = bits | bit;
+                let next_count = count.saturating_add(1);
+                if next_count < FRAME_BITS {
+                    self.frame = Some((next_bits, next_count));
+                    return None;
+                }
+                return command(next_bits);
+            }
+        }
+        if !is_near(mark_micros, LEADER_MARK_MICROS) {
+            return None;
+        }
+        if is_near(space_micros, FRAME_SPACE_MICROS) {
+            self.frame = Some((0, 0));
+            None
+        } else if is_near(space_micros, REPEAT_SPACE_MICROS) {
+            Some(NecEvent::Repeat)
+        } else {
+            None
+        }
+    }
+}
+
+/// The command in a complete frame, or `None` if its inverted command does not match.
+fn command(bits: u32) -> Option<NecEvent> {
+    let [address_low, address_high, command, inverted_command] = bits.to_le_bytes();
+    if command != !inverted_command {
+        return None;
+    }
+    // Extended NEC uses the second byte as more address instead of its inverse
+    let address = if address_low == !address_high {
+        u16::from(address_low)
+    } else {
+        u16::from_le_bytes([address_low, address_high])
+    };
+    Some(NecEvent::Command { address, command })
+}
+
+/// Whether `actual` is within a quarter of `expected`, the slack receivers need.
+#[expect(
+    clippy::integer_division_remainder_used,
+    reason = "Rounding the slack down does not matter."
+)]
+const fn is_near(actual: u32, expected: u32) -> bool {
+    let slack = expected / 4;
+    expected.saturating_sub(slack) <= actual && actual <= expected.saturating_add(slack)
+}
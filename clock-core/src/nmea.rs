@@ -0,0 +1,123 @@
+use time::{Date, Month, PrimitiveDateTime, Time};
+
+use crate::{Location, UnixSeconds};
+
+/// The longest NMEA 0183 sentence, from `$` to the end of the checksum.
+pub const MAX_NMEA_SENTENCE_LEN: usize = 82;
+
+/// What a GPS receiver's recommended-minimum (`RMC`) sentence says.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NmeaFix {
+    /// The UTC time of the fix, to the whole second.
+    pub unix_seconds: UnixSeconds,
+    /// Where the receiver is, if the sentence gives a position.
+    pub location: Option<Location>,
+}
+
+impl NmeaFix {
+    /// Parses an `RMC` sentence from any talker (`$GPRMC`, `$GNRMC`, ...), such as
+    /// `$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A`.
+    ///
+    /// Returns `None` for any other sentence, a bad checksum, or a receiver without a valid
+    /// fix (status `V`), whose clock may not yet have been set from the satellites.
+    #[must_use]
+    pub fn parse_rmc(sentence: &str) -> Option<Self> {
+        let body = checked_body(sentence.trim_end())?;
+        let mut fields = body.split(',');
+        let (talker_and_type, hms, status) = (fields.next()?, fields.next()?, fields.next()?);
+        let (latitude, north_south) = (fields.next()?, fields.next()?);
+        let (longitude, east_west) = (fields.next()?, fields.next()?);
+        let (_speed, _course, dmy) = (fields.next()?, fields.next()?, fields.next()?);
+        if talker_and_type.len() != 5 || !talker_and_type.ends_with("RMC") || status != "A" {
+            return None;
+        }
+
+        let time = Time::from_hms(
+            two_digit_field(hms, 0)?,
+            two_digit_field(hms, 2)?,
+            two_digit_field(hms, 4)?,
+        )
+        .ok()?;
+        let month = Month::try_from(two_digit_field(dmy, 2)?).ok()?;
+        let year = 2000_i32.saturating_add(i32::from(two_digit_field(dmy, 4)?));
+        let date = Date::from_calendar_date(year, month, two_digit_field(dmy, 0)?).ok()?;
+        let unix_seconds =
+            UnixSeconds(PrimitiveDateTime::new(date, time).assume_utc().unix_timestamp());
+
+        let location = coordinate(latitude, north_south, "N", "S", 2).and_then(
+            |latitude_millidegrees| {
+                let longitude_millidegrees = coordinate(longitude, east_west, "E", "W", 3)?;
+                ((-90_000..=90_000).contains(&latitude_millidegrees)
+                    && (-180_000..=180_000).contains(&longitude_millidegrees))
+                .then_some(Location {
+                    latitude_millidegrees,
+                    longitude_millidegrees,
+                })
+            },
+        );
+        Some(Self {
+            unix_seconds,
+            location,
+        })
+    }
+}
+
+/// The part of `sentence` between `$` and `*`, if its two-digit hex checksum (the XOR of
+/// those bytes) matches.
+fn checked_body(sentence: &str) -> Option<&str> {
+    let (body, checksum) = sentence.strip_prefix('$')?.split_once('*')?;
+    let expected = u8::from_str_radix(checksum, 16).ok()?;
+    let actual = body.bytes().fold(0, |sum, byte| sum ^ byte);
+    (checksum.len() == 2 && actual == expected).then_some(body)
+}
+
+/// The two decimal digits of `field` starting at `start`.
+fn two_digit_field(field: &str, start: usize) -> Option<u8> {
+    let digits = field.get(start..start.checked_add(2)?)?;
+    if digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        digits.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// An NMEA coordinate (`ddmm.mmmm` for latitude, `dddmm.mmmm` for longitude) in
+/// millidegrees, negative for the `negative` hemisphere. Digits past the third decimal
+/// place of the minutes are ignored.
+#[expect(
+    clippy::integer_division_remainder_used,
+    reason = "Thousandths of a minute to millidegrees needs no rounding."
+)]
+fn coordinate(
+    field: &str,
+    hemisphere: &str,
+    positive: &str,
+    negative: &str,
+    degree_digits: usize,
+) -> Option<i32> {
+    let (whole, fraction) = field.split_once('.').unwrap_or((field, ""));
+    let (degrees, minutes) = whole.split_at_checked(degree_digits)?;
+    if minutes.len() != 2 {
+        return None;
+    }
+    let mut thousandths = minutes.parse::<i32>().ok()?.checked_mul(1_000)?;
+    let mut place = 100;
+    for digit in fraction.chars().take(3) {
+        let value = i32::try_from(digit.to_digit(10)?).ok()?;
+        thousandths = thousandths.checked_add(value.checked_mul(place)?)?;
+        place /= 10;
+    }
+    let millidegrees = degrees
+        .parse::<i32>()
+        .ok()?
+        .checked_mul(1_000)?
+        .checked_add(thousandths / 60)?;
+    if hemisphere == positive {
+        Some(millidegrees)
+    } else if hemisphere == negative {
+        millidegrees.checked_neg()
+    } else {
+        None
+    }
+}
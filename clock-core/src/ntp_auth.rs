@@ -0,0 +1,56 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{Error, Result};
+
+/// Length of a plain NTP packet.
+pub const NTP_PACKET_LEN: usize = 48;
+
+/// Length of the HMAC-SHA256 code an authenticating server appends to its NTP packet.
+pub const NTP_AUTH_CODE_LEN: usize = 32;
+
+/// Where a client's transmit timestamp goes in its request.
+const TRANSMIT_TIMESTAMP: core::ops::Range<usize> = 40..48;
+
+/// Where a server echoes the client's transmit timestamp (the originate timestamp).
+const ORIGINATE_TIMESTAMP: core::ops::Range<usize> = 24..32;
+
+/// Builds an NTP v3 client request carrying `nonce` as its transmit timestamp.
+///
+/// A server echoes the transmit timestamp back as the originate timestamp, which lets
+/// [`verify_ntp_auth`] tie a response to this request.
+#[must_use]
+pub fn ntp_request(nonce: [u8; 8]) -> [u8; NTP_PACKET_LEN] {
+    let mut request = [0; NTP_PACKET_LEN];
+    if let Some(first_byte) = request.first_mut() {
+        *first_byte = 0x1B; // LI=0, VN=3, Mode=3 (client)
+    }
+    if let Some(transmit) = request.get_mut(TRANSMIT_TIMESTAMP) {
+        transmit.copy_from_slice(&nonce);
+    }
+    request
+}
+
+/// Checks a response from a user-run time server that signs its replies.
+///
+/// The server must echo `nonce` as the originate timestamp and append an HMAC-SHA256 of the
+/// 48-byte NTP packet, keyed with the shared `key`. The comparison is constant-time.
+///
+/// # Errors
+///
+/// Returns an error if the response is too short, answers a different request, or its
+/// authentication code does not match.
+pub fn verify_ntp_auth(response: &[u8], nonce: [u8; 8], key: &[u8]) -> Result<()> {
+    let (Some(packet), Some(code)) = (
+        response.get(..NTP_PACKET_LEN),
+        response.get(NTP_PACKET_LEN..NTP_PACKET_LEN.saturating_add(NTP_AUTH_CODE_LEN)),
+    ) else {
+        return Err(Error::TimeAuthTooShort);
+    };
+    if packet.get(ORIGINATE_TIMESTAMP) != Some(nonce.as_slice()) {
+        return Err(Error::TimeAuthNonceMismatch);
+    }
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).map_err(|_| Error::TimeAuthBadMac)?;
+    mac.update(packet);
+    mac.verify_slice(code).map_err(|_| Error::TimeAuthBadMac)
+}
@@ -0,0 +1,61 @@
+//! Display pages: the time page the clock's modes draw on, and a sensor page kept current
+//! beside it, so the display can flip between them without redrawing either.
+
+use embassy_time::Duration;
+
+/// One of the display's two pages.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Page {
+    /// What the current mode shows.
+    #[default]
+    Time,
+    /// The latest sensor reading (see `ClockState::render_sensor_page`).
+    Sensor,
+}
+
+/// How long the time page and then the sensor page show, in turn, while the clock shows the
+/// time.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageRotation {
+    time: Duration,
+    sensor: Duration,
+}
+
+impl PageRotation {
+    /// Parses `<time seconds>:<sensor seconds>`, such as `20:5`, each 1 to 3600 seconds.
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let (time, sensor) = text.split_once(':')?;
+        let seconds = |text: &str| {
+            text.trim()
+                .parse::<u16>()
+                .ok()
+                .filter(|seconds| (1..=3600).contains(seconds))
+                .map(|seconds| Duration::from_secs(u64::from(seconds)))
+        };
+        Some(Self {
+            time: seconds(time)?,
+            sensor: seconds(sensor)?,
+        })
+    }
+
+    /// The page showing `elapsed` after the rotation first started, and how long until the
+    /// other page's turn.
+    #[must_use]
+    #[expect(
+        clippy::arithmetic_side_effects,
+        clippy::integer_division_remainder_used,
+        reason = "Both turns are at least a second, and the position stays within the cycle."
+    )]
+    pub fn page_at(&self, elapsed: Duration) -> (Page, Duration) {
+        let cycle = self.time.as_ticks() + self.sensor.as_ticks();
+        let position = elapsed.as_ticks() % cycle;
+        if position < self.time.as_ticks() {
+            (Page::Time, Duration::from_ticks(self.time.as_ticks() - position))
+        } else {
+            (Page::Sensor, Duration::from_ticks(cycle - position))
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use crate::{Press, Settings};
+
+/// The most negative UTC offset, in hours, that the offset editor reaches.
+pub const MIN_UTC_OFFSET_HOURS: i32 = -12;
+
+/// The most positive UTC offset, in hours, that the offset editor reaches.
+pub const MAX_UTC_OFFSET_HOURS: i32 = 14;
+
+/// What a press does to an edit in progress.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditStep {
+    /// The edit goes on.
+    Continue,
+    /// The edit is done: commit it.
+    Commit,
+    /// The edit is abandoned: drop it.
+    Cancel,
+}
+
+/// A staged copy of the settings a user can edit on the clock.
+///
+/// Editors change a `PendingSettings` instead of the live clock. The display previews it while
+/// the edit lasts. Confirming commits (and saves) it as a whole; cancelling or timing out just
+/// drops it, so the clock is never left with half an edit.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PendingSettings {
+    /// The time zone, in minutes from UTC.
+    pub utc_offset_minutes: i32,
+    /// How to show the time.
+    pub settings: Settings,
+}
+
+impl PendingSettings {
+    /// Stages a copy of the given settings for editing.
+    #[must_use]
+    pub const fn new(utc_offset_minutes: i32, settings: Settings) -> Self {
+        Self {
+            utc_offset_minutes,
+            settings,
+        }
+    }
+
+    /// Moves the UTC offset by whole hours, wrapping from +14 to -12 (and back). The result is
+    /// always a whole number of hours.
+    pub fn adjust_utc_offset_hours(&mut self, hours: i32) {
+        let new_hours = round_to_hours(self.utc_offset_minutes).saturating_add(hours);
+        self.utc_offset_minutes = wrap_utc_offset_hours(new_hours).saturating_mul(60);
+    }
+
+    /// Applies `press` to an edit of the UTC offset: a short or double press moves the offset
+    /// an hour forward, a long press finishes the edit, and a very long press (held on past
+    /// the long one) abandons it.
+    pub fn press_utc_offset(&mut self, press: Press) -> EditStep {
+        match press {
+            Press::Short | Press::Double => {
+                self.adjust_utc_offset_hours(1);
+                EditStep::Continue
+            }
+            Press::Long => EditStep::Commit,
+            Press::VeryLong => EditStep::Cancel,
+        }
+    }
+}
+
+/// `utc_offset_minutes` rounded to the nearest hour, halves away from zero.
+#[expect(
+    clippy::integer_division_remainder_used,
+    reason = "Division is intentional for converting minutes to hours."
+)]
+pub(crate) const fn round_to_hours(utc_offset_minutes: i32) -> i32 {
+    if utc_offset_minutes >= 0 {
+        utc_offset_minutes.saturating_add(30) / 60
+    } else {
+        utc_offset_minutes.saturating_sub(30) / 60
+    }
+}
+
+/// `hours` wrapped into `MIN_UTC_OFFSET_HOURS..=MAX_UTC_OFFSET_HOURS`.
+#[expect(
+    clippy::arithmetic_side_effects,
+    reason = "The span is a non-zero constant and the sums stay small."
+)]
+pub(crate) const fn wrap_utc_offset_hours(hours: i32) -> i32 {
+    let span = MAX_UTC_OFFSET_HOURS - MIN_UTC_OFFSET_HOURS + 1;
+    hours.saturating_sub(MIN_UTC_OFFSET_HOURS).rem_euclid(span) + MIN_UTC_OFFSET_HOURS
+}
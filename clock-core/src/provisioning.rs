@@ -0,0 +1,371 @@
+//! The config page: a clock's `WiFi` network, access point, and country, time zone, display
+//! settings, and alarm settings, kept at a fixed place in flash so one firmware image serves
+//! every clock.
+//!
+//! The `clock-flash` host tool writes the page (or a UF2 file of it), and the firmware reads it
+//! at boot and rewrites it when the network changes. The page is a header, a run of
+//! tag-length-value records, and a CRC, with numbers big-endian:
+//!
+//! ```text
+//! "CLKC" | version: u8 | records' length: u16 | records | CRC-32 of all before it: u32
+//! record: tag: u8 | length: u8 | value
+//! ```
+//!
+//! Readers skip tags they do not know, so a newer tool's records do not upset older firmware.
+//! `PROVISIONING_VERSION` changes only when an existing record's meaning does; a page of
+//! another version is ignored, as is one whose CRC does not match.
+
+use core::fmt;
+
+use heapless::{String, Vec};
+
+use crate::{
+    Config, Error, Reminders, Result, Settings, UnixSeconds, CONFIG_BYTES, MAX_UTC_OFFSET_HOURS,
+    MIN_UTC_OFFSET_HOURS, REMINDERS_BYTES,
+};
+
+/// The longest `WiFi` network name (SSID), in bytes.
+pub const MAX_SSID_LEN: usize = 32;
+
+/// The longest `WiFi` password: 63 characters, or a 64-digit hex key.
+pub const MAX_WIFI_PASSWORD_LEN: usize = 64;
+
+/// Where the config page starts, from the start of flash (`0x1000_0000` in the RP2040's
+/// address map): the 4K sector that `memory.x` reserves before the crash report.
+pub const PROVISIONING_OFFSET: u32 = 0x1F_B000;
+
+/// The page layout written by `Provisioning::to_bytes`.
+pub const PROVISIONING_VERSION: u8 = 1;
+
+/// Marks a config page. An erased page reads as all `0xFF`.
+const PROVISIONING_MAGIC: [u8; 4] = *b"CLKC";
+
+/// The magic, the version, and the records' length.
+const HEADER_LEN: usize = 4 + 1 + 2;
+
+/// Where the records' length sits in the header.
+const RECORDS_LEN_AT: usize = 5;
+
+/// A record's tag and length bytes.
+const RECORD_HEADER_LEN: usize = 2;
+
+/// The network name, as UTF-8.
+const TAG_SSID: u8 = 1;
+/// The network password, as UTF-8 (left out for an open network).
+const TAG_PASSWORD: u8 = 2;
+/// The UTC offset in minutes, as an `i32`.
+const TAG_UTC_OFFSET: u8 = 3;
+/// The display options, as one byte of `Settings::to_bits`.
+const TAG_SETTINGS: u8 = 4;
+/// The date `DaysSince` counts from, as Unix seconds in an `i64`.
+const TAG_DAYS_SINCE: u8 = 5;
+/// Blink timing, multiplexing, sync interval, tick volume, and alarm sound, as
+/// `Config::to_bytes`.
+const TAG_CONFIG: u8 = 6;
+/// The reminders table, as `Reminders::to_bytes`.
+const TAG_REMINDERS: u8 = 7;
+/// The `WiFi` country, as its two ASCII letters (left out for worldwide).
+const TAG_COUNTRY: u8 = 8;
+/// The one access point to join, as its six-byte BSSID (left out for any).
+const TAG_BSSID: u8 = 9;
+/// Present, and empty, when the network hides its name.
+const TAG_HIDDEN: u8 = 10;
+
+/// The most bytes `Provisioning::to_bytes` uses: the header, every record at its longest, and
+/// the CRC. The rest of the array is `0xFF`, as erased flash reads.
+pub const PROVISIONING_BYTES: usize = HEADER_LEN
+    + RECORD_HEADER_LEN
+    + MAX_SSID_LEN
+    + RECORD_HEADER_LEN
+    + MAX_WIFI_PASSWORD_LEN
+    + RECORD_HEADER_LEN
+    + 4
+    + RECORD_HEADER_LEN
+    + 1
+    + RECORD_HEADER_LEN
+    + 8
+    + RECORD_HEADER_LEN
+    + CONFIG_BYTES
+    + RECORD_HEADER_LEN
+    + REMINDERS_BYTES
+    + RECORD_HEADER_LEN
+    + 2
+    + RECORD_HEADER_LEN
+    + 6
+    + RECORD_HEADER_LEN
+    + 4;
+
+/// The regulatory domain the `WiFi` radio follows, which decides the channels it may use.
+///
+/// It is an ISO 3166 two-letter country code, or `XX` for worldwide, which keeps to the
+/// channels every country allows (1 to 11), so an access point on channel 12 or 13 is not
+/// seen.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WifiCountry([u8; 2]);
+
+impl WifiCountry {
+    /// Channels 1 to 11 only, legal everywhere.
+    pub const WORLDWIDE: Self = Self(*b"XX");
+
+    /// Reads a two-letter country code such as `DE` or `jp`, or `None` if it is not two
+    /// ASCII letters.
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let &[first, second] = text.as_bytes() else {
+            return None;
+        };
+        (first.is_ascii_alphabetic() && second.is_ascii_alphabetic())
+            .then(|| Self([first.to_ascii_uppercase(), second.to_ascii_uppercase()]))
+    }
+
+    /// The code's two uppercase letters, as the `WiFi` chip takes them.
+    #[must_use]
+    pub const fn code(self) -> [u8; 2] {
+        self.0
+    }
+
+    /// The code, such as `DE`.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.0).unwrap_or("XX")
+    }
+}
+
+impl Default for WifiCountry {
+    fn default() -> Self {
+        Self::WORLDWIDE
+    }
+}
+
+/// One access point's hardware address, for a network (such as a mesh) with several that
+/// share its name.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bssid(pub [u8; 6]);
+
+impl Bssid {
+    /// Reads six hex bytes separated by `:` or `-`, such as `a4:2b:b0:12:34:56`, or `None`
+    /// if `text` is not one.
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut bytes = [0_u8; 6];
+        let mut parts = text.split([':', '-']);
+        for byte in &mut bytes {
+            let part = parts.next().filter(|part| part.len() == 2)?;
+            *byte = u8::from_str_radix(part, 16).ok()?;
+        }
+        parts.next().is_none().then_some(Self(bytes))
+    }
+}
+
+impl fmt::Display for Bssid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [b0, b1, b2, b3, b4, b5] = self.0;
+        write!(f, "{b0:02x}:{b1:02x}:{b2:02x}:{b3:02x}:{b4:02x}:{b5:02x}")
+    }
+}
+
+/// The settings on a clock's config page, which the firmware prefers to the compile-time
+/// defaults it was built with.
+///
+/// Settings saved on the clock itself (with the editor, the console, or HTTP) win over the
+/// page.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Provisioning {
+    /// The network to join.
+    pub ssid: String<MAX_SSID_LEN>,
+    /// Its password.
+    pub password: String<MAX_WIFI_PASSWORD_LEN>,
+    /// The country whose `WiFi` channels the radio uses.
+    pub country: WifiCountry,
+    /// The access point to join, or `None` for whichever the radio picks.
+    pub bssid: Option<Bssid>,
+    /// Whether the network hides its name, so the clock must ask for it by name.
+    pub hidden: bool,
+    /// The clock's offset from UTC until one is saved on the clock itself.
+    pub utc_offset_minutes: i32,
+    /// Display options, or `None` to use the firmware's defaults.
+    pub settings: Option<Settings>,
+    /// Timings and the alarm sound, or `None` to use the firmware's defaults.
+    pub config: Option<Config>,
+    /// Reminders, or `None` to start with none.
+    pub reminders: Option<Reminders>,
+}
+
+impl Provisioning {
+    /// Checks and collects a clock's network and time zone, with the radio kept to worldwide
+    /// channels and the other settings left to the firmware's defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::ProvisioningFormat` if the SSID is empty or too long, the password is
+    /// too long, or the UTC offset is outside -12 to +14 hours.
+    pub fn new(ssid: &str, password: &str, utc_offset_minutes: i32) -> Result<Self> {
+        let offset_range =
+            MIN_UTC_OFFSET_HOURS.saturating_mul(60)..=MAX_UTC_OFFSET_HOURS.saturating_mul(60);
+        if ssid.is_empty() || !offset_range.contains(&utc_offset_minutes) {
+            return Err(Error::ProvisioningFormat);
+        }
+        Ok(Self {
+            ssid: String::try_from(ssid).map_err(|_| Error::ProvisioningFormat)?,
+            password: String::try_from(password).map_err(|_| Error::ProvisioningFormat)?,
+            country: WifiCountry::WORLDWIDE,
+            bssid: None,
+            hidden: false,
+            utc_offset_minutes,
+            settings: None,
+            config: None,
+            reminders: None,
+        })
+    }
+
+    /// Packs the settings into a config page.
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; PROVISIONING_BYTES] {
+        let mut page = Vec::<u8, PROVISIONING_BYTES>::new();
+        put(&mut page, &PROVISIONING_MAGIC);
+        // The records' length is filled in once they are written
+        put(&mut page, &[PROVISIONING_VERSION, 0, 0]);
+        put_record(&mut page, TAG_SSID, self.ssid.as_bytes());
+        if !self.password.is_empty() {
+            put_record(&mut page, TAG_PASSWORD, self.password.as_bytes());
+        }
+        if self.country != WifiCountry::WORLDWIDE {
+            put_record(&mut page, TAG_COUNTRY, &self.country.code());
+        }
+        if let Some(Bssid(bssid)) = self.bssid {
+            put_record(&mut page, TAG_BSSID, &bssid);
+        }
+        if self.hidden {
+            put_record(&mut page, TAG_HIDDEN, &[]);
+        }
+        put_record(&mut page, TAG_UTC_OFFSET, &self.utc_offset_minutes.to_be_bytes());
+        if let Some(settings) = self.settings {
+            put_record(&mut page, TAG_SETTINGS, &[settings.to_bits()]);
+            if let Some(date) = settings.days_since {
+                put_record(&mut page, TAG_DAYS_SINCE, &date.as_i64().to_be_bytes());
+            }
+        }
+        if let Some(config) = &self.config {
+            put_record(&mut page, TAG_CONFIG, &config.to_bytes());
+        }
+        if let Some(reminders) = &self.reminders {
+            put_record(&mut page, TAG_REMINDERS, &reminders.to_bytes());
+        }
+        let records_len = u16::try_from(page.len().saturating_sub(HEADER_LEN)).unwrap_or(u16::MAX);
+        if let Some(len) = page.get_mut(RECORDS_LEN_AT..HEADER_LEN) {
+            len.copy_from_slice(&records_len.to_be_bytes());
+        }
+        let crc = crc32(&page);
+        put(&mut page, &crc.to_be_bytes());
+
+        let mut bytes = [0xFF; PROVISIONING_BYTES];
+        if let Some(start) = bytes.get_mut(..page.len()) {
+            start.copy_from_slice(&page);
+        }
+        bytes
+    }
+
+    /// Unpacks a page written with `to_bytes`, or `None` if it holds no config page of this
+    /// version (such as erased flash), its CRC does not match, or it lacks a network or time
+    /// zone. A country, BSSID, display, config, or reminders record that does not make sense
+    /// is skipped.
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8; PROVISIONING_BYTES]) -> Option<Self> {
+        let (&[m0, m1, m2, m3, version, len_high, len_low], rest) =
+            bytes.split_first_chunk::<HEADER_LEN>()?;
+        if [m0, m1, m2, m3] != PROVISIONING_MAGIC || version != PROVISIONING_VERSION {
+            return None;
+        }
+        let records_len = usize::from(u16::from_be_bytes([len_high, len_low]));
+        let (mut records, rest) = rest.split_at_checked(records_len)?;
+        let (crc, _) = rest.split_first_chunk::<4>()?;
+        let signed = bytes.get(..HEADER_LEN.saturating_add(records_len))?;
+        if crc32(signed) != u32::from_be_bytes(*crc) {
+            return None;
+        }
+
+        let mut ssid = None;
+        let mut password = "";
+        let mut country = None;
+        let mut bssid = None;
+        let mut hidden = false;
+        let mut utc_offset_minutes = None;
+        let mut settings_bits = None;
+        let mut days_since = None;
+        let mut config = None;
+        let mut reminders = None;
+        while let Some((&[tag, len], rest)) = records.split_first_chunk::<RECORD_HEADER_LEN>() {
+            let (value, rest) = rest.split_at_checked(usize::from(len))?;
+            records = rest;
+            match tag {
+                TAG_SSID => ssid = Some(core::str::from_utf8(value).ok()?),
+                TAG_PASSWORD => password = core::str::from_utf8(value).ok()?,
+                TAG_COUNTRY => {
+                    country = core::str::from_utf8(value).ok().and_then(WifiCountry::parse);
+                }
+                TAG_BSSID => bssid = value.first_chunk().copied().map(Bssid),
+                TAG_HIDDEN => hidden = true,
+                TAG_UTC_OFFSET => {
+                    utc_offset_minutes = Some(i32::from_be_bytes(*value.first_chunk()?));
+                }
+                TAG_SETTINGS => settings_bits = value.first().copied(),
+                TAG_DAYS_SINCE => {
+                    days_since = value
+                        .first_chunk()
+                        .map(|seconds| UnixSeconds(i64::from_be_bytes(*seconds)));
+                }
+                TAG_CONFIG => {
+                    config = value.first_chunk::<CONFIG_BYTES>().and_then(Config::from_bytes);
+                }
+                TAG_REMINDERS => {
+                    reminders = value.first_chunk::<REMINDERS_BYTES>().map(Reminders::from_bytes);
+                }
+                // A record from a newer tool
+                _ => {}
+            }
+        }
+        Some(Self {
+            country: country.unwrap_or_default(),
+            bssid,
+            hidden,
+            settings: settings_bits.map(|bits| Settings {
+                days_since,
+                ..Settings::from_bits(bits)
+            }),
+            config,
+            reminders,
+            ..Self::new(ssid?, password, utc_offset_minutes?).ok()?
+        })
+    }
+}
+
+/// Appends `bytes` to `page`. `PROVISIONING_BYTES` leaves room for every record, so nothing is
+/// dropped.
+fn put(page: &mut Vec<u8, PROVISIONING_BYTES>, bytes: &[u8]) {
+    let _fits = page.extend_from_slice(bytes);
+}
+
+/// Appends a record: `tag`, `value`'s length, and `value`.
+fn put_record(page: &mut Vec<u8, PROVISIONING_BYTES>, tag: u8, value: &[u8]) {
+    put(page, &[tag, u8::try_from(value.len()).unwrap_or(u8::MAX)]);
+    put(page, value);
+}
+
+/// The CRC-32 (IEEE 802.3, as zlib computes it) of `bytes`.
+fn crc32(bytes: &[u8]) -> u32 {
+    const POLYNOMIAL: u32 = 0xEDB8_8320;
+    let mut crc = u32::MAX;
+    for &byte in bytes {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 0 {
+                crc >> 1
+            } else {
+                (crc >> 1) ^ POLYNOMIAL
+            };
+        }
+    }
+    !crc
+}
@@ -0,0 +1,58 @@
+use embassy_time::Duration;
+use time::OffsetDateTime;
+
+use crate::UnixSeconds;
+
+/// A daily window, in local time, when the clock keeps non-essential network traffic down.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuietHours {
+    /// The first quiet hour (0-23).
+    start_hour: u8,
+    /// The first hour after the window (0-23).
+    end_hour: u8,
+}
+
+impl QuietHours {
+    /// Parses `start-end` in whole hours from 0 to 23, such as `23-6` for 11 pm to 6 am. The
+    /// window wraps past midnight when `end` is earlier than `start`.
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        let (start, end) = text.split_once('-')?;
+        let start_hour = start.trim().parse().ok().filter(|&hour| hour < 24)?;
+        let end_hour = end.trim().parse().ok().filter(|&hour| hour < 24)?;
+        (start_hour != end_hour).then_some(Self {
+            start_hour,
+            end_hour,
+        })
+    }
+
+    /// When the window begins, after local midnight.
+    #[must_use]
+    pub fn start(self) -> Duration {
+        Duration::from_secs(u64::from(self.start_hour).saturating_mul(60 * 60))
+    }
+
+    /// When the window ends, after local midnight.
+    #[must_use]
+    pub fn end(self) -> Duration {
+        Duration::from_secs(u64::from(self.end_hour).saturating_mul(60 * 60))
+    }
+
+    /// Whether `unix_seconds`, at `utc_offset_minutes` from UTC, falls in the window.
+    #[must_use]
+    pub fn contains(self, unix_seconds: UnixSeconds, utc_offset_minutes: i32) -> bool {
+        let local_seconds = unix_seconds
+            .as_i64()
+            .saturating_add(i64::from(utc_offset_minutes).saturating_mul(60));
+        let Ok(local) = OffsetDateTime::from_unix_timestamp(local_seconds) else {
+            return false;
+        };
+        let hour = local.hour();
+        if self.start_hour < self.end_hour {
+            (self.start_hour..self.end_hour).contains(&hour)
+        } else {
+            hour >= self.start_hour || hour < self.end_hour
+        }
+    }
+}
@@ -0,0 +1,319 @@
+//! Decoding of the DCF77 (Germany) and WWVB (US) time signals from measured pulse lengths.
+//!
+//! Both stations lower their carrier at the start of every second, and how long it stays low
+//! (the pulse) carries one symbol. A receiver module turns each pulse into a level on its
+//! output.
+//!
+//! * DCF77: 100 ms is a 0 and 200 ms a 1. Second 59 has no pulse, so the gap of two seconds
+//!   before the next pulse marks the start of a minute. The 59 bits give the local German
+//!   time (CET or CEST) of the minute that starts at the next mark, with even parity.
+//! * WWVB: 200 ms is a 0, 500 ms a 1, and 800 ms a marker. Markers fall at seconds 0, 9, 19,
+//!   29, 39, 49, and 59, so two in a row mark the start of a minute. The 60 symbols give the
+//!   UTC time of the minute they started in, without parity.
+//!
+//! Radio reception is noisy, so a time is only reported once two minutes in a row decode to
+//! times a minute apart.
+
+use time::{Date, Month, PrimitiveDateTime, Time};
+
+use crate::UnixSeconds;
+
+/// The time from one pulse to the next within a minute.
+const SECOND_MILLIS: u32 = 1_000;
+/// The time across the missing pulse of DCF77's second 59.
+const DCF77_MINUTE_GAP_MILLIS: u32 = 2_000;
+/// How far a gap between pulses may be from its nominal length.
+const GAP_TOLERANCE_MILLIS: u32 = 100;
+/// The most symbols in a minute (WWVB's, without a leap second).
+const MAX_FRAME_SYMBOLS: u8 = 60;
+
+/// DCF77's minute (bits 21-27), hour (29-34), day (36-41), month (45-49), and year (50-57)
+/// fields, as bit positions and their BCD weights.
+const DCF77_MINUTE: [(u8, u16); 7] = [
+    (21, 1),
+    (22, 2),
+    (23, 4),
+    (24, 8),
+    (25, 10),
+    (26, 20),
+    (27, 40),
+];
+const DCF77_HOUR: [(u8, u16); 6] = [(29, 1), (30, 2), (31, 4), (32, 8), (33, 10), (34, 20)];
+const DCF77_DAY: [(u8, u16); 6] = [(36, 1), (37, 2), (38, 4), (39, 8), (40, 10), (41, 20)];
+const DCF77_MONTH: [(u8, u16); 5] = [(45, 1), (46, 2), (47, 4), (48, 8), (49, 10)];
+const DCF77_YEAR: [(u8, u16); 8] = [
+    (50, 1),
+    (51, 2),
+    (52, 4),
+    (53, 8),
+    (54, 10),
+    (55, 20),
+    (56, 40),
+    (57, 80),
+];
+
+/// WWVB's minute, hour, day-of-year, and year fields, as symbol positions and BCD weights.
+const WWVB_MINUTE: [(u8, u16); 7] = [(1, 40), (2, 20), (3, 10), (5, 8), (6, 4), (7, 2), (8, 1)];
+const WWVB_HOUR: [(u8, u16); 6] = [(12, 20), (13, 10), (15, 8), (16, 4), (17, 2), (18, 1)];
+const WWVB_DAY_OF_YEAR: [(u8, u16); 10] = [
+    (22, 200),
+    (23, 100),
+    (25, 80),
+    (26, 40),
+    (27, 20),
+    (28, 10),
+    (30, 8),
+    (31, 4),
+    (32, 2),
+    (33, 1),
+];
+const WWVB_YEAR: [(u8, u16); 8] = [
+    (45, 80),
+    (46, 40),
+    (47, 20),
+    (48, 10),
+    (50, 8),
+    (51, 4),
+    (52, 2),
+    (53, 1),
+];
+/// Where WWVB sends its markers.
+const WWVB_MARKERS: [u8; 7] = [0, 9, 19, 29, 39, 49, 59];
+
+/// Which time signal a receiver module is tuned to.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RadioStation {
+    /// DCF77, 77.5 kHz, from Mainflingen, Germany.
+    Dcf77,
+    /// WWVB, 60 kHz, from Fort Collins, Colorado.
+    Wwvb,
+}
+
+impl RadioStation {
+    /// Parses `dcf77` or `wwvb` (in any case).
+    #[must_use]
+    pub fn parse(text: &str) -> Option<Self> {
+        if text.eq_ignore_ascii_case("dcf77") {
+            Some(Self::Dcf77)
+        } else if text.eq_ignore_ascii_case("wwvb") {
+            Some(Self::Wwvb)
+        } else {
+            None
+        }
+    }
+
+    /// The symbol a pulse of `pulse_millis` stands for, or `None` for noise.
+    fn symbol(self, pulse_millis: u32) -> Option<Symbol> {
+        match self {
+            Self::Dcf77 => match pulse_millis {
+                60..=140 => Some(Symbol::Zero),
+                160..=240 => Some(Symbol::One),
+                _ => None,
+            },
+            Self::Wwvb => match pulse_millis {
+                100..=300 => Some(Symbol::Zero),
+                400..=600 => Some(Symbol::One),
+                700..=900 => Some(Symbol::Marker),
+                _ => None,
+            },
+        }
+    }
+
+    /// The time at the start of the minute that follows `frame`.
+    fn decode(self, frame: Frame) -> Option<UnixSeconds> {
+        match self {
+            Self::Dcf77 => decode_dcf77(frame),
+            Self::Wwvb => decode_wwvb(frame),
+        }
+    }
+}
+
+/// What one pulse carries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Symbol {
+    Zero,
+    One,
+    Marker,
+}
+
+/// The symbols of one minute so far, one bit per second.
+#[derive(Debug, Clone, Copy, Default)]
+struct Frame {
+    ones: u64,
+    markers: u64,
+    count: u8,
+}
+
+impl Frame {
+    /// Adds the next second's symbol, or returns `false` if the minute is already full.
+    fn push(&mut self, symbol: Symbol) -> bool {
+        if self.count >= MAX_FRAME_SYMBOLS {
+            return false;
+        }
+        let bit = 1_u64.checked_shl(u32::from(self.count)).unwrap_or_default();
+        match symbol {
+            Symbol::Zero => {}
+            Symbol::One => self.ones |= bit,
+            Symbol::Marker => self.markers |= bit,
+        }
+        self.count = self.count.saturating_add(1);
+        true
+    }
+
+    fn is_one(self, position: u8) -> bool {
+        self.ones
+            .checked_shr(u32::from(position))
+            .is_some_and(|bits| bits & 1 == 1)
+    }
+
+    fn is_marker(self, position: u8) -> bool {
+        self.markers
+            .checked_shr(u32::from(position))
+            .is_some_and(|bits| bits & 1 == 1)
+    }
+
+    /// The sum of the weights of the fields that are 1.
+    fn value(self, fields: &[(u8, u16)]) -> u16 {
+        fields
+            .iter()
+            .filter(|(position, _)| self.is_one(*position))
+            .fold(0, |sum, (_, weight)| sum.saturating_add(*weight))
+    }
+
+    /// Whether the 1s from `first` to `last` (inclusive) are even in number.
+    fn has_even_parity(self, first: u8, last: u8) -> bool {
+        (first..=last)
+            .filter(|position| self.is_one(*position))
+            .count()
+            .is_multiple_of(2)
+    }
+}
+
+/// Turns a stream of pulse lengths into the times of minute marks.
+#[derive(Debug, Clone, Copy)]
+pub struct RadioTimeDecoder {
+    station: RadioStation,
+    /// The minute being received, or `None` while waiting for a minute mark.
+    frame: Option<Frame>,
+    /// Whether the last pulse was a marker (WWVB only).
+    last_was_marker: bool,
+    /// The time the last complete minute decoded to, for checking the next one against.
+    previous: Option<UnixSeconds>,
+}
+
+impl RadioTimeDecoder {
+    /// Creates a decoder waiting for a minute mark.
+    #[must_use]
+    pub const fn new(station: RadioStation) -> Self {
+        Self {
+            station,
+            frame: None,
+            last_was_marker: false,
+            previous: None,
+        }
+    }
+
+    /// Takes the length of a pulse and the time since the previous pulse started, in
+    /// milliseconds. Returns the UTC time at the start of this pulse when it marks a minute
+    /// that completes two good minutes in a row.
+    ///
+    /// A pulse that fits nowhere drops the minute under way, so a burst of noise costs at most
+    /// a minute or two.
+    pub fn push(&mut self, pulse_millis: u32, since_previous_millis: u32) -> Option<UnixSeconds> {
+        let Some(symbol) = self.station.symbol(pulse_millis) else {
+            self.frame = None;
+            self.last_was_marker = false;
+            return None;
+        };
+        let on_time = is_near(since_previous_millis, SECOND_MILLIS);
+        let minute_mark = match self.station {
+            RadioStation::Dcf77 => is_near(since_previous_millis, DCF77_MINUTE_GAP_MILLIS),
+            RadioStation::Wwvb => on_time && self.last_was_marker && symbol == Symbol::Marker,
+        };
+        self.last_was_marker = symbol == Symbol::Marker;
+        if !on_time && !minute_mark {
+            self.frame = None;
+            return None;
+        }
+
+        let mut decoded = None;
+        if minute_mark {
+            let minute = self.frame.take().and_then(|frame| self.station.decode(frame));
+            let expected = self
+                .previous
+                .map(|previous| UnixSeconds(previous.as_i64().saturating_add(60)));
+            decoded = minute.filter(|&time| Some(time) == expected);
+            self.previous = minute;
+            self.frame = Some(Frame::default());
+        }
+        self.frame = self
+            .frame
+            .and_then(|mut frame| frame.push(symbol).then_some(frame));
+        decoded
+    }
+}
+
+/// A DCF77 minute: 59 bits ending in the mark for the local time it gives.
+fn decode_dcf77(frame: Frame) -> Option<UnixSeconds> {
+    // Bit 20 always starts the time; bits 17 and 18 say CEST (UTC+2) or CET (UTC+1)
+    let summer = frame.is_one(17);
+    if frame.count != 59
+        || !frame.is_one(20)
+        || summer == frame.is_one(18)
+        || !frame.has_even_parity(21, 28)
+        || !frame.has_even_parity(29, 35)
+        || !frame.has_even_parity(36, 58)
+    {
+        return None;
+    }
+    let offset_seconds = if summer { 7_200 } else { 3_600 };
+    let local = unix_seconds(
+        Date::from_calendar_date(
+            year(frame.value(&DCF77_YEAR))?,
+            Month::try_from(u8::try_from(frame.value(&DCF77_MONTH)).ok()?).ok()?,
+            u8::try_from(frame.value(&DCF77_DAY)).ok()?,
+        )
+        .ok()?,
+        frame.value(&DCF77_HOUR),
+        frame.value(&DCF77_MINUTE),
+    )?;
+    Some(UnixSeconds(local.as_i64().saturating_sub(offset_seconds)))
+}
+
+/// A WWVB minute: 60 symbols giving the UTC minute they were sent in.
+fn decode_wwvb(frame: Frame) -> Option<UnixSeconds> {
+    if frame.count != MAX_FRAME_SYMBOLS
+        || !WWVB_MARKERS.iter().all(|&position| frame.is_marker(position))
+    {
+        return None;
+    }
+    let started = unix_seconds(
+        Date::from_ordinal_date(
+            year(frame.value(&WWVB_YEAR))?,
+            frame.value(&WWVB_DAY_OF_YEAR),
+        )
+        .ok()?,
+        frame.value(&WWVB_HOUR),
+        frame.value(&WWVB_MINUTE),
+    )?;
+    Some(UnixSeconds(started.as_i64().saturating_add(60)))
+}
+
+/// A two-digit year from either station, which both count from 2000.
+fn year(two_digits: u16) -> Option<i32> {
+    (two_digits < 100).then(|| 2000_i32.saturating_add(i32::from(two_digits)))
+}
+
+/// `date` at `hour`:`minute`, as if it were UTC.
+fn unix_seconds(date: Date, hour: u16, minute: u16) -> Option<UnixSeconds> {
+    let time = Time::from_hms(u8::try_from(hour).ok()?, u8::try_from(minute).ok()?, 0).ok()?;
+    Some(UnixSeconds(
+        PrimitiveDateTime::new(date, time).assume_utc().unix_timestamp(),
+    ))
+}
+
+/// Whether a gap is within `GAP_TOLERANCE_MILLIS` of `nominal`.
+const fn is_near(millis: u32, nominal: u32) -> bool {
+    millis.abs_diff(nominal) <= GAP_TOLERANCE_MILLIS
+}
@@ -0,0 +1,169 @@
+use core::fmt::Write;
+
+use time::Date;
+
+use crate::{Error, Result};
+
+/// The most reminders `Reminders` holds.
+pub const MAX_REMINDERS: usize = 8;
+
+/// How many characters a reminder's label has.
+pub const REMINDER_LABEL_LEN: usize = 4;
+
+/// Bytes used by `Reminders::to_bytes`: a count, then month, day, and label for each slot.
+pub const REMINDERS_BYTES: usize = 49;
+
+/// Bytes for one reminder: month, day, and label.
+const REMINDER_BYTES: usize = 6;
+
+/// A yearly date (such as a birthday) and the label to show on it.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reminder {
+    /// 1 (January) to 12 (December).
+    pub month: u8,
+    /// 1 to 31.
+    pub day: u8,
+    /// Shown scrolling across the display on the day.
+    pub label: [char; REMINDER_LABEL_LEN],
+}
+
+impl Reminder {
+    /// Whether this reminder falls on `date`.
+    #[must_use]
+    pub fn matches(&self, date: Date) -> bool {
+        u8::from(date.month()) == self.month && date.day() == self.day
+    }
+
+    /// Parses `MMDDLABL`: a two-digit month and day, then up to four label characters.
+    /// Letters, digits, and `-` are allowed in the label; `_` stands for a blank.
+    fn parse(entry: &str) -> Option<Self> {
+        let (month, rest) = entry.split_at_checked(2)?;
+        let (day, label_text) = rest.split_at_checked(2)?;
+        let (month, day) = (month.parse::<u8>().ok()?, day.parse::<u8>().ok()?);
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+        let mut label = [' '; REMINDER_LABEL_LEN];
+        let mut chars = label_text.chars();
+        for slot in &mut label {
+            match chars.next() {
+                Some('_') | None => {}
+                Some(char) if char.is_ascii_alphanumeric() || char == '-' => *slot = char,
+                Some(_) => return None,
+            }
+        }
+        chars.next().is_none().then_some(Self { month, day, label })
+    }
+}
+
+/// The table of yearly reminders.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Reminders([Option<Reminder>; MAX_REMINDERS]);
+
+impl Reminders {
+    /// Creates an empty table.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self([None; MAX_REMINDERS])
+    }
+
+    /// The reminders in the table.
+    pub fn iter(&self) -> impl Iterator<Item = &Reminder> {
+        self.0.iter().flatten()
+    }
+
+    /// The first reminder that falls on `date`, if any.
+    #[must_use]
+    pub fn for_date(&self, date: Date) -> Option<&Reminder> {
+        self.iter().find(|reminder| reminder.matches(date))
+    }
+
+    /// Parses a comma-separated list of `MMDDLABL` entries, such as `0517ANNA,1225XMAS`.
+    /// An empty list clears the table.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an entry is malformed or there are more than `MAX_REMINDERS`.
+    pub fn parse(list: &str) -> Result<Self> {
+        let mut reminders = Self::new();
+        let mut slots = reminders.0.iter_mut();
+        for entry in list.split(',').filter(|entry| !entry.is_empty()) {
+            let slot = slots.next().ok_or(Error::ReminderFormat)?;
+            *slot = Some(Reminder::parse(entry).ok_or(Error::ReminderFormat)?);
+        }
+        Ok(reminders)
+    }
+
+    /// Writes the table in the format `parse` reads.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `out` is full.
+    pub fn write_list(&self, out: &mut impl Write) -> core::fmt::Result {
+        for (index, reminder) in self.iter().enumerate() {
+            if index > 0 {
+                out.write_char(',')?;
+            }
+            write!(out, "{:02}{:02}", reminder.month, reminder.day)?;
+            for char in reminder.label {
+                out.write_char(if char == ' ' { '_' } else { char })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Packs the table for storage.
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; REMINDERS_BYTES] {
+        let mut bytes = [0; REMINDERS_BYTES];
+        let (count, slots) = bytes.split_at_mut(1);
+        let mut used = 0_u8;
+        for (chunk, reminder) in slots.chunks_exact_mut(REMINDER_BYTES).zip(self.iter()) {
+            let [month, day, label @ ..] = chunk else {
+                continue;
+            };
+            *month = reminder.month;
+            *day = reminder.day;
+            for (byte, char) in label.iter_mut().zip(reminder.label) {
+                *byte = u8::try_from(char).unwrap_or(b' ');
+            }
+            used = used.saturating_add(1);
+        }
+        if let Some(count) = count.first_mut() {
+            *count = used;
+        }
+        bytes
+    }
+
+    /// Unpacks a table stored with `to_bytes`. Erased flash (all `0xFF`) reads as empty, and
+    /// entries that do not make sense are skipped.
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8; REMINDERS_BYTES]) -> Self {
+        let mut reminders = Self::new();
+        let [count, slots @ ..] = bytes;
+        let count = usize::from(*count).min(MAX_REMINDERS);
+        let entries = slots
+            .chunks_exact(REMINDER_BYTES)
+            .take(count)
+            .filter_map(|chunk| {
+                let [month, day, label @ ..] = chunk else {
+                    return None;
+                };
+                let mut reminder = Reminder {
+                    month: *month,
+                    day: *day,
+                    label: [' '; REMINDER_LABEL_LEN],
+                };
+                for (char, byte) in reminder.label.iter_mut().zip(label) {
+                    *char = char::from(*byte);
+                }
+                ((1..=12).contains(month) && (1..=31).contains(day)).then_some(reminder)
+            });
+        for (slot, reminder) in reminders.0.iter_mut().zip(entries) {
+            *slot = Some(reminder);
+        }
+        reminders
+    }
+}
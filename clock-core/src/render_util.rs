@@ -0,0 +1,107 @@
+//! Heap-free helpers that lay out numbers in the characters of a four-cell display field.
+
+use crate::CellText;
+
+/// The two digits of `value`, such as `['0', '7']`. `value` must be below 100.
+#[must_use]
+#[expect(
+    clippy::arithmetic_side_effects,
+    clippy::integer_division_remainder_used,
+    reason = "Because value < 100, the division is safe and both digits fit."
+)]
+pub const fn two_digits(value: u8) -> [char; 2] {
+    debug_assert!(value < 100, "Value is between 0 and 99 (inclusive)");
+    [((value / 10) + b'0') as char, ((value % 10) + b'0') as char]
+}
+
+/// An hour from 1 to 12 as two characters, blank-padded (` 9`) unless `leading_zero` (`09`).
+/// Months share the range, so they use it too.
+#[must_use]
+pub const fn hour_digits(value: u8, leading_zero: bool) -> [char; 2] {
+    debug_assert!(
+        1 <= value && value <= 12,
+        "Value is between 1 and 12 (inclusive)"
+    );
+    let [tens, ones] = two_digits(value);
+    if tens == '0' && !leading_zero {
+        [' ', ones]
+    } else {
+        [tens, ones]
+    }
+}
+
+/// Puts two pairs of characters side by side, such as hours and minutes.
+#[must_use]
+pub const fn join(left: [char; 2], right: [char; 2]) -> [char; 4] {
+    let ([first, second], [third, fourth]) = (left, right);
+    [first, second, third, fourth]
+}
+
+/// The cell that `centered` puts the character at `index` of its field in.
+#[must_use]
+#[inline]
+#[expect(
+    clippy::arithmetic_side_effects,
+    clippy::integer_division_remainder_used,
+    reason = "The display has at least four cells."
+)]
+pub const fn centered_index(index: usize) -> usize {
+    (crate::CELL_COUNT - 4) / 2 + index
+}
+
+/// Lays out a four-character field in the middle of the display.
+#[cfg(not(feature = "eight-digits"))]
+#[must_use]
+#[inline]
+pub const fn centered(chars: [char; 4]) -> CellText {
+    chars
+}
+
+/// Lays out a four-character field in the middle of the display.
+#[cfg(feature = "eight-digits")]
+#[must_use]
+#[inline]
+pub const fn centered(chars: [char; 4]) -> CellText {
+    let [first, second, third, fourth] = chars;
+    [' ', ' ', first, second, third, fourth, ' ', ' ']
+}
+
+/// Right-aligns `value` in four characters, with a leading `-` if negative, such as `  42`
+/// or ` -7`. Returns `None` unless `value` is in -999..=9999.
+#[must_use]
+pub fn right_align(value: i16) -> Option<[char; 4]> {
+    let mut chars = [' '; 4];
+    right_align_into(value, &mut chars)?;
+    Some(chars)
+}
+
+/// Right-aligns `degrees` in three characters followed by a degree sign, such as ` 72°` or
+/// `-12°`. Returns `None` unless `degrees` is in -99..=999.
+#[must_use]
+pub fn temperature_chars(degrees: i16) -> Option<[char; 4]> {
+    let mut chars = [' ', ' ', ' ', '°'];
+    right_align_into(degrees, chars.get_mut(..3)?)?;
+    Some(chars)
+}
+
+/// Writes `value` into the right of `field`, which starts blank. `None` if it does not fit.
+#[expect(
+    clippy::arithmetic_side_effects,
+    clippy::integer_division_remainder_used,
+    reason = "Dividing by 10 is safe, and the remainder is a single digit."
+)]
+fn right_align_into(value: i16, field: &mut [char]) -> Option<()> {
+    let mut remaining = value.unsigned_abs();
+    let mut cells = field.iter_mut().rev();
+    loop {
+        *cells.next()? = char::from_digit(u32::from(remaining % 10), 10)?;
+        remaining /= 10;
+        if remaining == 0 {
+            break;
+        }
+    }
+    if value < 0 {
+        *cells.next()? = '-';
+    }
+    Some(())
+}
@@ -0,0 +1,175 @@
+//! A cron-lite schedule: jobs that run once a day at a local time, or every so often.
+//!
+//! A `Schedule` only keeps the books. The firmware's scheduler task asks it when to look
+//! again, sleeps until then, and runs the jobs `take_due` hands back, so each job's work stays
+//! with the task it belongs to.
+
+use embassy_time::{Duration, Instant};
+use heapless::Vec;
+
+/// The most jobs a `Schedule` holds.
+pub const MAX_SCHEDULED_JOBS: usize = 8;
+
+/// The longest a scheduler sleeps before looking at the wall clock again, so a sync or a new
+/// UTC offset that moves local time is noticed within a minute.
+pub const SCHEDULE_RECHECK: Duration = Duration::from_secs(60);
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// When a job runs.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum When {
+    /// Once a local day, this long after midnight (wrapping at a day). It runs when local
+    /// time reaches or jumps past it, once the clock knows the time, and at most once a day.
+    Daily(Duration),
+    /// Every so often (at least a second), counting from when it was added or last ran.
+    Every(Duration),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Timing {
+    Daily {
+        /// Seconds after local midnight.
+        at: i64,
+        /// The local day (days since 1970) it last ran on.
+        ran_on_day: Option<i64>,
+    },
+    Every {
+        period: Duration,
+        last_ran: Instant,
+    },
+}
+
+/// Jobs and when they run, for a task to sleep on.
+#[derive(Debug, Clone)]
+pub struct Schedule<J> {
+    entries: Vec<(J, Timing), MAX_SCHEDULED_JOBS>,
+    /// The local time (seconds since 1970) at the last `take_due` that knew it.
+    local_seconds: Option<i64>,
+}
+
+impl<J> Default for Schedule<J> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<J> Schedule<J> {
+    /// A schedule with no jobs.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            local_seconds: None,
+        }
+    }
+
+    /// Adds `job` to run `when`, as of `now`. Returns `false` if the schedule is full.
+    pub fn add(&mut self, job: J, when: When, now: Instant) -> bool {
+        let timing = match when {
+            When::Daily(after_midnight) => Timing::Daily {
+                at: i64::try_from(after_midnight.as_secs())
+                    .unwrap_or(i64::MAX)
+                    .rem_euclid(SECONDS_PER_DAY),
+                ran_on_day: None,
+            },
+            When::Every(period) => Timing::Every {
+                period: period.max(Duration::from_secs(1)),
+                last_ran: now,
+            },
+        };
+        self.entries.push((job, timing)).is_ok()
+    }
+
+    /// When the scheduler should call `take_due` next: at the first job due, or after
+    /// `SCHEDULE_RECHECK`, whichever is sooner. `local_seconds` is local time in seconds since
+    /// 1970, or `None` if the clock does not know it (then daily jobs wait).
+    #[must_use]
+    pub fn next_check(&self, now: Instant, local_seconds: Option<i64>) -> Instant {
+        let recheck = now.checked_add(SCHEDULE_RECHECK).unwrap_or(Instant::MAX);
+        self.entries
+            .iter()
+            .filter_map(|(_, timing)| match *timing {
+                Timing::Every { period, last_ran } => {
+                    Some(last_ran.checked_add(period).unwrap_or(Instant::MAX))
+                }
+                Timing::Daily { at, .. } => {
+                    // In 1 to 86,400 seconds: a job due this second has just run
+                    let wait = at
+                        .saturating_sub(local_seconds?)
+                        .saturating_sub(1)
+                        .rem_euclid(SECONDS_PER_DAY)
+                        .saturating_add(1);
+                    now.checked_add(Duration::from_secs(wait.unsigned_abs()))
+                }
+            })
+            .fold(recheck, Instant::min)
+    }
+}
+
+impl<J: Copy + PartialEq> Schedule<J> {
+    /// Changes how often `job` runs, counting from when it last ran, such as after the config
+    /// changes. Returns `false` if `job` is not scheduled every so often.
+    pub fn set_period(&mut self, job: J, new_period: Duration) -> bool {
+        let mut found = false;
+        for (entry_job, timing) in &mut self.entries {
+            if let Timing::Every { period, .. } = timing {
+                if *entry_job == job {
+                    *period = new_period.max(Duration::from_secs(1));
+                    found = true;
+                }
+            }
+        }
+        found
+    }
+
+    /// The jobs due at `now`, in the order they were added, marking them as run.
+    /// `local_seconds` is as for `next_check`.
+    ///
+    /// A daily job is due when local time has reached its time of day since the last call
+    /// that knew the time, so the first such call only notes the time, and local time moving
+    /// backward (a sync, a smaller UTC offset) runs nothing.
+    pub fn take_due(
+        &mut self,
+        now: Instant,
+        local_seconds: Option<i64>,
+    ) -> Vec<J, MAX_SCHEDULED_JOBS> {
+        let previous = self.local_seconds;
+        if local_seconds.is_some() {
+            self.local_seconds = local_seconds;
+        }
+        let mut due = Vec::new();
+        for (job, timing) in &mut self.entries {
+            let runs = match timing {
+                Timing::Every { period, last_ran } => {
+                    let runs = now >= last_ran.checked_add(*period).unwrap_or(Instant::MAX);
+                    if runs {
+                        *last_ran = now;
+                    }
+                    runs
+                }
+                Timing::Daily { at, ran_on_day } => {
+                    let (Some(previous), Some(local_seconds)) = (previous, local_seconds) else {
+                        continue;
+                    };
+                    // The latest time local time was at `at`
+                    let latest = local_seconds.saturating_sub(
+                        local_seconds.saturating_sub(*at).rem_euclid(SECONDS_PER_DAY),
+                    );
+                    let day = latest.div_euclid(SECONDS_PER_DAY);
+                    let runs = latest > previous && *ran_on_day != Some(day);
+                    if runs {
+                        *ran_on_day = Some(day);
+                    }
+                    runs
+                }
+            };
+            if runs {
+                // Cannot fail: there are no more due jobs than entries
+                let _pushed = due.push(*job);
+            }
+        }
+        due
+    }
+}
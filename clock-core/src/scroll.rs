@@ -0,0 +1,32 @@
+use embassy_time::Duration;
+
+use crate::{CellText, CELL_COUNT};
+
+/// How long each step of scrolling text stays on the display.
+pub(crate) const SCROLL_STEP: Duration = Duration::from_millis(300);
+
+/// How many frames it takes `text_len` characters to scroll in from the right and out to
+/// the left.
+#[must_use]
+pub const fn scroll_frame_count(text_len: usize) -> usize {
+    text_len.saturating_add(CELL_COUNT)
+}
+
+/// The display at `step` of a scroll of `text` from right to left.
+///
+/// Step 0 is blank; the text then enters one cell per step from the right. Steps at or past
+/// [`scroll_frame_count`] are blank again.
+#[must_use]
+pub fn scroll_frame(text: &[char], step: usize) -> CellText {
+    let mut frame = [' '; CELL_COUNT];
+    for (cell_index, cell) in frame.iter_mut().enumerate() {
+        if let Some(&char) = step
+            .saturating_add(cell_index)
+            .checked_sub(CELL_COUNT)
+            .and_then(|text_index| text.get(text_index))
+        {
+            *cell = char;
+        }
+    }
+    frame
+}
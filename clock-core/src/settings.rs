@@ -0,0 +1,52 @@
+use crate::UnixSeconds;
+
+/// User preferences for how the clock displays time.
+///
+/// The default turns every option off. Firmware decides where non-default values come from.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Settings {
+    /// Light the decimal point of the last digit during PM hours.
+    pub pm_indicator: bool,
+    /// Show hours before ten with a leading zero ("09:30") rather than a blank (" 9:30").
+    pub leading_zero_hours: bool,
+    /// Rotate the display 180° for mounting upside down.
+    pub rotate_180: bool,
+    /// The date (as midnight UTC) that `ClockState::DaysSince` counts up from.
+    pub days_since: Option<UnixSeconds>,
+}
+
+impl Settings {
+    const PM_INDICATOR: u8 = 0b_0000_0001;
+    const LEADING_ZERO_HOURS: u8 = 0b_0000_0010;
+    const ROTATE_180: u8 = 0b_0000_0100;
+
+    /// Packs the on/off options into one byte, one bit per option, for storage.
+    /// `days_since` is stored separately.
+    #[must_use]
+    pub const fn to_bits(self) -> u8 {
+        let mut bits = 0;
+        if self.pm_indicator {
+            bits |= Self::PM_INDICATOR;
+        }
+        if self.leading_zero_hours {
+            bits |= Self::LEADING_ZERO_HOURS;
+        }
+        if self.rotate_180 {
+            bits |= Self::ROTATE_180;
+        }
+        bits
+    }
+
+    /// Unpacks settings stored with [`Settings::to_bits`], with no `days_since` date.
+    /// Unknown bits are ignored.
+    #[must_use]
+    pub const fn from_bits(bits: u8) -> Self {
+        Self {
+            pm_indicator: (bits & Self::PM_INDICATOR) != 0,
+            leading_zero_hours: (bits & Self::LEADING_ZERO_HOURS) != 0,
+            rotate_180: (bits & Self::ROTATE_180) != 0,
+            days_since: None,
+        }
+    }
+}
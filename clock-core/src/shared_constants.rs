@@ -0,0 +1,58 @@
+use core::num::NonZero;
+
+use embassy_time::Duration;
+use heapless::{LinearMap, Vec};
+
+/// The number of cells (digits) in the display.
+/// The Display a 4-digit, 8-segment display.
+#[cfg(not(feature = "eight-digits"))]
+pub const CELL_COUNT_U8: u8 = 4;
+/// The number of cells (digits) in the display.
+/// The Display is two chained 4-digit, 8-segment displays.
+#[cfg(feature = "eight-digits")]
+pub const CELL_COUNT_U8: u8 = 8;
+pub const CELL_COUNT: usize = CELL_COUNT_U8 as usize;
+
+/// One cell's segments, a bit each (see `Leds` for which bit is which).
+#[cfg(not(feature = "fourteen-segment"))]
+pub type Segments = u8;
+/// One cell's segments, a bit each (see `Leds` for which bit is which).
+/// A 14-segment cell needs more than eight bits.
+#[cfg(feature = "fourteen-segment")]
+pub type Segments = u16;
+
+/// The number of segments per digit in the display.
+/// Each digit has 8 segments (7 segments plus a decimal point).
+#[cfg(not(feature = "fourteen-segment"))]
+pub const SEGMENT_COUNT: usize = 8;
+/// The number of segments per digit in the display.
+/// Each digit has 15 segments (14 segments plus a decimal point).
+#[cfg(feature = "fourteen-segment")]
+pub const SEGMENT_COUNT: usize = 15;
+
+/// Duration representing one second.
+pub const ONE_SECOND: Duration = Duration::from_secs(1);
+
+/// Duration representing one minute (60 seconds).
+pub const ONE_MINUTE: Duration = Duration::from_secs(60);
+
+/// Duration representing one hour (60 minutes).
+pub const ONE_HOUR: Duration = Duration::from_secs(60 * 60);
+
+/// Duration representing one day (24 hours).
+pub const ONE_DAY: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Duration representing the number of ticks in one day.
+pub const TICKS_IN_ONE_DAY: u64 = ONE_DAY.as_ticks();
+
+/// A map from patterns to the indexes of the cells that contain that pattern.
+///
+/// For example, on a 7-segment display the numeral five has the LED bit pattern `0b_0110_1101`.
+/// If the display is " 55 ", then the map will contain just one entry with the key`0b_0110_1101`
+/// and the value `[1, 2]` representing the indexes of the cells that contain the numeral five.
+///
+/// The map is used to multiplex efficiently.
+///
+/// - The inner vector is statically allocated with a capacity of `CELL_COUNT`, the most possible.
+/// - The map itself can hold up to `CELL_COUNT` entries, the most possible.
+pub type BitsToIndexes = LinearMap<NonZero<Segments>, Vec<u8, CELL_COUNT>, CELL_COUNT>;
@@ -0,0 +1,324 @@
+//! Stand-ins for the firmware's time sync and `WiFi`, and a `Scenario` that drives the clock's
+//! logic with them the way the firmware's state machine does, for tests on the host.
+//!
+//! A `Scenario` takes one step per call (a sync, a press of the button, a change of UTC
+//! offset, the alarm's time coming) and skips the firmware's timers, so a test says what happens and then checks the
+//! frame the display would show. Time passes only through the `MockInstant` the test holds.
+
+use core::cell::{Cell, RefCell};
+
+use embassy_time::{Duration, Instant};
+use heapless::Deque;
+
+use crate::{
+    mock_instant::MockInstant, Arbitration, BitMatrix, BlinkState, ClockFace, ClockState,
+    ClockTime, DisplayContent, EditStep, ModeConditions, PendingSettings, Press, Reminders,
+    Schedule, Settings, SyncError, TimeArbiter, TimeSyncEvent, TimeSyncPort, When, WifiPort,
+};
+
+/// The most outcomes a `ScriptedTimeSync` holds at once.
+pub const MAX_SCRIPTED_EVENTS: usize = 8;
+
+/// A `TimeSyncPort` that hands out the outcomes a test scripted, in order.
+#[derive(Default)]
+pub struct ScriptedTimeSync {
+    events: RefCell<Deque<TimeSyncEvent, MAX_SCRIPTED_EVENTS>>,
+    requests: Cell<u32>,
+    wifi: MockWifi,
+}
+
+impl ScriptedTimeSync {
+    /// A time sync with nothing scripted.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues `event` as the last outcome. Returns `false` if the script is full.
+    pub fn push(&self, event: TimeSyncEvent) -> bool {
+        self.events.borrow_mut().push_back(event).is_ok()
+    }
+
+    /// Takes the next outcome, if any is left.
+    pub fn take(&self) -> Option<TimeSyncEvent> {
+        self.events.borrow_mut().pop_front()
+    }
+
+    /// How many syncs have been requested.
+    #[must_use]
+    pub const fn requests(&self) -> u32 {
+        self.requests.get()
+    }
+}
+
+impl TimeSyncPort for ScriptedTimeSync {
+    type Wifi = MockWifi;
+
+    /// The next scripted outcome. With none left, never returns, like a sync that never ends.
+    #[expect(
+        clippy::future_not_send,
+        reason = "Tests drive a scenario on one thread, as the firmware's executor does."
+    )]
+    async fn wait(&self) -> TimeSyncEvent {
+        match self.take() {
+            Some(event) => event,
+            None => core::future::pending().await,
+        }
+    }
+
+    fn request_sync(&self) {
+        self.requests.set(self.requests.get().saturating_add(1));
+    }
+
+    /// Scripted outcomes are those of syncs still to come, so none is ever unread.
+    fn take_unread(&self) -> Option<TimeSyncEvent> {
+        None
+    }
+
+    fn wifi(&self) -> &MockWifi {
+        &self.wifi
+    }
+}
+
+/// A `WifiPort` that remembers whether its radio is on, and reports a set signal strength.
+pub struct MockWifi {
+    radio_on: Cell<bool>,
+    signal_dbm: Cell<Option<i16>>,
+}
+
+impl Default for MockWifi {
+    /// The radio starts on, as the firmware's does.
+    fn default() -> Self {
+        Self {
+            radio_on: Cell::new(true),
+            signal_dbm: Cell::new(None),
+        }
+    }
+}
+
+impl MockWifi {
+    /// Whether the radio was last turned on.
+    #[must_use]
+    pub const fn radio_on(&self) -> bool {
+        self.radio_on.get()
+    }
+
+    /// Sets the signal strength that `signal_dbm` reports from now on.
+    pub fn set_signal_dbm(&self, signal_dbm: Option<i16>) {
+        self.signal_dbm.set(signal_dbm);
+    }
+}
+
+impl WifiPort for MockWifi {
+    fn set_radio(&self, on: bool) {
+        self.radio_on.set(on);
+    }
+
+    fn signal_dbm(&self) -> impl Future<Output = Option<i16>> {
+        core::future::ready(self.signal_dbm.get())
+    }
+}
+
+/// A frame the clock task showed, and until when.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShownFrame {
+    /// When it was rendered.
+    pub shown_at: Instant,
+    /// How it blinks.
+    pub blink_state: BlinkState,
+    /// Its segments.
+    pub bit_matrix: BitMatrix,
+    /// When the clock task renders again, unless something changes the display first.
+    pub deadline: Instant,
+}
+
+/// The clock's logic, stepped the way the firmware's state machine and clock task drive it.
+///
+/// Frames come from `ClockFace::render`, as the clock task's do.
+pub struct Scenario {
+    face: ClockFace,
+    arbiter: TimeArbiter,
+    conditions: ModeConditions,
+    /// The alarm, as the firmware's scheduler keeps it.
+    alarm: Schedule<()>,
+}
+
+impl Scenario {
+    /// A clock just booted in the time zone `utc_offset_minutes` from UTC, showing the time
+    /// (12:00 until the first sync) with default settings.
+    #[must_use]
+    pub fn new(utc_offset_minutes: i32) -> Self {
+        Self {
+            face: ClockFace::new(
+                ClockTime::new(utc_offset_minutes),
+                DisplayContent::default(),
+            ),
+            arbiter: TimeArbiter::new(),
+            conditions: ModeConditions::default(),
+            alarm: Schedule::new(),
+        }
+    }
+
+    /// The state the clock is in.
+    #[must_use]
+    pub const fn state(&self) -> ClockState {
+        self.face.state
+    }
+
+    /// The clock's time.
+    #[must_use]
+    pub const fn clock_time(&self) -> &ClockTime {
+        &self.face.clock_time
+    }
+
+    /// What the display shows now: how it blinks and its segments. While the UTC offset is
+    /// being edited, it previews the edited offset, as the clock task does.
+    pub fn frame(&mut self) -> (BlinkState, BitMatrix) {
+        let (blink_state, bit_matrix, _) = self.face.render();
+        (blink_state, bit_matrix)
+    }
+
+    /// Shows the next frame as the clock task does: renders it, then sleeps until the
+    /// display next changes, moving `time` to that deadline.
+    #[must_use = "The frame is what the test checks"]
+    pub fn show_next(&mut self, time: &MockInstant) -> ShownFrame {
+        let shown_at = time.now();
+        let (blink_state, bit_matrix, sleep_duration) = self.face.render();
+        let deadline = shown_at.checked_add(sleep_duration).unwrap_or(Instant::MAX);
+        time.advance_to(deadline);
+        ShownFrame {
+            shown_at,
+            blink_state,
+            bit_matrix,
+            deadline,
+        }
+    }
+
+    /// Handles a sync outcome as the state machine does: a time the arbiter accepts sets the
+    /// clock, a failure is shown the next time the display shows why, and anything else leaves
+    /// the clock alone. Returns whether the clock was set.
+    pub fn handle_sync(&mut self, event: &TimeSyncEvent) -> bool {
+        let &TimeSyncEvent::Success {
+            unix_seconds,
+            leap,
+            source,
+            ..
+        } = event
+        else {
+            self.face.content.sync_error = event.sync_error();
+            return false;
+        };
+        match self.arbiter.offer(source, Instant::now()) {
+            Arbitration::Accept => {
+                self.face.clock_time.set_from_unix(unix_seconds);
+                self.face
+                    .clock_time
+                    .schedule_leap_second(unix_seconds, leap, false);
+                self.face.content.time_source = Some(source);
+                true
+            }
+            Arbitration::Outranked(_) => false,
+        }
+    }
+
+    /// Presses the button. A press while the display is held (see `run_due_alarm`) only ends
+    /// the hold. Otherwise the display modes move as `ModeDescriptor::after_press` says, and
+    /// `EditUtcOffset` takes the press as `PendingSettings::press_utc_offset` says. A sync the
+    /// press starts takes the next outcome `time_sync` holds (none counts as a timeout) and
+    /// shows the result. Other states ignore presses here.
+    pub fn press(&mut self, press: Press, time_sync: &ScriptedTimeSync) {
+        let face = &mut self.face;
+        if face.state == ClockState::EditUtcOffset {
+            let live =
+                PendingSettings::new(face.clock_time.utc_offset_minutes(), face.content.settings);
+            let pending = face.preview.get_or_insert(live);
+            match pending.press_utc_offset(press) {
+                EditStep::Continue => return,
+                EditStep::Commit => {
+                    let pending = *pending;
+                    face.commit(pending);
+                }
+                EditStep::Cancel => face.preview = None,
+            }
+            face.state = ClockState::HoursMinutes;
+            return;
+        }
+        let Some(mode) = face.state.mode() else {
+            return;
+        };
+        if face.held.take().is_some() {
+            return;
+        }
+        face.state = mode.after_press(press, &self.conditions);
+        face.preview = None;
+        if face.state == ClockState::Syncing {
+            time_sync.request_sync();
+            let outcome = time_sync.take();
+            match &outcome {
+                Some(event) => {
+                    self.handle_sync(event);
+                }
+                None => self.face.content.sync_error = Some(SyncError::Timeout),
+            }
+            self.face.state = ClockState::after_sync(outcome.as_ref());
+        }
+    }
+
+    /// Lets the current state's timer run out, as `ClockState::after_timeout` says. An edit
+    /// in progress is dropped.
+    pub fn time_out(&mut self) {
+        let next_state = self.face.state.after_timeout();
+        if next_state != self.face.state {
+            self.face.state = next_state;
+            self.face.preview = None;
+        }
+    }
+
+    /// Sets the alarm to go off `after_midnight` local time each day, as `ALARM_AT` does.
+    pub fn set_alarm(&mut self, after_midnight: Duration) {
+        let now = Instant::now();
+        self.alarm = Schedule::new();
+        // Cannot fail: the schedule is empty
+        let _added = self.alarm.add((), When::Daily(after_midnight), now);
+        // The firmware's scheduler looks at the time as soon as it starts
+        let _due = self.alarm.take_due(now, self.local_seconds());
+    }
+
+    /// Runs the alarm if its time has come since the last look, as the firmware's scheduler
+    /// does: its `blink` action holds the display, blinking, until the next press. Returns
+    /// whether it went off.
+    pub fn run_due_alarm(&mut self) -> bool {
+        let went_off = !self
+            .alarm
+            .take_due(Instant::now(), self.local_seconds())
+            .is_empty();
+        if went_off {
+            self.face.hold();
+        }
+        went_off
+    }
+
+    /// Local time in seconds since 1970, if the clock knows the time.
+    fn local_seconds(&self) -> Option<i64> {
+        self.face.clock_time.now().local_seconds()
+    }
+
+    /// Moves to the UTC offset `utc_offset_minutes`, as the web page or USB console does (such
+    /// as at a daylight saving transition).
+    pub fn set_utc_offset_minutes(&mut self, utc_offset_minutes: i32) {
+        self.face
+            .clock_time
+            .set_utc_offset_minutes(utc_offset_minutes);
+    }
+
+    /// Replaces the settings.
+    pub const fn set_settings(&mut self, settings: Settings) {
+        self.face.content.settings = settings;
+    }
+
+    /// Replaces the reminders.
+    pub const fn set_reminders(&mut self, reminders: Reminders) {
+        self.face.content.reminders = reminders;
+    }
+}
@@ -0,0 +1,51 @@
+use heapless::Deque;
+
+/// How many syncs `SkewHistory` remembers.
+pub const SKEW_HISTORY_LEN: usize = 16;
+
+/// The corrections applied at recent syncs, for measuring crystal drift.
+///
+/// Each entry is how far the sync moved the clock, in milliseconds. Positive means the clock
+/// was behind. Once full, each new entry replaces the oldest.
+#[derive(Debug, Clone, Default)]
+pub struct SkewHistory(Deque<i32, SKEW_HISTORY_LEN>);
+
+impl SkewHistory {
+    /// Creates an empty history.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self(Deque::new())
+    }
+
+    /// Records the correction applied by a sync, dropping the oldest if full.
+    pub fn record(&mut self, correction_millis: i32) {
+        if self.0.is_full() {
+            let _oldest = self.0.pop_front();
+        }
+        // Cannot fail: there is room now
+        let _pushed = self.0.push_back(correction_millis);
+    }
+
+    /// The most recent correction, if there has been a sync since the first.
+    #[must_use]
+    pub fn latest(&self) -> Option<i32> {
+        self.0.back().copied()
+    }
+
+    /// The corrections, most recent first.
+    pub fn iter_recent_first(&self) -> impl Iterator<Item = i32> + '_ {
+        self.0.iter().rev().copied()
+    }
+
+    /// How many corrections are recorded.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether no corrections are recorded yet.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
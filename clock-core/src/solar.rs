@@ -0,0 +1,259 @@
+//! Sunrise and sunset from NOAA's approximate solar equations, in integer math.
+//!
+//! Angles are in millidegrees and trigonometric values are scaled by a million, so the
+//! results are good to a minute or two without floating point.
+
+use time::{util::days_in_year, Date};
+
+use crate::{Brightness, ClockTime};
+
+/// A place on Earth, for working out when the sun rises and sets there.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    /// North is positive, from -90,000 to 90,000.
+    pub latitude_millidegrees: i32,
+    /// East is positive, from -180,000 to 180,000.
+    pub longitude_millidegrees: i32,
+}
+
+impl Location {
+    /// Parses decimal degrees such as `47.6062` and `-122.3321`. Digits past the third
+    /// decimal place are ignored.
+    #[must_use]
+    pub fn parse(latitude: &str, longitude: &str) -> Option<Self> {
+        let latitude_millidegrees = parse_millidegrees(latitude)?;
+        let longitude_millidegrees = parse_millidegrees(longitude)?;
+        ((-90_000..=90_000).contains(&latitude_millidegrees)
+            && (-180_000..=180_000).contains(&longitude_millidegrees))
+        .then_some(Self {
+            latitude_millidegrees,
+            longitude_millidegrees,
+        })
+    }
+}
+
+/// When the sun is up on a given day.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SunTimes {
+    /// The sun rises and sets. Either time may fall outside `0..86_400` far from Greenwich.
+    RisesAndSets {
+        /// Seconds after UTC midnight.
+        sunrise: i32,
+        /// Seconds after UTC midnight.
+        sunset: i32,
+    },
+    /// The sun never sets (polar day).
+    AlwaysUp,
+    /// The sun never rises (polar night).
+    AlwaysDown,
+}
+
+impl SunTimes {
+    /// Whether the sun is up at `local_seconds` after local midnight, in a time zone
+    /// `utc_offset_minutes` ahead of UTC.
+    #[must_use]
+    pub fn is_day(self, local_seconds: i32, utc_offset_minutes: i32) -> bool {
+        const SECONDS_PER_DAY: i32 = 86_400;
+        match self {
+            Self::RisesAndSets { sunrise, sunset } => {
+                let offset_seconds = utc_offset_minutes.saturating_mul(60);
+                let local = |utc_seconds: i32| {
+                    utc_seconds
+                        .saturating_add(offset_seconds)
+                        .rem_euclid(SECONDS_PER_DAY)
+                };
+                let (sunrise, sunset) = (local(sunrise), local(sunset));
+                if sunrise <= sunset {
+                    (sunrise..sunset).contains(&local_seconds)
+                } else {
+                    // Day spans local midnight
+                    local_seconds >= sunrise || local_seconds < sunset
+                }
+            }
+            Self::AlwaysUp => true,
+            Self::AlwaysDown => false,
+        }
+    }
+}
+
+/// Sunrise and sunset at `location` on `date`.
+#[must_use]
+#[expect(
+    clippy::arithmetic_side_effects,
+    clippy::integer_division_remainder_used,
+    reason = "Every term is bounded by the trigonometric scale; divisors are non-zero constants or checked."
+)]
+pub fn sun_times(date: Date, location: Location) -> SunTimes {
+    // Zenith of the sun's center at sunrise: 90° plus refraction and the sun's radius
+    const SUNRISE_ZENITH_MILLIDEGREES: i64 = 90_833;
+
+    // Fractional year, as an angle
+    let gamma = 360_000 * i64::from(date.ordinal() - 1) / i64::from(days_in_year(date.year()));
+    let (sin_1, cos_1) = (sin(gamma), cos(gamma));
+    let (sin_2, cos_2) = (sin(2 * gamma), cos(2 * gamma));
+    let (sin_3, cos_3) = (sin(3 * gamma), cos(3 * gamma));
+
+    // Equation of time, in seconds (229.18 minutes per radian)
+    let equation_of_time = (75
+        + (1_868 * cos_1 - 32_077 * sin_1 - 14_615 * cos_2 - 40_849 * sin_2) / SCALE)
+        * 13_751
+        / SCALE;
+
+    // Solar declination, in microradians, then millidegrees
+    let declination_microradians = 6_918
+        + (-399_912 * cos_1 + 70_257 * sin_1 - 6_758 * cos_2 + 907 * sin_2 - 2_697 * cos_3
+            + 1_480 * sin_3)
+            / SCALE;
+    let declination = declination_microradians * 57_296 / SCALE;
+
+    // Hour angle of sunrise
+    let latitude = i64::from(location.latitude_millidegrees);
+    let numerator = cos(SUNRISE_ZENITH_MILLIDEGREES) - sin(latitude) * sin(declination) / SCALE;
+    let denominator = cos(latitude) * cos(declination) / SCALE;
+    if denominator == 0 {
+        // At a pole, the sun is up whenever it is north (or south) of the equator
+        return if (latitude > 0) == (declination > 0) {
+            SunTimes::AlwaysUp
+        } else {
+            SunTimes::AlwaysDown
+        };
+    }
+    let cos_hour_angle = numerator * SCALE / denominator;
+    if cos_hour_angle > SCALE {
+        return SunTimes::AlwaysDown;
+    }
+    if cos_hour_angle < -SCALE {
+        return SunTimes::AlwaysUp;
+    }
+    let hour_angle = acos(cos_hour_angle);
+
+    // Four minutes (240 s) per degree of longitude or hour angle
+    let longitude = i64::from(location.longitude_millidegrees);
+    let noon = 43_200 - longitude * 240 / 1_000 - equation_of_time;
+    let half_day = hour_angle * 240 / 1_000;
+    // Both are within a few days of UTC midnight, so they fit in an i32
+    let seconds = |value: i64| i32::try_from(value).unwrap_or_default();
+    SunTimes::RisesAndSets {
+        sunrise: seconds(noon - half_day),
+        sunset: seconds(noon + half_day),
+    }
+}
+
+/// Dims the display between sunset and sunrise.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolarDimming {
+    /// Where the clock is.
+    pub location: Location,
+    /// The brightness from sunset to sunrise. `Brightness::OFF` blanks the display.
+    pub night: Brightness,
+}
+
+impl SolarDimming {
+    /// The brightness for the current local time. Full until the clock knows the date.
+    #[must_use]
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "Seconds within a day fit in an i32."
+    )]
+    pub fn brightness(&self, clock_time: &ClockTime) -> Brightness {
+        let Some(date) = clock_time.local_date() else {
+            return Brightness::MAX;
+        };
+        let local_seconds = clock_time.now().since_midnight().as_secs() as i32;
+        let today = sun_times(date, self.location);
+        if today.is_day(local_seconds, clock_time.utc_offset_minutes()) {
+            Brightness::MAX
+        } else {
+            self.night
+        }
+    }
+}
+
+/// Trigonometric values are scaled by this.
+const SCALE: i64 = 1_000_000;
+
+/// `sin` of each whole degree from 0° to 90°, scaled by `SCALE`.
+const SINE_TABLE: [i64; 91] = [
+    0, 17_452, 34_899, 52_336, 69_756, 87_156, 104_528, 121_869, 139_173, 156_434,
+    173_648, 190_809, 207_912, 224_951, 241_922, 258_819, 275_637, 292_372, 309_017, 325_568,
+    342_020, 358_368, 374_607, 390_731, 406_737, 422_618, 438_371, 453_990, 469_472, 484_810,
+    500_000, 515_038, 529_919, 544_639, 559_193, 573_576, 587_785, 601_815, 615_661, 629_320,
+    642_788, 656_059, 669_131, 681_998, 694_658, 707_107, 719_340, 731_354, 743_145, 754_710,
+    766_044, 777_146, 788_011, 798_636, 809_017, 819_152, 829_038, 838_671, 848_048, 857_167,
+    866_025, 874_620, 882_948, 891_007, 898_794, 906_308, 913_545, 920_505, 927_184, 933_580,
+    939_693, 945_519, 951_057, 956_305, 961_262, 965_926, 970_296, 974_370, 978_148, 981_627,
+    984_808, 987_688, 990_268, 992_546, 994_522, 996_195, 997_564, 998_630, 999_391, 999_848,
+    1_000_000,
+];
+
+/// `sin` of an angle in millidegrees, scaled by `SCALE`, interpolated from `SINE_TABLE`.
+#[expect(
+    clippy::arithmetic_side_effects,
+    clippy::integer_division_remainder_used,
+    reason = "The angle is reduced to 0..=90° first, so the arithmetic is bounded."
+)]
+fn sin(millidegrees: i64) -> i64 {
+    let angle = millidegrees.rem_euclid(360_000);
+    let (first_quadrant, sign) = match angle {
+        0..=90_000 => (angle, 1),
+        90_001..=180_000 => (180_000 - angle, 1),
+        180_001..=270_000 => (angle - 180_000, -1),
+        _ => (360_000 - angle, -1),
+    };
+    let degrees = usize::try_from(first_quadrant / 1_000).unwrap_or_default();
+    let fraction = first_quadrant % 1_000;
+    let low = SINE_TABLE.get(degrees).copied().unwrap_or_default();
+    let high = SINE_TABLE
+        .get(degrees.saturating_add(1))
+        .copied()
+        .unwrap_or(low);
+    sign * (low + (high - low) * fraction / 1_000)
+}
+
+/// `cos` of an angle in millidegrees, scaled by `SCALE`.
+fn cos(millidegrees: i64) -> i64 {
+    sin(90_000_i64.saturating_sub(millidegrees))
+}
+
+/// The angle in millidegrees (0° to 180°) whose `cos` is `value`, scaled by `SCALE`.
+#[expect(clippy::arithmetic_side_effects, reason = "The search stays within 0..=180,000.")]
+fn acos(value: i64) -> i64 {
+    // cos falls steadily from 0° to 180°, so binary search for the angle
+    let (mut low, mut high) = (0_i64, 180_000_i64);
+    while low < high {
+        let middle = i64::midpoint(low, high);
+        if cos(middle) > value {
+            low = middle + 1;
+        } else {
+            high = middle;
+        }
+    }
+    low
+}
+
+/// Parses decimal degrees such as `-122.3321` into millidegrees.
+#[expect(
+    clippy::arithmetic_side_effects,
+    clippy::integer_division_remainder_used,
+    reason = "Digits are checked, and the whole part is limited to three digits."
+)]
+fn parse_millidegrees(text: &str) -> Option<i32> {
+    let (negative, unsigned) = text
+        .strip_prefix('-')
+        .map_or((false, text), |rest| (true, rest));
+    let (whole, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    if whole.is_empty() || whole.len() > 3 {
+        return None;
+    }
+    let mut millidegrees = whole.parse::<i32>().ok()? * 1_000;
+    let mut place = 100;
+    for digit in fraction.chars() {
+        let digit = i32::try_from(digit.to_digit(10)?).ok()?;
+        millidegrees += digit * place;
+        place /= 10;
+    }
+    Some(if negative { -millidegrees } else { millidegrees })
+}
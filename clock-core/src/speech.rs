@@ -0,0 +1,154 @@
+//! The talking clock's words: which recorded clips say a time, and where each clip is in a
+//! voice file.
+//!
+//! A voice file is `VOX1`, the sample rate as a little-endian `u32`, the number of clips
+//! (`VOICE_CLIPS`) as a `u32`, then each clip's start (from the start of the file) and length
+//! in bytes, as `u32`s, in `Word::clip_index` order. The clips are mono, 16-bit,
+//! little-endian PCM. `tools/make_voice.py` writes one from a folder of WAV files.
+
+use heapless::Vec;
+
+use crate::{Error, Result};
+
+/// The most words `spoken_time` uses, as in "It's twelve forty-five".
+pub const MAX_SPOKEN_WORDS: usize = 4;
+
+/// The clips a voice file holds: "It's", "o'clock", "oh", one to nineteen, and twenty to
+/// fifty.
+pub const VOICE_CLIPS: usize = 26;
+
+/// Marks a voice file.
+const VOICE_MAGIC: [u8; 4] = *b"VOX1";
+
+/// Bytes before the clip table: the magic, the sample rate, and the clip count.
+const VOICE_HEADER_LEN: usize = 12;
+
+/// The sample rates a voice file may use, in hertz, at least and at most.
+const SAMPLE_RATES_HZ: (u32, u32) = (8_000, 48_000);
+
+/// A word the talking clock can say.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Word {
+    /// "It's", which starts every announcement.
+    Its,
+    /// "O'clock", after the hour on the hour.
+    OClock,
+    /// "Oh", before the minutes one to nine.
+    Oh,
+    /// One to nineteen, or twenty, thirty, forty, or fifty.
+    Number(u8),
+}
+
+impl Word {
+    /// Where the word's clip is in a voice file's table, or `None` for a number without one.
+    #[must_use]
+    pub fn clip_index(self) -> Option<usize> {
+        match self {
+            Self::Its => Some(0),
+            Self::OClock => Some(1),
+            Self::Oh => Some(2),
+            Self::Number(number @ 1..=19) => Some(usize::from(number).saturating_add(2)),
+            Self::Number(20) => Some(22),
+            Self::Number(30) => Some(23),
+            Self::Number(40) => Some(24),
+            Self::Number(50) => Some(25),
+            Self::Number(_) => None,
+        }
+    }
+}
+
+/// The words that say `hours` (1-12) and `minutes` (0-59), such as "It's eight thirty",
+/// "It's nine oh five", or "It's ten o'clock".
+#[must_use]
+#[expect(
+    clippy::arithmetic_side_effects,
+    clippy::integer_division_remainder_used,
+    reason = "Minutes are less than 60, and 10 is not zero."
+)]
+pub fn spoken_time(hours: u8, minutes: u8) -> Vec<Word, MAX_SPOKEN_WORDS> {
+    let hour = Word::Number(hours);
+    let words: &[Word] = match minutes {
+        0 => &[Word::Its, hour, Word::OClock],
+        1..=9 => &[Word::Its, hour, Word::Oh, Word::Number(minutes)],
+        10..=19 => &[Word::Its, hour, Word::Number(minutes)],
+        _ if minutes % 10 == 0 => &[Word::Its, hour, Word::Number(minutes)],
+        _ => &[
+            Word::Its,
+            hour,
+            Word::Number(minutes / 10 * 10),
+            Word::Number(minutes % 10),
+        ],
+    };
+    Vec::from_slice(words).unwrap_or_default()
+}
+
+/// The recorded words of a voice file, checked by `VoiceBank::new`.
+#[derive(Debug, Clone, Copy)]
+pub struct VoiceBank<'a> {
+    bytes: &'a [u8],
+    sample_rate_hz: u32,
+}
+
+impl<'a> VoiceBank<'a> {
+    /// Reads a voice file in the format described in this module.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file is not marked as a voice file, has the wrong number of
+    /// clips or an unsupported sample rate, or a clip runs past its end.
+    pub fn new(bytes: &'a [u8]) -> Result<Self> {
+        let (min_hz, max_hz) = SAMPLE_RATES_HZ;
+        if bytes.get(..4) != Some(&VOICE_MAGIC[..])
+            || read_u32(bytes, 8).and_then(|count| usize::try_from(count).ok())
+                != Some(VOICE_CLIPS)
+        {
+            return Err(Error::VoiceFormat);
+        }
+        let sample_rate_hz = read_u32(bytes, 4)
+            .filter(|rate| (min_hz..=max_hz).contains(rate))
+            .ok_or(Error::VoiceFormat)?;
+        let voice_bank = Self {
+            bytes,
+            sample_rate_hz,
+        };
+        if (0..VOICE_CLIPS).all(|index| voice_bank.clip_bytes(index).is_some()) {
+            Ok(voice_bank)
+        } else {
+            Err(Error::VoiceFormat)
+        }
+    }
+
+    /// The clips' sample rate, in hertz.
+    #[must_use]
+    pub const fn sample_rate_hz(&self) -> u32 {
+        self.sample_rate_hz
+    }
+
+    /// The samples of `words`, one after another, with `gap_samples` of silence after each.
+    pub fn samples(self, words: &[Word], gap_samples: usize) -> impl Iterator<Item = i16> {
+        words.iter().flat_map(move |word| {
+            let clip = word
+                .clip_index()
+                .and_then(|index| self.clip_bytes(index))
+                .unwrap_or_default();
+            clip.chunks_exact(2)
+                .map(|pair| <[u8; 2]>::try_from(pair).map_or(0, i16::from_le_bytes))
+                .chain(core::iter::repeat_n(0, gap_samples))
+        })
+    }
+
+    /// The bytes of clip `index`, or `None` if its table entry runs past the file.
+    fn clip_bytes(&self, index: usize) -> Option<&'a [u8]> {
+        let entry = index.checked_mul(8)?.checked_add(VOICE_HEADER_LEN)?;
+        let start = usize::try_from(read_u32(self.bytes, entry)?).ok()?;
+        let len = usize::try_from(read_u32(self.bytes, entry.checked_add(4)?)?).ok()?;
+        self.bytes.get(start..start.checked_add(len)?)
+    }
+}
+
+/// The little-endian `u32` at `offset` in `bytes`.
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let word = bytes.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes(word.try_into().ok()?))
+}
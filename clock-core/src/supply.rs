@@ -0,0 +1,69 @@
+/// How far the voltage must come back past a threshold before the level changes back, so a
+/// reading that wobbles around a threshold does not flap between levels.
+pub const SUPPLY_HYSTERESIS_MILLIVOLTS: u16 = 100;
+
+/// The ADC's full-scale reading (12 bits) and the voltage it stands for.
+const ADC_FULL_SCALE: u32 = 4096;
+const ADC_REFERENCE_MILLIVOLTS: u32 = 3300;
+
+/// How a battery-powered supply is measured and when it counts as low.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SupplyConfig {
+    /// How many times larger the supply is than the voltage at the ADC pin (3 for a divider
+    /// like the Pico's own 200k/100k one on VSYS).
+    pub divider: u16,
+    /// Below this the clock warns of a flat battery.
+    pub low_millivolts: u16,
+    /// Below this the clock stops writing flash, since power may fail partway through.
+    pub critical_millivolts: u16,
+}
+
+impl SupplyConfig {
+    /// The supply voltage, in millivolts, for a raw 12-bit ADC reading.
+    #[must_use]
+    #[expect(
+        clippy::integer_division_remainder_used,
+        reason = "Scaling down to millivolts needs no rounding."
+    )]
+    pub fn millivolts(&self, raw: u16) -> u16 {
+        let scaled = u32::from(raw)
+            .saturating_mul(ADC_REFERENCE_MILLIVOLTS)
+            .saturating_mul(u32::from(self.divider));
+        u16::try_from(scaled / ADC_FULL_SCALE).unwrap_or(u16::MAX)
+    }
+}
+
+/// How healthy the supply is.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SupplyLevel {
+    #[default]
+    Normal,
+    /// Below `SupplyConfig::low_millivolts`: the battery needs changing soon.
+    Low,
+    /// Below `SupplyConfig::critical_millivolts`: power may fail at any moment.
+    Critical,
+}
+
+impl SupplyLevel {
+    /// The level after a reading of `millivolts`. Falling to a lower level happens as soon as
+    /// the voltage drops below its threshold; climbing back needs `SUPPLY_HYSTERESIS_MILLIVOLTS`
+    /// more.
+    #[must_use]
+    pub const fn next(self, millivolts: u16, config: &SupplyConfig) -> Self {
+        let low_exit = config
+            .low_millivolts
+            .saturating_add(SUPPLY_HYSTERESIS_MILLIVOLTS);
+        let critical_exit = config
+            .critical_millivolts
+            .saturating_add(SUPPLY_HYSTERESIS_MILLIVOLTS);
+        match self {
+            _ if millivolts < config.critical_millivolts => Self::Critical,
+            Self::Critical if millivolts < critical_exit => Self::Critical,
+            _ if millivolts < config.low_millivolts => Self::Low,
+            Self::Critical | Self::Low if millivolts < low_exit => Self::Low,
+            Self::Normal | Self::Low | Self::Critical => Self::Normal,
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use crate::render_util::temperature_chars;
+
+/// A temperature in whole degrees, in whatever unit its source reports.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Temperature(pub i16);
+
+impl Temperature {
+    /// Right-aligns the temperature with a degree sign, such as ` 72°` or `-12°`.
+    /// Returns `None` unless it is in -99..=999.
+    #[must_use]
+    pub fn chars(self) -> Option<[char; 4]> {
+        let Self(degrees) = self;
+        temperature_chars(degrees)
+    }
+}
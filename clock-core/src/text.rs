@@ -0,0 +1,99 @@
+use core::fmt::{self, Write};
+
+use crate::{Error, Result};
+
+/// Up to `N` characters, held by value, so text can go through a channel or into a `Copy`
+/// struct without a lifetime or an allocation. Pushed messages, the carousel's message, and
+/// messages built with `write!` all use it.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Text<const N: usize> {
+    chars: [char; N],
+    len: usize,
+}
+
+impl<const N: usize> Text<N> {
+    /// Creates an empty text.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            chars: [' '; N],
+            len: 0,
+        }
+    }
+
+    /// Copies as much of `text` as fits, for messages where a cut-off end beats none.
+    #[must_use]
+    pub fn truncated(text: &str) -> Self {
+        let mut copy = Self::new();
+        for char in text.chars() {
+            if copy.push(char).is_err() {
+                break;
+            }
+        }
+        copy
+    }
+
+    /// Adds `char` to the end.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::TextTooLong` if the text already has `N` characters.
+    pub fn push(&mut self, char: char) -> Result<()> {
+        *self.chars.get_mut(self.len).ok_or(Error::TextTooLong)? = char;
+        self.len = self.len.saturating_add(1);
+        Ok(())
+    }
+
+    /// The characters.
+    #[must_use]
+    pub fn chars(&self) -> &[char] {
+        self.chars.get(..self.len).unwrap_or_default()
+    }
+
+    /// How many characters there are.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether there are no characters.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<const N: usize> TryFrom<&str> for Text<N> {
+    type Error = Error;
+
+    /// Copies `text`, or returns `Error::TextTooLong` if it has more than `N` characters.
+    fn try_from(text: &str) -> Result<Self> {
+        let mut copy = Self::new();
+        for char in text.chars() {
+            copy.push(char)?;
+        }
+        Ok(copy)
+    }
+}
+
+impl<const N: usize> Default for Text<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> fmt::Display for Text<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.chars().iter().try_for_each(|&char| f.write_char(char))
+    }
+}
+
+impl<const N: usize> Write for Text<N> {
+    /// Appends `text`, failing (with what fit kept) if it does not all fit.
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        text.chars()
+            .try_for_each(|char| self.push(char))
+            .map_err(|_| fmt::Error)
+    }
+}
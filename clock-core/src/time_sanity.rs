@@ -0,0 +1,104 @@
+use embassy_time::{Duration, Instant};
+
+use crate::UnixSeconds;
+
+/// 2024-01-01 00:00:00 UTC. Any earlier time must come from a broken or spoofed source.
+pub const EARLIEST_PLAUSIBLE: UnixSeconds = UnixSeconds(1_704_067_200);
+
+/// Seconds in an average (365.25-day) year.
+const SECONDS_PER_YEAR: i64 = 31_557_600;
+
+/// Crystal drift allowed between syncs: 200 ppm, one part in 5,000.
+const DRIFT_DIVISOR: u64 = 5_000;
+
+/// After this many rejected jumps in a row, the next time is believed and becomes the new
+/// baseline, so one bad sync cannot lock out every good one that follows.
+const MAX_CONSECUTIVE_JUMPS: u8 = 3;
+
+/// Why a network time was not believed.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncRejection {
+    /// The time is before [`EARLIEST_PLAUSIBLE`].
+    TooEarly,
+    /// The time is more years after the firmware was built than allowed.
+    TooLate,
+    /// The time is further from what the last accepted sync predicts than allowed.
+    TooFarFromExpected {
+        /// How far ahead (positive) or behind (negative) the predicted time it was.
+        jump_seconds: i64,
+    },
+}
+
+/// Decides whether times from the network are believable.
+///
+/// Each accepted time becomes the baseline for the next: the next time must be within
+/// `max_jump` (plus crystal drift) of the baseline advanced by the elapsed monotonic time.
+#[derive(Debug, Clone)]
+pub struct TimeSanity {
+    latest_plausible: UnixSeconds,
+    max_jump: Duration,
+    last_accepted: Option<(UnixSeconds, Instant)>,
+    consecutive_jumps: u8,
+}
+
+impl TimeSanity {
+    /// Creates a checker that rejects times more than `max_years_ahead` years after `built_at`
+    /// (usually the firmware's build time) or that move the clock by more than `max_jump`.
+    #[must_use]
+    pub fn new(built_at: UnixSeconds, max_years_ahead: u16, max_jump: Duration) -> Self {
+        let latest = built_at
+            .as_i64()
+            .saturating_add(i64::from(max_years_ahead).saturating_mul(SECONDS_PER_YEAR));
+        Self {
+            latest_plausible: UnixSeconds(latest),
+            max_jump,
+            last_accepted: None,
+            consecutive_jumps: 0,
+        }
+    }
+
+    /// Checks `unix_seconds`, received at `received_at`, and records it as the new baseline
+    /// if it is believable.
+    ///
+    /// # Errors
+    ///
+    /// Returns the reason the time was not believed.
+    #[expect(
+        clippy::integer_division_remainder_used,
+        clippy::cast_possible_wrap,
+        reason = "Dividing by a non-zero constant; elapsed seconds fit easily in an i64."
+    )]
+    pub fn check(
+        &mut self,
+        unix_seconds: UnixSeconds,
+        received_at: Instant,
+    ) -> Result<(), SyncRejection> {
+        if unix_seconds < EARLIEST_PLAUSIBLE {
+            return Err(SyncRejection::TooEarly);
+        }
+        if unix_seconds > self.latest_plausible {
+            return Err(SyncRejection::TooLate);
+        }
+        if let Some((last_unix_seconds, last_received_at)) = self.last_accepted {
+            let elapsed = received_at.saturating_duration_since(last_received_at);
+            let expected = last_unix_seconds
+                .as_i64()
+                .saturating_add(elapsed.as_secs() as i64);
+            let jump_seconds = unix_seconds.as_i64().saturating_sub(expected);
+            let allowed = self
+                .max_jump
+                .as_secs()
+                .saturating_add(elapsed.as_secs() / DRIFT_DIVISOR);
+            if jump_seconds.unsigned_abs() > allowed
+                && self.consecutive_jumps < MAX_CONSECUTIVE_JUMPS
+            {
+                self.consecutive_jumps = self.consecutive_jumps.saturating_add(1);
+                return Err(SyncRejection::TooFarFromExpected { jump_seconds });
+            }
+        }
+        self.consecutive_jumps = 0;
+        self.last_accepted = Some((unix_seconds, received_at));
+        Ok(())
+    }
+}
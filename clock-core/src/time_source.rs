@@ -0,0 +1,125 @@
+use embassy_time::{Duration, Instant};
+
+/// How long a source keeps its claim on the clock without setting it again. Every source
+/// syncs hourly, so this allows two missed syncs before a worse source may take over.
+pub const SOURCE_STALE_AFTER: Duration = Duration::from_secs(3 * 60 * 60);
+
+/// Where a synced time came from.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeSource {
+    /// An NTP server.
+    Ntp,
+    /// The `Date:` header of an HTTP response (`TIME_FALLBACK_URL`).
+    HttpDate,
+    /// A GPS receiver's NMEA sentences.
+    Gps,
+    /// A DCF77 or WWVB radio time signal.
+    Radio,
+    /// A PC, through `set time` on the USB console.
+    Usb,
+}
+
+impl TimeSource {
+    /// How much this source is trusted over the others, higher being better: NTP (to a few
+    /// milliseconds), then GPS (a sentence's delay), then radio (a pulse's length), then a PC
+    /// over USB (whole seconds, sent on the second), then an HTTP date (whole seconds plus a
+    /// round trip).
+    #[must_use]
+    pub const fn rank(self) -> u8 {
+        match self {
+            Self::Ntp => 5,
+            Self::Gps => 4,
+            Self::Radio => 3,
+            Self::Usb => 2,
+            Self::HttpDate => 1,
+        }
+    }
+
+    /// A short lowercase name, for logs and metrics labels.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Ntp => "ntp",
+            Self::HttpDate => "http-date",
+            Self::Gps => "gps",
+            Self::Radio => "radio",
+            Self::Usb => "usb",
+        }
+    }
+
+    /// How the display spells this source.
+    #[must_use]
+    pub const fn chars(self) -> [char; 4] {
+        match self {
+            Self::Ntp => ['n', 't', 'P', ' '],
+            Self::HttpDate => ['H', 't', 't', 'P'],
+            Self::Gps => ['G', 'P', 'S', ' '],
+            Self::Radio => ['r', 'A', 'd', 'o'],
+            Self::Usb => ['U', 'S', 'b', ' '],
+        }
+    }
+}
+
+/// What `TimeArbiter::offer` decided.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arbitration {
+    /// Set the clock: the source now disciplines it.
+    Accept,
+    /// Leave the clock alone: a better source set it recently.
+    Outranked(TimeSource),
+}
+
+/// Decides which of several time sources sets the clock, and remembers which one does.
+///
+/// A time is accepted from the source that set the clock last, from any better-ranked
+/// source, or from any source once the last one has gone quiet for `SOURCE_STALE_AFTER`.
+/// When two sources disagree, the better one wins: a worse one is ignored while the better
+/// one keeps syncing, so the clock does not jump back and forth between them.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TimeArbiter {
+    /// The source that last set the clock, and when.
+    current: Option<(TimeSource, Instant)>,
+    /// How many times a worse source was ignored.
+    outranked_count: u32,
+}
+
+impl TimeArbiter {
+    /// An arbiter that has not yet accepted any time.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            current: None,
+            outranked_count: 0,
+        }
+    }
+
+    /// Decides whether a time from `source`, arriving at `now`, should set the clock, and
+    /// records the source if so.
+    pub fn offer(&mut self, source: TimeSource, now: Instant) -> Arbitration {
+        if let Some((current, set_at)) = self.current {
+            if current.rank() > source.rank()
+                && now.saturating_duration_since(set_at) < SOURCE_STALE_AFTER
+            {
+                self.outranked_count = self.outranked_count.saturating_add(1);
+                return Arbitration::Outranked(current);
+            }
+        }
+        self.current = Some((source, now));
+        Arbitration::Accept
+    }
+
+    /// The source that disciplines the clock and when it last set it, if any has.
+    #[must_use]
+    pub const fn current(&self) -> Option<(TimeSource, Instant)> {
+        self.current
+    }
+
+    /// How many times since boot a time was ignored because a better source was fresh.
+    #[must_use]
+    pub const fn outranked_count(&self) -> u32 {
+        self.outranked_count
+    }
+}
@@ -0,0 +1,147 @@
+//! What the clock's state machine needs from time sync and `WiFi`.
+//!
+//! The firmware's `TimeSync` and `Wifi` devices implement these traits; with the `host`
+//! feature, `ScriptedTimeSync` and `MockWifi` implement them for scenario tests.
+
+use crate::{LeapIndicator, SyncRejection, TimeSource, UnixSeconds};
+
+/// The outcome of a sync (or a time from another source), as the state machine sees it.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeSyncEvent {
+    Success {
+        unix_seconds: UnixSeconds,
+        /// Any leap second the server announced for the end of the UTC day
+        leap: LeapIndicator,
+        /// How much to trust the time
+        accuracy: SyncAccuracy,
+        /// Where the time came from
+        source: TimeSource,
+    },
+    /// No time arrived
+    Failed(SyncError),
+    /// A time arrived but was implausible, so the clock was left alone
+    Rejected(SyncRejection),
+}
+
+impl TimeSyncEvent {
+    /// What the display shows for this outcome (`SyncError::Rejected` for an implausible
+    /// time), or `None` for a success.
+    #[must_use]
+    pub const fn sync_error(&self) -> Option<SyncError> {
+        match self {
+            Self::Success { .. } => None,
+            Self::Failed(error) => Some(*error),
+            Self::Rejected(_) => Some(SyncError::Rejected),
+        }
+    }
+}
+
+/// Why a sync got no time.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncError {
+    /// The server's name did not resolve.
+    Dns,
+    /// The server did not answer in time.
+    Timeout,
+    /// `WiFi` is down (or not built in), or a socket on it failed.
+    Wifi,
+    /// The reply could not be read: too short, a bad timestamp, or no usable `Date:` header.
+    Parse,
+    /// A captive portal (such as a hotel's sign-in page) holds the network until someone
+    /// signs in.
+    Portal,
+    /// The server answered but its time cannot be trusted: it says it is unsynchronized, or
+    /// its reply failed authentication. The display also shows an implausible time
+    /// (`TimeSyncEvent::Rejected`) this way.
+    Rejected,
+}
+
+impl SyncError {
+    /// Every kind of failure, in the order metrics list them.
+    pub const ALL: [Self; 6] = [
+        Self::Dns,
+        Self::Timeout,
+        Self::Wifi,
+        Self::Parse,
+        Self::Portal,
+        Self::Rejected,
+    ];
+
+    /// A short lowercase name, for logs and metrics labels.
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Dns => "dns",
+            Self::Timeout => "timeout",
+            Self::Wifi => "wifi",
+            Self::Parse => "parse",
+            Self::Portal => "portal",
+            Self::Rejected => "rejected",
+        }
+    }
+
+    /// How the display spells this failure, in place of `FAIL`.
+    #[must_use]
+    pub const fn chars(self) -> [char; 4] {
+        match self {
+            Self::Dns => ['d', 'n', 'S', ' '],
+            Self::Timeout => ['t', 'o', 'u', 't'],
+            Self::Wifi => ['n', 'E', 't', ' '],
+            Self::Parse => ['P', 'A', 'r', 'S'],
+            Self::Portal => ['c', 'P', 't', 'l'],
+            Self::Rejected => ['d', 'E', 'n', 'Y'],
+        }
+    }
+}
+
+/// How precise a synced time is.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncAccuracy {
+    /// From NTP: well under a second.
+    Fine,
+    /// Whole seconds, plus a delay: the round trip of an HTTP `Date:` request, the time a
+    /// GPS receiver takes to send its sentence, the length of a radio time pulse, or the USB
+    /// latency of a PC's `set time`.
+    Coarse,
+}
+
+/// Where the state machine gets sync outcomes, and asks for a sync now.
+#[expect(
+    async_fn_in_trait,
+    reason = "Single-threaded executor, so the futures need not be Send."
+)]
+pub trait TimeSyncPort {
+    /// The `WiFi` the syncs run over.
+    type Wifi: WifiPort;
+
+    /// Waits for and returns the next sync outcome.
+    async fn wait(&self) -> TimeSyncEvent;
+
+    /// Syncs now instead of at the next scheduled time. The outcome arrives through `wait`.
+    fn request_sync(&self);
+
+    /// Takes the outcome `wait` would return at once, if there is one. Before `request_sync`,
+    /// this is an outcome from before the request, which `wait` must not report as its own.
+    fn take_unread(&self) -> Option<TimeSyncEvent>;
+
+    /// The `WiFi` the syncs run over.
+    fn wifi(&self) -> &Self::Wifi;
+}
+
+/// What the state machine needs from `WiFi`: the radio off while the clock is off, and the
+/// signal measured for `ClockState::Survey`.
+#[expect(
+    async_fn_in_trait,
+    reason = "Single-threaded executor, so the futures need not be Send."
+)]
+pub trait WifiPort {
+    /// Powers the radio up (`true`) or down.
+    fn set_radio(&self, on: bool);
+
+    /// Scans for the configured network and returns its strongest signal in dBm, or `None`
+    /// if it did not answer (or there is none).
+    async fn signal_dbm(&self) -> Option<i16>;
+}
@@ -0,0 +1,94 @@
+//! Certificate pinning for outbound TLS: reading pins from settings, fingerprinting a server's
+//! certificate, and the pieces needed to check its TLS 1.3 `CertificateVerify` signature.
+
+use sha2::{Digest, Sha256};
+
+/// The length of a SHA-256 certificate fingerprint, in bytes.
+pub const CERT_FINGERPRINT_LEN: usize = 32;
+
+/// The length of the message a TLS 1.3 server signs in `CertificateVerify` (with SHA-256).
+pub const CERTIFICATE_VERIFY_MESSAGE_LEN: usize = 130;
+
+/// The context string a TLS 1.3 server's `CertificateVerify` signature covers (RFC 8446
+/// section 4.4.3), after 64 spaces and before a zero byte and the transcript hash.
+const SERVER_VERIFY_CONTEXT: &[u8] = b"TLS 1.3, server CertificateVerify";
+
+/// What precedes a P-256 public key in a certificate: the `prime256v1` curve OID, then the
+/// header of the 66-byte BIT STRING holding the uncompressed point.
+const P256_KEY_PREFIX: &[u8] = &[
+    0x06, 0x08, 0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00,
+];
+
+/// The length of an uncompressed P-256 point (`0x04`, then x and y).
+const P256_POINT_LEN: usize = 65;
+
+/// Finds the pin for `host` in `pins`, a list of `host=fingerprint` entries separated by
+/// commas (`TLS_PINS`). Fingerprints are 64 hex digits, with or without colons between
+/// bytes, as `openssl x509 -fingerprint -sha256` prints them.
+#[must_use]
+pub fn find_tls_pin(pins: &str, host: &str) -> Option<[u8; CERT_FINGERPRINT_LEN]> {
+    pins.split(',').find_map(|entry| {
+        let (pin_host, fingerprint) = entry.split_once('=')?;
+        pin_host
+            .trim()
+            .eq_ignore_ascii_case(host)
+            .then(|| parse_fingerprint(fingerprint))
+            .flatten()
+    })
+}
+
+/// Parses a SHA-256 fingerprint written as 64 hex digits, ignoring colons and spaces.
+#[must_use]
+pub fn parse_fingerprint(text: &str) -> Option<[u8; CERT_FINGERPRINT_LEN]> {
+    let mut digits = text
+        .chars()
+        .filter(|&symbol| symbol != ':' && !symbol.is_ascii_whitespace())
+        .map(|symbol| symbol.to_digit(16).and_then(|digit| u8::try_from(digit).ok()));
+    let mut fingerprint = [0_u8; CERT_FINGERPRINT_LEN];
+    for byte in &mut fingerprint {
+        let high = digits.next()??;
+        let low = digits.next()??;
+        *byte = high.checked_mul(16)?.checked_add(low)?;
+    }
+    digits.next().is_none().then_some(fingerprint)
+}
+
+/// The SHA-256 fingerprint of a DER-encoded certificate.
+#[must_use]
+pub fn certificate_fingerprint(certificate: &[u8]) -> [u8; CERT_FINGERPRINT_LEN] {
+    Sha256::digest(certificate).into()
+}
+
+/// The uncompressed P-256 public key (65 bytes, starting `0x04`) of a DER-encoded
+/// certificate, or `None` if its key is of another kind.
+///
+/// This looks for the key's encoding rather than parsing the certificate, which is only safe
+/// once the certificate is trusted, such as after its fingerprint matched a pin.
+#[must_use]
+pub fn p256_public_key(certificate: &[u8]) -> Option<&[u8]> {
+    let start = certificate
+        .windows(P256_KEY_PREFIX.len())
+        .position(|window| window == P256_KEY_PREFIX)?
+        .checked_add(P256_KEY_PREFIX.len())?;
+    certificate
+        .get(start..start.checked_add(P256_POINT_LEN)?)
+        .filter(|point| point.first() == Some(&0x04))
+}
+
+/// The message a TLS 1.3 server signs in `CertificateVerify`, given the SHA-256 hash of the
+/// handshake up to and including its `Certificate`.
+#[must_use]
+pub fn certificate_verify_message(
+    transcript_hash: &[u8; CERT_FINGERPRINT_LEN],
+) -> [u8; CERTIFICATE_VERIFY_MESSAGE_LEN] {
+    let mut message = [0_u8; CERTIFICATE_VERIFY_MESSAGE_LEN];
+    let parts = [b' '; 64]
+        .iter()
+        .chain(SERVER_VERIFY_CONTEXT)
+        .chain(&[0])
+        .chain(transcript_hash);
+    for (slot, byte) in message.iter_mut().zip(parts) {
+        *slot = *byte;
+    }
+    message
+}
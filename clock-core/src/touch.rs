@@ -0,0 +1,51 @@
+//! Tap detection for a bare capacitive touch pad read by charge time.
+//!
+//! The pad is discharged, then left to charge through a pull-up while the firmware counts
+//! how long it takes to read high. A finger adds capacitance, so the count goes up. The
+//! untouched count drifts with temperature and humidity, so the baseline follows it slowly.
+
+/// How far above the baseline, in eighths, a count must be to be a touch.
+const TOUCH_EIGHTHS: u32 = 3;
+
+/// The smallest rise that counts as a touch, so a pad with a tiny baseline is not jittery.
+const MIN_TOUCH_RISE: u32 = 4;
+
+/// Finds taps in charge-time counts from a touch pad.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TouchDetector {
+    /// The untouched count, or `None` before the first sample.
+    baseline: Option<u32>,
+    /// Whether the pad is being touched, so a long touch is one tap.
+    touched: bool,
+}
+
+impl TouchDetector {
+    /// Creates a detector that takes its first sample as the untouched count.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            baseline: None,
+            touched: false,
+        }
+    }
+
+    /// Takes the next charge-time count. Returns `true` when a touch starts.
+    #[expect(
+        clippy::integer_division_remainder_used,
+        reason = "Eighths of a count need no rounding."
+    )]
+    pub fn sample(&mut self, count: u32) -> bool {
+        let baseline = *self.baseline.get_or_insert(count);
+        let rise = (baseline / 8)
+            .saturating_mul(TOUCH_EIGHTHS)
+            .max(MIN_TOUCH_RISE);
+        let is_touch = count >= baseline.saturating_add(rise);
+        if !is_touch {
+            // Follow slow drift, an eighth of the way each sample
+            self.baseline = Some((baseline.saturating_mul(7).saturating_add(count)) / 8);
+        }
+        let started = is_touch && !self.touched;
+        self.touched = is_touch;
+        started
+    }
+}
@@ -0,0 +1,200 @@
+//! Unix timestamp type for time-related devices
+
+use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+
+/// Units-safe wrapper for Unix timestamps (seconds since 1970-01-01 00:00:00 UTC)
+#[repr(transparent)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Debug)]
+pub struct UnixSeconds(pub i64);
+
+impl UnixSeconds {
+    /// Get the underlying i64 value
+    #[must_use]
+    pub const fn as_i64(self) -> i64 {
+        self.0
+    }
+
+    /// Convert NTP seconds (since 1900-01-01) to Unix seconds (since 1970-01-01)
+    ///
+    /// NTP's 32-bit seconds wrap on 2036-02-07 (the start of era 1). As in RFC 4330, a value
+    /// with the top bit clear is taken to be in era 1, so the clock keeps working until 2104.
+    #[must_use]
+    #[expect(
+        clippy::arithmetic_side_effects,
+        reason = "A u32 plus 2^32 minus a constant cannot overflow an i64."
+    )]
+    pub const fn from_ntp_seconds(ntp: u32) -> Option<Self> {
+        // 1900→1970 offset: 70 years * 365.25 days/year * 86400 seconds/day
+        const NTP_TO_UNIX_SECONDS: i64 = 2_208_988_800;
+        // Length of one NTP era (2^32 seconds, about 136 years)
+        const NTP_ERA_SECONDS: i64 = 1 << 32;
+        let era_offset = if (ntp & 0x8000_0000) == 0 {
+            NTP_ERA_SECONDS
+        } else {
+            0
+        };
+        // Promote to i64 safely, then subtract
+        let s = (ntp as i64) + era_offset - NTP_TO_UNIX_SECONDS;
+        // Reject negative (pre-1970)
+        if s >= 0 {
+            Some(Self(s))
+        } else {
+            None
+        }
+    }
+
+    /// Parses an HTTP `Date:` header value such as `Sun, 06 Nov 1994 08:49:37 GMT`.
+    ///
+    /// Only the IMF-fixdate form that current servers send is accepted. The weekday is not
+    /// checked.
+    #[must_use]
+    pub fn from_http_date(value: &str) -> Option<Self> {
+        let mut parts = value.split_ascii_whitespace();
+        let (_weekday, day, month, year, hms, zone) = (
+            parts.next()?,
+            parts.next()?,
+            parts.next()?,
+            parts.next()?,
+            parts.next()?,
+            parts.next()?,
+        );
+        if zone != "GMT" || parts.next().is_some() {
+            return None;
+        }
+        let month = match month {
+            "Jan" => Month::January,
+            "Feb" => Month::February,
+            "Mar" => Month::March,
+            "Apr" => Month::April,
+            "May" => Month::May,
+            "Jun" => Month::June,
+            "Jul" => Month::July,
+            "Aug" => Month::August,
+            "Sep" => Month::September,
+            "Oct" => Month::October,
+            "Nov" => Month::November,
+            "Dec" => Month::December,
+            _ => return None,
+        };
+        let date = Date::from_calendar_date(year.parse().ok()?, month, day.parse().ok()?).ok()?;
+        let mut hms = hms.split(':');
+        let time = Time::from_hms(
+            hms.next()?.parse().ok()?,
+            hms.next()?.parse().ok()?,
+            hms.next()?.parse().ok()?,
+        )
+        .ok()?;
+        Some(Self(PrimitiveDateTime::new(date, time).assume_utc().unix_timestamp()))
+    }
+
+    /// Midnight UTC at the start of `date`.
+    #[must_use]
+    pub fn from_date(date: Date) -> Self {
+        Self(date.midnight().assume_utc().unix_timestamp())
+    }
+
+    /// Parses an ISO 8601 date such as `2024-03-15` as midnight UTC at its start.
+    #[must_use]
+    pub fn from_iso_date(text: &str) -> Option<Self> {
+        let mut parts = text.split('-');
+        let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+        if parts.next().is_some() {
+            return None;
+        }
+        let month = Month::try_from(month.parse::<u8>().ok()?).ok()?;
+        let date = Date::from_calendar_date(year.parse().ok()?, month, day.parse().ok()?).ok()?;
+        Some(Self::from_date(date))
+    }
+
+    /// Whole days from `earlier` to `self`, rounded down (negative if `earlier` is later).
+    #[must_use]
+    pub const fn days_since(self, earlier: Self) -> i64 {
+        const SECONDS_PER_DAY: i64 = 86_400;
+        self.0.saturating_sub(earlier.0).div_euclid(SECONDS_PER_DAY)
+    }
+
+    /// Convert to OffsetDateTime with the given timezone offset
+    #[must_use]
+    pub fn to_offset_datetime(self, offset: UtcOffset) -> Option<OffsetDateTime> {
+        OffsetDateTime::from_unix_timestamp(self.as_i64())
+            .ok()
+            .map(|dt| dt.to_offset(offset))
+    }
+
+    /// Writes the local date, `utc_offset_minutes` from UTC, such as `Fri 2026-10-16`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `out` is full.
+    pub fn write_local_date(
+        self,
+        out: &mut impl core::fmt::Write,
+        utc_offset_minutes: i32,
+    ) -> core::fmt::Result {
+        const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+        let Some(local) = self.to_local(utc_offset_minutes) else {
+            return out.write_str("----------");
+        };
+        let weekday = WEEKDAYS
+            .get(usize::from(local.weekday().number_days_from_monday()))
+            .copied()
+            .unwrap_or_default();
+        write!(
+            out,
+            "{weekday} {:04}-{:02}-{:02}",
+            local.year(),
+            u8::from(local.month()),
+            local.day()
+        )
+    }
+
+    /// Writes the local time of day, `utc_offset_minutes` from UTC, such as `14:05:09`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `out` is full.
+    pub fn write_local_time(
+        self,
+        out: &mut impl core::fmt::Write,
+        utc_offset_minutes: i32,
+    ) -> core::fmt::Result {
+        let Some(local) = self.to_local(utc_offset_minutes) else {
+            return out.write_str("--:--:--");
+        };
+        write!(out, "{:02}:{:02}:{:02}", local.hour(), local.minute(), local.second())
+    }
+
+    fn to_local(self, utc_offset_minutes: i32) -> Option<OffsetDateTime> {
+        let offset = UtcOffset::from_whole_seconds(utc_offset_minutes.saturating_mul(60)).ok()?;
+        self.to_offset_datetime(offset)
+    }
+}
+
+/// The leap-second warning an NTP server sends with the time.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum LeapIndicator {
+    /// No leap second is coming.
+    #[default]
+    NoWarning,
+    /// The last minute of the current UTC day has 61 seconds.
+    InsertSecond,
+    /// The last minute of the current UTC day has 59 seconds.
+    DeleteSecond,
+    /// The server's own clock is not synchronized, so its time should not be trusted.
+    Unsynchronized,
+}
+
+impl LeapIndicator {
+    /// Reads the leap indicator from the top two bits of an NTP packet's first byte.
+    #[must_use]
+    pub const fn from_ntp_header(first_byte: u8) -> Self {
+        match first_byte >> 6 {
+            0 => Self::NoWarning,
+            1 => Self::InsertSecond,
+            2 => Self::DeleteSecond,
+            _ => Self::Unsynchronized,
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Numbers into cells, grouping cells by what they show, and the dimmed cells through a turn.
+//!
+//! Run with `cargo test -p clock-core --features host --target <host triple>`.
+#![cfg(all(feature = "host", not(target_os = "none")))]
+
+use clock_core::{BitMatrix, BitsToIndexes, Leds, CELL_COUNT};
+
+#[test]
+fn a_number_fills_the_cells_from_the_right() {
+    let bit_matrix = BitMatrix::from_number(42, Leds::SPACE);
+    let mut expected = [Leds::SPACE; CELL_COUNT];
+    expected[CELL_COUNT - 2] = Leds::DIGITS[4];
+    expected[CELL_COUNT - 1] = Leds::DIGITS[2];
+    assert_eq!(bit_matrix, BitMatrix::new(expected));
+}
+
+#[test]
+fn a_number_too_long_lights_every_decimal_point() {
+    let too_long = 10_u16.pow(u32::try_from(CELL_COUNT).expect("a few cells"));
+    let bit_matrix = BitMatrix::from_number(too_long, Leds::SPACE);
+    assert!(bit_matrix.iter().all(|&bits| bits & Leds::DECIMAL != 0));
+}
+
+#[test]
+fn cells_showing_the_same_bits_are_grouped() {
+    // Alternating digits, with the last cell blank
+    let mut cells = [Leds::DIGITS[1]; CELL_COUNT];
+    for cell in cells.iter_mut().skip(1).step_by(2) {
+        *cell = Leds::DIGITS[7];
+    }
+    cells[CELL_COUNT - 1] = Leds::SPACE;
+    let mut bits_to_indexes = BitsToIndexes::new();
+    BitMatrix::new(cells)
+        .bits_to_indexes(&mut bits_to_indexes)
+        .expect("room for every cell");
+
+    // Blank cells are not lit, so they are in no group
+    let grouped: usize = bits_to_indexes.values().map(|indexes| indexes.len()).sum();
+    assert_eq!(grouped, CELL_COUNT - 1);
+    for (bits, indexes) in &bits_to_indexes {
+        for &index in indexes {
+            assert_eq!(cells[usize::from(index)], bits.get(), "cell {index}");
+        }
+    }
+}
+
+#[test]
+fn turning_the_display_over_moves_the_dimmed_cells() {
+    let mut bit_matrix = BitMatrix::from_bits(Leds::DIGITS[8]);
+    bit_matrix.dim(0);
+    let rotated = bit_matrix.rotated_180();
+    assert!(rotated.is_dimmed(CELL_COUNT - 1));
+    assert!((0..CELL_COUNT - 1).all(|index| !rotated.is_dimmed(index)));
+}
@@ -0,0 +1,107 @@
+//! Finding where the climate log goes on: through many wraps of a ring written the way the
+//! firmware writes it, after a power failure between erasing and writing, and in flash that
+//! does not hold a log.
+//!
+//! Run with `cargo test -p clock-core --features host --target <host triple>`.
+#![cfg(all(feature = "host", not(target_os = "none")))]
+
+use core::convert::Infallible;
+
+use clock_core::climate_log_head;
+
+const SLOTS: usize = 16;
+const SLOTS_PER_SECTOR: usize = 8;
+
+/// A ring of slots, each erased (`None`) or holding a sample number.
+struct Ring([Option<u32>; SLOTS]);
+
+impl Ring {
+    fn head(&self) -> Option<usize> {
+        let result: Result<_, Infallible> =
+            climate_log_head(SLOTS, |slot| Ok(self.0[slot].is_none()));
+        result.expect("infallible")
+    }
+
+    /// Erases the sector that starts at `slot`.
+    fn erase_sector(&mut self, slot: usize) {
+        self.0[slot..slot + SLOTS_PER_SECTOR].fill(None);
+    }
+
+    /// Appends as `append_climate_sample` does, erasing the next sector before the last slot of
+    /// a sector is written, or stopping just after that erase if `power_fails`.
+    fn append(&mut self, sample: u32, power_fails: bool) {
+        let slot = self.head().expect("a log");
+        let next = (slot + 1) % SLOTS;
+        if next % SLOTS_PER_SECTOR == 0 {
+            self.erase_sector(next);
+        }
+        if !power_fails {
+            self.0[slot] = Some(sample);
+        }
+    }
+}
+
+#[test]
+fn follows_the_writes_through_many_wraps() {
+    let mut ring = Ring([None; SLOTS]);
+    assert_eq!(ring.head(), Some(0));
+    for sample in 0..100_u32 {
+        let slot = ring.head().expect("a log");
+        ring.append(sample, false);
+        assert_eq!(ring.0[slot], Some(sample));
+        assert_eq!(ring.head(), Some((slot + 1) % SLOTS));
+        // There is always an erased slot to find the head by
+        assert!(ring.0.contains(&None));
+    }
+    // The newest sample is just before the head
+    let newest = (ring.head().expect("a log") + SLOTS - 1) % SLOTS;
+    assert_eq!(ring.0[newest], Some(99));
+}
+
+#[test]
+fn a_power_failure_after_the_erase_loses_only_that_sample() {
+    let mut ring = Ring([None; SLOTS]);
+    for sample in 0..SLOTS as u32 + 3 {
+        ring.append(sample, false);
+    }
+    let head = ring.head().expect("a log");
+    assert_eq!(head, 3);
+    // Slot 7 is the last of its sector: the next sector is erased, then power fails
+    for sample in 100..104 {
+        ring.append(sample, false);
+    }
+    assert_eq!(ring.head(), Some(7));
+    ring.append(200, true);
+    assert_eq!(ring.head(), Some(7));
+    ring.append(201, false);
+    assert_eq!(ring.0[7], Some(201));
+    assert_eq!(ring.head(), Some(8));
+}
+
+#[test]
+fn a_sample_cut_short_counts_as_written() {
+    let mut ring = Ring([None; SLOTS]);
+    ring.0[..5].fill(Some(1));
+    assert_eq!(ring.head(), Some(5));
+}
+
+#[test]
+fn flash_that_is_not_a_log_is_refused() {
+    // Nothing erased, as after other firmware
+    assert_eq!(Ring([Some(0); SLOTS]).head(), None);
+    // Two runs of written slots
+    let mut ring = Ring([None; SLOTS]);
+    ring.0[1] = Some(0);
+    ring.0[9] = Some(0);
+    assert_eq!(ring.head(), None);
+    assert_eq!(
+        climate_log_head::<Infallible>(0, |_| Ok(true)).expect("infallible"),
+        None
+    );
+}
+
+#[test]
+fn read_errors_are_passed_on() {
+    let result = climate_log_head(SLOTS, |slot| if slot == 3 { Err(slot) } else { Ok(true) });
+    assert_eq!(result, Err(3));
+}
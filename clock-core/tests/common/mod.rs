@@ -0,0 +1,16 @@
+//! What the scenario and time-travel tests share.
+
+use clock_core::{LeapIndicator, SyncAccuracy, TimeSource, TimeSyncEvent, UnixSeconds};
+
+/// Pacific Standard Time.
+pub const PST: i32 = -8 * 60;
+
+/// An NTP sync that succeeded with `unix_seconds`.
+pub fn ntp(unix_seconds: UnixSeconds) -> TimeSyncEvent {
+    TimeSyncEvent::Success {
+        unix_seconds,
+        leap: LeapIndicator::NoWarning,
+        accuracy: SyncAccuracy::Fine,
+        source: TimeSource::Ntp,
+    }
+}
@@ -0,0 +1,52 @@
+//! Every character the display can show, through `BitMatrix` and back with `decode`, with and
+//! without a decimal point.
+//!
+//! Run with `cargo test -p clock-core --features host --target <host triple>`.
+#![cfg(all(feature = "host", not(target_os = "none")))]
+
+use clock_core::{BitMatrix, CellText, Leds, CELL_COUNT};
+
+/// The characters `Leds::segments` knows: printable ASCII and `°`.
+fn displayable() -> impl Iterator<Item = char> {
+    (' '..='~').chain(['°'])
+}
+
+#[test]
+fn every_character_decodes_to_one_that_looks_the_same() {
+    for char in displayable() {
+        let text: CellText = [char; CELL_COUNT];
+        let decoded = BitMatrix::from_text(&text).decode();
+        for decoded_char in decoded {
+            // Characters that share a pattern (such as `0` and `O`) decode to the same one
+            assert_eq!(
+                Leds::segments(decoded_char),
+                Leds::segments(char),
+                "{char:?} decoded as {decoded_char:?}"
+            );
+            assert_eq!(Leds::char_for(Leds::segments(char)), Some(decoded_char));
+        }
+    }
+}
+
+#[test]
+fn a_decimal_point_does_not_change_the_character() {
+    for char in displayable() {
+        let segments = Leds::segments(char);
+        // `!` and `.` already light the dot, and some dotted characters are others (a dotted
+        // blank is `.`, and a dotted `1` is `!`)
+        if segments & Leds::DECIMAL != 0 || Leds::char_for(segments | Leds::DECIMAL).is_some() {
+            continue;
+        }
+        let mut bit_matrix = BitMatrix::from_text(&[char; CELL_COUNT]);
+        let undotted = bit_matrix.decode();
+        bit_matrix.set_dot(0);
+        assert_eq!(bit_matrix.decode(), undotted, "{char:?}");
+    }
+}
+
+#[test]
+fn unknown_patterns_decode_as_question_marks() {
+    // Segments A and D alone are no character in either font
+    let bit_matrix = BitMatrix::from_bits(Leds::SEG_A | Leds::SEG_D);
+    assert_eq!(bit_matrix.decode(), ['?'; CELL_COUNT]);
+}
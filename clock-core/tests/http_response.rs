@@ -0,0 +1,71 @@
+//! Reading an HTTP response from a buffer: plain, `Content-Length`, and chunked bodies, and
+//! responses cut short.
+//!
+//! Run with `cargo test -p clock-core --features host --target <host triple>`.
+#![cfg(all(feature = "host", not(target_os = "none")))]
+
+use clock_core::HttpResponse;
+
+/// Parses `bytes` as a response, from a copy, since chunked bodies are decoded in place.
+fn parse(bytes: &[u8]) -> Option<(u16, Vec<u8>)> {
+    let mut buffer = bytes.to_vec();
+    let response = HttpResponse::parse(&mut buffer)?;
+    Some((response.status(), response.body().to_vec()))
+}
+
+#[test]
+fn a_content_length_body_stops_at_its_length() {
+    let bytes = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello, and more";
+    assert_eq!(parse(bytes), Some((200, b"hello".to_vec())));
+    assert!(HttpResponse::is_complete(bytes, false));
+    // Until all five bytes arrive, reading goes on
+    assert!(!HttpResponse::is_complete(&bytes[..bytes.len() - 12], false));
+}
+
+#[test]
+fn a_chunked_body_is_decoded_ignoring_chunk_extensions() {
+    let bytes = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+                  5;name=value\r\nhello\r\n6\r\n world\r\n0\r\n\r\n";
+    assert_eq!(parse(bytes), Some((200, b"hello world".to_vec())));
+    assert!(HttpResponse::is_complete(bytes, false));
+}
+
+#[test]
+fn a_chunk_cut_short_keeps_what_arrived() {
+    let bytes = b"HTTP/1.1 200 OK\r\ntransfer-encoding: Chunked\r\n\r\n5\r\nhello\r\nA\r\n worl";
+    assert_eq!(parse(bytes), Some((200, b"hello worl".to_vec())));
+    assert!(!HttpResponse::is_complete(bytes, false));
+}
+
+#[test]
+fn headers_without_their_blank_line_are_incomplete() {
+    let bytes = b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n";
+    assert_eq!(parse(bytes), None);
+    assert!(!HttpResponse::is_complete(bytes, false));
+    assert!(!HttpResponse::is_complete(bytes, true));
+}
+
+#[test]
+fn a_status_other_than_200_is_read_too() {
+    let mut bytes = *b"HTTP/1.1 404 Not Found\r\nContent-Type: text/plain\r\n\
+                       Content-Length: 9\r\n\r\nnot found";
+    let response = HttpResponse::parse(&mut bytes).expect("a response");
+    assert_eq!(response.status(), 404);
+    assert!(!response.is_success());
+    assert_eq!(response.header("content-type"), Some("text/plain"));
+    assert_eq!(response.text(), "not found");
+}
+
+#[test]
+fn a_head_response_is_complete_at_its_headers() {
+    let bytes = b"HTTP/1.1 204 No Content\r\nContent-Length: 100\r\n\r\n";
+    assert!(HttpResponse::is_complete(bytes, true));
+    assert!(!HttpResponse::is_complete(bytes, false));
+    assert_eq!(parse(bytes), Some((204, Vec::new())));
+}
+
+#[test]
+fn a_malformed_status_line_is_refused() {
+    assert_eq!(parse(b"HTPP/1.1 200 OK\r\n\r\n"), None);
+    assert_eq!(parse(b"HTTP/1.1 OK\r\n\r\n"), None);
+}
@@ -0,0 +1,127 @@
+//! The MQTT packets the clock sends, byte for byte, and reading what a broker sends back,
+//! whole, cut short, or malformed.
+//!
+//! Run with `cargo test -p clock-core --features host --target <host triple>`.
+#![cfg(all(feature = "host", not(target_os = "none")))]
+
+use clock_core::{
+    mqtt_connect, mqtt_packet_len, mqtt_publish, mqtt_subscribe, Error, MqttCredentials, MqttPacket,
+};
+
+#[test]
+fn connect_without_credentials() {
+    let mut buffer = [0; 64];
+    let len = mqtt_connect(&mut buffer, "clock", None, 60).expect("fits");
+    assert_eq!(
+        &buffer[..len],
+        b"\x10\x11\x00\x04MQTT\x04\x02\x00\x3c\x00\x05clock"
+    );
+}
+
+#[test]
+fn connect_with_credentials() {
+    let mut buffer = [0; 64];
+    let credentials = MqttCredentials {
+        user: "me",
+        password: "pw",
+    };
+    let len = mqtt_connect(&mut buffer, "c", Some(credentials), 30).expect("fits");
+    assert_eq!(
+        &buffer[..len],
+        b"\x10\x15\x00\x04MQTT\x04\xc2\x00\x1e\x00\x01c\x00\x02me\x00\x02pw"
+    );
+}
+
+#[test]
+fn publish_and_subscribe() {
+    let mut buffer = [0; 64];
+    let len = mqtt_publish(&mut buffer, "a/b", b"hi", true).expect("fits");
+    assert_eq!(&buffer[..len], b"\x31\x07\x00\x03a/bhi");
+    let len = mqtt_subscribe(&mut buffer, 1, "a/#").expect("fits");
+    assert_eq!(&buffer[..len], b"\x82\x08\x00\x01\x00\x03a/#\x00");
+}
+
+#[test]
+fn long_packets_take_more_length_bytes() {
+    let mut buffer = [0; 300];
+    let payload = [b'x'; 200];
+    let len = mqtt_publish(&mut buffer, "t", &payload, false).expect("fits");
+    // 3 topic bytes and 200 payload bytes: 203 = 0x4B + 0x01 * 128
+    assert_eq!(&buffer[..6], b"\x30\xcb\x01\x00\x01t");
+    assert_eq!(len, 206);
+    assert_eq!(mqtt_packet_len(&buffer[..3]).expect("valid"), Some(206));
+    assert_eq!(mqtt_packet_len(&buffer[..2]).expect("valid so far"), None);
+    let (packet, used) = MqttPacket::parse(&buffer[..len])
+        .expect("valid")
+        .expect("whole");
+    assert_eq!(used, len);
+    assert_eq!(
+        packet,
+        MqttPacket::Publish {
+            topic: "t",
+            payload: &payload
+        }
+    );
+}
+
+#[test]
+fn packets_that_do_not_fit_are_refused() {
+    let mut buffer = [0; 8];
+    assert_eq!(mqtt_publish(&mut buffer, "topic", b"payload", false), None);
+    assert_eq!(mqtt_connect(&mut buffer, "clock", None, 60), None);
+}
+
+#[test]
+fn reads_broker_packets() {
+    assert_eq!(
+        MqttPacket::parse(b"\x20\x02\x00\x05").expect("valid"),
+        Some((MqttPacket::ConnAck { return_code: 5 }, 4))
+    );
+    assert_eq!(
+        MqttPacket::parse(b"\x90\x03\x00\x01\x80").expect("valid"),
+        Some((MqttPacket::SubAck { granted: false }, 5))
+    );
+    assert_eq!(
+        MqttPacket::parse(b"\xd0\x00\xd0\x00").expect("valid"),
+        Some((MqttPacket::PingResp, 2))
+    );
+    // QoS 1: a packet identifier comes between the topic and the payload
+    assert_eq!(
+        MqttPacket::parse(b"\x32\x07\x00\x01t\x00\x09ok").expect("valid"),
+        Some((
+            MqttPacket::Publish {
+                topic: "t",
+                payload: b"ok"
+            },
+            9
+        ))
+    );
+}
+
+#[test]
+fn waits_for_a_whole_packet() {
+    let packet = b"\x30\x07\x00\x03a/bhi";
+    for cut in 0..packet.len() {
+        assert_eq!(
+            MqttPacket::parse(&packet[..cut]).expect("valid so far"),
+            None
+        );
+    }
+}
+
+#[test]
+fn rejects_malformed_packets() {
+    assert!(matches!(
+        MqttPacket::parse(b"\x30\xff\xff\xff\xff\x01"),
+        Err(Error::MqttMalformed)
+    ));
+    // A topic longer than the packet
+    assert!(matches!(
+        MqttPacket::parse(b"\x30\x03\x00\x09a"),
+        Err(Error::MqttMalformed)
+    ));
+    assert!(matches!(
+        MqttPacket::parse(b"\x20\x01\x00"),
+        Err(Error::MqttMalformed)
+    ));
+}
@@ -0,0 +1,84 @@
+//! Checking a signed reply from the clock's own time server: a good code, a tampered one, a
+//! reply to another request, and one cut short.
+//!
+//! Run with `cargo test -p clock-core --features host --target <host triple>`.
+#![cfg(all(feature = "host", not(target_os = "none")))]
+
+use clock_core::{ntp_request, verify_ntp_auth, Error, NTP_AUTH_CODE_LEN, NTP_PACKET_LEN};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+const KEY: &[u8] = b"a key shared with the time server";
+const NONCE: [u8; 8] = [0x5A, 0x17, 0xC3, 0x08, 0x9E, 0x41, 0xD2, 0x6B];
+
+/// A server's reply to a request carrying `echoed`: a packet that echoes it as the originate
+/// timestamp, signed with `KEY`.
+fn reply(echoed: [u8; 8]) -> Vec<u8> {
+    let mut packet = [0_u8; NTP_PACKET_LEN];
+    packet[0] = 0x1C; // LI=0, VN=3, Mode=4 (server)
+    packet[24..32].copy_from_slice(&echoed);
+    packet[40..44].copy_from_slice(&0xEC8F_1A00_u32.to_be_bytes());
+    let mut mac = Hmac::<Sha256>::new_from_slice(KEY).expect("any key length");
+    mac.update(&packet);
+    packet
+        .into_iter()
+        .chain(mac.finalize().into_bytes())
+        .collect()
+}
+
+#[test]
+fn the_request_carries_the_nonce_as_its_transmit_timestamp() {
+    let request = ntp_request(NONCE);
+    assert_eq!(request[0], 0x1B);
+    assert_eq!(request[40..48], NONCE);
+}
+
+#[test]
+fn a_reply_signed_with_the_key_is_accepted() {
+    let response = reply(NONCE);
+    assert_eq!(response.len(), NTP_PACKET_LEN + NTP_AUTH_CODE_LEN);
+    assert!(verify_ntp_auth(&response, NONCE, KEY).is_ok());
+}
+
+#[test]
+fn a_flipped_byte_fails_the_code() {
+    // In the code, then in the packet it signs
+    for index in [NTP_PACKET_LEN + 5, 41] {
+        let mut response = reply(NONCE);
+        response[index] ^= 0x01;
+        assert!(
+            matches!(verify_ntp_auth(&response, NONCE, KEY), Err(Error::TimeAuthBadMac)),
+            "byte {index}"
+        );
+    }
+    // Or signed with another key
+    assert!(matches!(
+        verify_ntp_auth(&reply(NONCE), NONCE, b"another key"),
+        Err(Error::TimeAuthBadMac)
+    ));
+}
+
+#[test]
+fn a_reply_to_another_request_is_refused() {
+    let mut other = NONCE;
+    other[7] ^= 0xFF;
+    // Signed properly, but echoing a different nonce, as a replayed reply would
+    assert!(matches!(
+        verify_ntp_auth(&reply(other), NONCE, KEY),
+        Err(Error::TimeAuthNonceMismatch)
+    ));
+}
+
+#[test]
+fn a_reply_cut_short_is_refused() {
+    let response = reply(NONCE);
+    for len in [0, NTP_PACKET_LEN, NTP_PACKET_LEN + NTP_AUTH_CODE_LEN - 1] {
+        assert!(
+            matches!(
+                verify_ntp_auth(&response[..len], NONCE, KEY),
+                Err(Error::TimeAuthTooShort)
+            ),
+            "{len} bytes"
+        );
+    }
+}
@@ -0,0 +1,74 @@
+//! Reading a config page back: whole, erased, cut short by a write that did not finish, or
+//! with bytes that changed after it was written.
+//!
+//! Run with `cargo test -p clock-core --features host --target <host triple>`.
+#![cfg(all(feature = "host", not(target_os = "none")))]
+
+use clock_core::{Bssid, Provisioning, WifiCountry, PROVISIONING_BYTES};
+
+/// A page with every record, and how many bytes of it are written (the rest read as erased).
+fn written_page() -> (Provisioning, [u8; PROVISIONING_BYTES], usize) {
+    let mut provisioning = Provisioning::new("home", "secret", -8 * 60).expect("valid");
+    provisioning.country = WifiCountry::parse("US").expect("a country");
+    provisioning.bssid = Bssid::parse("a4:2b:b0:12:34:56");
+    provisioning.hidden = true;
+    let page = provisioning.to_bytes();
+    let written = page
+        .iter()
+        .rposition(|&byte| byte != 0xFF)
+        .expect("a written page")
+        + 1;
+    (provisioning, page, written)
+}
+
+#[test]
+fn a_whole_page_reads_back() {
+    let (provisioning, page, _) = written_page();
+    assert_eq!(Provisioning::from_bytes(&page), Some(provisioning));
+}
+
+#[test]
+fn erased_flash_holds_no_page() {
+    assert_eq!(Provisioning::from_bytes(&[0xFF; PROVISIONING_BYTES]), None);
+}
+
+#[test]
+fn a_page_cut_short_is_refused() {
+    let (_, page, written) = written_page();
+    // Power fails partway through writing: the bytes after the cut still read as erased
+    for cut in 0..written {
+        let mut truncated = page;
+        truncated[cut..].fill(0xFF);
+        assert_eq!(Provisioning::from_bytes(&truncated), None, "cut at {cut}");
+    }
+}
+
+#[test]
+fn records_longer_than_the_page_are_refused() {
+    let (_, mut page, _) = written_page();
+    // The records' length, after the magic and version, claims more than the page holds
+    page[5..7].copy_from_slice(&u16::MAX.to_be_bytes());
+    assert_eq!(Provisioning::from_bytes(&page), None);
+}
+
+#[test]
+fn a_changed_byte_fails_the_crc() {
+    let (_, page, written) = written_page();
+    // Past the header, any flipped bit (in a record or in the CRC itself) is caught
+    for at in 7..written {
+        let mut corrupted = page;
+        corrupted[at] ^= 0x01;
+        assert_eq!(Provisioning::from_bytes(&corrupted), None, "changed at {at}");
+    }
+}
+
+#[test]
+fn another_magic_or_version_is_refused() {
+    let (_, page, _) = written_page();
+    let mut other_magic = page;
+    other_magic[0] = b'X';
+    assert_eq!(Provisioning::from_bytes(&other_magic), None);
+    let mut other_version = page;
+    other_version[4] = other_version[4].wrapping_add(1);
+    assert_eq!(Provisioning::from_bytes(&other_version), None);
+}
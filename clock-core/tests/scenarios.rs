@@ -0,0 +1,183 @@
+//! Scenario tests on the host: the clock boots, syncs, crosses a daylight saving change, has
+//! its UTC offset or time edited with the button, shows a reminder, and sounds its alarm,
+//! checked frame by frame.
+//!
+//! Run with `cargo test -p clock-core --features host --target <host triple>`.
+#![cfg(all(feature = "host", not(feature = "eight-digits")))]
+
+mod common;
+
+use clock_core::{
+    BitMatrix, BlinkState, ClockState, MockInstant, Press, Reminders, Scenario, ScriptedTimeSync,
+    SyncError, TimeSyncEvent, UnixSeconds,
+};
+use common::{ntp, PST};
+use embassy_time::Duration;
+
+/// Pacific Daylight Time.
+const PDT: i32 = -7 * 60;
+
+/// 2026-03-08 09:59:30 UTC: 01:59:30 PST, half a minute before the clocks go forward.
+const BEFORE_DST: UnixSeconds = UnixSeconds(1_772_963_970);
+
+fn shows(scenario: &mut Scenario, blink_state: BlinkState, text: &str) {
+    let expected: BitMatrix = text.parse().expect("a displayable frame");
+    assert_eq!(scenario.frame(), (blink_state, expected), "expected {text:?}");
+}
+
+/// Checks the UTC offset editor shows `text`, its hours blinking and its minutes dimmed.
+fn shows_hours_edit(scenario: &mut Scenario, text: &str) {
+    let mut expected: BitMatrix = text.parse().expect("a displayable frame");
+    expected.dim(2);
+    expected.dim(3);
+    let (blink_state, bit_matrix) = scenario.frame();
+    assert_eq!(
+        (blink_state, bit_matrix),
+        (BlinkState::BlinkingAndOn, expected),
+        "expected {text:?}"
+    );
+    // While dark, the blink keeps the minutes
+    let mut minutes: BitMatrix = format!("  {}", &text[2..]).parse().expect("a displayable frame");
+    minutes.dim(2);
+    minutes.dim(3);
+    assert_eq!(bit_matrix.blinked_off(), minutes);
+}
+
+#[test]
+fn boot_sync_dst_edit_and_reminder() {
+    let time = MockInstant::take();
+    let time_sync = ScriptedTimeSync::new();
+    let mut scenario = Scenario::new(PST);
+    shows(&mut scenario, BlinkState::Solid, "1200");
+
+    // A sync in the background sets the time
+    assert!(scenario.handle_sync(&ntp(BEFORE_DST)));
+    shows(&mut scenario, BlinkState::Solid, " 159");
+    time.advance(Duration::from_secs(30));
+    shows(&mut scenario, BlinkState::Solid, " 200");
+
+    // At 2:00 PST the clocks go forward to 3:00 PDT
+    scenario.set_utc_offset_minutes(PDT);
+    shows(&mut scenario, BlinkState::Solid, " 300");
+
+    // A long press edits the offset: each short press previews an hour later, and a long
+    // press keeps it
+    scenario.press(Press::Long, &time_sync);
+    assert_eq!(scenario.state(), ClockState::EditUtcOffset);
+    shows_hours_edit(&mut scenario, " 300");
+    scenario.press(Press::Short, &time_sync);
+    scenario.press(Press::Short, &time_sync);
+    shows_hours_edit(&mut scenario, " 500");
+    assert_eq!(scenario.clock_time().utc_offset_minutes(), PDT);
+    scenario.press(Press::Long, &time_sync);
+    assert_eq!(scenario.state(), ClockState::HoursMinutes);
+    assert_eq!(scenario.clock_time().utc_offset_minutes(), PDT + 120);
+    shows(&mut scenario, BlinkState::Solid, " 500");
+
+    // On the reminder's day, its label scrolls in at the top of the minute: one cell per
+    // step, so four steps in it fills the display
+    scenario.set_reminders(Reminders::parse("0308ALRM").expect("a valid reminder"));
+    time.advance(Duration::from_millis(1_200));
+    shows(&mut scenario, BlinkState::Solid, "ALRM");
+    time.advance(Duration::from_secs(10));
+    shows(&mut scenario, BlinkState::Solid, " 500");
+}
+
+#[test]
+fn edit_abandoned_keeps_the_offset() {
+    let _time = MockInstant::take();
+    let time_sync = ScriptedTimeSync::new();
+    let mut scenario = Scenario::new(PST);
+    assert!(scenario.handle_sync(&ntp(BEFORE_DST)));
+    scenario.press(Press::Long, &time_sync);
+    scenario.press(Press::Short, &time_sync);
+    shows_hours_edit(&mut scenario, " 259");
+    scenario.time_out();
+    assert_eq!(scenario.clock_time().utc_offset_minutes(), PST);
+    shows(&mut scenario, BlinkState::Solid, " 159");
+}
+
+#[test]
+fn double_press_syncs_now() {
+    let _time = MockInstant::take();
+    let time_sync = ScriptedTimeSync::new();
+    let mut scenario = Scenario::new(PST);
+    assert!(time_sync.push(ntp(BEFORE_DST)));
+    scenario.press(Press::Double, &time_sync);
+    assert_eq!(time_sync.requests(), 1);
+    assert_eq!(scenario.state(), ClockState::SyncSucceeded);
+    shows(&mut scenario, BlinkState::Solid, "Sync");
+    scenario.time_out();
+    shows(&mut scenario, BlinkState::Solid, " 159");
+}
+
+#[test]
+fn failed_and_missing_syncs_show_why() {
+    let _time = MockInstant::take();
+    let time_sync = ScriptedTimeSync::new();
+    let mut scenario = Scenario::new(PST);
+    assert!(time_sync.push(TimeSyncEvent::Failed(SyncError::Wifi)));
+    scenario.press(Press::Double, &time_sync);
+    shows(&mut scenario, BlinkState::Fast, "nEt ");
+    scenario.time_out();
+    // With nothing scripted the sync times out
+    scenario.press(Press::Double, &time_sync);
+    assert_eq!(time_sync.requests(), 2);
+    shows(&mut scenario, BlinkState::Fast, "tout");
+    scenario.time_out();
+    shows(&mut scenario, BlinkState::Solid, "1200");
+}
+
+#[test]
+fn set_time_blinks_the_minutes_and_dims_the_hours() {
+    let _time = MockInstant::take();
+    let time_sync = ScriptedTimeSync::new();
+    let mut scenario = Scenario::new(PST);
+    assert!(scenario.handle_sync(&ntp(BEFORE_DST)));
+    scenario.press(Press::Short, &time_sync);
+    scenario.press(Press::Long, &time_sync);
+    assert_eq!(scenario.state(), ClockState::SetTime);
+    let mut expected: BitMatrix = " 159".parse().expect("a displayable frame");
+    expected.dim(0);
+    expected.dim(1);
+    let (blink_state, bit_matrix) = scenario.frame();
+    assert_eq!((blink_state, bit_matrix), (BlinkState::BlinkingAndOn, expected));
+    let mut hours: BitMatrix = " 1  ".parse().expect("a displayable frame");
+    hours.dim(0);
+    hours.dim(1);
+    assert_eq!(bit_matrix.blinked_off(), hours);
+}
+
+#[test]
+fn alarm_blinks_until_a_press() {
+    let time = MockInstant::take();
+    let time_sync = ScriptedTimeSync::new();
+    let mut scenario = Scenario::new(PST);
+    assert!(scenario.handle_sync(&ntp(BEFORE_DST)));
+    scenario.set_alarm(Duration::from_secs(2 * 60 * 60));
+    assert!(!scenario.run_due_alarm());
+
+    // The clock task sleeps until 2:00, when the alarm goes off and freezes that frame
+    let shown = scenario.show_next(&time);
+    assert_eq!(shown.bit_matrix, " 159".parse().expect("a displayable frame"));
+    assert!(scenario.run_due_alarm());
+    shows(&mut scenario, BlinkState::BlinkingAndOn, " 200");
+    time.advance(Duration::from_secs(90));
+    shows(&mut scenario, BlinkState::BlinkingAndOn, " 200");
+
+    // A press only ends the hold, and the alarm waits for tomorrow
+    scenario.press(Press::Short, &time_sync);
+    assert_eq!(scenario.state(), ClockState::HoursMinutes);
+    shows(&mut scenario, BlinkState::Solid, " 201");
+    assert!(!scenario.run_due_alarm());
+}
+
+#[test]
+fn mock_wifi_starts_with_the_radio_on() {
+    use clock_core::{TimeSyncPort, WifiPort};
+
+    let time_sync = ScriptedTimeSync::new();
+    assert!(time_sync.wifi().radio_on());
+    time_sync.wifi().set_radio(false);
+    assert!(!time_sync.wifi().radio_on());
+}
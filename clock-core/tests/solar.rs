@@ -0,0 +1,97 @@
+//! Sunrise and sunset for known places and dates, against NOAA's solar calculator, and the
+//! polar day and night.
+//!
+//! Run with `cargo test -p clock-core --features host --target <host triple>`.
+#![cfg(all(feature = "host", not(target_os = "none")))]
+
+use clock_core::{sun_times, Location, SunTimes};
+use time::{Date, Month};
+
+/// The integer math is good to a minute or two.
+const TOLERANCE_SECONDS: i32 = 120;
+
+fn date(year: i32, month: Month, day: u8) -> Date {
+    Date::from_calendar_date(year, month, day).expect("a valid date")
+}
+
+fn place(latitude: &str, longitude: &str) -> Location {
+    Location::parse(latitude, longitude).expect("a valid location")
+}
+
+/// Checks `sun_times` against a sunrise and sunset given as hours and minutes after UTC
+/// midnight. A sunset past midnight counts on from 24:00, and a sunrise the day before counts
+/// back, so 18:41 the day before is `(-6, 41)`.
+fn rises_and_sets(location: Location, on: Date, sunrise: (i32, i32), sunset: (i32, i32)) {
+    let SunTimes::RisesAndSets {
+        sunrise: actual_sunrise,
+        sunset: actual_sunset,
+    } = sun_times(on, location)
+    else {
+        panic!("expected a sunrise and sunset on {on}");
+    };
+    let seconds = |(hours, minutes): (i32, i32)| hours * 3_600 + minutes * 60;
+    assert!(
+        (actual_sunrise - seconds(sunrise)).abs() <= TOLERANCE_SECONDS,
+        "sunrise {actual_sunrise} s on {on}"
+    );
+    assert!(
+        (actual_sunset - seconds(sunset)).abs() <= TOLERANCE_SECONDS,
+        "sunset {actual_sunset} s on {on}"
+    );
+}
+
+#[test]
+fn seattle_at_the_summer_solstice() {
+    // 05:11 and 21:10 PDT
+    rises_and_sets(
+        place("47.6062", "-122.3321"),
+        date(2026, Month::June, 21),
+        (12, 11),
+        (28, 10),
+    );
+}
+
+#[test]
+fn london_at_the_winter_solstice() {
+    // 08:04 and 15:53 GMT
+    rises_and_sets(
+        place("51.5074", "-0.1278"),
+        date(2026, Month::December, 21),
+        (8, 4),
+        (15, 53),
+    );
+}
+
+#[test]
+fn sydney_in_the_southern_summer() {
+    // 05:41 and 20:05 AEDT (UTC+11), so sunrise falls on the UTC day before
+    rises_and_sets(
+        place("-33.8688", "151.2093"),
+        date(2026, Month::December, 21),
+        (-6, 41),
+        (9, 5),
+    );
+}
+
+#[test]
+fn night_falls_on_the_local_evening() {
+    let seattle = place("47.6062", "-122.3321");
+    let times = sun_times(date(2026, Month::June, 21), seattle);
+    let pdt = -7 * 60;
+    assert!(times.is_day(12 * 3_600, pdt));
+    assert!(!times.is_day(23 * 3_600, pdt));
+    assert!(!times.is_day(4 * 3_600, pdt));
+}
+
+#[test]
+fn the_midnight_sun_and_the_polar_night() {
+    let tromso = place("69.6492", "18.9553");
+    assert_eq!(
+        sun_times(date(2026, Month::June, 21), tromso),
+        SunTimes::AlwaysUp
+    );
+    assert_eq!(
+        sun_times(date(2026, Month::December, 21), tromso),
+        SunTimes::AlwaysDown
+    );
+}
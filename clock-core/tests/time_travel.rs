@@ -0,0 +1,114 @@
+//! Time-travel tests on the host: the `MockInstant` driver jumps straight to a boundary, and
+//! the clock task's frames and sleep deadlines on each side of it are checked exactly.
+//!
+//! Run with `cargo test -p clock-core --features host --target <host triple>`.
+#![cfg(all(feature = "host", not(feature = "eight-digits")))]
+
+mod common;
+
+use clock_core::{BitMatrix, MockInstant, Press, Scenario, ScriptedTimeSync, UnixSeconds};
+use common::{ntp, PST};
+use embassy_time::Duration;
+
+/// 2026-03-07 23:59:58 PST.
+const BEFORE_MIDNIGHT: UnixSeconds = UnixSeconds(1_772_956_798);
+
+/// Lets the clock task show one frame after another, checking each one's text and how long
+/// it stays before the task renders again.
+fn expect_frames(scenario: &mut Scenario, time: &MockInstant, expected: &[(&str, Duration)]) {
+    for &(text, stays) in expected {
+        let frame = scenario.show_next(time);
+        let bit_matrix: BitMatrix = text.parse().expect("a displayable frame");
+        assert_eq!(frame.bit_matrix, bit_matrix, "expected {text:?}");
+        assert_eq!(
+            frame.deadline.duration_since(frame.shown_at),
+            stays,
+            "{text:?} stays"
+        );
+        assert_eq!(time.now(), frame.deadline);
+    }
+}
+
+/// The local date, as (month, day).
+fn local_month_day(scenario: &Scenario) -> Option<(u8, u8)> {
+    let date = scenario.clock_time().local_date()?;
+    Some((u8::from(date.month()), date.day()))
+}
+
+#[test]
+fn twelve_to_one() {
+    let time = MockInstant::take();
+    // Boots at 12:00:00
+    let mut scenario = Scenario::new(PST);
+    expect_frames(&mut scenario, &time, &[("1200", Duration::from_secs(60))]);
+    time.advance(Duration::from_secs(58 * 60));
+    expect_frames(
+        &mut scenario,
+        &time,
+        &[
+            ("1259", Duration::from_secs(60)),
+            (" 100", Duration::from_secs(60)),
+            (" 101", Duration::from_secs(60)),
+        ],
+    );
+}
+
+#[test]
+fn midnight_wrap() {
+    let time = MockInstant::take();
+    let time_sync = ScriptedTimeSync::new();
+    let mut scenario = Scenario::new(PST);
+    assert!(scenario.handle_sync(&ntp(BEFORE_MIDNIGHT)));
+    assert_eq!(local_month_day(&scenario), Some((3, 7)));
+    expect_frames(
+        &mut scenario,
+        &time,
+        &[
+            ("1159", Duration::from_secs(2)),
+            ("1200", Duration::from_secs(60)),
+            ("1201", Duration::from_secs(60)),
+        ],
+    );
+    assert_eq!(local_month_day(&scenario), Some((3, 8)));
+
+    // Again in minutes and seconds, which redraws every second
+    assert!(scenario.handle_sync(&ntp(BEFORE_MIDNIGHT)));
+    scenario.press(Press::Short, &time_sync);
+    expect_frames(
+        &mut scenario,
+        &time,
+        &[
+            ("5958", Duration::from_secs(1)),
+            ("5959", Duration::from_secs(1)),
+            ("0000", Duration::from_secs(1)),
+        ],
+    );
+}
+
+#[test]
+fn deadlines_land_on_the_boundary() {
+    let time = MockInstant::take();
+    let mut scenario = Scenario::new(PST);
+    // A quarter second past 12:34:56, the minute ends in 3.75 seconds
+    time.advance(Duration::from_secs(34 * 60 + 56));
+    time.advance(Duration::from_millis(250));
+    expect_frames(
+        &mut scenario,
+        &time,
+        &[
+            ("1234", Duration::from_millis(3_750)),
+            ("1235", Duration::from_secs(60)),
+        ],
+    );
+}
+
+#[test]
+fn a_long_jump_lands_on_the_right_minute() {
+    let time = MockInstant::take();
+    let mut scenario = Scenario::new(PST);
+    // A day and a half later, it is midnight
+    time.advance(Duration::from_secs(36 * 60 * 60));
+    expect_frames(&mut scenario, &time, &[("1200", Duration::from_secs(60))]);
+    time.advance(Duration::from_secs(60 * 60));
+    expect_frames(&mut scenario, &time, &[(" 101", Duration::from_secs(60))]);
+}
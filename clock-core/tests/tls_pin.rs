@@ -0,0 +1,89 @@
+//! Reading certificate pins from `TLS_PINS`, and finding a certificate's P-256 key.
+//!
+//! Run with `cargo test -p clock-core --features host --target <host triple>`.
+#![cfg(all(feature = "host", not(target_os = "none")))]
+
+use clock_core::{find_tls_pin, p256_public_key, parse_fingerprint, CERT_FINGERPRINT_LEN};
+
+/// A fingerprint of the bytes 0x00, 0x11, ... 0xFF, 0x00, ..., as `openssl` prints it.
+const COLONS: &str = "00:11:22:33:44:55:66:77:88:99:AA:BB:CC:DD:EE:FF:\
+                      00:11:22:33:44:55:66:77:88:99:aa:bb:cc:dd:ee:ff";
+
+fn expected() -> [u8; CERT_FINGERPRINT_LEN] {
+    core::array::from_fn(|index| u8::try_from(index % 16 * 0x11).expect("a byte"))
+}
+
+#[test]
+fn a_fingerprint_parses_with_or_without_colons() {
+    assert_eq!(parse_fingerprint(COLONS), Some(expected()));
+    let bare: String = COLONS.chars().filter(|&symbol| symbol != ':').collect();
+    assert_eq!(parse_fingerprint(&bare), Some(expected()));
+}
+
+#[test]
+fn a_fingerprint_of_the_wrong_length_or_not_hex_is_refused() {
+    // One byte short, then one byte over
+    assert_eq!(parse_fingerprint(&COLONS[..COLONS.len() - 3]), None);
+    assert_eq!(parse_fingerprint(&format!("{COLONS}:00")), None);
+    // An odd number of digits
+    assert_eq!(parse_fingerprint(&format!("{COLONS}0")), None);
+    // The right length, but not all hex
+    assert_eq!(parse_fingerprint(&COLONS.replace('A', "G")), None);
+}
+
+#[test]
+fn the_pin_for_the_host_is_found() {
+    let pins = format!("other.example={COLONS}, Time.Example = {COLONS}");
+    assert_eq!(find_tls_pin(&pins, "time.example"), Some(expected()));
+}
+
+#[test]
+fn a_host_without_a_pin_has_none() {
+    let pins = format!("time.example={COLONS}");
+    assert_eq!(find_tls_pin(&pins, "time.example.org"), None);
+    assert_eq!(find_tls_pin(&pins, "example"), None);
+    assert_eq!(find_tls_pin("", "time.example"), None);
+}
+
+/// The `prime256v1` OID and BIT STRING header that come before a P-256 key.
+const P256_KEY_PREFIX: [u8; 13] = [
+    0x06, 0x08, 0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00,
+];
+
+#[test]
+fn a_p256_key_is_found() {
+    let point: Vec<u8> = [0x04].into_iter().chain(1..=64).collect();
+    let certificate: Vec<u8> = [0x30, 0x82, 0x01, 0x00]
+        .into_iter()
+        .chain(P256_KEY_PREFIX)
+        .chain(point.iter().copied())
+        .chain([0xA3, 0x00])
+        .collect();
+    assert_eq!(p256_public_key(&certificate), Some(point.as_slice()));
+}
+
+#[test]
+fn a_certificate_with_another_key_has_no_p256_key() {
+    // An `rsaEncryption` key, then an Ed25519 one
+    let rsa: Vec<u8> = [
+        0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x01, 0x05, 0x00, 0x03, 0x82,
+        0x01, 0x0F, 0x00,
+    ]
+    .into_iter()
+    .chain([0x30; 64])
+    .collect();
+    assert_eq!(p256_public_key(&rsa), None);
+    let ed25519: Vec<u8> = [0x06, 0x03, 0x2B, 0x65, 0x70, 0x03, 0x21, 0x00]
+        .into_iter()
+        .chain([0x42; 32])
+        .collect();
+    assert_eq!(p256_public_key(&ed25519), None);
+}
+
+#[test]
+fn a_p256_key_cut_short_or_compressed_is_refused() {
+    let cut_short: Vec<u8> = P256_KEY_PREFIX.into_iter().chain([0x04; 40]).collect();
+    assert_eq!(p256_public_key(&cut_short), None);
+    let compressed: Vec<u8> = P256_KEY_PREFIX.into_iter().chain([0x02; 65]).collect();
+    assert_eq!(p256_public_key(&compressed), None);
+}
@@ -0,0 +1,104 @@
+//! Property tests of the UTC offset arithmetic: editing wraps within -12 to +14 hours from
+//! any start, and changing the offset agrees with setting the time (and date) in the new zone.
+//!
+//! Run with `cargo test -p clock-core --features host --target <host triple>`.
+#![cfg(all(feature = "host", not(target_os = "none")))]
+
+use clock_core::{
+    ClockTime, MockInstant, PendingSettings, Settings, UnixSeconds, MAX_UTC_OFFSET_HOURS,
+    MIN_UTC_OFFSET_HOURS,
+};
+use embassy_time::Duration;
+use proptest::prelude::*;
+
+/// The offsets a time zone may have, in minutes: -12:00 to +14:00.
+const OFFSET_MINUTES: core::ops::RangeInclusive<i32> = -720..=840;
+
+/// 1970 to 2100.
+const UNIX_SECONDS: core::ops::RangeInclusive<i64> = 0..=4_102_444_800;
+
+/// The first day of 1970, when a negative offset puts local time before 1970.
+const FIRST_DAY: core::ops::RangeInclusive<i64> = 0..=86_400;
+
+/// How many hours `adjust_utc_offset_hours` steps through before it is back where it began.
+const OFFSET_HOURS: usize = 27;
+
+proptest! {
+    #[test]
+    fn adjusting_stays_in_range(start in OFFSET_MINUTES, hours in -1_000..=1_000_i32) {
+        let mut clock_time = ClockTime::new(start);
+        clock_time.adjust_utc_offset_hours(hours);
+        let minutes = clock_time.utc_offset_minutes();
+        prop_assert!(OFFSET_MINUTES.contains(&minutes), "{start} + {hours}h gave {minutes}");
+        prop_assert_eq!(minutes.rem_euclid(60), 0);
+
+        // The editor's staged copy moves the same way
+        let mut pending = PendingSettings::new(start, Settings::default());
+        pending.adjust_utc_offset_hours(hours);
+        prop_assert_eq!(pending.utc_offset_minutes, minutes);
+    }
+
+    #[test]
+    fn adjusting_any_offset_stays_in_range(start in any::<i32>(), hours in any::<i32>()) {
+        let mut pending = PendingSettings::new(start, Settings::default());
+        pending.adjust_utc_offset_hours(hours);
+        prop_assert!(OFFSET_MINUTES.contains(&pending.utc_offset_minutes));
+    }
+
+    #[test]
+    fn plus_one_cycles_through_every_offset(
+        start_hours in MIN_UTC_OFFSET_HOURS..=MAX_UTC_OFFSET_HOURS,
+    ) {
+        let start = start_hours.saturating_mul(60);
+        let mut pending = PendingSettings::new(start, Settings::default());
+        let mut seen = Vec::with_capacity(OFFSET_HOURS);
+        for _ in 0..OFFSET_HOURS {
+            pending.adjust_utc_offset_hours(1);
+            seen.push(pending.utc_offset_minutes);
+        }
+        prop_assert_eq!(pending.utc_offset_minutes, start);
+        seen.sort_unstable();
+        let every_offset: Vec<i32> = (MIN_UTC_OFFSET_HOURS..=MAX_UTC_OFFSET_HOURS)
+            .map(|hours| hours.saturating_mul(60))
+            .collect();
+        prop_assert_eq!(seen, every_offset);
+    }
+
+    #[test]
+    fn offset_change_commutes_with_set_from_unix(
+        first in OFFSET_MINUTES,
+        second in OFFSET_MINUTES,
+        unix_seconds in UNIX_SECONDS,
+        uptime_ms in 0..=200_000_000_u64,
+    ) {
+        let time = MockInstant::take();
+        time.advance(Duration::from_millis(uptime_ms));
+
+        let mut changed_after = ClockTime::new(first);
+        changed_after.set_from_unix(UnixSeconds(unix_seconds));
+        changed_after.set_utc_offset_minutes(second);
+
+        let mut changed_before = ClockTime::new(first);
+        changed_before.set_utc_offset_minutes(second);
+        changed_before.set_from_unix(UnixSeconds(unix_seconds));
+
+        // The date moves with the time of day
+        prop_assert_eq!(changed_after.now(), changed_before.now());
+        let local_seconds = unix_seconds.saturating_add(i64::from(second).saturating_mul(60));
+        prop_assert_eq!(changed_before.now().local_seconds(), Some(local_seconds));
+    }
+
+    #[test]
+    fn time_of_day_stays_within_the_day(
+        utc_offset_minutes in OFFSET_MINUTES,
+        unix_seconds in FIRST_DAY,
+    ) {
+        let _time = MockInstant::take();
+        let mut clock_time = ClockTime::new(utc_offset_minutes);
+        clock_time.set_from_unix(UnixSeconds(unix_seconds));
+        let local_seconds =
+            unix_seconds.saturating_add(i64::from(utc_offset_minutes).saturating_mul(60));
+        let expected = u64::try_from(local_seconds.rem_euclid(86_400)).expect("non-negative");
+        prop_assert_eq!(clock_time.now().since_midnight().as_secs(), expected);
+    }
+}
@@ -0,0 +1,184 @@
+//! Flashes a clock and writes its config page (WiFi network, access point, and country, time
+//! zone, display settings, and alarm settings), so one firmware image serves every clock.
+//!
+//! ```text
+//! clock-flash --ssid <name> [--password <password>] [--bssid <address>] [--hidden]
+//!             [--country <code>] --utc-offset-minutes <minutes> [--pm-indicator]
+//!             [--leading-zero-hours] [--rotate-180] [--days-since <date>] [--config <list>]
+//!             [--reminders <list>] [--firmware <elf>] [--picotool | --uf2 <file>]
+//! ```
+//!
+//! It runs `probe-rs` through a debug probe, or `picotool` with `--picotool` (hold BOOTSEL
+//! while plugging the Pico in). With `--uf2` it flashes nothing and writes the page as a UF2
+//! file instead, to copy to the Pico's `RPI-RP2` drive. Without `--firmware` only the config
+//! page is written, so the clock keeps its firmware and the settings saved on it.
+
+use core::error::Error;
+use std::{env, fs, process::Command};
+
+use clock_core::{
+    Bssid, Config, Provisioning, Reminders, Settings, UnixSeconds, WifiCountry,
+    PROVISIONING_OFFSET,
+};
+
+mod uf2;
+
+/// Where flash starts in the RP2040's address map.
+const FLASH_BASE: u32 = 0x1000_0000;
+
+/// The chip `probe-rs` is told it is talking to.
+const CHIP: &str = "RP2040";
+
+const USAGE: &str = "usage: clock-flash --ssid <name> [--password <password>] \
+                     [--bssid <address>] [--hidden] [--country <code>] \
+                     --utc-offset-minutes <minutes> [--pm-indicator] [--leading-zero-hours] \
+                     [--rotate-180] [--days-since <YYYY-MM-DD>] [--config <list>] \
+                     [--reminders <list>] [--firmware <elf>] [--picotool | --uf2 <file>]";
+
+/// What to write, and how.
+struct Options {
+    provisioning: Provisioning,
+    /// The firmware ELF to flash first, if any.
+    firmware: Option<String>,
+    /// Flash over USB in BOOTSEL mode with `picotool`, rather than through a debug probe.
+    picotool: bool,
+    /// Write the page to this UF2 file rather than flashing anything.
+    uf2: Option<String>,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let options = parse_args(env::args().skip(1))?;
+    if let Some(uf2) = &options.uf2 {
+        let address = FLASH_BASE.saturating_add(PROVISIONING_OFFSET);
+        fs::write(uf2, uf2::uf2_file(address, &options.provisioning.to_bytes()))?;
+        println!("Wrote {uf2}; copy it to the RPI-RP2 drive");
+        return Ok(());
+    }
+    let page_path = env::temp_dir().join("clock-provisioning.bin");
+    fs::write(&page_path, options.provisioning.to_bytes())?;
+    let page = page_path.to_str().ok_or("the temporary directory's path is not UTF-8")?;
+    let address = format!("{:#x}", FLASH_BASE.saturating_add(PROVISIONING_OFFSET));
+
+    if options.picotool {
+        if let Some(firmware) = &options.firmware {
+            run("picotool", &["load", "--verify", firmware])?;
+        }
+        run("picotool", &["load", "--verify", page, "--offset", &address])?;
+        run("picotool", &["reboot"])?;
+    } else {
+        if let Some(firmware) = &options.firmware {
+            run("probe-rs", &["download", "--chip", CHIP, firmware])?;
+        }
+        run(
+            "probe-rs",
+            &[
+                "download",
+                "--chip",
+                CHIP,
+                "--binary-format",
+                "bin",
+                "--base-address",
+                &address,
+                page,
+            ],
+        )?;
+        run("probe-rs", &["reset", "--chip", CHIP])?;
+    }
+    fs::remove_file(&page_path)?;
+    println!(
+        "Provisioned for {} at UTC{:+} minutes",
+        options.provisioning.ssid, options.provisioning.utc_offset_minutes
+    );
+    Ok(())
+}
+
+/// Reads the command line (without the program name).
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, Box<dyn Error>> {
+    let mut ssid = None;
+    let mut password = None;
+    let mut bssid = None;
+    let mut hidden = false;
+    let mut country = WifiCountry::WORLDWIDE;
+    let mut utc_offset_minutes = None;
+    let mut firmware = None;
+    let mut picotool = false;
+    let mut uf2 = None;
+    let mut settings = None::<Settings>;
+    let mut config = None;
+    let mut reminders = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ssid" => ssid = Some(args.next().ok_or(USAGE)?),
+            "--password" => password = Some(args.next().ok_or(USAGE)?),
+            "--bssid" => {
+                bssid = Some(
+                    Bssid::parse(&args.next().ok_or(USAGE)?)
+                        .ok_or("--bssid needs an address such as a4:2b:b0:12:34:56")?,
+                );
+            }
+            "--hidden" => hidden = true,
+            "--country" => {
+                country = WifiCountry::parse(&args.next().ok_or(USAGE)?)
+                    .ok_or("--country needs a two-letter code such as DE, or XX for worldwide")?;
+            }
+            "--utc-offset-minutes" => {
+                utc_offset_minutes = Some(args.next().ok_or(USAGE)?.parse::<i32>()?);
+            }
+            "--firmware" => firmware = Some(args.next().ok_or(USAGE)?),
+            "--picotool" => picotool = true,
+            "--uf2" => uf2 = Some(args.next().ok_or(USAGE)?),
+            "--pm-indicator" => settings.get_or_insert_default().pm_indicator = true,
+            "--leading-zero-hours" => settings.get_or_insert_default().leading_zero_hours = true,
+            "--rotate-180" => settings.get_or_insert_default().rotate_180 = true,
+            "--days-since" => {
+                let date = args.next().ok_or(USAGE)?;
+                settings.get_or_insert_default().days_since = Some(
+                    UnixSeconds::from_iso_date(&date).ok_or("--days-since needs YYYY-MM-DD")?,
+                );
+            }
+            "--config" => {
+                config = Some(Config::DEFAULT.with_changes(&args.next().ok_or(USAGE)?)?);
+            }
+            "--reminders" => reminders = Some(Reminders::parse(&args.next().ok_or(USAGE)?)?),
+            _ => return Err(USAGE.into()),
+        }
+    }
+    // A UF2 file holds only the page
+    if uf2.is_some() && (picotool || firmware.is_some()) {
+        return Err(USAGE.into());
+    }
+    // An open network has no password
+    let provisioning = Provisioning {
+        bssid,
+        hidden,
+        country,
+        settings,
+        config,
+        reminders,
+        ..Provisioning::new(
+            &ssid.ok_or(USAGE)?,
+            &password.unwrap_or_default(),
+            utc_offset_minutes.ok_or(USAGE)?,
+        )?
+    };
+    Ok(Options {
+        provisioning,
+        firmware,
+        picotool,
+        uf2,
+    })
+}
+
+/// Runs `program` with `args`, echoing the command.
+fn run(program: &str, args: &[&str]) -> Result<(), Box<dyn Error>> {
+    println!("{program} {}", args.join(" "));
+    let status = Command::new(program)
+        .args(args)
+        .status()
+        .map_err(|err| format!("cannot run {program}: {err}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{program} failed ({status})").into())
+    }
+}
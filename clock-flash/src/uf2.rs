@@ -0,0 +1,59 @@
+//! UF2, the format the RP2040's boot ROM accepts as a file copied to the `RPI-RP2` drive that
+//! appears while BOOTSEL is held.
+
+/// The first magic number of every block.
+const MAGIC_START0: u32 = 0x0A32_4655;
+/// The second magic number of every block.
+const MAGIC_START1: u32 = 0x9E5D_5157;
+/// The magic number ending every block.
+const MAGIC_END: u32 = 0x0AB1_6F30;
+/// The block names its chip family in the file size field.
+const FLAG_FAMILY_ID_PRESENT: u32 = 0x0000_2000;
+/// The RP2040's family ID.
+const RP2040_FAMILY_ID: u32 = 0xE48B_FF56;
+
+/// Each block's size in the file.
+const BLOCK_LEN: usize = 512;
+/// The bytes of flash each block carries: the RP2040 boot ROM wants one 256-byte flash page.
+const PAYLOAD_LEN: usize = 256;
+/// A block's eight header words.
+const HEADER_LEN: usize = 32;
+
+/// A UF2 file that writes `data` to flash at `address` (in the RP2040's address map, which
+/// must start a 256-byte page), padding the last page with `0xFF` as erased flash reads.
+#[must_use]
+pub fn uf2_file(address: u32, data: &[u8]) -> Vec<u8> {
+    let block_count = data.chunks(PAYLOAD_LEN).count();
+    let mut file = Vec::with_capacity(block_count.saturating_mul(BLOCK_LEN));
+    for (block_number, (chunk, block_address)) in data
+        .chunks(PAYLOAD_LEN)
+        .zip((address..).step_by(PAYLOAD_LEN))
+        .enumerate()
+    {
+        let header = [
+            MAGIC_START0,
+            MAGIC_START1,
+            FLAG_FAMILY_ID_PRESENT,
+            block_address,
+            word(PAYLOAD_LEN),
+            word(block_number),
+            word(block_count),
+            RP2040_FAMILY_ID,
+        ];
+        let start = file.len();
+        for value in header {
+            file.extend_from_slice(&value.to_le_bytes());
+        }
+        file.extend_from_slice(chunk);
+        file.resize(start.saturating_add(HEADER_LEN).saturating_add(PAYLOAD_LEN), 0xFF);
+        // The rest of the data area is unused
+        file.resize(start.saturating_add(BLOCK_LEN).saturating_sub(4), 0);
+        file.extend_from_slice(&MAGIC_END.to_le_bytes());
+    }
+    file
+}
+
+/// `value` as a UF2 header word. Every count here is far below `u32::MAX`.
+fn word(value: usize) -> u32 {
+    u32::try_from(value).unwrap_or(u32::MAX)
+}
@@ -1,7 +1,10 @@
 MEMORY
 {
     BOOT2 : ORIGIN = 0x10000000, LENGTH = 0x100
-    FLASH : ORIGIN = 0x10000100, LENGTH = 2048K - 0x100
+    /* The last 20K are left out of FLASH: 4K for the config page, 4K for the     */
+    /* last crash report, 8K for the climate log, then 4K for saved settings (see   */
+    /* src/storage.rs)                                                              */
+    FLASH : ORIGIN = 0x10000100, LENGTH = 2048K - 0x100 - 20K
     /* OPTION A (default): Use all RAM banks as one block */
     /* Reasonable, unless you are doing something         */
     /* really particular with DMA or other concurrent     */
@@ -13,3 +16,10 @@ MEMORY
     /* SCRATCH_A: ORIGIN = 0x20040000, LENGTH = 4K        */
     /* SCRATCH_B: ORIGIN = 0x20041000, LENGTH = 4K        */
 }
+
+/* Fail the link if the statics leave less than 16K of RAM for the stack. The statics hold  */
+/* every Embassy task's state and the cyw43 and network stack buffers, which no size_of in  */
+/* src/memory_report.rs can see; the stack runs from __sheap (their end) to _stack_start    */
+/* (the top of RAM). Keep 16K in step with MIN_STACK_BYTES there.                           */
+ASSERT(_stack_start - __sheap >= 16K, "
+ERROR(clock-wifi): the statics leave less than 16K of RAM for the stack (MIN_STACK_BYTES)");
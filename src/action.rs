@@ -0,0 +1,108 @@
+//! Actions - what the clock does when something it is waiting for happens
+
+use clock_core::{AlarmSound, BlinkState, Message};
+
+use crate::{info, warn};
+use crate::buzzer::{play_sound, silence_sound};
+use crate::clock::Clock;
+use crate::config::config;
+use crate::http::switch_command;
+use crate::mqtt;
+use crate::switch::{Switch, SwitchCommand};
+
+/// The topic, after `MQTT_TOPIC/`, that `Action::Publish` publishes to.
+const EVENT_TOPIC: &str = "event";
+
+/// Something the clock does in response to an event, such as the countdown timer or the
+/// alarm going off.
+///
+/// Events name an `Action` instead of driving devices themselves, so each new trigger
+/// reuses the same set of responses.
+#[cfg_attr(feature = "logging", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy)]
+pub enum Action {
+    /// Freeze the display on what it shows now and blink it until the next press of the
+    /// button or `Clock::resume`.
+    Blink,
+    /// Scroll a message across the display, then go back to the clock.
+    ShowMessage(Message),
+    /// Turn the `Switch` on or off, or pulse it.
+    Switch(SwitchCommand),
+    /// Play an alarm sound on the buzzer until it is silenced.
+    Sound(AlarmSound),
+    /// Stop the alarm sound.
+    Silence,
+    /// Publish the event's name (such as `timer`) to `MQTT_TOPIC/event`.
+    Publish(&'static str),
+}
+
+impl Action {
+    /// The action one entry of an action list (`TIMER_ACTIONS`, `ALARM_ACTIONS`) names, for
+    /// the event called `event`, or `None` if the entry is not one of:
+    ///
+    /// * `blink` - `Blink`
+    /// * `buzz` - `Sound`, the config's `alarm_sound`, or `buzz:chime` for another
+    /// * `silence` - `Silence`
+    /// * `switch:on`, `switch:off`, `switch:pulse` - `Switch`, where a pulse lasts a second,
+    ///   or `switch:pulse:500` for 500 ms
+    /// * `message:WAKE UP` - `ShowMessage`, scrolling the text past once
+    /// * `publish` - `Publish`
+    #[must_use]
+    pub fn parse(entry: &str, event: &'static str) -> Option<Self> {
+        let (name, argument) = entry
+            .trim()
+            .split_once(':')
+            .map_or((entry.trim(), None), |(name, argument)| (name, Some(argument)));
+        match (name, argument) {
+            ("blink", None) => Some(Self::Blink),
+            ("buzz", None) => Some(Self::Sound(config().alarm_sound)),
+            ("buzz", Some(sound)) => AlarmSound::parse(sound).map(Self::Sound),
+            ("silence", None) => Some(Self::Silence),
+            ("switch", Some(command)) => {
+                let (state, ms) = command
+                    .split_once(':')
+                    .map_or((command, None), |(state, ms)| (state, Some(ms)));
+                switch_command(state, ms).map(Self::Switch)
+            }
+            ("message", Some(text)) => Message::new(text, 1, BlinkState::Solid)
+                .ok()
+                .map(Self::ShowMessage),
+            ("publish", None) => Some(Self::Publish(event)),
+            _ => None,
+        }
+    }
+
+    /// Carries out the action.
+    pub async fn run(self, clock: &Clock<'_>, switch: &Switch<'_>) {
+        info!("Action: {:?}", self);
+        match self {
+            Self::Blink => clock.hold().await,
+            Self::ShowMessage(message) => clock.show_message(message).await,
+            Self::Switch(command) => switch.command(command),
+            Self::Sound(sound) => play_sound(sound),
+            Self::Silence => silence_sound(),
+            Self::Publish(event) => {
+                if !mqtt::publish(EVENT_TOPIC, event) {
+                    warn!("Not published: MQTT is off or its outbox is full");
+                }
+            }
+        }
+    }
+}
+
+/// Carries out each action in `list`, a comma-separated action list such as `blink,buzz`
+/// (see `Action::parse`), for the event called `event`, in order. Entries that name no action
+/// are logged and skipped.
+pub(crate) async fn run_actions(
+    list: &str,
+    event: &'static str,
+    clock: &Clock<'_>,
+    switch: &Switch<'_>,
+) {
+    for entry in list.split(',').filter(|entry| !entry.trim().is_empty()) {
+        match Action::parse(entry, event) {
+            Some(action) => action.run(clock, switch).await,
+            None => warn!("Unknown action in the {=str} action list: {=str}", event, entry),
+        }
+    }
+}
@@ -1,74 +1,140 @@
+use clock_core::{BitMatrix, BlinkState};
 use embassy_futures::select::{select, Either};
-use embassy_time::Timer;
+use embassy_time::{Duration, Timer};
 
-use crate::{
-    blinker::{BlinkerOuterNotifier, Text},
-    Display, BLINK_OFF_DELAY, BLINK_ON_DELAY, CELL_COUNT,
-};
+use crate::{blinker::BlinkerOuterNotifier, config::config, Display};
 
-#[derive(Debug, Clone, Copy, defmt::Format, Default)]
-pub enum BlinkState {
-    #[default]
-    Solid,
-    BlinkingAndOn,
-    BlinkingButOff,
+/// How long a blinking display stays lit, and then dark, in each blink.
+#[cfg_attr(feature = "logging", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BlinkTiming {
+    /// How long the display is lit.
+    pub on: Duration,
+    /// How long the display is dark.
+    pub off: Duration,
 }
 
-impl BlinkState {
+impl Default for BlinkTiming {
+    /// The timing in the runtime `Config`.
+    fn default() -> Self {
+        let config = config();
+        Self {
+            on: config.blink_on(),
+            off: config.blink_off(),
+        }
+    }
+}
+
+impl BlinkTiming {
+    /// The timing for `BlinkState::Fast`: each half of the blink takes half as long.
+    #[must_use]
+    #[expect(
+        clippy::integer_division_remainder_used,
+        reason = "Halving a delay needs no rounding."
+    )]
+    pub const fn fast(self) -> Self {
+        Self {
+            on: Duration::from_ticks(self.on.as_ticks() / 2),
+            off: Duration::from_ticks(self.off.as_ticks() / 2),
+        }
+    }
+}
+
+/// Runs a `BlinkState` against the display.
+///
+/// `BlinkState` lives in `clock-core`; this trait adds the timing that turns it into blinking.
+pub trait BlinkStateExt {
+    async fn execute(
+        self,
+        outer_notifier: &'static BlinkerOuterNotifier,
+        display: &Display<'_>,
+        bit_matrix: BitMatrix,
+        timing: BlinkTiming,
+    ) -> (BlinkState, BitMatrix);
+}
+
+impl BlinkStateExt for BlinkState {
     #[inline]
-    pub async fn execute(
+    async fn execute(
         self,
         outer_notifier: &'static BlinkerOuterNotifier,
         display: &Display<'_>,
-        text: Text,
-    ) -> (Self, Text) {
+        bit_matrix: BitMatrix,
+        timing: BlinkTiming,
+    ) -> (BlinkState, BitMatrix) {
         match self {
-            Self::Solid => Self::execute_solid(outer_notifier, display, text).await,
+            Self::Solid => execute_solid(outer_notifier, display, bit_matrix).await,
             Self::BlinkingAndOn => {
-                Self::execute_blinking_and_on(outer_notifier, display, text).await
+                execute_blinking_and_on(outer_notifier, display, bit_matrix, timing.on).await
             }
             Self::BlinkingButOff => {
-                Self::execute_blinking_but_off(outer_notifier, display, text).await
+                execute_blinking_but_off(outer_notifier, display, bit_matrix, timing.off).await
             }
+            Self::Fast => execute_fast(outer_notifier, display, bit_matrix, timing.fast()).await,
         }
     }
+}
+
+async fn execute_solid(
+    outer_notifier: &'static BlinkerOuterNotifier,
+    display: &Display<'_>,
+    bit_matrix: BitMatrix,
+) -> (BlinkState, BitMatrix) {
+    display.write_bit_matrix(bit_matrix);
+    outer_notifier.wait().await
+}
+
+async fn execute_blinking_and_on(
+    outer_notifier: &'static BlinkerOuterNotifier,
+    display: &Display<'_>,
+    bit_matrix: BitMatrix,
+    on_delay: Duration,
+) -> (BlinkState, BitMatrix) {
+    display.write_bit_matrix(bit_matrix);
+    if let Either::First((new_blink_state, new_bit_matrix)) =
+        select(outer_notifier.wait(), Timer::after(on_delay)).await
+    {
+        (new_blink_state, new_bit_matrix)
+    } else {
+        (BlinkState::BlinkingButOff, bit_matrix)
+    }
+}
 
-    async fn execute_solid(
-        outer_notifier: &'static BlinkerOuterNotifier,
-        display: &Display<'_>,
-        text: Text,
-    ) -> (Self, Text) {
-        display.write_text(text);
-        outer_notifier.wait().await
+async fn execute_blinking_but_off(
+    outer_notifier: &'static BlinkerOuterNotifier,
+    display: &Display<'_>,
+    bit_matrix: BitMatrix,
+    off_delay: Duration,
+) -> (BlinkState, BitMatrix) {
+    // Dimmed cells hold steady while the rest blink
+    display.write_bit_matrix(bit_matrix.blinked_off());
+    if let Either::First((new_blink_state, new_bit_matrix)) =
+        select(outer_notifier.wait(), Timer::after(off_delay)).await
+    {
+        (new_blink_state, new_bit_matrix)
+    } else {
+        (BlinkState::BlinkingAndOn, bit_matrix)
     }
+}
 
-    async fn execute_blinking_and_on(
-        outer_notifier: &'static BlinkerOuterNotifier,
-        display: &Display<'_>,
-        text: Text,
-    ) -> (Self, Text) {
-        display.write_text(text);
-        if let Either::First((new_blink_state, new_text)) =
-            select(outer_notifier.wait(), Timer::after(BLINK_ON_DELAY)).await
+/// Blinks with `timing` until told to show something else. Unlike the slow blink, the lit and
+/// dark halves are not separate states, so a new notice always starts from a lit display.
+async fn execute_fast(
+    outer_notifier: &'static BlinkerOuterNotifier,
+    display: &Display<'_>,
+    bit_matrix: BitMatrix,
+    timing: BlinkTiming,
+) -> (BlinkState, BitMatrix) {
+    loop {
+        display.write_bit_matrix(bit_matrix);
+        if let Either::First(notice) = select(outer_notifier.wait(), Timer::after(timing.on)).await
         {
-            (new_blink_state, new_text)
-        } else {
-            (Self::BlinkingButOff, text)
+            return notice;
         }
-    }
-
-    async fn execute_blinking_but_off(
-        outer_notifier: &'static BlinkerOuterNotifier,
-        display: &Display<'_>,
-        text: Text,
-    ) -> (Self, Text) {
-        display.write_text([' '; CELL_COUNT]);
-        if let Either::First((new_blink_state, new_text)) =
-            select(outer_notifier.wait(), Timer::after(BLINK_OFF_DELAY)).await
+        display.write_bit_matrix(bit_matrix.blinked_off());
+        if let Either::First(notice) = select(outer_notifier.wait(), Timer::after(timing.off)).await
         {
-            (new_blink_state, new_text)
-        } else {
-            (Self::BlinkingAndOn, text)
+            return notice;
         }
     }
 }
@@ -1,32 +1,55 @@
 use crate::{
-    blink_state::BlinkState,
+    blink_state::{BlinkStateExt, BlinkTiming},
+    button::{self, wait_for_press},
     display::{Display, DisplayNotifier},
-    output_array::OutputArray,
-    shared_constants::{CELL_COUNT, SEGMENT_COUNT},
+    hardware::DisplayBackend,
+    info,
+    shared_constants::{BRIGHTNESS_RAMP_STEP, BRIGHTNESS_RAMP_STEPS, PRESS_FEEDBACK_DURATION},
+    warn, Display2Format,
 };
-use defmt::info;
+use clock_core::{BitMatrix, BlinkState, Brightness, CellText, Page, MIN_BLINK_MS};
 use embassy_executor::{SpawnError, Spawner};
+use embassy_futures::select::{select, select4, Either, Either4};
 use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, signal::Signal};
+use embassy_time::{Duration, Timer};
 
 /// A struct representing a display with the ability to blink.
-pub struct Blinker<'a>(&'a BlinkerOuterNotifier);
+///
+/// It keeps two pages (see `Page`): the time page, which `write_text` and `write_bit_matrix`
+/// draw on, and the sensor page. Both stay current while one shows, so `show_page` flips
+/// between them without either being drawn again.
+pub struct Blinker<'a>(&'a BlinkerNotifier);
 
 /// A type alias for the notifier that sends messages to the `Blinker`
 /// and the `Display` it controls.
-pub type BlinkerNotifier = (BlinkerOuterNotifier, DisplayNotifier);
+pub type BlinkerNotifier = (
+    BlinkerOuterNotifier,
+    BlinkTimingNotifier,
+    BrightnessNotifier,
+    DisplayNotifier,
+    BlinkerOuterNotifier,
+    PageNotifier,
+);
 
-/// A type alias for the outer notifier that sends messages to the `Blinker`.
-pub type BlinkerOuterNotifier = Signal<CriticalSectionRawMutex, (BlinkState, Text)>;
+/// A type alias for the outer notifier that sends messages to the `Blinker`: what one of its
+/// pages shows, and how it blinks.
+pub type BlinkerOuterNotifier = Signal<CriticalSectionRawMutex, (BlinkState, BitMatrix)>;
 
-pub type Text = [char; CELL_COUNT];
+/// A type alias for the notifier that picks which page the `Blinker` shows.
+pub type PageNotifier = Signal<CriticalSectionRawMutex, Page>;
+
+/// A type alias for the notifier that changes how the `Blinker` blinks.
+pub type BlinkTimingNotifier = Signal<CriticalSectionRawMutex, BlinkTiming>;
+
+/// A type alias for the notifier that sets the brightness the `Blinker` ramps toward.
+pub type BrightnessNotifier = Signal<CriticalSectionRawMutex, Brightness>;
 
 impl Blinker<'_> {
     /// Creates a new `Blinker` instance, which entails starting an Embassy task.
     ///
     /// # Arguments
     ///
-    /// * `cell_pins` - The pins that control the cells (digits) of the display.
-    /// * `segment_pins` - The pins that control the segments of the display.
+    /// * `driver` - The board's display hardware (see `Hardware::display`).
     /// * `notifier` - The static notifier that sends messages to the `Blinker` and the `Display` it controls.
     ///         This notifier is created with the `Blinker::notifier()` method.
     /// * `spawner` - The spawner that will spawn the task that controls the blinker.
@@ -36,15 +59,28 @@ impl Blinker<'_> {
     /// Returns a `SpawnError` if the task cannot be spawned.
     #[must_use = "Must be used to manage the spawned task"]
     pub fn new(
-        cell_pins: OutputArray<'static, CELL_COUNT>,
-        segment_pins: OutputArray<'static, SEGMENT_COUNT>,
+        driver: DisplayBackend,
         notifier: &'static BlinkerNotifier,
         spawner: Spawner,
     ) -> Result<Self, SpawnError> {
-        let (outer_notifier, display_notifier) = notifier;
-        let display = Display::new(cell_pins, segment_pins, display_notifier, spawner)?;
-        spawner.spawn(device_loop(outer_notifier, display))?;
-        Ok(Self(outer_notifier))
+        let (
+            outer_notifier,
+            timing_notifier,
+            brightness_notifier,
+            display_notifier,
+            sensor_notifier,
+            page_notifier,
+        ) = notifier;
+        let display = Display::new(driver, display_notifier, spawner)?;
+        let (_, display_brightness_notifier, _) = display_notifier;
+        spawner.spawn(brightness_loop(brightness_notifier, display_brightness_notifier))?;
+        spawner.spawn(device_loop(
+            [outer_notifier, sensor_notifier],
+            timing_notifier,
+            page_notifier,
+            display,
+        ))?;
+        Ok(Self(notifier))
     }
 
     /// Creates a new `BlinkerNotifier` instance.
@@ -54,29 +90,163 @@ impl Blinker<'_> {
     /// This should be assigned to a static variable and passed to the `Blinker::new()` method.
     #[must_use]
     pub const fn notifier() -> BlinkerNotifier {
-        (Signal::new(), Display::notifier())
+        (
+            Signal::new(),
+            Signal::new(),
+            Signal::new(),
+            Display::notifier(),
+            Signal::new(),
+            Signal::new(),
+        )
     }
 
     /// Writes possibly-blinking characters to the blinkable display.
     ///
     /// The characters can be be any Unicode character but
     /// an unknown or hard-to-display character will be displayed as a blank.
-    pub fn write_text(&self, blink_state: BlinkState, text: Text) {
-        info!("blink_state: {:?}, text: {:?}", blink_state, text);
-        let Self(outer_notifier) = self;
-        outer_notifier.signal((blink_state, text));
+    pub fn write_text(&self, blink_state: BlinkState, text: CellText) {
+        self.write_bit_matrix(blink_state, BitMatrix::from_text(&text));
+    }
+
+    /// Writes possibly-blinking segment patterns to the blinkable display.
+    ///
+    /// Use this instead of `write_text` when cells need more than a character, for example
+    /// a lit decimal point.
+    pub fn write_bit_matrix(&self, blink_state: BlinkState, bit_matrix: BitMatrix) {
+        self.write_page(Page::Time, blink_state, bit_matrix);
+    }
+
+    /// Writes possibly-blinking segment patterns to `page`, which shows them at once if it is
+    /// the page showing, or else keeps them until it is.
+    pub fn write_page(&self, page: Page, blink_state: BlinkState, bit_matrix: BitMatrix) {
+        let Self((time_notifier, _, _, _, sensor_notifier, _)) = self;
+        match page {
+            Page::Time => time_notifier.signal((blink_state, bit_matrix)),
+            Page::Sensor => sensor_notifier.signal((blink_state, bit_matrix)),
+        }
+    }
+
+    /// Shows `page`, as last written.
+    pub fn show_page(&self, page: Page) {
+        let Self((_, _, _, _, _, page_notifier)) = self;
+        page_notifier.signal(page);
+    }
+
+    /// Sets how brightly the display is lit, easing to it over about half a second.
+    /// Blinking and the segments shown are unchanged.
+    pub fn set_brightness(&self, brightness: Brightness) {
+        info!("brightness: {:?}", brightness);
+        let Self((_, _, brightness_notifier, _, _, _)) = self;
+        brightness_notifier.signal(brightness);
+    }
+
+    /// Asks the display to exercise its cathodes (see `Display::refresh_cathodes`).
+    pub fn refresh_cathodes(&self) {
+        let Self((_, _, _, display_notifier, _, _)) = self;
+        let (_, _, cathode_notifier) = display_notifier;
+        cathode_notifier.signal(());
+    }
+
+    /// Sets how long a blinking display stays lit and then dark, in milliseconds.
+    /// `BlinkState::Fast` blinks at twice this rate. Each is at least `MIN_BLINK_MS`, so a 0
+    /// cannot leave the blinker redrawing without pause.
+    pub fn set_blink_timing(&self, on_ms: u64, off_ms: u64) {
+        let at_least_min = |ms: u64| Duration::from_millis(ms.max(u64::from(MIN_BLINK_MS)));
+        let timing = BlinkTiming {
+            on: at_least_min(on_ms),
+            off: at_least_min(off_ms),
+        };
+        info!("blink timing: {:?}", timing);
+        let Self((_, timing_notifier, _, _, _, _)) = self;
+        timing_notifier.signal(timing);
     }
 }
 
 #[embassy_executor::task]
 async fn device_loop(
-    outer_notifier: &'static BlinkerOuterNotifier,
+    page_notifiers: [&'static BlinkerOuterNotifier; 2],
+    timing_notifier: &'static BlinkTimingNotifier,
+    page_notifier: &'static PageNotifier,
     display: Display<'static>,
 ) -> ! {
-    let mut blink_state = BlinkState::default();
-    let mut text = [' '; CELL_COUNT];
-    #[expect(clippy::shadow_unrelated, reason = "false positive. Not shadowing.")]
+    let [time_notifier, sensor_notifier] = page_notifiers;
+    let mut shown = Page::Time;
+    // Each page's blink state and segments
+    let mut time_page = (BlinkState::default(), BitMatrix::default());
+    let mut sensor_page = (BlinkState::default(), BitMatrix::default());
+    let mut timing = BlinkTiming::default();
+    let mut presses = button::subscribe()
+        .inspect_err(|err| warn!("No press feedback: {}", Display2Format(err)))
+        .ok();
+    loop {
+        let (shown_notifier, hidden_notifier, (blink_state, bit_matrix)) = match shown {
+            Page::Time => (time_notifier, sensor_notifier, time_page),
+            Page::Sensor => (sensor_notifier, time_notifier, sensor_page),
+        };
+        // New timing takes effect at once, redrawing the current display with it. So does the
+        // end of a press's feedback, and a page flip. A write to the hidden page is kept for
+        // when it shows.
+        match select4(
+            blink_state.execute(shown_notifier, &display, bit_matrix, timing),
+            timing_notifier.wait(),
+            wait_for_press(presses.as_mut()),
+            select(page_notifier.wait(), hidden_notifier.wait()),
+        )
+        .await
+        {
+            Either4::First(next) => match shown {
+                Page::Time => time_page = next,
+                Page::Sensor => sensor_page = next,
+            },
+            Either4::Second(new_timing) => timing = new_timing,
+            Either4::Third(()) => show_press_feedback(&display, bit_matrix).await,
+            Either4::Fourth(Either::First(page)) => shown = page,
+            Either4::Fourth(Either::Second(next)) => match shown {
+                Page::Time => sensor_page = next,
+                Page::Sensor => time_page = next,
+            },
+        }
+    }
+}
+
+/// Briefly dims what is shown, flashing the cells that are already dimmed dark. A new notice
+/// waits in its signal until the feedback ends.
+async fn show_press_feedback(display: &Display<'_>, bit_matrix: BitMatrix) {
+    display.write_bit_matrix(bit_matrix.without_dimmed().dimmed());
+    Timer::after(PRESS_FEEDBACK_DURATION).await;
+}
+
+/// Eases the display's brightness toward each new target. A new target mid-ramp starts a
+/// fresh ramp from wherever the old one had reached.
+#[embassy_executor::task]
+async fn brightness_loop(
+    brightness_notifier: &'static BrightnessNotifier,
+    display_brightness_notifier: &'static Signal<CriticalSectionRawMutex, Brightness>,
+) -> ! {
+    let mut brightness = Brightness::default();
+    let mut target = brightness_notifier.wait().await;
     loop {
-        (blink_state, text) = blink_state.execute(outer_notifier, &display, text).await;
+        let start = brightness;
+        let mut next_target = None;
+        for step in 1..=BRIGHTNESS_RAMP_STEPS {
+            let stepped = start.ramp_toward(target, step, BRIGHTNESS_RAMP_STEPS);
+            if stepped != brightness {
+                brightness = stepped;
+                display_brightness_notifier.signal(brightness);
+            }
+            if brightness == target {
+                break;
+            }
+            if let Either::Second(new_target) =
+                select(Timer::after(BRIGHTNESS_RAMP_STEP), brightness_notifier.wait()).await
+            {
+                next_target = Some(new_target);
+                break;
+            }
+        }
+        target = match next_target {
+            Some(new_target) => new_target,
+            None => brightness_notifier.wait().await,
+        };
     }
 }
@@ -0,0 +1,4 @@
+//! Version, commit, and build date, generated by `build.rs` so units in the field can be
+//! identified without a debug probe.
+
+include!(concat!(env!("OUT_DIR"), "/build_info.rs"));
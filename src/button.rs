@@ -1,63 +1,431 @@
-use defmt::info;
-use embassy_futures::select::{select, Either};
-use embassy_rp::gpio::Input;
-use embassy_time::Timer;
+//! Button virtual device - turns the button, the IR remote, the touch pad, and presses
+//! simulated over HTTP into a stream of debounced `ButtonEvent`s that any number of tasks can
+//! subscribe to
 
-use crate::shared_constants::{BUTTON_DEBOUNCE_DELAY, LONG_PRESS_DURATION};
+use core::cell::Cell;
+use core::convert::Infallible;
 
-/// A button abstraction backed by an Embassy input pin.
-pub struct Button<'a>(Input<'a>);
+use embassy_executor::{SpawnError, Spawner};
+use embassy_futures::select::{select, select4, Either, Either4};
+use embassy_rp::gpio::DormantWakeConfig;
+use embassy_sync::blocking_mutex::{raw::CriticalSectionRawMutex, Mutex};
+use embassy_sync::channel::Channel;
+use embassy_sync::pubsub::{PubSubChannel, Subscriber};
+use embassy_sync::signal::Signal;
+use embassy_time::{Duration, Instant, Timer};
+use embedded_hal_async::digital::Wait;
 
-impl<'a> Button<'a> {
+use clock_core::GestureTimings;
+
+use crate::info;
+use crate::hardware::ButtonPin;
+use crate::ir_remote::IrRemoteNotifier;
+use crate::touch_pad::TouchPadNotifier;
+use crate::shared_constants::{BUTTON_DEBOUNCE_DELAY, BUTTON_REPEAT_INTERVAL};
+use crate::Result;
+
+/// How many events a subscriber may fall behind before it misses the oldest.
+const EVENT_CAPACITY: usize = 8;
+
+/// The most subscribers at once: the state machine, the blinker's press feedback, the status
+/// display, and the buzzer (a press silences an alarm).
+const MAX_SUBSCRIBERS: usize = 4;
+
+/// How presses are told apart, shared so HTTP and the USB console can change it while the
+/// button is in use. Each press reads it as it starts.
+static GESTURE_TIMINGS: Mutex<CriticalSectionRawMutex, Cell<GestureTimings>> =
+    Mutex::new(Cell::new(GestureTimings::DEFAULT));
+
+/// Every event the button task publishes.
+static BUTTON_EVENTS: PubSubChannel<
+    CriticalSectionRawMutex,
+    ButtonEvent,
+    EVENT_CAPACITY,
+    MAX_SUBSCRIBERS,
+    1,
+> = PubSubChannel::new();
+
+/// Starts receiving the button's events, from the next one on. Devices created before the
+/// `ButtonMonitor`, such as the `Blinker`, subscribe with this.
+///
+/// # Errors
+///
+/// Returns an error if there are already `MAX_SUBSCRIBERS` subscriptions.
+pub(crate) fn subscribe() -> Result<ButtonEvents> {
+    Ok(BUTTON_EVENTS.subscriber()?)
+}
+
+/// Waits for the button to go down, or forever without a subscription.
+pub(crate) async fn wait_for_press(presses: Option<&mut ButtonEvents>) {
+    let Some(presses) = presses else {
+        return core::future::pending().await;
+    };
+    while presses.next_message_pure().await != ButtonEvent::Pressed {}
+}
+
+/// Asks the button task to put the chip into dormant sleep until the next press.
+static DORMANT_SLEEP_REQUESTS: Signal<CriticalSectionRawMutex, ()> = Signal::new();
+
+/// When the latest very long press began, for the state machine, which resets the clock on
+/// one whatever state it is in (see `wait_for_very_long_press`).
+static VERY_LONG_PRESSES: Signal<CriticalSectionRawMutex, Instant> = Signal::new();
+
+/// Presses simulated over HTTP, waiting to be published like the remote's. A double press is
+/// two short presses, so there is room for a few.
+static SIMULATED_PRESSES: Channel<CriticalSectionRawMutex, PressDuration, 4> = Channel::new();
+
+/// The gesture timings presses are measured with.
+#[must_use]
+pub fn gesture_timings() -> GestureTimings {
+    GESTURE_TIMINGS.lock(Cell::get)
+}
+
+/// Measures presses with `timings` from the next press on.
+pub fn set_gesture_timings(timings: GestureTimings) {
+    GESTURE_TIMINGS.lock(|cell| cell.set(timings));
+}
+
+/// Asks the button task to put the chip into dormant sleep (see `sleep_until_pressed`) once it
+/// is between presses. The press that wakes it is published as usual.
+pub(crate) fn request_dormant_sleep() {
+    DORMANT_SLEEP_REQUESTS.signal(());
+}
+
+/// Waits for the next very long press, returning when it began. Unlike `ButtonEvent::VeryLong`,
+/// which only the state running at the time sees, this waits across states, so a press that
+/// went long in one state and on to very long in the next is not missed.
+pub(crate) async fn wait_for_very_long_press() -> Instant {
+    VERY_LONG_PRESSES.wait().await
+}
+
+/// Publishes `presses`, one after another, as if they came from the remote (two short presses
+/// make a double press). Returns `false`, doing nothing, if there is no room for them all.
+pub fn simulate_presses(presses: &[PressDuration]) -> bool {
+    if SIMULATED_PRESSES.free_capacity() < presses.len() {
+        return false;
+    }
+    presses
+        .iter()
+        .all(|&press_duration| SIMULATED_PRESSES.try_send(press_duration).is_ok())
+}
+
+/// What happened to the button (or the remote or touch pad standing in for it).
+///
+/// A press publishes `Pressed`, then `Long`, `Repeat`s, and `VeryLong` as it is held that
+/// long, then `Short` or `Double` if it was released before it became long, then `Released`.
+#[cfg_attr(feature = "logging", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ButtonEvent {
+    /// The button went down.
+    Pressed,
+    /// The button came back up.
+    Released,
+    /// A press released before the long press threshold.
+    Short,
+    /// A short press that started within the double-press window after a `Short` ended. It
+    /// takes the place of its own `Short`.
+    Double,
+    /// The press has been held for the long press threshold.
+    Long,
+    /// The long press is still held, sent every `BUTTON_REPEAT_INTERVAL`.
+    Repeat,
+    /// The press has been held for the very long press threshold.
+    VeryLong,
+    /// A tap on the touch pad, which only wakes the clock.
+    Tap,
+}
+
+/// A subscription to the button's events.
+pub type ButtonEvents =
+    Subscriber<'static, CriticalSectionRawMutex, ButtonEvent, EVENT_CAPACITY, MAX_SUBSCRIBERS, 1>;
+
+/// Button virtual device - watches the button, publishing what it does as `ButtonEvent`s
+pub struct ButtonMonitor;
+
+impl ButtonMonitor {
+    /// Create the button device and spawn its task, which owns the button from now on.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SpawnError` if the task cannot be spawned.
+    pub fn new(button: Button<ButtonPin>, spawner: Spawner) -> Result<Self, SpawnError> {
+        spawner.spawn(device_loop(button))?;
+        Ok(Self)
+    }
+
+    /// Starts receiving the button's events, from the next one on.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are already `MAX_SUBSCRIBERS` subscriptions.
+    pub fn subscribe(&self) -> Result<ButtonEvents> {
+        subscribe()
+    }
+}
+
+#[embassy_executor::task]
+async fn device_loop(mut button: Button<ButtonPin>) -> ! {
+    // When the last short press ended, while a second could still make it a double press
+    let mut short_ended_at: Option<Instant> = None;
+    loop {
+        match button.next_input().await {
+            Input::Down => {
+                short_ended_at = button.measure_press(short_ended_at).await;
+            }
+            Input::Remote(press_duration) => {
+                let pressed_at = Instant::now();
+                publish(ButtonEvent::Pressed);
+                short_ended_at = match press_duration {
+                    PressDuration::Short => publish_short(pressed_at, short_ended_at),
+                    PressDuration::Long => {
+                        publish(ButtonEvent::Long);
+                        None
+                    }
+                    PressDuration::VeryLong => {
+                        publish(ButtonEvent::Long);
+                        publish_very_long(pressed_at);
+                        None
+                    }
+                };
+                publish(ButtonEvent::Released);
+            }
+            Input::Tap => publish(ButtonEvent::Tap),
+            Input::DormantSleep => {
+                button.sleep_until_pressed();
+                short_ended_at = button.measure_press(None).await;
+            }
+        }
+    }
+}
+
+/// Publishes `event` to every subscriber.
+fn publish(event: ButtonEvent) {
+    if event != ButtonEvent::Repeat {
+        info!("Button: {:?}", event);
+    }
+    BUTTON_EVENTS.immediate_publisher().publish_immediate(event);
+}
+
+/// Publishes `VeryLong` for the press that started at `pressed_at`, and signals it to
+/// `wait_for_very_long_press`.
+fn publish_very_long(pressed_at: Instant) {
+    publish(ButtonEvent::VeryLong);
+    VERY_LONG_PRESSES.signal(pressed_at);
+}
+
+/// Publishes `Double` if the short press that started at `pressed_at` followed the one that
+/// ended at `short_ended_at` closely enough, or `Short` otherwise. Returns when this press
+/// ended if it could start a double press.
+fn publish_short(pressed_at: Instant, short_ended_at: Option<Instant>) -> Option<Instant> {
+    let window = gesture_timings().double_press();
+    if short_ended_at
+        .is_some_and(|ended_at| pressed_at.saturating_duration_since(ended_at) <= window)
+    {
+        publish(ButtonEvent::Double);
+        None
+    } else {
+        publish(ButtonEvent::Short);
+        Some(Instant::now())
+    }
+}
+
+/// What the button task waits for between presses.
+enum Input {
+    Down,
+    Remote(PressDuration),
+    Tap,
+    DormantSleep,
+}
+
+/// A button abstraction backed by an input pin.
+///
+/// Any `embedded-hal-async` pin that cannot fail works, such as Embassy's `Input`.
+pub struct Button<P> {
+    pin: P,
+    config: ButtonConfig,
+    /// Presses from an IR remote, reported as if they came from the button.
+    remote: Option<&'static IrRemoteNotifier>,
+    /// Taps on a touch pad, which only wake the clock.
+    touch_pad: Option<&'static TouchPadNotifier>,
+}
+
+/// How the button is wired and debounced. How its presses are timed is in `gesture_timings`.
+#[cfg_attr(feature = "logging", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ButtonConfig {
+    /// Which level the pin reads while the button is pressed.
+    pub active_level: ActiveLevel,
+    /// How long to ignore the pin after it changes, to ride out contact bounce.
+    pub debounce_delay: Duration,
+}
+
+impl Default for ButtonConfig {
+    /// Active-high with a pull-down, matching the wiring in the `README.md`.
+    fn default() -> Self {
+        Self {
+            active_level: ActiveLevel::High,
+            debounce_delay: BUTTON_DEBOUNCE_DELAY,
+        }
+    }
+}
+
+/// The level a button's pin reads while the button is pressed.
+#[cfg_attr(feature = "logging", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ActiveLevel {
+    /// The button connects the pin to 3.3V, so the pin needs a pull-down.
+    #[default]
+    High,
+    /// The button connects the pin to ground, so the pin needs a pull-up.
+    Low,
+}
+
+impl<P: Wait<Error = Infallible>> Button<P> {
     /// Creates a new `Button` instance.
+    ///
+    /// The pin's pull resistor must match `config.active_level`.
     #[must_use]
-    pub const fn new(button: Input<'a>) -> Self {
-        Self(button)
+    pub const fn new(pin: P, config: ButtonConfig) -> Self {
+        Self {
+            pin,
+            config,
+            remote: None,
+            touch_pad: None,
+        }
+    }
+
+    /// Also reports the presses an `IrRemote` sends on `notifier`.
+    pub const fn add_remote(&mut self, notifier: &'static IrRemoteNotifier) {
+        self.remote = Some(notifier);
+    }
+
+    /// Also reports the taps a `TouchPad` sends on `notifier`.
+    pub const fn add_touch_pad(&mut self, notifier: &'static TouchPadNotifier) {
+        self.touch_pad = Some(notifier);
     }
 
     #[inline]
     async fn wait_for_button_up(&mut self) -> &mut Self {
-        self.0.wait_for_low().await;
+        let Ok(()) = match self.config.active_level {
+            ActiveLevel::High => self.pin.wait_for_low().await,
+            ActiveLevel::Low => self.pin.wait_for_high().await,
+        };
         self
     }
 
     #[inline]
     async fn wait_for_button_down(&mut self) -> &mut Self {
-        self.0.wait_for_high().await;
+        let Ok(()) = match self.config.active_level {
+            ActiveLevel::High => self.pin.wait_for_high().await,
+            ActiveLevel::Low => self.pin.wait_for_low().await,
+        };
         self
     }
 
-    /// Measures the duration of a button press.
-    ///
-    /// This method does not wait for the button to be released.  It only waits
-    /// as long as necessary to determine whether the press was "short" or "long".
-    pub async fn press_duration(&mut self) -> PressDuration {
-        self.wait_for_button_up().await;
-        Timer::after(BUTTON_DEBOUNCE_DELAY).await;
-        self.wait_for_button_down().await;
-        Timer::after(BUTTON_DEBOUNCE_DELAY).await;
-        let press_duration =
-            match select(self.wait_for_button_up(), Timer::after(LONG_PRESS_DURATION)).await {
-                Either::First(_) => PressDuration::Short,
-                Either::Second(()) => PressDuration::Long,
+    /// Waits for the button to go down, a press from the remote (or a simulated one), a tap
+    /// on the touch pad, or a request for dormant sleep.
+    async fn next_input(&mut self) -> Input {
+        let remote = self.remote;
+        let touch_pad = self.touch_pad;
+        let remote_press = async {
+            match remote {
+                Some(notifier) => {
+                    match select(notifier.wait(), SIMULATED_PRESSES.receive()).await {
+                        Either::First(press_duration) | Either::Second(press_duration) => {
+                            press_duration
+                        }
+                    }
+                }
+                None => SIMULATED_PRESSES.receive().await,
+            }
+        };
+        let tap = async {
+            match touch_pad {
+                Some(notifier) => notifier.wait().await,
+                None => core::future::pending().await,
+            }
+        };
+        match select4(
+            self.wait_for_button_down(),
+            remote_press,
+            tap,
+            DORMANT_SLEEP_REQUESTS.wait(),
+        )
+        .await
+        {
+            Either4::First(_) => Input::Down,
+            Either4::Second(press_duration) => Input::Remote(press_duration),
+            Either4::Third(()) => Input::Tap,
+            Either4::Fourth(()) => Input::DormantSleep,
+        }
+    }
+
+    /// Publishes the events of a press that has just gone down, returning once the button is
+    /// back up and settled. `short_ended_at` is when the last short press ended, if it could
+    /// start a double press; returns the same for this press.
+    async fn measure_press(&mut self, short_ended_at: Option<Instant>) -> Option<Instant> {
+        let pressed_at = Instant::now();
+        let timings = gesture_timings();
+        publish(ButtonEvent::Pressed);
+        Timer::after(self.config.debounce_delay).await;
+        let next_short_ended_at =
+            match select(self.wait_for_button_up(), Timer::after(timings.long_press())).await {
+                Either::First(_) => publish_short(pressed_at, short_ended_at),
+                Either::Second(()) => {
+                    publish(ButtonEvent::Long);
+                    self.hold(pressed_at, timings.very_long_press()).await;
+                    None
+                }
             };
-        info!("Press duration: {:?}", press_duration);
-        press_duration
+        publish(ButtonEvent::Released);
+        Timer::after(self.config.debounce_delay).await;
+        next_short_ended_at
     }
 
-    /// Waits for the button to be pressed.
-    #[inline]
-    pub async fn wait_for_press(&mut self) -> &mut Self {
-        self.0.wait_for_rising_edge().await;
-        self
+    /// Publishes `Repeat` while a long press is held, and `VeryLong` once (if) it has been
+    /// held for `very_long`, returning when the button is released.
+    async fn hold(&mut self, pressed_at: Instant, very_long: Duration) {
+        let mut very_long_sent = false;
+        while let Either::Second(()) =
+            select(self.wait_for_button_up(), Timer::after(BUTTON_REPEAT_INTERVAL)).await
+        {
+            publish(ButtonEvent::Repeat);
+            if !very_long_sent && pressed_at.elapsed() >= very_long {
+                publish_very_long(pressed_at);
+                very_long_sent = true;
+            }
+        }
+    }
+}
+
+impl Button<ButtonPin> {
+    /// Puts the chip into dormant sleep, its lowest-power state, until the button is pressed.
+    ///
+    /// The press wakes the chip through the pin's dormant-wake edge detector, with nothing
+    /// polling while asleep. Every task and the timer halt too, so `Instant` (and with it the
+    /// clock) loses the time spent asleep, and the remote and touch pad cannot wake it.
+    fn sleep_until_pressed(&mut self) {
+        let pressed_edge = DormantWakeConfig {
+            edge_high: self.config.active_level == ActiveLevel::High,
+            edge_low: self.config.active_level == ActiveLevel::Low,
+            level_high: false,
+            level_low: false,
+        };
+        let _wake = self.pin.dormant_wake(pressed_edge);
+        info!("Entering dormant sleep");
+        embassy_rp::clocks::dormant_sleep();
+        info!("Woken from dormant sleep");
     }
 }
 
 // Instead of having API describing a short vs a long button-press vaguely using a `bool`, we define
 // an `enum` to clarify what each state represents.  The compiler will compile this down to the
-// very same `boolean` that we would have coded by hand.
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, defmt::Format)]
+// very same single byte that we would have coded by hand.
+/// How long a press from the IR remote stands for.
+#[cfg_attr(feature = "logging", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum PressDuration {
     Short,
     Long,
+    VeryLong,
 }
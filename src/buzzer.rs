@@ -0,0 +1,229 @@
+//! Buzzer virtual device - a passive piezo buzzer on GPIO 27, driven by PWM, that can tick
+//! softly each second and play alarm sounds
+
+use embassy_executor::{SpawnError, Spawner};
+use embassy_futures::select::{select3, Either3};
+use embassy_rp::{
+    peripherals::{PIN_27, PWM_SLICE5},
+    pwm::{self, Pwm},
+    Peri,
+};
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, signal::Signal};
+use embassy_time::{with_timeout, Duration, Instant, Timer};
+
+use clock_core::{AlarmSound, ClockTime, Melody};
+
+use crate::{
+    button::{self, wait_for_press, ButtonEvents},
+    config::config,
+    info,
+    network_policy::utc_offset_minutes,
+    settings::{alarm_melody, tick_quiet_hours},
+    shared_constants::{ALARM_SOUND_LIMIT, NOTE_GAP, ONE_SECOND, TICK_DURATION},
+    soft_reboot::latest_unix_seconds,
+    warn, Display2Format,
+};
+
+/// PWM counter clock: the 125 MHz system clock divided by 16.
+const PWM_DIVIDER: u8 = 16;
+/// How fast the PWM counter counts (125 MHz / 16).
+const PWM_COUNTER_HZ: u32 = 7_812_500;
+/// The counter's wrap for a 4 kHz tone (125 MHz / 16 / 4 kHz), near a piezo's loudest pitch.
+const TONE_TOP: u16 = 1_953;
+
+/// Alarm sounds for the buzzer to play (`Some`), in place of any playing, or a request to
+/// silence it (`None`).
+static SOUND_REQUESTS: Signal<CriticalSectionRawMutex, Option<AlarmSound>> = Signal::new();
+
+/// The next second boundary of the clock's time, published by the clock task whenever the
+/// time is set or moved, so ticks land on the second the display shows.
+static SECOND_BOUNDARY: Signal<CriticalSectionRawMutex, Instant> = Signal::new();
+
+/// Publishes when the clock's next second begins (called by the clock task whenever the time
+/// is set or moved).
+pub(crate) fn publish_second_boundary(clock_time: &ClockTime) {
+    let till_next = ClockTime::till_next(clock_time.now().since_midnight(), ONE_SECOND);
+    SECOND_BOUNDARY.signal(Instant::now().checked_add(till_next).unwrap_or(Instant::MAX));
+}
+
+/// Starts playing `sound` over and over, in place of any sound playing, until it is silenced
+/// (by `silence_sound` or a press of the button) or has played for `ALARM_SOUND_LIMIT`.
+pub(crate) fn play_sound(sound: AlarmSound) {
+    SOUND_REQUESTS.signal(Some(sound));
+}
+
+/// Stops the alarm sound playing, if any.
+pub(crate) fn silence_sound() {
+    SOUND_REQUESTS.signal(None);
+}
+
+/// Buzzer virtual device - ticks each second at the config's `tick_volume`, except during
+/// the tick's quiet hours, and plays alarm sounds at full volume
+pub struct Buzzer;
+
+impl Buzzer {
+    /// Create the buzzer and spawn its task.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SpawnError` if the task cannot be spawned.
+    pub fn new(
+        slice: Peri<'static, PWM_SLICE5>,
+        pin: Peri<'static, PIN_27>,
+        spawner: Spawner,
+    ) -> Result<Self, SpawnError> {
+        let pwm = Pwm::new_output_b(slice, pin, tone_config(TONE_TOP, 0));
+        spawner.spawn(device_loop(pwm))?;
+        Ok(Self)
+    }
+}
+
+#[embassy_executor::task]
+async fn device_loop(mut pwm: Pwm<'static>) -> ! {
+    let mut presses = button::subscribe()
+        .inspect_err(|err| warn!("A press cannot silence alarms: {}", Display2Format(err)))
+        .ok();
+    // No ticks until the clock publishes its first second boundary
+    let mut next_tick = Instant::MAX;
+    loop {
+        match select3(
+            Timer::at(next_tick),
+            SECOND_BOUNDARY.wait(),
+            SOUND_REQUESTS.wait(),
+        )
+        .await
+        {
+            Either3::First(()) => {
+                let volume_percent = config().tick_volume_percent;
+                if volume_percent > 0 && !quiet_now() {
+                    tick(&mut pwm, volume_percent).await;
+                }
+                next_tick = next_tick.checked_add(ONE_SECOND).unwrap_or(Instant::MAX);
+            }
+            Either3::Second(boundary) => {
+                info!("Ticks realigned to the clock's seconds");
+                next_tick = boundary;
+            }
+            Either3::Third(mut request) => {
+                // A new sound cuts off the one playing; the ticks wait until all are done
+                while let Some(sound) = request {
+                    request = play_alarm(&mut pwm, sound, presses.as_mut()).await;
+                }
+                next_tick = first_tick_after(next_tick, Instant::now());
+            }
+        }
+    }
+}
+
+/// Plays `sound` over and over for up to `ALARM_SOUND_LIMIT`, or until a press of the button
+/// or the next request, which it returns if it is another sound to play.
+async fn play_alarm(
+    pwm: &mut Pwm<'static>,
+    sound: AlarmSound,
+    presses: Option<&mut ButtonEvents>,
+) -> Option<AlarmSound> {
+    let melody = melody_for(sound).or_else(|| {
+        warn!("ALARM_MELODY is unset or malformed, so the custom alarm sound beeps");
+        melody_for(AlarmSound::Beep)
+    })?;
+    info!("Playing the {=str} alarm sound", sound.name());
+    let next_request = match select3(
+        with_timeout(ALARM_SOUND_LIMIT, repeat_melody(pwm, melody)),
+        wait_for_press(presses),
+        SOUND_REQUESTS.wait(),
+    )
+    .await
+    {
+        Either3::First(_) | Either3::Second(()) => None,
+        Either3::Third(request) => request,
+    };
+    // A sound cut off mid-note would otherwise keep sounding
+    pwm.set_config(&tone_config(TONE_TOP, 0));
+    info!("Alarm sound stopped");
+    next_request
+}
+
+/// The melody `sound` plays: a built-in one, or `ALARM_MELODY` for `AlarmSound::Custom`.
+fn melody_for(sound: AlarmSound) -> Option<Melody<'static>> {
+    match sound.built_in_melody() {
+        Some(text) => Melody::parse(text).ok(),
+        None => alarm_melody(),
+    }
+}
+
+/// Plays `melody` from the start again each time it ends, as loud as the buzzer goes.
+async fn repeat_melody(pwm: &mut Pwm<'static>, melody: Melody<'static>) -> ! {
+    loop {
+        for note in melody.notes() {
+            match note.pitch_hz {
+                Some(pitch_hz) => {
+                    let top = top_for(pitch_hz);
+                    let sounding = note.duration.checked_sub(NOTE_GAP).unwrap_or_default();
+                    play(pwm, top, duty_for(top, 100), sounding).await;
+                    Timer::after(note.duration.checked_sub(sounding).unwrap_or_default()).await;
+                }
+                None => Timer::after(note.duration).await,
+            }
+        }
+    }
+}
+
+/// The first tick at or after `now`, keeping to the seconds `next_tick` is aligned to.
+fn first_tick_after(next_tick: Instant, now: Instant) -> Instant {
+    let mut tick = next_tick;
+    while tick < now {
+        tick = tick.checked_add(ONE_SECOND).unwrap_or(Instant::MAX);
+    }
+    tick
+}
+
+/// Whether the tick is muted for the night, once the clock knows the time.
+fn quiet_now() -> bool {
+    tick_quiet_hours().is_some_and(|quiet_hours| {
+        latest_unix_seconds().is_some_and(|unix_seconds| {
+            quiet_hours.contains(unix_seconds, utc_offset_minutes())
+        })
+    })
+}
+
+/// A very short burst of tone, louder with a wider pulse (up to a square wave at 100%).
+async fn tick(pwm: &mut Pwm<'static>, volume_percent: u16) {
+    play(pwm, TONE_TOP, duty_for(TONE_TOP, volume_percent), TICK_DURATION).await;
+}
+
+/// Sounds the tone that wraps at `top`, at `compare`, for `duration`, then silences it.
+async fn play(pwm: &mut Pwm<'static>, top: u16, compare: u16, duration: Duration) {
+    pwm.set_config(&tone_config(top, compare));
+    Timer::after(duration).await;
+    pwm.set_config(&tone_config(top, 0));
+}
+
+/// The counter's wrap for a tone of `pitch_hz`. Melodies stay above middle C, whose wrap
+/// still fits in a `u16`.
+fn top_for(pitch_hz: u16) -> u16 {
+    PWM_COUNTER_HZ
+        .checked_div(u32::from(pitch_hz))
+        .and_then(|top| u16::try_from(top).ok())
+        .unwrap_or(u16::MAX)
+}
+
+/// The compare value for `volume_percent` of the tone that wraps at `top`: a share of half
+/// its period.
+#[expect(
+    clippy::arithmetic_side_effects,
+    clippy::integer_division_remainder_used,
+    reason = "The volume is at most 100, so the product fits in a u32, and 200 is not zero."
+)]
+fn duty_for(top: u16, volume_percent: u16) -> u16 {
+    let compare = u32::from(top) * u32::from(volume_percent.min(100)) / 200;
+    u16::try_from(compare).unwrap_or(top)
+}
+
+/// The PWM config for the tone that wraps at `top`, at `compare` (0 is silent).
+fn tone_config(top: u16, compare: u16) -> pwm::Config {
+    let mut config = pwm::Config::default();
+    config.divider = PWM_DIVIDER.into();
+    config.top = top;
+    config.compare_b = compare;
+    config
+}
@@ -0,0 +1,103 @@
+//! Climate log virtual device - keeps about hourly temperature samples in flash
+
+use embassy_executor::{SpawnError, Spawner};
+use embassy_futures::select::{select, Either};
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, signal::Signal};
+
+use clock_core::{ClimateSample, MidnightTick, UnixSeconds};
+
+use crate::{info, warn, Display2Format};
+use crate::clock::{subscribe_midnight, MidnightTicks};
+use crate::storage::{SharedStorage, CLIMATE_LOG_SLOTS};
+
+/// The least time between logged samples. Readings arrive with each half-hourly weather
+/// fetch, so this logs one an hour even when a fetch runs a little early.
+const MIN_SAMPLE_SPACING_SECONDS: i64 = 50 * 60;
+
+/// The latest reading, published by the clock task when a temperature arrives.
+static SAMPLES: Signal<CriticalSectionRawMutex, ClimateSample> = Signal::new();
+
+/// Publishes a reading for the climate log (called by the clock task).
+pub(crate) fn publish_climate_sample(sample: ClimateSample) {
+    SAMPLES.signal(sample);
+}
+
+/// Climate log virtual device - writes about one reading an hour to a ring in flash, so the
+/// clock keeps a room-climate history across reboots. `GET /climate.csv` reads it back.
+///
+/// At each local midnight it writes the latest reading again, stamped 00:00, if that reading
+/// is under `MIN_SAMPLE_SPACING_SECONDS` old, so each day's history starts at midnight.
+pub struct ClimateLog;
+
+impl ClimateLog {
+    /// Create the climate log and spawn its task.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SpawnError` if the task cannot be spawned.
+    pub fn new(storage: &'static SharedStorage, spawner: Spawner) -> Result<Self, SpawnError> {
+        spawner.spawn(device_loop(storage))?;
+        Ok(Self)
+    }
+}
+
+#[embassy_executor::task]
+async fn device_loop(storage: &'static SharedStorage) -> ! {
+    let mut midnight_ticks = subscribe_midnight()
+        .inspect_err(|err| warn!("Climate log ignores midnight: {}", Display2Format(err)))
+        .ok();
+    let mut last_logged = newest_logged(storage).await;
+    let mut latest: Option<ClimateSample> = None;
+    loop {
+        let sample = match select(SAMPLES.wait(), next_midnight(midnight_ticks.as_mut())).await {
+            Either::First(sample) => {
+                latest = Some(sample);
+                if last_logged.is_some_and(|logged| {
+                    sample.unix_seconds.as_i64().saturating_sub(logged.as_i64())
+                        < MIN_SAMPLE_SPACING_SECONDS
+                }) {
+                    continue;
+                }
+                sample
+            }
+            Either::Second(tick) => {
+                let Some(fresh) = latest.filter(|sample| {
+                    tick.unix_seconds.as_i64().saturating_sub(sample.unix_seconds.as_i64())
+                        < MIN_SAMPLE_SPACING_SECONDS
+                }) else {
+                    continue;
+                };
+                ClimateSample {
+                    unix_seconds: tick.unix_seconds,
+                    ..fresh
+                }
+            }
+        };
+        match storage.lock().await.append_climate_sample(&sample) {
+            Ok(()) => {
+                info!("Logged climate sample: {:?}", sample);
+                last_logged = Some(sample.unix_seconds);
+            }
+            Err(err) => warn!("Climate log write failed: {}", Display2Format(&err)),
+        }
+    }
+}
+
+/// Waits for the next local midnight, or forever without a subscription.
+async fn next_midnight(midnight_ticks: Option<&mut MidnightTicks>) -> MidnightTick {
+    match midnight_ticks {
+        Some(midnight_ticks) => midnight_ticks.next_message_pure().await,
+        None => core::future::pending().await,
+    }
+}
+
+/// When the newest logged sample was taken, if there is one.
+async fn newest_logged(storage: &SharedStorage) -> Option<UnixSeconds> {
+    let mut storage = storage.lock().await;
+    let newest_slot = storage
+        .next_climate_slot()
+        .ok()?
+        .checked_sub(1)
+        .unwrap_or(CLIMATE_LOG_SLOTS.saturating_sub(1));
+    storage.climate_sample(newest_slot).ok().flatten().map(|sample| sample.unix_seconds)
+}
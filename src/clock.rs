@@ -1,18 +1,81 @@
-use defmt::info;
+use core::sync::atomic::{AtomicBool, Ordering};
+
 use embassy_executor::{SpawnError, Spawner};
 use embassy_futures::select::{select, Either};
-use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, channel::Channel};
-use embassy_time::{Duration, Timer};
+use embassy_sync::{
+    blocking_mutex::raw::CriticalSectionRawMutex,
+    channel::Channel,
+    pubsub::{PubSubChannel, Subscriber},
+};
+use embassy_time::{Duration, Instant, Timer};
 
+use clock_core::{
+    BlinkState, Brightness, ClimateSample, ClockFace, ClockState, ClockTime, DisplayContent,
+    LeapIndicator, Message, MidnightTick, Page, PendingSettings, Reminders, Settings, SolarDimming,
+    SyncError, Temperature, TimeSource, UnixSeconds,
+};
+
+#[cfg(feature = "talking-clock")]
+use crate::talker;
+use crate::Result;
 use crate::{
     blinker::{Blinker, BlinkerNotifier},
-    clock_time::ClockTime,
-    output_array::OutputArray,
-    shared_constants::{CELL_COUNT, ONE_MINUTE, SEGMENT_COUNT},
-    ClockState,
+    build_info,
+    buzzer::publish_second_boundary,
+    climate_log::publish_climate_sample,
+    gps::gps_location,
+    hardware::DisplayBackend,
+    info,
+    metrics::publish_skew_history,
+    network_policy::publish_utc_offset_minutes,
+    settings::{
+        default_carousel, default_settings, default_utc_offset_minutes, leap_smear,
+        max_backward_hold, max_slew, night_brightness, page_rotation, publish_live_settings,
+        solar_dimming,
+    },
+    shared_constants::ONE_MINUTE,
+    soft_reboot::{publish_clock_state, publish_unix_seconds},
 };
 
+/// The most subscribers to `MidnightTick`s at once: the climate log, with room for more.
+const MAX_MIDNIGHT_SUBSCRIBERS: usize = 2;
+
+/// Every `MidnightTick` the clock task publishes. A subscriber that falls a day behind misses
+/// the older one.
+static MIDNIGHT_TICKS: PubSubChannel<
+    CriticalSectionRawMutex,
+    MidnightTick,
+    1,
+    MAX_MIDNIGHT_SUBSCRIBERS,
+    0,
+> = PubSubChannel::new();
+
+/// Whether the display is held (see `Clock::hold`), for the state machine, which releases a
+/// hold on the next press.
+static HELD: AtomicBool = AtomicBool::new(false);
+
+/// Whether the display is held, frozen on what it showed when `Clock::hold` was called.
+pub(crate) fn is_held() -> bool {
+    HELD.load(Ordering::Relaxed)
+}
+
+/// Receives the clock task's `MidnightTick`s.
+pub type MidnightTicks =
+    Subscriber<'static, CriticalSectionRawMutex, MidnightTick, 1, MAX_MIDNIGHT_SUBSCRIBERS, 0>;
+
+/// Starts receiving a `MidnightTick` each time a new local day begins: when local time
+/// passes 00:00, or a sync or UTC offset change moves it into a later day. Subsystems that
+/// act on the date subscribe here instead of working out midnight themselves.
+///
+/// # Errors
+///
+/// Returns an error if there are already `MAX_MIDNIGHT_SUBSCRIBERS` subscriptions.
+pub fn subscribe_midnight() -> Result<MidnightTicks> {
+    Ok(MIDNIGHT_TICKS.subscriber()?)
+}
+
 /// A struct representing a clock abstraction.
+#[derive(Clone, Copy)]
 pub struct Clock<'a>(&'a ClockOuterNotifier);
 /// Type alias for notifier that sends messages to the `Clock` and the `Blinker` it controls.
 pub type ClockNotifier = (ClockOuterNotifier, BlinkerNotifier);
@@ -26,8 +89,7 @@ impl Clock<'_> {
     ///
     /// # Arguments
     ///
-    /// * `cell_pins` - The pins that control the cells (digits) of the display.
-    /// * `segment_pins` - The pins that control the segments of the display.
+    /// * `driver` - The board's display hardware (see `Hardware::display`).
     /// * `notifier` - The static notifier that sends messages to the `Clock` and the `Blinker` it controls.
     ///          This notifier is created with the `Clock::notifier()` method.
     /// * `spawner` - The spawner that will spawn the task that controls the clock.
@@ -37,13 +99,12 @@ impl Clock<'_> {
     /// Returns a `SpawnError` if the task cannot be spawned.
     #[must_use = "Must be used to manage the spawned task"]
     pub fn new(
-        cell_pins: OutputArray<'static, CELL_COUNT>,
-        segment_pins: OutputArray<'static, SEGMENT_COUNT>,
+        driver: DisplayBackend,
         notifier: &'static ClockNotifier,
         spawner: Spawner,
     ) -> Result<Self, SpawnError> {
         let (outer_notifier, blinker_notifier) = notifier;
-        let blinkable_display = Blinker::new(cell_pins, segment_pins, blinker_notifier, spawner)?;
+        let blinkable_display = Blinker::new(driver, blinker_notifier, spawner)?;
         spawner.spawn(device_loop(outer_notifier, blinkable_display))?;
         Ok(Self(outer_notifier))
     }
@@ -60,33 +121,163 @@ impl Clock<'_> {
     /// ```rust,ignore
     /// #[expect(clippy::items_after_statements, reason = "Keeps related code together")]
     /// static CLOCK_NOTIFIER: ClockNotifier = Clock::notifier();
-    /// let mut clock = Clock::new(hardware.cells, hardware.segments, &CLOCK_NOTIFIER, spawner)?;
+    /// let mut clock = Clock::new(hardware.display, &CLOCK_NOTIFIER, spawner)?;
     /// ```
     #[must_use]
     pub const fn notifier() -> ClockNotifier {
         (Channel::new(), Blinker::notifier())
     }
 
-    pub(crate) async fn set_state(&self, clock_state: ClockState) {
+    /// Sends any `ClockNotice`, for a caller the methods below do not cover.
+    pub async fn send(&self, notice: ClockNotice) {
+        self.0.send(notice).await;
+    }
+
+    /// Switches the display to `clock_state`'s mode. The state machine calls this as it
+    /// enters each state.
+    pub async fn set_state(&self, clock_state: ClockState) {
         self.0.send(ClockNotice::SetState(clock_state)).await;
     }
 
-    pub(crate) async fn set_time_from_unix(&self, unix_seconds: crate::UnixSeconds) {
-        self.0.send(ClockNotice::SetTimeFromUnix(unix_seconds)).await;
+    /// Sets the time, as a sync does.
+    pub async fn set_time_from_unix(&self, unix_seconds: UnixSeconds, leap: LeapIndicator) {
+        self.0
+            .send(ClockNotice::SetTimeFromUnix(unix_seconds, leap))
+            .await;
+    }
+
+    /// Sets how long a blinking display stays lit and then dark, in milliseconds.
+    pub async fn set_blink_timing(&self, on_ms: u64, off_ms: u64) {
+        self.0
+            .send(ClockNotice::SetBlinkTiming { on_ms, off_ms })
+            .await;
+    }
+
+    /// Sets the local time of day to `hours`:`minutes`:`seconds` (24-hour), keeping the date
+    /// and time zone. Unlike `adjust_time`, the caller need not know the time shown now.
+    pub async fn set_time(&self, hours: u8, minutes: u8, seconds: u8) {
+        self.0
+            .send(ClockNotice::SetTime(hours, minutes, seconds))
+            .await;
     }
 
+    /// Moves the displayed time forward by `delta`, wrapping past midnight.
+    pub async fn adjust_time(&self, delta: Duration) {
+        self.0.send(ClockNotice::AdjustClockTime(delta)).await;
+    }
+
+    /// Moves the clock to another time zone, keeping the same instant.
+    pub async fn set_utc_offset_minutes(&self, utc_offset_minutes: i32) {
+        self.0
+            .send(ClockNotice::SetUtcOffsetMinutes(utc_offset_minutes))
+            .await;
+    }
+
+    /// Shows `pending` in place of the live settings until it is committed, or drops the
+    /// preview if `None`.
+    pub async fn preview_settings(&self, pending: Option<PendingSettings>) {
+        self.0.send(ClockNotice::PreviewSettings(pending)).await;
+    }
+
+    /// Makes `pending` the live UTC offset and settings, ending any preview.
+    pub async fn commit_settings(&self, pending: PendingSettings) {
+        self.0.send(ClockNotice::CommitSettings(pending)).await;
+    }
+
+    /// Replaces the display settings, taking effect at the next render.
+    pub async fn set_settings(&self, settings: Settings) {
+        self.0.send(ClockNotice::SetSettings(settings)).await;
+    }
+
+    /// Sets the outdoor temperature shown by `ClockState::Weather`, or `None` if it is unknown.
+    pub async fn set_temperature(&self, temperature: Option<Temperature>) {
+        self.0.send(ClockNotice::SetTemperature(temperature)).await;
+    }
+
+    /// Records the source that last set the time, shown by `ClockState::TimeSource`.
+    pub async fn set_time_source(&self, time_source: TimeSource) {
+        self.0.send(ClockNotice::SetTimeSource(time_source)).await;
+    }
+
+    /// Records why the latest sync failed, shown by `ClockState::SyncFailed`.
+    pub async fn set_sync_error(&self, sync_error: SyncError) {
+        self.0.send(ClockNotice::SetSyncError(sync_error)).await;
+    }
+
+    /// Sets the battery charge shown by `ClockState::Battery`, or `None` if it is unknown.
+    pub async fn set_battery_percent(&self, battery_percent: Option<u8>) {
+        self.0
+            .send(ClockNotice::SetBatteryPercent(battery_percent))
+            .await;
+    }
+
+    /// Sets the signal strength shown by `ClockState::Survey`, or `None` if the network did
+    /// not answer.
+    pub async fn set_signal_dbm(&self, signal_dbm: Option<i16>) {
+        self.0.send(ClockNotice::SetSignalDbm(signal_dbm)).await;
+    }
 
-    pub(crate) async fn adjust_utc_offset_hours(&self, hours: i32) {
-        self.0.send(ClockNotice::AdjustUtcOffsetHours(hours)).await;
+    /// Scrolls `message` across the display in place of the current mode, then goes back to
+    /// it. A new message replaces one still showing.
+    pub async fn show_message(&self, message: Message) {
+        self.0.send(ClockNotice::ShowMessage(message)).await;
+    }
+
+    /// Replaces the reminder table, taking effect at the next render.
+    pub async fn set_reminders(&self, reminders: Reminders) {
+        self.0.send(ClockNotice::SetReminders(reminders)).await;
+    }
+
+    /// Freezes the display on what it shows now, blinking. The time keeps running underneath,
+    /// and the next press of the button (or `resume`) ends the hold.
+    pub async fn hold(&self) {
+        self.0.send(ClockNotice::Hold).await;
+    }
+
+    /// Ends a `hold`, going back to the current time.
+    pub async fn resume(&self) {
+        self.0.send(ClockNotice::Resume).await;
+    }
+
+    /// Has the `Talker` say the time the clock keeps.
+    #[cfg(feature = "talking-clock")]
+    pub async fn announce_time(&self) {
+        self.0.send(ClockNotice::AnnounceTime).await;
+    }
+
+    /// Exercises a Nixie display's cathodes. Other displays ignore it.
+    pub async fn refresh_cathodes(&self) {
+        self.0.send(ClockNotice::RefreshCathodes).await;
     }
 }
 
+/// A message to the clock task. Each `Clock` method sends one; `Clock::send` sends any.
 pub enum ClockNotice {
     SetState(ClockState),
-    SetTimeFromUnix(crate::UnixSeconds),
+    SetTimeFromUnix(UnixSeconds, LeapIndicator),
+    SetTime(u8, u8, u8),
     AdjustClockTime(Duration),
     ResetSeconds,
-    AdjustUtcOffsetHours(i32),
+    SetUtcOffsetMinutes(i32),
+    PreviewSettings(Option<PendingSettings>),
+    CommitSettings(PendingSettings),
+    SetSettings(Settings),
+    SetReminders(Reminders),
+    SetTemperature(Option<Temperature>),
+    SetBatteryPercent(Option<u8>),
+    SetSignalDbm(Option<i16>),
+    SetTimeSource(TimeSource),
+    SetSyncError(SyncError),
+    ShowMessage(Message),
+    Hold,
+    Resume,
+    #[cfg(feature = "talking-clock")]
+    AnnounceTime,
+    SetBlinkTiming {
+        on_ms: u64,
+        off_ms: u64,
+    },
+    RefreshCathodes,
 }
 
 impl ClockNotice {
@@ -95,44 +286,221 @@ impl ClockNotice {
         reason = "The += operator wraps around to always produce a result less than one day."
     )]
     /// Handles the action associated with the given `ClockNotice`.
-    pub(crate) fn apply(self, clock_time: &mut ClockTime, clock_state: &mut ClockState) {
+    ///
+    /// Returns whether the notice may have changed what the display shows, in which case the
+    /// clock re-renders at once and works out a new deadline.
+    #[must_use]
+    pub(crate) fn apply(self, face: &mut ClockFace, blinker: &Blinker<'_>) -> bool {
+        let clock_time = &mut face.clock_time;
         match self {
-            Self::SetTimeFromUnix(unix_seconds) => {
+            Self::SetTimeFromUnix(unix_seconds, leap) => {
                 clock_time.set_from_unix(unix_seconds);
+                clock_time.schedule_leap_second(unix_seconds, leap, leap_smear());
+                publish_skew_history(clock_time.skew_history());
+                publish_unix_seconds(clock_time.unix_seconds());
+                publish_second_boundary(clock_time);
+            }
+            Self::SetTime(hours, minutes, seconds) => {
+                clock_time.set_time_of_day(hours, minutes, seconds);
+                publish_second_boundary(clock_time);
             }
             Self::AdjustClockTime(delta) => {
                 *clock_time += delta;
+                publish_second_boundary(clock_time);
             }
             Self::SetState(new_clock_mode) => {
-                *clock_state = new_clock_mode;
+                face.state = new_clock_mode;
+                publish_clock_state(new_clock_mode);
             }
             Self::ResetSeconds => {
-                let sleep_duration = ClockTime::till_next(clock_time.now(), ONE_MINUTE);
+                let sleep_duration =
+                    ClockTime::till_next(clock_time.now().since_midnight(), ONE_MINUTE);
                 *clock_time += sleep_duration;
+                publish_second_boundary(clock_time);
+            }
+            Self::SetUtcOffsetMinutes(utc_offset_minutes) => {
+                clock_time.set_utc_offset_minutes(utc_offset_minutes);
+                publish_utc_offset_minutes(utc_offset_minutes);
+            }
+            Self::PreviewSettings(pending) => {
+                face.preview = pending;
+            }
+            Self::CommitSettings(pending) => {
+                face.commit(pending);
+                publish_utc_offset_minutes(pending.utc_offset_minutes);
+                publish_live_settings(&pending.settings);
+            }
+            Self::SetSettings(new_settings) => {
+                face.content.settings = new_settings;
+                publish_live_settings(&new_settings);
+            }
+            Self::SetReminders(new_reminders) => {
+                face.content.reminders = new_reminders;
+            }
+            Self::SetTemperature(new_temperature) => {
+                face.content.temperature = new_temperature;
+                // Log the reading once the time is known, so each sample has a date
+                if let (Some(temperature), Some(unix_seconds)) =
+                    (new_temperature, clock_time.unix_seconds())
+                {
+                    publish_climate_sample(ClimateSample {
+                        unix_seconds,
+                        temperature,
+                        humidity_percent: None,
+                    });
+                }
             }
-            Self::AdjustUtcOffsetHours(hours) => {
-                clock_time.adjust_utc_offset_hours(hours);
+            Self::SetBatteryPercent(new_battery_percent) => {
+                face.content.battery_percent = new_battery_percent;
+            }
+            Self::SetSignalDbm(new_signal_dbm) => {
+                face.content.signal_dbm = new_signal_dbm;
+            }
+            Self::SetTimeSource(new_time_source) => {
+                face.content.time_source = Some(new_time_source);
+            }
+            Self::SetSyncError(new_sync_error) => {
+                face.content.sync_error = Some(new_sync_error);
+            }
+            Self::ShowMessage(new_message) => {
+                face.message = Some((new_message, Instant::now()));
+            }
+            Self::Hold => {
+                face.hold();
+                HELD.store(true, Ordering::Relaxed);
+            }
+            Self::Resume => {
+                face.held = None;
+                HELD.store(false, Ordering::Relaxed);
+            }
+            #[cfg(feature = "talking-clock")]
+            Self::AnnounceTime => {
+                // Saying the time changes nothing on the display
+                talker::announce_time(clock_time);
+                return false;
+            }
+            Self::SetBlinkTiming { on_ms, off_ms } => {
+                // The blinker picks up new timing itself; re-rendering would only restart its
+                // blink
+                blinker.set_blink_timing(on_ms, off_ms);
+                return false;
+            }
+            Self::RefreshCathodes => {
+                blinker.refresh_cathodes();
+                return false;
             }
         }
+        true
     }
 }
 
 #[embassy_executor::task]
 async fn device_loop(clock_notifier: &'static ClockOuterNotifier, blinker: Blinker<'static>) -> ! {
-    let mut clock_time = ClockTime::default();
-    let mut clock_state = ClockState::default();
+    let mut clock_time = ClockTime::new(default_utc_offset_minutes());
+    clock_time.set_max_slew(max_slew());
+    clock_time.set_max_hold(max_backward_hold());
+    publish_utc_offset_minutes(default_utc_offset_minutes());
+    publish_live_settings(&default_settings());
+    publish_second_boundary(&clock_time);
+    let mut face = ClockFace::new(
+        clock_time,
+        DisplayContent {
+            settings: default_settings(),
+            carousel: default_carousel(),
+            version: build_info::SUMMARY,
+            ..DisplayContent::default()
+        },
+    );
+    let mut brightness = Brightness::MAX;
+    let mut today: Option<MidnightTick> = None;
+    let mut sensor_page = None;
 
     loop {
-        // Compute the blinkable display and time until the display change.
-        let (blink_mode, text, sleep_duration) = clock_state.render(&clock_time);
-        blinker.write_text(blink_mode, text);
+        // Compute the blinkable display and time until the display changes (see
+        // `ClockFace::render`)
+        let rendered_at = Instant::now();
+        today = publish_new_day(today, face.clock_time.today());
+        let (blink_mode, bit_matrix, sleep_duration) = face.render();
+        blinker.write_bit_matrix(blink_mode, bit_matrix);
 
-        // Wait for a notification or for the sleep duration to elapse
+        // Keep the sensor page current. While the time shows undisturbed, the pages take turns.
+        let sensor_matrix = face.render_sensor_page();
+        if sensor_page != Some(sensor_matrix) {
+            sensor_page = Some(sensor_matrix);
+            blinker.write_page(Page::Sensor, BlinkState::Solid, sensor_matrix);
+        }
+        let (page, till_turn) = page_rotation()
+            .filter(|_| face.undisturbed())
+            .map_or((Page::Time, Duration::MAX), |rotation| {
+                rotation.page_at(Instant::now().duration_since(Instant::MIN))
+            });
+        blinker.show_page(page);
+        let sleep_duration = sleep_duration.min(till_turn);
+
+        // Dim between sunset and sunrise. When night means off, the other modes stay faintly
+        // lit so a button press can still show the time. A GPS receiver's position stands in
+        // for a location that was not set at build time.
+        let solar_dimming = solar_dimming().or_else(|| {
+            gps_location().map(|location| SolarDimming {
+                location,
+                night: night_brightness(),
+            })
+        });
+        let new_brightness = solar_dimming.map_or(Brightness::MAX, |dimming| {
+            let night_brightness = dimming.brightness(&face.clock_time);
+            if night_brightness.is_off()
+                && (face.message.is_some() || !matches!(face.state, ClockState::HoursMinutes))
+            {
+                Brightness::MIN
+            } else {
+                night_brightness
+            }
+        });
+        if new_brightness != brightness {
+            brightness = new_brightness;
+            blinker.set_brightness(brightness);
+        }
+
+        // Wait for the display to change, or for a notification that changes it, and then
+        // render again right away. The deadline is absolute, so the time spent writing the
+        // display above does not push the next change late. It is measured from just before
+        // rendering, so it errs early (a redundant redraw), not late.
+        let deadline = rendered_at
+            .checked_add(sleep_duration)
+            .unwrap_or(Instant::MAX);
         info!("Sleep for {:?}", sleep_duration);
-        if let Either::First(notification) =
-            select(clock_notifier.receive(), Timer::after(sleep_duration)).await
+        while let Either::First(notification) =
+            select(clock_notifier.receive(), Timer::at(deadline)).await
         {
-            notification.apply(&mut clock_time, &mut clock_state);
+            // Apply everything already queued too, so a burst (rapid presses, or a sync
+            // arriving with them) renders once, from its final state
+            let mut changed = false;
+            let mut next = Some(notification);
+            while let Some(notice) = next.take().or_else(|| clock_notifier.try_receive().ok()) {
+                changed |= notice.apply(&mut face, &blinker);
+            }
+            if changed {
+                break;
+            }
         }
     }
 }
+
+/// Publishes a `MidnightTick` if `new_today` is a later day than `today`, and returns it as
+/// the day to compare with next. The first day the clock learns is not announced, nor is a
+/// move back to an earlier day.
+fn publish_new_day(
+    today: Option<MidnightTick>,
+    new_today: Option<MidnightTick>,
+) -> Option<MidnightTick> {
+    if let Some(tick) = new_today.filter(|tick| today.is_some_and(|day| tick.date > day.date)) {
+        info!(
+            "New day: {}-{}-{}",
+            tick.date.year(),
+            u8::from(tick.date.month()),
+            tick.date.day()
+        );
+        MIDNIGHT_TICKS.immediate_publisher().publish_immediate(tick);
+    }
+    new_today
+}
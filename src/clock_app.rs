@@ -0,0 +1,410 @@
+//! ClockApp - wires the standard set of virtual devices together, so a firmware crate can run
+//! the whole clock with one call, leaving out the optional subsystems it does not want
+
+#![allow(clippy::future_not_send, reason = "single-threaded")]
+
+use core::convert::Infallible;
+
+use embassy_executor::Spawner;
+use embassy_sync::{mutex::Mutex, signal::Signal};
+use static_cell::StaticCell;
+
+use clock_core::{ClockState, LeapIndicator};
+
+use crate::{build_info, info, warn, Display2Format};
+use crate::button::{set_gesture_timings, ButtonMonitor};
+#[cfg(not(feature = "fourteen-segment"))]
+use crate::buzzer::Buzzer;
+use crate::climate_log::ClimateLog;
+use crate::clock::{Clock, ClockNotifier};
+use crate::clock_state::{ClockStateExt, OffRequests};
+use crate::config::set_config;
+use crate::gps::{self, Gps};
+use crate::hardware::Hardware;
+use crate::http::{self, HttpServer};
+use crate::ir_remote::{IrRemote, IrRemoteNotifier};
+use crate::memory_report::{log_memory_report, paint_stack};
+use crate::mqtt::{self, Mqtt};
+use crate::provisioning::set_provisioning;
+use crate::radio_time::RadioTime;
+use crate::scheduler::Scheduler;
+use crate::settings::{default_gesture_timings, default_settings};
+use crate::soft_reboot::{SoftReboot, SoftRebootNotifier};
+use crate::stack_guard::StackGuard;
+#[cfg(feature = "oled")]
+use crate::status_display::StatusDisplay;
+use crate::storage::SharedStorage;
+use crate::supply::SupplyMonitor;
+use crate::switch::{Switch, SwitchNotifier};
+#[cfg(feature = "talking-clock")]
+use crate::talker::{self, Talker};
+use crate::time_sync::{TimeSync, TimeSyncNotifier};
+use crate::touch_pad::{TouchPad, TouchPadNotifier};
+use crate::usb_console::{self, UsbConsole};
+use crate::weather::{self, Weather};
+use crate::Result;
+
+/// The whole clock: every virtual device the standard firmware runs, created in the order
+/// they depend on each other, followed by the button-driven state machine.
+///
+/// A firmware crate that wants the standard clock calls `ClockApp::run` from its
+/// `#[embassy_executor::main]`, and one that wants fewer subsystems starts from
+/// `ClockApp::builder()`. One that wants a different set altogether creates the devices
+/// itself, in the same order:
+///
+/// 0. `paint_stack`, before anything else uses the stack, and then the `StackGuard`.
+/// 1. The `Clock` (which starts its `Blinker` and `Display`), so the display lights first.
+/// 2. The button and its extra inputs (`IrRemote`, `TouchPad`), then the `ButtonMonitor`.
+/// 3. `SharedStorage`, then the saved settings, applied to the `Clock` before anything
+///    else reads them. `set_provisioning` must come before the `TimeSync`, which joins WiFi
+///    at once.
+/// 4. `SoftReboot`, whose snapshot restores the time and mode.
+/// 5. `TimeSync` (which creates the `Wifi`) and the `Switch`, then the devices that use the
+///    network, the clock, or the switch: `HttpServer`, `Weather`, `Mqtt`, `Scheduler`,
+///    `ClimateLog`, `SupplyMonitor`, `Gps`, `RadioTime`, `UsbConsole`, and the optional ones.
+/// 6. The state machine: `ClockState::execute` in a loop.
+pub struct ClockApp;
+
+impl ClockApp {
+    /// Creates the standard virtual devices from `hardware`, with every optional subsystem,
+    /// and runs the clock. Call it once: the devices live in statics.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a task cannot be spawned or storage cannot be read. Otherwise it
+    /// never returns.
+    pub async fn run(hardware: Hardware, spawner: Spawner) -> Result<Infallible> {
+        Self::builder().run(hardware, spawner).await
+    }
+
+    /// Starts picking the optional subsystems, all of them on.
+    #[must_use]
+    pub const fn builder() -> ClockAppBuilder {
+        ClockAppBuilder {
+            http: true,
+            weather: true,
+            mqtt: true,
+            alarm: true,
+            sensors: true,
+            console: true,
+        }
+    }
+}
+
+/// A part of the clock that `ClockAppBuilder` can leave out. The clock, button, storage, and
+/// time sync always run. Which hardware the others drive is chosen at build time, with the
+/// crate's features: `no-wifi` leaves out the radio (and so the network), and
+/// `talking-clock`, `radio-time`, and `oled` add theirs.
+#[cfg_attr(feature = "logging", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subsystem {
+    /// The `HttpServer`.
+    Http,
+    /// `Weather`, fetching the outdoor temperature.
+    Weather,
+    /// `Mqtt`, the connection to an MQTT broker.
+    Mqtt,
+    /// The `Buzzer` and, with `talking-clock`, the `Talker`.
+    Alarm,
+    /// The `SupplyMonitor`, `Gps`, `RadioTime`, and `ClimateLog`.
+    Sensors,
+    /// The `UsbConsole`.
+    Console,
+}
+
+impl Subsystem {
+    /// Every subsystem, in the order `ClockApp` starts them.
+    pub const ALL: [Self; 6] = [
+        Self::Http,
+        Self::Weather,
+        Self::Mqtt,
+        Self::Sensors,
+        Self::Console,
+        Self::Alarm,
+    ];
+
+    /// The RAM its tasks hold in buffers, in bytes. Embassy reserves each task's memory at
+    /// build time, so it is taken whether or not the subsystem starts; this says what leaving
+    /// a subsystem out of a custom build would save.
+    #[must_use]
+    pub const fn ram_bytes(self) -> usize {
+        match self {
+            Self::Http => http::RAM_BYTES,
+            Self::Weather => weather::RAM_BYTES,
+            Self::Mqtt => mqtt::RAM_BYTES,
+            #[cfg(feature = "talking-clock")]
+            Self::Alarm => talker::RAM_BYTES,
+            // The buzzer holds no buffers
+            #[cfg(not(feature = "talking-clock"))]
+            Self::Alarm => 0,
+            Self::Sensors => gps::RAM_BYTES,
+            Self::Console => usb_console::RAM_BYTES,
+        }
+    }
+}
+
+/// Picks which optional subsystems `ClockApp` starts (see `ClockApp::builder`). The choice is
+/// made at run time, so it can follow a setting read at boot; a subsystem left out spawns no
+/// tasks and leaves its pins alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockAppBuilder {
+    http: bool,
+    weather: bool,
+    mqtt: bool,
+    alarm: bool,
+    sensors: bool,
+    console: bool,
+}
+
+impl ClockAppBuilder {
+    /// Whether to start the HTTP server.
+    #[must_use]
+    pub const fn http(self, enabled: bool) -> Self {
+        Self {
+            http: enabled,
+            ..self
+        }
+    }
+
+    /// Whether to fetch the outdoor temperature.
+    #[must_use]
+    pub const fn weather(self, enabled: bool) -> Self {
+        Self {
+            weather: enabled,
+            ..self
+        }
+    }
+
+    /// Whether to connect to the MQTT broker.
+    #[must_use]
+    pub const fn mqtt(self, enabled: bool) -> Self {
+        Self {
+            mqtt: enabled,
+            ..self
+        }
+    }
+
+    /// Whether to start the buzzer (and the talker).
+    #[must_use]
+    pub const fn alarm(self, enabled: bool) -> Self {
+        Self {
+            alarm: enabled,
+            ..self
+        }
+    }
+
+    /// Whether to start the supply monitor, GPS, radio time receiver, and climate log.
+    #[must_use]
+    pub const fn sensors(self, enabled: bool) -> Self {
+        Self {
+            sensors: enabled,
+            ..self
+        }
+    }
+
+    /// Whether to start the USB console.
+    #[must_use]
+    pub const fn console(self, enabled: bool) -> Self {
+        Self {
+            console: enabled,
+            ..self
+        }
+    }
+
+    /// Whether `subsystem` is picked.
+    #[must_use]
+    pub const fn starts(&self, subsystem: Subsystem) -> bool {
+        match subsystem {
+            Subsystem::Http => self.http,
+            Subsystem::Weather => self.weather,
+            Subsystem::Mqtt => self.mqtt,
+            Subsystem::Alarm => self.alarm,
+            Subsystem::Sensors => self.sensors,
+            Subsystem::Console => self.console,
+        }
+    }
+
+    /// Logs each optional subsystem, whether it starts, and the RAM its buffers take.
+    fn report(&self) {
+        for subsystem in Subsystem::ALL {
+            let status = if self.starts(subsystem) { "on" } else { "off" };
+            info!(
+                "{:?}: {=str}, {} bytes of buffers",
+                subsystem,
+                status,
+                subsystem.ram_bytes()
+            );
+        }
+    }
+
+    /// Creates the virtual devices from `hardware`, with the subsystems picked, and runs the
+    /// clock. Call it once: the devices live in statics.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a task cannot be spawned or storage cannot be read. Otherwise it
+    /// never returns.
+    #[expect(clippy::items_after_statements, reason = "Keeps related code together")]
+    pub async fn run(self, hardware: Hardware, spawner: Spawner) -> Result<Infallible> {
+        // Before anything else runs, so the stack's high-water mark covers it all
+        paint_stack();
+        info!("Firmware {=str}", build_info::SUMMARY);
+        self.report();
+        let _stack_guard = StackGuard::new(spawner)?;
+        static CLOCK_NOTIFIER: ClockNotifier = Clock::notifier();
+        let mut clock = Clock::new(hardware.display, &CLOCK_NOTIFIER, spawner)?;
+        let mut button = hardware.button;
+        static IR_REMOTE_NOTIFIER: IrRemoteNotifier = IrRemote::notifier();
+        let _ir_remote = IrRemote::new(hardware.ir_receiver, &IR_REMOTE_NOTIFIER, spawner)?;
+        button.add_remote(&IR_REMOTE_NOTIFIER);
+        static TOUCH_PAD_NOTIFIER: TouchPadNotifier = TouchPad::notifier();
+        let _touch_pad = TouchPad::new(hardware.touch_pad, &TOUCH_PAD_NOTIFIER, spawner)?;
+        button.add_touch_pad(&TOUCH_PAD_NOTIFIER);
+        let button_monitor = ButtonMonitor::new(button, spawner)?;
+        let mut button_events = button_monitor.subscribe()?;
+        static STORAGE: StaticCell<SharedStorage> = StaticCell::new();
+        let storage: &'static SharedStorage = STORAGE.init(Mutex::new(hardware.storage));
+        {
+            let mut storage = storage.lock().await;
+            // The config page's settings apply until others are saved on the clock itself
+            let provisioning = storage.load_provisioning()?;
+            set_provisioning(provisioning.clone());
+            if let Some(provisioning) = &provisioning {
+                info!("Provisioned for WiFi network {=str}", provisioning.ssid.as_str());
+                clock
+                    .set_utc_offset_minutes(provisioning.utc_offset_minutes)
+                    .await;
+            }
+            let settings = storage.load_settings()?.unwrap_or_else(default_settings);
+            info!("Using settings: {:?}", settings);
+            clock.set_settings(settings).await;
+            if let Some(utc_offset_minutes) = storage.load_utc_offset_minutes()? {
+                info!("Using saved UTC offset: {} minutes", utc_offset_minutes);
+                clock.set_utc_offset_minutes(utc_offset_minutes).await;
+            }
+            if let Some(report) = storage.load_crash_report()? {
+                warn!("Crashed before the last reboot: {}", Display2Format(&report));
+            }
+            let gesture_timings =
+                storage.load_gesture_timings()?.unwrap_or_else(default_gesture_timings);
+            info!("Using gesture timings: {:?}", gesture_timings);
+            set_gesture_timings(gesture_timings);
+            let config = storage.load_config()?.or_else(|| provisioning.as_ref()?.config);
+            if let Some(config) = config {
+                info!("Using config: {:?}", config);
+                set_config(config);
+                clock
+                    .set_blink_timing(config.blink_on_ms.into(), config.blink_off_ms.into())
+                    .await;
+            }
+            // No reminders saved reads as an empty table
+            let saved_reminders = storage.load_reminders()?;
+            let reminders = provisioning
+                .as_ref()
+                .and_then(|provisioning| provisioning.reminders)
+                .filter(|_| saved_reminders.iter().next().is_none())
+                .unwrap_or(saved_reminders);
+            info!("Using reminders: {:?}", reminders);
+            clock.set_reminders(reminders).await;
+        }
+        // After a soft reboot, come back in the same mode and on time (after the saved UTC
+        // offset is set, so the time is shown in the right zone)
+        let mut watchdog = hardware.watchdog;
+        let snapshot = SoftReboot::take_snapshot(&mut watchdog);
+        if let Some(snapshot) = snapshot {
+            info!("Resuming after a soft reboot: {:?}", snapshot);
+            if let Some(unix_seconds) = snapshot.unix_seconds {
+                clock
+                    .set_time_from_unix(unix_seconds, LeapIndicator::NoWarning)
+                    .await;
+            }
+        }
+        static SOFT_REBOOT_NOTIFIER: SoftRebootNotifier = SoftReboot::notifier();
+        let soft_reboot = SoftReboot::new(watchdog, &SOFT_REBOOT_NOTIFIER, spawner)?;
+        info!("Clock and button created");
+
+        // Create TimeSync virtual device (creates WiFi internally). Its WiFi task waits for the
+        // display's first frame before uploading the CYW43 firmware.
+        static TIME_SYNC: TimeSyncNotifier = TimeSync::notifier();
+        let time_sync = TimeSync::new(
+            &TIME_SYNC,
+            hardware.wifi.pin_23,
+            hardware.wifi.pin_25,
+            hardware.wifi.pio0,
+            hardware.wifi.pin_24,
+            hardware.wifi.pin_29,
+            hardware.wifi.dma_ch0,
+            spawner,
+        );
+
+        static OFF_REQUESTS: OffRequests = Signal::new();
+        static SWITCH_NOTIFIER: SwitchNotifier = Switch::notifier();
+        let switch = Switch::new(hardware.switch, &SWITCH_NOTIFIER, spawner)?;
+        if self.starts(Subsystem::Http) {
+            let _http_server = HttpServer::new(
+                time_sync.wifi(),
+                clock,
+                storage,
+                &OFF_REQUESTS,
+                switch,
+                soft_reboot,
+                spawner,
+            )?;
+        }
+        if self.starts(Subsystem::Weather) {
+            let _weather = Weather::new(time_sync.wifi(), clock, spawner)?;
+        }
+        if self.starts(Subsystem::Mqtt) {
+            let _mqtt = Mqtt::new(time_sync.wifi(), clock, switch, spawner)?;
+        }
+        let _scheduler = Scheduler::new(clock, time_sync, switch, spawner)?;
+        if self.starts(Subsystem::Sensors) {
+            let _climate_log = ClimateLog::new(storage, spawner)?;
+            let _supply_monitor =
+                SupplyMonitor::new(hardware.adc, hardware.supply_pin, clock, spawner)?;
+            let _gps = Gps::new(
+                hardware.gps_uart,
+                hardware.gps_rx,
+                hardware.gps_dma,
+                time_sync,
+                spawner,
+            )?;
+            let _radio_time = RadioTime::new(hardware.radio_receiver, time_sync, spawner)?;
+        }
+        if self.starts(Subsystem::Console) {
+            let _usb_console = UsbConsole::new(hardware.usb, clock, time_sync, storage, spawner)?;
+        }
+        if self.starts(Subsystem::Alarm) {
+            #[cfg(not(feature = "fourteen-segment"))]
+            let _buzzer = Buzzer::new(hardware.buzzer_pwm, hardware.buzzer_pin, spawner)?;
+            #[cfg(feature = "talking-clock")]
+            let _talker = Talker::new(
+                hardware.talker_pio,
+                hardware.talker_dma,
+                hardware.talker_data,
+                hardware.talker_bit_clock,
+                hardware.talker_word_clock,
+                spawner,
+            )?;
+        }
+        #[cfg(feature = "oled")]
+        let _status_display = StatusDisplay::new(
+            hardware.status_i2c,
+            hardware.status_sda,
+            hardware.status_scl,
+            time_sync.wifi(),
+            spawner,
+        )?;
+
+        log_memory_report();
+
+        // Run the state machine
+        let mut state = snapshot.map_or_else(ClockState::default, |snapshot| snapshot.clock_state);
+        loop {
+            info!("State: {:?}", state);
+            state = state
+                .execute(&mut clock, &mut button_events, time_sync, storage, &OFF_REQUESTS)
+                .await;
+        }
+    }
+}
@@ -1,197 +1,537 @@
+#[cfg(feature = "talking-clock")]
+use crate::shared_constants::ANNOUNCE_EDIT_HOLD;
 use crate::{
-    button::{Button, PressDuration},
-    clock::Clock,
-    time_sync::{TimeSync, TimeSyncEvent},
-    BlinkState, ClockTime, ONE_MINUTE, ONE_SECOND,
+    button::{
+        gesture_timings, request_dormant_sleep, wait_for_very_long_press, ButtonEvent,
+        ButtonEvents,
+    },
+    clock::{is_held, Clock},
+    info,
+    network_policy::utc_offset_minutes,
+    settings::{auto_return, deep_sleep, live_settings, mode_conditions},
+    shared_constants::{
+        BUTTON_REPEAT_INTERVAL, DEEP_SLEEP_SETTLE, EDIT_INACTIVITY_TIMEOUT,
+        FACTORY_RESET_DISPLAY_DURATION, HOUR_EDIT_SPEED, ONE_HOUR, ONE_MINUTE, SET_TIME_DONE_DELAY,
+        SET_TIME_RAMP, SURVEY_INTERVAL, SYNC_REQUEST_TIMEOUT, SYNC_RESULT_DISPLAY_DURATION,
+    },
+    storage::SharedStorage,
+    time_sync::{arbitrate, TimeSyncEvent},
+    warn, Display2Format,
 };
-use defmt::info;
-use embassy_futures::select::{select, Either};
-use embassy_time::Duration;
+use clock_core::{
+    Arbitration, ClockState, EditStep, ModeDescriptor, PendingSettings, Press, SyncError,
+    TimeSyncPort, WifiPort,
+};
+use embassy_futures::select::{select, select3, Either, Either3};
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, signal::Signal};
+use embassy_time::{with_timeout, Duration, Instant, Timer};
+
+/// Asks the state machine to go to `ClockState::Off` from whatever state it is in.
+pub type OffRequests = Signal<CriticalSectionRawMutex, ()>;
 
-/// Represents the different states the clock can operate in.
+/// Runs a `ClockState` against the clock's devices.
 ///
-/// The clock has two display modes: `HoursMinutes` (HH:MM) and `MinutesSeconds` (MM:SS).
-/// Short press toggles between them. Long press enters UTC offset edit mode.
-#[expect(missing_docs, reason = "The variants are self-explanatory.")]
-#[derive(Debug, defmt::Format, Clone, Copy, Default)]
-pub enum ClockState {
-    #[default]
-    HoursMinutes,
-    MinutesSeconds,
-    EditUtcOffset,
-}
-
-impl ClockState {
+/// `ClockState` and its rendering live in `clock-core`; this trait adds the part that
+/// waits on the button's events and time sync.
+#[expect(
+    async_fn_in_trait,
+    reason = "Single-threaded executor, so the futures need not be Send."
+)]
+pub trait ClockStateExt {
     /// Run the clock in the current state and return the next state.
     ///
     /// # Returns
     ///
     /// The next state of the clock.
-    pub async fn execute(
+    async fn execute(
+        self,
+        clock: &mut Clock<'_>,
+        events: &mut ButtonEvents,
+        time_sync: &impl TimeSyncPort,
+        storage: &SharedStorage,
+        off_requests: &OffRequests,
+    ) -> ClockState;
+}
+
+impl ClockStateExt for ClockState {
+    async fn execute(
         self,
         clock: &mut Clock<'_>,
-        button: &mut Button<'_>,
-        time_sync: &TimeSync,
-    ) -> Self {
-        match self {
-            Self::HoursMinutes => self.execute_hours_minutes(clock, button, time_sync).await,
-            Self::MinutesSeconds => self.execute_minutes_seconds(clock, button, time_sync).await,
-            Self::EditUtcOffset => self.execute_edit_utc_offset(clock, button).await,
+        events: &mut ButtonEvents,
+        time_sync: &impl TimeSyncPort,
+        storage: &SharedStorage,
+        off_requests: &OffRequests,
+    ) -> ClockState {
+        // Saved settings must not be left half-erased, so this runs to the end
+        if self == Self::FactoryReset {
+            return execute_factory_reset(self, clock, storage).await;
         }
+        let run = async {
+            if let Some(mode) = self.mode() {
+                let run_mode = execute_mode(mode, clock, events, time_sync);
+                if self == Self::Survey {
+                    return or_off(while_surveying(run_mode, clock, time_sync.wifi()), off_requests)
+                        .await;
+                }
+                return or_off(run_mode, off_requests).await;
+            }
+            match self {
+                Self::EditUtcOffset => {
+                    or_off(execute_edit_utc_offset(self, clock, events, storage), off_requests)
+                        .await
+                }
+                Self::Syncing => {
+                    or_off(execute_syncing(self, clock, events, time_sync), off_requests).await
+                }
+                Self::SyncSucceeded | Self::SyncFailed => {
+                    or_off(execute_sync_result(self, clock, events), off_requests).await
+                }
+                Self::SetTime => {
+                    or_off(execute_set_time(self, clock, events), off_requests).await
+                }
+                Self::Off => execute_off(self, clock, events, time_sync, off_requests).await,
+                // Every other state is a display mode, handled above
+                _ => Self::HoursMinutes,
+            }
+        };
+        or_factory_reset(self, run).await
     }
+}
 
-    /// Given the current `ClockMode` and `ClockTime`, generates the information the `Clock` abstraction should display.
-    ///
-    /// # Example
-    ///
-    /// If the `ClockState` is `HoursMinutes` and the `ClockTime` is 1:23:45, the function will return:
-    /// - Characters: `[' ', '1', '2', '3']`
-    /// - Blink Mode: `BlinkState::Solid`
-    /// - Sleep Duration: `Duration::from_secs(15)`
-    pub(crate) fn render(self, clock_time: &ClockTime) -> (BlinkState, [char; 4], Duration) {
-        match self {
-            Self::HoursMinutes => Self::render_hours_minutes(clock_time),
-            Self::MinutesSeconds => Self::render_minutes_seconds(clock_time),
-            Self::EditUtcOffset => Self::render_edit_utc_offset(clock_time),
+/// Runs `state` until it picks the next one, or until a very long press asks for a factory
+/// reset. The editors keep a very long press that starts in them for themselves (the UTC
+/// offset editor cancels on one), so there only a press held on from before resets the clock.
+async fn or_factory_reset(
+    state: ClockState,
+    run: impl Future<Output = ClockState>,
+) -> ClockState {
+    let entered_at = Instant::now();
+    let keeps_own = matches!(state, ClockState::EditUtcOffset | ClockState::SetTime);
+    let reset = async {
+        loop {
+            let pressed_at = wait_for_very_long_press().await;
+            if !keeps_own || pressed_at < entered_at {
+                break;
+            }
+        }
+    };
+    match select(run, reset).await {
+        Either::First(next_state) => next_state,
+        Either::Second(()) => {
+            info!("Very long press: factory reset");
+            ClockState::FactoryReset
         }
     }
+}
 
-    async fn execute_hours_minutes(
-        self,
-        clock: &Clock<'_>,
-        button: &mut Button<'_>,
-        time_sync: &TimeSync,
-    ) -> Self {
-        clock.set_state(self).await;
-        match select(button.press_duration(), time_sync.wait()).await {
-            Either::First(PressDuration::Short) => Self::MinutesSeconds,
-            Either::First(PressDuration::Long) => Self::EditUtcOffset,
-            Either::Second(event) => {
-                Self::handle_time_sync_event(clock, event).await;
-                self
-            }
+/// Runs a state until it picks the next one, or until something asks the clock to turn off.
+async fn or_off(
+    run: impl Future<Output = ClockState>,
+    off_requests: &OffRequests,
+) -> ClockState {
+    match select(run, off_requests.wait()).await {
+        Either::First(next_state) => next_state,
+        Either::Second(()) => {
+            info!("Turning off");
+            ClockState::Off
         }
     }
+}
 
-    async fn execute_minutes_seconds(
-        self,
-        clock: &Clock<'_>,
-        button: &mut Button<'_>,
-        time_sync: &TimeSync,
-    ) -> Self {
-        clock.set_state(self).await;
-        match select(button.press_duration(), time_sync.wait()).await {
-            Either::First(PressDuration::Short) => Self::HoursMinutes,
-            Either::First(PressDuration::Long) => Self::EditUtcOffset,
-            Either::Second(event) => {
-                Self::handle_time_sync_event(clock, event).await;
-                self
+/// Blanks the display and turns the WiFi radio off until the button is pressed or the touch
+/// pad tapped.
+async fn execute_off(
+    state: ClockState,
+    clock: &Clock<'_>,
+    events: &mut ButtonEvents,
+    time_sync: &impl TimeSyncPort,
+    off_requests: &OffRequests,
+) -> ClockState {
+    clock.set_state(state).await;
+    time_sync.wifi().set_radio(false);
+    // A press or tap from before the clock went off does not wake it
+    clear(events);
+    if deep_sleep() {
+        Timer::after(DEEP_SLEEP_SETTLE).await;
+        request_dormant_sleep();
+    }
+    loop {
+        match events.next_message_pure().await {
+            ButtonEvent::Short
+            | ButtonEvent::Double
+            | ButtonEvent::Long
+            | ButtonEvent::VeryLong
+            | ButtonEvent::Tap => break,
+            ButtonEvent::Pressed | ButtonEvent::Released | ButtonEvent::Repeat => {}
+        }
+    }
+    info!("Turning on");
+    time_sync.wifi().set_radio(true);
+    // Dormant sleep stopped the clock, so it needs the time again
+    if deep_sleep() {
+        time_sync.request_sync();
+    }
+    // A request made while already off has been honored
+    off_requests.reset();
+    ClockState::HoursMinutes
+}
+
+/// Drops the events waiting for `events`, for a state that ignored the button meanwhile.
+fn clear(events: &mut ButtonEvents) {
+    while events.try_next_message_pure().is_some() {}
+}
+
+/// Runs a display mode as its `MODES` entry describes (see `ModeDescriptor::after_press`): a
+/// short press moves on through the cycle (or, quickly followed by another, makes a double
+/// press), a long press goes where the entry says, and time syncs are applied while it waits.
+async fn execute_mode(
+    mode: &ModeDescriptor,
+    clock: &Clock<'_>,
+    events: &mut ButtonEvents,
+    time_sync: &impl TimeSyncPort,
+) -> ClockState {
+    clock.set_state(mode.state).await;
+    let return_at = if mode.auto_return {
+        auto_return_at()
+    } else {
+        Instant::MAX
+    };
+    loop {
+        match select3(events.next_message_pure(), time_sync.wait(), Timer::at(return_at)).await {
+            // A press while the display is held only ends the hold
+            Either3::First(
+                ButtonEvent::Short
+                | ButtonEvent::Double
+                | ButtonEvent::Long
+                | ButtonEvent::VeryLong
+                | ButtonEvent::Tap,
+            ) if is_held() => clock.resume().await,
+            // A double press anywhere else counts as another short press
+            Either3::First(ButtonEvent::Short | ButtonEvent::Double) => {
+                let press = if mode.double_press.is_none() {
+                    Press::Short
+                } else {
+                    match second_press(events).await {
+                        Some(ButtonEvent::Double | ButtonEvent::Short) => Press::Double,
+                        Some(ButtonEvent::Long) => Press::Long,
+                        Some(ButtonEvent::VeryLong) => Press::VeryLong,
+                        _ => Press::Short,
+                    }
+                };
+                return mode.after_press(press, &mode_conditions());
+            }
+            // The talking clock says the time on a long press in `HoursMinutes`; holding on
+            // goes where the long press would have
+            #[cfg(feature = "talking-clock")]
+            Either3::First(ButtonEvent::Long) if mode.state == ClockState::HoursMinutes => {
+                clock.announce_time().await;
+                match with_timeout(ANNOUNCE_EDIT_HOLD, finish_long_press(events)).await {
+                    Ok(ButtonEvent::VeryLong) => {
+                        return mode.after_press(Press::VeryLong, &mode_conditions());
+                    }
+                    Ok(_) => {}
+                    Err(_) => return mode.after_press(Press::Long, &mode_conditions()),
+                }
             }
+            Either3::First(ButtonEvent::Long) => {
+                return mode.after_press(Press::Long, &mode_conditions());
+            }
+            Either3::First(ButtonEvent::VeryLong) => {
+                return mode.after_press(Press::VeryLong, &mode_conditions());
+            }
+            Either3::First(
+                ButtonEvent::Pressed
+                | ButtonEvent::Released
+                | ButtonEvent::Repeat
+                | ButtonEvent::Tap,
+            ) => {}
+            Either3::Second(event) => handle_time_sync_event(clock, event).await,
+            Either3::Third(()) => return mode.state.after_timeout(),
+        }
+    }
+}
+
+/// Runs `ClockState::Survey`'s display mode while measuring the configured network's signal
+/// over and over, so the reading follows the clock as it is carried around.
+async fn while_surveying(
+    run_mode: impl Future<Output = ClockState>,
+    clock: &Clock<'_>,
+    wifi: &impl WifiPort,
+) -> ClockState {
+    match select(run_mode, survey(clock, wifi)).await {
+        Either::First(next_state) => next_state,
+        Either::Second(never) => never,
+    }
+}
+
+/// Measures the configured network's signal for `ClockState::Survey`, pausing between scans.
+async fn survey(clock: &Clock<'_>, wifi: &impl WifiPort) -> ! {
+    loop {
+        clock.set_signal_dbm(wifi.signal_dbm().await).await;
+        Timer::after(SURVEY_INTERVAL).await;
+    }
+}
+
+/// After a short press, waits for another to start within the double-press window and
+/// returns what it turns out to be, or `None` if none starts in time.
+async fn second_press(events: &mut ButtonEvents) -> Option<ButtonEvent> {
+    let pressed = async {
+        while events.next_message_pure().await != ButtonEvent::Pressed {}
+    };
+    with_timeout(gesture_timings().double_press(), pressed).await.ok()?;
+    loop {
+        match events.next_message_pure().await {
+            event @ (ButtonEvent::Short
+            | ButtonEvent::Double
+            | ButtonEvent::Long
+            | ButtonEvent::VeryLong) => return Some(event),
+            ButtonEvent::Pressed
+            | ButtonEvent::Released
+            | ButtonEvent::Repeat
+            | ButtonEvent::Tap => {}
+        }
+    }
+}
+
+/// When a mode that returns on its own should go back to `HoursMinutes`: `auto_return` from
+/// now, or never if it is unset. Time syncs along the way do not restart the count.
+fn auto_return_at() -> Instant {
+    auto_return()
+        .and_then(|timeout| Instant::now().checked_add(timeout))
+        .unwrap_or(Instant::MAX)
+}
+
+/// Requests a sync and waits for its outcome, ignoring the button meanwhile.
+async fn execute_syncing(
+    state: ClockState,
+    clock: &Clock<'_>,
+    events: &mut ButtonEvents,
+    time_sync: &impl TimeSyncPort,
+) -> ClockState {
+    clock.set_state(state).await;
+    // An outcome left unread is from an earlier sync: apply it now, so the one shown is this
+    // request's
+    if let Some(earlier) = time_sync.take_unread() {
+        handle_time_sync_event(clock, earlier).await;
+    }
+    time_sync.request_sync();
+    let outcome = with_timeout(SYNC_REQUEST_TIMEOUT, time_sync.wait()).await.ok();
+    clear(events);
+    let next_state = ClockState::after_sync(outcome.as_ref());
+    match outcome {
+        Some(event) => handle_time_sync_event(clock, event).await,
+        None => {
+            warn!("Requested time sync timed out");
+            clock.set_sync_error(SyncError::Timeout).await;
         }
     }
+    next_state
+}
 
-    async fn execute_edit_utc_offset(self, clock: &Clock<'_>, button: &mut Button<'_>) -> Self {
-        clock.set_state(self).await;
-        match button.press_duration().await {
-            PressDuration::Short => {
-                // Advance UTC offset by 1 hour
-                clock.adjust_utc_offset_hours(1).await;
-                clock.set_state(self).await;
-                self
+/// Shows the outcome of a requested sync for a moment, then goes back to the time.
+async fn execute_sync_result(
+    state: ClockState,
+    clock: &Clock<'_>,
+    events: &mut ButtonEvents,
+) -> ClockState {
+    clock.set_state(state).await;
+    Timer::after(SYNC_RESULT_DISPLAY_DURATION).await;
+    clear(events);
+    state.after_timeout()
+}
+
+async fn execute_edit_utc_offset(
+    state: ClockState,
+    clock: &Clock<'_>,
+    events: &mut ButtonEvents,
+    storage: &SharedStorage,
+) -> ClockState {
+    clock.set_state(state).await;
+    // Edits go to a staged copy, which only a long press commits
+    let mut pending = live_pending_settings();
+    loop {
+        let Ok(event) = with_timeout(EDIT_INACTIVITY_TIMEOUT, events.next_message_pure()).await
+        else {
+            info!("UTC offset edit timed out");
+            clock.preview_settings(None).await;
+            return state.after_timeout();
+        };
+        let press = match event {
+            ButtonEvent::Short => Press::Short,
+            ButtonEvent::Double => Press::Double,
+            ButtonEvent::Long => match finish_long_press(events).await {
+                ButtonEvent::VeryLong => Press::VeryLong,
+                _ => Press::Long,
+            },
+            // Held on from the press that started the edit, which resets the clock (see
+            // `or_factory_reset`)
+            ButtonEvent::VeryLong
+            | ButtonEvent::Pressed
+            | ButtonEvent::Released
+            | ButtonEvent::Repeat
+            | ButtonEvent::Tap => continue,
+        };
+        match pending.press_utc_offset(press) {
+            EditStep::Continue => clock.preview_settings(Some(pending)).await,
+            EditStep::Commit => {
+                commit_settings(clock, storage, pending).await;
+                return ClockState::HoursMinutes;
+            }
+            EditStep::Cancel => {
+                info!("UTC offset edit cancelled");
+                clock.preview_settings(None).await;
+                return ClockState::HoursMinutes;
             }
-            PressDuration::Long => Self::HoursMinutes,
         }
     }
+}
+
+/// After a `Long`, waits until the button is released, or until the press becomes very long,
+/// and returns `Released` or `VeryLong` to say which happened.
+async fn finish_long_press(events: &mut ButtonEvents) -> ButtonEvent {
+    loop {
+        match events.next_message_pure().await {
+            event @ (ButtonEvent::Released | ButtonEvent::VeryLong) => return event,
+            ButtonEvent::Pressed
+            | ButtonEvent::Short
+            | ButtonEvent::Double
+            | ButtonEvent::Long
+            | ButtonEvent::Repeat
+            | ButtonEvent::Tap => {}
+        }
+    }
+}
+
+/// The clock's live UTC offset and settings, staged for an edit. These are what the clock
+/// shows, even when saving an earlier change to flash failed.
+pub(crate) fn live_pending_settings() -> PendingSettings {
+    PendingSettings::new(utc_offset_minutes(), live_settings())
+}
+
+/// Makes an edit live and saves it. The clock keeps the edit even if saving fails.
+pub(crate) async fn commit_settings(
+    clock: &Clock<'_>,
+    storage: &SharedStorage,
+    pending: PendingSettings,
+) {
+    info!("Committing {:?}", pending);
+    clock.commit_settings(pending).await;
+    if let Err(err) = storage.lock().await.save_pending_settings(&pending) {
+        warn!("Could not save the settings: {}", Display2Format(&err));
+    }
+}
 
-    async fn handle_time_sync_event(clock: &Clock<'_>, event: TimeSyncEvent) {
+/// Moves the time forward a minute per short press, or faster and faster while the button
+/// is held, until the button has been left alone for `SET_TIME_DONE_DELAY`.
+async fn execute_set_time(
+    state: ClockState,
+    clock: &Clock<'_>,
+    events: &mut ButtonEvents,
+) -> ClockState {
+    clock.set_state(state).await;
+    // Whether a press has started here, as opposed to the long press that started set time
+    // still being held
+    let mut pressed = false;
+    loop {
+        let Ok(event) = with_timeout(SET_TIME_DONE_DELAY, events.next_message_pure()).await
+        else {
+            return state.after_timeout();
+        };
         match event {
-            TimeSyncEvent::Success { unix_seconds } => {
-                info!("Time sync success: setting clock to {}", unix_seconds.as_i64());
-                clock.set_time_from_unix(unix_seconds).await;
-            }
-            TimeSyncEvent::Failed(msg) => {
-                info!("Time sync failed: {}", msg);
+            ButtonEvent::Pressed => pressed = true,
+            ButtonEvent::Short | ButtonEvent::Double => clock.adjust_time(ONE_MINUTE).await,
+            ButtonEvent::Long if pressed => fast_forward(clock, events).await,
+            ButtonEvent::Long
+            | ButtonEvent::VeryLong
+            | ButtonEvent::Released
+            | ButtonEvent::Repeat
+            | ButtonEvent::Tap => {}
+        }
+    }
+}
+
+/// Advances the time for as long as the button stays down, following `SET_TIME_RAMP`, timed
+/// by the `Repeat` events of the long press.
+async fn fast_forward(clock: &Clock<'_>, events: &mut ButtonEvents) {
+    let mut steps = SET_TIME_RAMP
+        .into_iter()
+        .flat_map(|(interval, count)| {
+            core::iter::repeat_n((interval, ONE_MINUTE), usize::from(count))
+        })
+        .chain(core::iter::repeat((HOUR_EDIT_SPEED, ONE_HOUR)));
+    let mut held = Duration::from_ticks(0);
+    let mut next_step_at = Duration::from_ticks(0);
+    loop {
+        while held >= next_step_at {
+            let Some((interval, amount)) = steps.next() else {
+                return;
+            };
+            clock.adjust_time(amount).await;
+            next_step_at = next_step_at.checked_add(interval).unwrap_or(Duration::MAX);
+        }
+        match events.next_message_pure().await {
+            ButtonEvent::Released => return,
+            ButtonEvent::Repeat => {
+                held = held.checked_add(BUTTON_REPEAT_INTERVAL).unwrap_or(Duration::MAX);
             }
+            ButtonEvent::Pressed
+            | ButtonEvent::Short
+            | ButtonEvent::Double
+            | ButtonEvent::Long
+            | ButtonEvent::VeryLong
+            | ButtonEvent::Tap => {}
         }
     }
+}
 
-    fn render_hours_minutes(clock_time: &ClockTime) -> (BlinkState, [char; 4], Duration) {
-        let (hours, minutes, _, sleep_duration) = clock_time.h_m_s_sleep_duration(ONE_MINUTE);
-        (
-            BlinkState::Solid,
-            [
-                tens_hours(hours),
-                ones_digit(hours),
-                tens_digit(minutes),
-                ones_digit(minutes),
-            ],
-            sleep_duration,
-        )
-    }
-
-    fn render_minutes_seconds(clock_time: &ClockTime) -> (BlinkState, [char; 4], Duration) {
-        let (_, minutes, seconds, sleep_duration) = clock_time.h_m_s_sleep_duration(ONE_SECOND);
-        (
-            BlinkState::Solid,
-            [
-                tens_digit(minutes),
-                ones_digit(minutes),
-                tens_digit(seconds),
-                ones_digit(seconds),
-            ],
-            sleep_duration,
-        )
-    }
-
-    fn render_edit_utc_offset(clock_time: &ClockTime) -> (BlinkState, [char; 4], Duration) {
-        // Display the current time in HH:MM format while blinking
-        // This shows what the time looks like with the current UTC offset
-        let (hours, minutes, _, _) = clock_time.h_m_s_sleep_duration(ONE_MINUTE);
-        
-        (
-            BlinkState::BlinkingAndOn,
-            [
-                tens_hours(hours),
-                ones_digit(hours),
-                tens_digit(minutes),
-                ones_digit(minutes),
-            ],
-            Duration::from_millis(500), // Blink at 1Hz
-        )
-    }
-}
-
-#[inline]
-#[expect(
-    clippy::arithmetic_side_effects,
-    clippy::integer_division_remainder_used,
-    reason = "Because value < 60, the division is safe."
-)]
-const fn tens_digit(value: u8) -> char {
-    debug_assert!(value < 60, "Value is between 0 and 59 (inclusive)");
-    ((value / 10) + b'0') as char
-}
-
-#[inline]
-const fn tens_hours(value: u8) -> char {
-    debug_assert!(
-        1 <= value && value <= 12,
-        "Value is between 1 and 12 (inclusive)"
-    );
-    if value >= 10 {
-        '1'
-    } else {
-        ' '
+async fn execute_factory_reset(
+    state: ClockState,
+    clock: &Clock<'_>,
+    storage: &SharedStorage,
+) -> ClockState {
+    // An edit the reset cut short is dropped
+    clock.preview_settings(None).await;
+    clock.set_state(state).await;
+    // Leave "rSt" up long enough to read
+    Timer::after(FACTORY_RESET_DISPLAY_DURATION).await;
+    info!("Factory reset: erasing saved settings and the config page, then rebooting");
+    let mut storage = storage.lock().await;
+    if let Err(err) = storage.erase() {
+        warn!("Factory reset could not erase flash: {}", Display2Format(&err));
     }
+    // The config page holds the WiFi network and its password
+    if let Err(err) = storage.erase_provisioning() {
+        warn!("Factory reset could not erase the config page: {}", Display2Format(&err));
+    }
+    cortex_m::peripheral::SCB::sys_reset()
 }
 
-#[expect(
-    clippy::arithmetic_side_effects,
-    clippy::integer_division_remainder_used,
-    reason = "Because value < 60, the division is safe."
-)]
-#[inline]
-const fn ones_digit(value: u8) -> char {
-    debug_assert!(value < 60, "Value is be between 0 and 59 (inclusive)");
-    ((value % 10) + b'0') as char
+async fn handle_time_sync_event(clock: &Clock<'_>, event: TimeSyncEvent) {
+    if let Some(error) = event.sync_error() {
+        clock.set_sync_error(error).await;
+    }
+    match event {
+        TimeSyncEvent::Success {
+            unix_seconds,
+            leap,
+            accuracy,
+            source,
+        } => match arbitrate(source) {
+            Arbitration::Accept => {
+                info!(
+                    "Time sync success: setting clock to {} ({:?} from {:?})",
+                    unix_seconds.as_i64(),
+                    accuracy,
+                    source
+                );
+                clock.set_time_from_unix(unix_seconds, leap).await;
+                clock.set_time_source(source).await;
+            }
+            Arbitration::Outranked(current) => {
+                info!("Ignoring {:?} time while {:?} sets the clock", source, current);
+            }
+        },
+        TimeSyncEvent::Failed(error) => info!("Time sync failed: {=str}", error.name()),
+        TimeSyncEvent::Rejected(reason) => info!("Time sync rejected: {:?}", reason),
+    }
 }
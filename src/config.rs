@@ -0,0 +1,24 @@
+//! Runtime config - the `Config` the clock runs with, shared so HTTP and the USB console can
+//! retune it without a rebuild
+
+use core::cell::Cell;
+
+use clock_core::Config;
+use embassy_sync::blocking_mutex::{raw::CriticalSectionRawMutex, Mutex};
+
+/// The config in use. `main` replaces the built-in defaults with the saved config at boot.
+static CONFIG: Mutex<CriticalSectionRawMutex, Cell<Config>> =
+    Mutex::new(Cell::new(Config::DEFAULT));
+
+/// The config the clock is running with. Each user reads it as it needs a value, so a change
+/// takes effect by the next blink, multiplex slot, or time sync.
+#[must_use]
+pub fn config() -> Config {
+    CONFIG.lock(Cell::get)
+}
+
+/// Runs the clock with `config` from now on. Blink timing is applied through
+/// `Clock::set_blink_timing`, which the blinker also picks up at once.
+pub fn set_config(config: Config) {
+    CONFIG.lock(|cell| cell.set(config));
+}
@@ -0,0 +1,160 @@
+//! Crash dumps - the panic handler saves what went wrong to flash for the next boot
+//!
+//! Only builds without `logging` have this panic handler. With `logging`, `panic-probe` takes
+//! panics instead, so a debug probe stops at them and prints the backtrace.
+
+use core::fmt::{self, Write};
+#[cfg(not(feature = "logging"))]
+use core::panic::PanicInfo;
+
+#[cfg(not(feature = "logging"))]
+use embassy_rp::peripherals::FLASH;
+#[cfg(not(feature = "logging"))]
+use embassy_time::Instant;
+use heapless::String;
+
+#[cfg(not(feature = "logging"))]
+use crate::storage::Storage;
+
+/// Bytes in a saved crash record: one flash page.
+pub const CRASH_RECORD_LEN: usize = 256;
+
+/// The most bytes of source file path a crash record keeps, from the end of the path.
+pub const MAX_CRASH_FILE_LEN: usize = 64;
+
+/// The most bytes of panic message a crash record keeps.
+pub const MAX_CRASH_TEXT_LEN: usize = CRASH_RECORD_LEN - CRASH_HEADER_LEN - MAX_CRASH_FILE_LEN;
+
+/// Bytes before the file path: the magic, the line and uptime, and the two lengths.
+const CRASH_HEADER_LEN: usize = 16;
+
+/// Marks a saved crash record. An erased page reads as all `0xFF`. `CRS1` records, which held
+/// the panic handler's own registers, are no longer read.
+const CRASH_MAGIC: [u8; 4] = *b"CRS2";
+
+/// What panicked: the source location, the panic message, and how long the clock had run.
+pub struct CrashReport {
+    /// The source file that panicked, keeping the end of a path too long for the record.
+    pub file: String<MAX_CRASH_FILE_LEN>,
+    /// The line in `file`.
+    pub line: u32,
+    /// How long the clock had been running, in milliseconds.
+    pub uptime_ms: u32,
+    /// The panic message, cut short if too long.
+    pub text: String<MAX_CRASH_TEXT_LEN>,
+}
+
+impl CrashReport {
+    /// Packs the report as `CRASH_MAGIC`, the little-endian `line` and `uptime_ms`, the
+    /// lengths of the file and text as `u16`s, and then the file and the text.
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; CRASH_RECORD_LEN] {
+        let mut record = [0xFF; CRASH_RECORD_LEN];
+        let (magic, rest) = record.split_at_mut(CRASH_MAGIC.len());
+        let (line, rest) = rest.split_at_mut(4);
+        let (uptime_ms, rest) = rest.split_at_mut(4);
+        let (file_len, rest) = rest.split_at_mut(2);
+        let (text_len, rest) = rest.split_at_mut(2);
+        magic.copy_from_slice(&CRASH_MAGIC);
+        line.copy_from_slice(&self.line.to_le_bytes());
+        uptime_ms.copy_from_slice(&self.uptime_ms.to_le_bytes());
+        // The file and text are at most `MAX_CRASH_FILE_LEN` and `MAX_CRASH_TEXT_LEN` bytes, so
+        // the lengths and the bytes all fit
+        file_len.copy_from_slice(&u16::try_from(self.file.len()).unwrap_or_default().to_le_bytes());
+        text_len.copy_from_slice(&u16::try_from(self.text.len()).unwrap_or_default().to_le_bytes());
+        if let Some((file, text)) = rest.split_at_mut_checked(self.file.len()) {
+            file.copy_from_slice(self.file.as_bytes());
+            if let Some(text_bytes) = text.get_mut(..self.text.len()) {
+                text_bytes.copy_from_slice(self.text.as_bytes());
+            }
+        }
+        record
+    }
+
+    /// Unpacks a record written by `to_bytes`, or `None` if there is none.
+    #[must_use]
+    pub fn from_bytes(record: &[u8; CRASH_RECORD_LEN]) -> Option<Self> {
+        let (magic, rest) = record.split_first_chunk::<4>()?;
+        let (line, rest) = rest.split_first_chunk::<4>()?;
+        let (uptime_ms, rest) = rest.split_first_chunk::<4>()?;
+        let (file_len, rest) = rest.split_first_chunk::<2>()?;
+        let (text_len, rest) = rest.split_first_chunk::<2>()?;
+        if *magic != CRASH_MAGIC {
+            return None;
+        }
+        let (file, rest) = rest.split_at_checked(usize::from(u16::from_le_bytes(*file_len)))?;
+        let text = rest.get(..usize::from(u16::from_le_bytes(*text_len)))?;
+        let mut report = Self {
+            file: String::new(),
+            line: u32::from_le_bytes(*line),
+            uptime_ms: u32::from_le_bytes(*uptime_ms),
+            text: String::new(),
+        };
+        report.file.push_str(valid_utf8(file)).ok()?;
+        report.text.push_str(valid_utf8(text)).ok()?;
+        Some(report)
+    }
+}
+
+impl fmt::Display for CrashReport {
+    fn fmt(&self, out: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(out, "{}:{} uptime_ms={}", self.file, self.line, self.uptime_ms)?;
+        writeln!(out, "{}", self.text)
+    }
+}
+
+/// The valid UTF-8 at the start of `bytes`. Text cut short may end partway through a character.
+fn valid_utf8(bytes: &[u8]) -> &str {
+    core::str::from_utf8(bytes).unwrap_or_else(|err| {
+        core::str::from_utf8(bytes.get(..err.valid_up_to()).unwrap_or_default()).unwrap_or_default()
+    })
+}
+
+/// Writes into a `String`, keeping every whole character that fits and dropping the rest, so
+/// a message too long for the record still keeps its start.
+struct Truncating<'a, const N: usize>(&'a mut String<N>);
+
+impl<const N: usize> Write for Truncating<'_, N> {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        for character in text.chars() {
+            if self.0.push(character).is_err() {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Saves a `CrashReport` of the panic's location and message to flash, and reboots.
+///
+/// Only the first crash is saved until it is cleared (with `DELETE /last-crash`), so a crash
+/// that repeats at every boot neither wears the flash nor hides the first report.
+#[cfg(not(feature = "logging"))]
+#[panic_handler]
+#[expect(unsafe_code, reason = "The panic handler takes the flash from its owner to save.")]
+fn panic(info: &PanicInfo<'_>) -> ! {
+    cortex_m::interrupt::disable();
+    let mut report = CrashReport {
+        file: String::new(),
+        line: 0,
+        uptime_ms: u32::try_from(Instant::now().as_millis()).unwrap_or(u32::MAX),
+        text: String::new(),
+    };
+    if let Some(location) = info.location() {
+        // Keep the end of the path, which names the crate and file
+        let path = location.file();
+        let start = path
+            .char_indices()
+            .map(|(index, _)| index)
+            .find(|&index| path.len().saturating_sub(index) <= MAX_CRASH_FILE_LEN)
+            .unwrap_or(path.len());
+        let _ = report.file.push_str(path.get(start..).unwrap_or_default());
+        report.line = location.line();
+    }
+    let _ = write!(Truncating(&mut report.text), "{}", info.message());
+    // SAFETY: Nothing else runs once interrupts are off, so the flash is not in use elsewhere.
+    let mut storage = Storage::new(unsafe { FLASH::steal() });
+    // There is nowhere to report a failure to save
+    let _ = storage.save_crash_report(&report);
+    cortex_m::peripheral::SCB::sys_reset()
+}
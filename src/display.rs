@@ -1,24 +1,89 @@
-use defmt::info;
+use core::{cell::Cell, convert::Infallible};
+
 use embassy_executor::{SpawnError, Spawner};
 use embassy_futures::select::{select, Either};
-use embassy_rp::gpio::Level;
-use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, signal::Signal};
-use embassy_time::Timer;
+use embassy_sync::{
+    blocking_mutex::{raw::CriticalSectionRawMutex, Mutex},
+    signal::Signal,
+};
+use embassy_time::{Duration, Timer};
+use embedded_hal::digital::PinState;
+use heapless::Vec;
+
+use clock_core::{BitMatrix, Brightness, CellText};
+
+/// The frame the display shows now, published for the HTTP server's live mirror.
+static SHOWN_FRAME: Mutex<CriticalSectionRawMutex, Cell<Frame>> =
+    Mutex::new(Cell::new((BitMatrix::new([0; CELL_COUNT]), Brightness::MAX)));
+
+/// The frame the display shows now.
+pub(crate) fn shown_frame() -> Frame {
+    SHOWN_FRAME.lock(Cell::get)
+}
+
+/// Signaled once the display starts showing the clock's first frame.
+static FIRST_FRAME_SHOWN: Signal<CriticalSectionRawMutex, ()> = Signal::new();
+
+/// Waits until the display shows the clock's first frame, for start-up work that would hold
+/// it blank, such as uploading the WiFi firmware. Only one task may wait.
+#[cfg(not(feature = "no-wifi"))]
+pub(crate) async fn first_frame_shown() {
+    FIRST_FRAME_SHOWN.wait().await;
+}
 
-use crate::blinker::Text;
 use crate::{
-    bit_matrix::BitMatrix,
+    config::config,
     error,
-    output_array::OutputArray,
-    shared_constants::{CELL_COUNT, MULTIPLEX_SLEEP, SEGMENT_COUNT},
+    hardware::{CellPins, DisplayBackend, SegmentPins},
+    settings::tx_dimming,
+    wifi::{transmit_changed, transmitting},
 };
-use crate::{BitsToIndexes, CELL_COUNT_U8};
+use crate::{info, BitsToIndexes, CELL_COUNT, CELL_COUNT_U8};
 use error::Result;
 
-/// A display abstraction that controls a 4-cell (digit), 8-segment LED display.
+/// A display abstraction that controls a 4-cell (digit), 8-segment LED display through the
+/// board's `DisplayDriver`.
 pub struct Display<'a>(&'a DisplayNotifier);
-/// A notifier that sends messages to the `Display`.
-pub type DisplayNotifier = Signal<CriticalSectionRawMutex, BitMatrix>;
+/// A notifier that sends messages to the `Display`: new segments (with the cells to show
+/// dimmed), a new brightness, and requests to exercise a Nixie display's cathodes.
+///
+/// Together with the frame a driver is showing, the segments' `Signal` makes a double buffer:
+/// a new frame replaces the waiting one whole (in a critical section), and the driver swaps it
+/// in when it is ready for it, so it never shows a frame half written.
+pub type DisplayNotifier = (
+    Signal<CriticalSectionRawMutex, BitMatrix>,
+    Signal<CriticalSectionRawMutex, Brightness>,
+    Signal<CriticalSectionRawMutex, ()>,
+);
+
+/// The brightness to show the cell at `index` of `bit_matrix` at, given the display's
+/// brightness: about half of it if the cell is dimmed (see `BitMatrix::dim`).
+#[must_use]
+pub(crate) const fn cell_brightness(
+    bit_matrix: &BitMatrix,
+    index: usize,
+    display_brightness: Brightness,
+) -> Brightness {
+    if bit_matrix.is_dimmed(index) {
+        display_brightness.dimmed()
+    } else {
+        display_brightness
+    }
+}
+
+/// Hardware that can show a `Frame`, such as a multiplexed LED display or a strip of RGB LEDs.
+///
+/// The `Display` task owns the board's driver (`DisplayBackend`) and hands it each frame.
+#[expect(async_fn_in_trait, reason = "Single-threaded executor, so the futures need not be Send.")]
+pub trait DisplayDriver {
+    /// Shows `frame` until the next notification arrives, then returns the frame it brings
+    /// (see `next_frame`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the hardware cannot be driven.
+    async fn show(&mut self, frame: Frame, notifier: &DisplayNotifier) -> Result<Frame>;
+}
 
 impl Display<'_> {
     /// Creates a new `DisplayNotifier`.
@@ -28,15 +93,14 @@ impl Display<'_> {
     /// This should be assigned to a static variable and passed to the `Display::new()` method.
     #[must_use]
     pub const fn notifier() -> DisplayNotifier {
-        Signal::new()
+        (Signal::new(), Signal::new(), Signal::new())
     }
 
     /// Create a new `Display`, which entails starting an Embassy task.
     ///
     /// # Arguments
     ///
-    /// * `cell_pins` - The pins that control the cells (digits) of the display.
-    /// * `segment_pins` - The pins that control the segments of the display.
+    /// * `driver` - The board's display hardware (see `Hardware::display`).
     /// * `notifier` - The static notifier that sends messages to the `Display`.
     ///          This notifier is created with the `Display::notifier()` method.
     /// * `spawner` - The Embassy task spawner.
@@ -46,12 +110,11 @@ impl Display<'_> {
     /// Returns a `SpawnError` if the task cannot be spawned.
     #[must_use = "Must be used to manage the spawned task"]
     pub fn new(
-        cell_pins: OutputArray<'static, CELL_COUNT>,
-        segment_pins: OutputArray<'static, SEGMENT_COUNT>,
+        driver: DisplayBackend,
         notifier: &'static DisplayNotifier,
         spawner: Spawner,
     ) -> Result<Self, SpawnError> {
-        spawner.spawn(device_loop(cell_pins, segment_pins, notifier))?;
+        spawner.spawn(device_loop(driver, notifier))?;
         Ok(Self(notifier))
     }
 
@@ -59,40 +122,75 @@ impl Display<'_> {
     ///
     /// The characters can be be any Unicode character but
     /// unknown or hard to display characters will be displayed as blanks.
-    pub fn write_text(&self, text: Text) {
+    pub fn write_text(&self, text: CellText) {
         info!("write_chars: {:?}", text);
-        self.0.signal(BitMatrix::from_text(&text));
+        self.write_bit_matrix(BitMatrix::from_text(&text));
+    }
+
+    /// Writes segment patterns, one per cell, to the display, showing the cells the
+    /// `BitMatrix` dims at reduced brightness.
+    pub fn write_bit_matrix(&self, bit_matrix: BitMatrix) {
+        let (bit_matrix_notifier, _, _) = self.0;
+        bit_matrix_notifier.signal(bit_matrix);
+    }
+
+    /// Sets how brightly the display is lit, keeping what it shows.
+    pub fn set_brightness(&self, brightness: Brightness) {
+        let (_, brightness_notifier, _) = self.0;
+        brightness_notifier.signal(brightness);
+    }
+
+    /// Asks the display to light every digit of every cell in turn, so digits that are rarely
+    /// shown do not dim from cathode poisoning. Only drivers for Nixie tubes act on it.
+    pub fn refresh_cathodes(&self) {
+        let (_, _, cathode_notifier) = self.0;
+        cathode_notifier.signal(());
     }
 }
 
 #[embassy_executor::task]
-async fn device_loop(
-    cell_pins: OutputArray<'static, CELL_COUNT>,
-    segment_pins: OutputArray<'static, SEGMENT_COUNT>,
-    notifier: &'static DisplayNotifier,
-) -> ! {
+async fn device_loop(driver: DisplayBackend, notifier: &'static DisplayNotifier) -> ! {
     // should never return
-    let err = inner_device_loop(cell_pins, segment_pins, notifier).await;
+    let err = inner_device_loop(driver, notifier).await;
     panic!("{:?}", err);
 }
 
+async fn inner_device_loop(
+    mut driver: DisplayBackend,
+    notifier: &'static DisplayNotifier,
+) -> Result<Infallible> {
+    let mut frame: Frame = (BitMatrix::default(), Brightness::default());
+    // Starts blank, until the clock sends its first frame
+    let mut first = true;
+    loop {
+        SHOWN_FRAME.lock(|shown| shown.set(frame));
+        frame = driver.show(frame, notifier).await?;
+        if first {
+            first = false;
+            FIRST_FRAME_SHOWN.signal(());
+        }
+    }
+}
+
 #[expect(dead_code, reason = "for article")]
 async fn simple_inner_device_loop(
-    mut cell_pins: OutputArray<'static, CELL_COUNT>,
-    mut segment_pins: OutputArray<'static, SEGMENT_COUNT>,
+    mut cell_pins: CellPins,
+    mut segment_pins: SegmentPins,
     notifier: &'static DisplayNotifier,
-) -> Result<!> {
+) -> Result<Infallible> {
+    let (notifier, _, _) = notifier;
     let mut bit_matrix: BitMatrix = BitMatrix::default();
     'outer: loop {
         info!("bit_matrix: {:?}", bit_matrix);
         for index in (0..CELL_COUNT_U8).cycle() {
-            segment_pins.set_from_bits(bit_matrix[index]);
+            segment_pins.set_from_bits(bit_matrix[index])?;
 
-            cell_pins.set_level_at_index(index, Level::Low)?;
-            let timeout_or_signal = select(Timer::after(MULTIPLEX_SLEEP), notifier.wait()).await;
-            cell_pins.set_level_at_index(index, Level::High)?;
+            cell_pins.set_level_at_index(index, PinState::Low)?;
+            let timeout_or_signal =
+                select(Timer::after(config().multiplex_sleep()), notifier.wait()).await;
+            cell_pins.set_level_at_index(index, PinState::High)?;
 
-            if let Either::Second(notification) = timeout_or_signal {
+            if let Either::Second((notification, _)) = timeout_or_signal {
                 bit_matrix = notification;
                 continue 'outer;
             }
@@ -100,43 +198,160 @@ async fn simple_inner_device_loop(
     }
 }
 
-async fn inner_device_loop(
-    mut cell_pins: OutputArray<'static, CELL_COUNT>,
-    mut segment_pins: OutputArray<'static, SEGMENT_COUNT>,
-    notifier: &'static DisplayNotifier,
-) -> Result<!> {
-    let mut bit_matrix: BitMatrix = BitMatrix::default();
-    let mut bits_to_indexes = BitsToIndexes::default();
-    'outer: loop {
-        info!("bit_matrix: {:?}", bit_matrix);
-        bit_matrix.bits_to_indexes(&mut bits_to_indexes)?;
-        info!("# of unique cell bit_matrix: {:?}", bits_to_indexes.len());
-
-        match bits_to_indexes.iter().next() {
-            // If the display should be empty, then just wait for the next notification
-            None => bit_matrix = notifier.wait().await,
-            // If only one bit pattern should be displayed (even on multiple cells), display it
-            // and wait for the next notification
-            Some((&bits, indexes)) if bits_to_indexes.len() == 1 => {
-                segment_pins.set_from_nonzero_bits(bits);
-                cell_pins.set_levels_at_indexes(indexes, Level::Low)?;
-                bit_matrix = notifier.wait().await;
-                cell_pins.set_levels_at_indexes(indexes, Level::High)?;
+/// Drives a common-anode LED display directly from GPIO: one cell pin per digit and one
+/// segment pin per segment, lighting the cells in turn (multiplexing) when they differ.
+pub struct MultiplexedDisplay {
+    cell_pins: CellPins,
+    segment_pins: SegmentPins,
+    bits_to_indexes: BitsToIndexes,
+}
+
+impl MultiplexedDisplay {
+    /// Creates a driver for the display wired to `cell_pins` and `segment_pins`.
+    #[must_use]
+    pub fn new(cell_pins: CellPins, segment_pins: SegmentPins) -> Self {
+        Self {
+            cell_pins,
+            segment_pins,
+            bits_to_indexes: BitsToIndexes::default(),
+        }
+    }
+}
+
+impl DisplayDriver for MultiplexedDisplay {
+    async fn show(&mut self, frame: Frame, notifier: &DisplayNotifier) -> Result<Frame> {
+        let (bit_matrix, display_brightness) = frame;
+        let dimmed_for_transmit = dim_for_transmit();
+        let brightness = if dimmed_for_transmit {
+            display_brightness.dimmed()
+        } else {
+            display_brightness
+        };
+        bit_matrix.bits_to_indexes(&mut self.bits_to_indexes)?;
+
+        match self
+            .bits_to_indexes
+            .iter()
+            .next()
+            .filter(|_| !brightness.is_off())
+        {
+            // If the display should be empty (or dark), then just wait for the next notification
+            None => Ok(next_frame_or_burst(notifier, frame).await),
+            // If only one bit pattern should be displayed (even on multiple cells) at full
+            // brightness, display it and wait for the next notification
+            Some((&bits, indexes))
+                if self.bits_to_indexes.len() == 1
+                    && brightness == Brightness::MAX
+                    && !indexes.iter().any(|&index| bit_matrix.is_dimmed(usize::from(index))) =>
+            {
+                self.segment_pins.set_from_nonzero_bits(bits)?;
+                self.cell_pins.set_levels_at_indexes(indexes, PinState::Low)?;
+                let notification = next_frame_or_burst(notifier, frame).await;
+                self.cell_pins.set_levels_at_indexes(indexes, PinState::High)?;
+                Ok(notification)
             }
-            // If multiple patterns should be displayed, multiplex them until the next notification
-            _ => loop {
-                for (bits, indexes) in &bits_to_indexes {
-                    segment_pins.set_from_nonzero_bits(*bits);
-                    cell_pins.set_levels_at_indexes(indexes, Level::Low)?;
-                    let timeout_or_signal =
-                        select(Timer::after(MULTIPLEX_SLEEP), notifier.wait()).await;
-                    cell_pins.set_levels_at_indexes(indexes, Level::High)?;
-                    if let Either::Second(notification) = timeout_or_signal {
-                        bit_matrix = notification;
-                        continue 'outer;
+            // Otherwise, multiplex the patterns (dark for part of each slot when dimmed, and
+            // dimmed cells turned off partway through the lit part) until the next
+            // notification, taking it only between full scans so no scan shows some cells from
+            // one frame and some from the next. Each slot ends with the blanking
+            // time, so a cell still turning off is not lit by the next cell's segments, and
+            // with segment compensation a sparse pattern is lit for less of its slot. A new
+            // multiplex rate or blanking in the `Config` applies from the next frame, and a WiFi
+            // transmit burst starting or ending from the next scan.
+            _ => {
+                let config = config();
+                let blanking = config.blanking();
+                let slot = config
+                    .multiplex_sleep()
+                    .checked_sub(blanking)
+                    .unwrap_or(Duration::from_ticks(0));
+                let (lit, dark) = brightness.split(slot);
+                let (dimmed_lit, _) = brightness.dimmed().split(slot);
+                let dark = dark.checked_add(blanking).unwrap_or(dark);
+                loop {
+                    for (bits, indexes) in &self.bits_to_indexes {
+                        // Sparse patterns give up some of their lit time (see
+                        // `Config::compensated_lit`), which stays dark
+                        let cell_lit = config.compensated_lit(lit, bits.get());
+                        let dimmed_cell_lit = config.compensated_lit(dimmed_lit, bits.get());
+                        let cell_dark = dark
+                            .checked_add(lit.checked_sub(cell_lit).unwrap_or(Duration::from_ticks(0)))
+                            .unwrap_or(dark);
+                        let (dimmed, full): (Vec<u8, CELL_COUNT>, Vec<u8, CELL_COUNT>) = indexes
+                            .iter()
+                            .partition(|&&index| bit_matrix.is_dimmed(usize::from(index)));
+                        self.segment_pins.set_from_nonzero_bits(*bits)?;
+                        self.cell_pins.set_levels_at_indexes(indexes, PinState::Low)?;
+                        let mut lit_so_far = Duration::from_ticks(0);
+                        if !dimmed.is_empty() {
+                            Timer::after(dimmed_cell_lit).await;
+                            self.cell_pins.set_levels_at_indexes(&dimmed, PinState::High)?;
+                            lit_so_far = dimmed_cell_lit;
+                        }
+                        Timer::after(
+                            cell_lit.checked_sub(lit_so_far).unwrap_or(Duration::from_ticks(0)),
+                        )
+                        .await;
+                        self.cell_pins.set_levels_at_indexes(&full, PinState::High)?;
+                        if cell_dark > Duration::from_ticks(0) {
+                            Timer::after(cell_dark).await;
+                        }
+                    }
+                    if let Some(notification) = try_next_frame(notifier, frame) {
+                        return Ok(notification);
+                    }
+                    if dim_for_transmit() != dimmed_for_transmit {
+                        return Ok(frame);
                     }
                 }
-            },
+            }
         }
     }
 }
+
+/// Whether a multiplexed display shows frames dimmed now, during a WiFi transmit burst when
+/// `TX_DIMMING` is set, so the radio's peak current does not droop the supply into flicker.
+fn dim_for_transmit() -> bool {
+    tx_dimming() && transmitting()
+}
+
+/// Like `next_frame`, but with `TX_DIMMING` set it also returns `frame` unchanged when a WiFi
+/// transmit burst starts or ends, so the driver shows it again at the right brightness.
+async fn next_frame_or_burst(notifier: &DisplayNotifier, frame: Frame) -> Frame {
+    if !tx_dimming() {
+        return next_frame(notifier, frame).await;
+    }
+    match select(next_frame(notifier, frame), transmit_changed()).await {
+        Either::First(notification) => notification,
+        Either::Second(()) => frame,
+    }
+}
+
+/// What the display shows (with the cells it dims) and how brightly.
+pub type Frame = (BitMatrix, Brightness);
+
+/// Takes new segments and a new brightness, if either is waiting, and returns `frame` updated
+/// with them, or `None` if neither is.
+fn try_next_frame(notifier: &DisplayNotifier, frame: Frame) -> Option<Frame> {
+    let (bit_matrix_notifier, brightness_notifier, _) = notifier;
+    let (bit_matrix, brightness) = frame;
+    let new_segments = bit_matrix_notifier.try_take();
+    let new_brightness = brightness_notifier.try_take();
+    if new_segments.is_none() && new_brightness.is_none() {
+        return None;
+    }
+    Some((
+        new_segments.unwrap_or(bit_matrix),
+        new_brightness.unwrap_or(brightness),
+    ))
+}
+
+/// Waits for new segments or a new brightness, and returns `frame` updated with it.
+pub(crate) async fn next_frame(notifier: &DisplayNotifier, frame: Frame) -> Frame {
+    let (bit_matrix_notifier, brightness_notifier, _) = notifier;
+    let (bit_matrix, brightness) = frame;
+    match select(bit_matrix_notifier.wait(), brightness_notifier.wait()).await {
+        Either::First(new_bit_matrix) => (new_bit_matrix, brightness),
+        Either::Second(new_brightness) => (bit_matrix, new_brightness),
+    }
+}
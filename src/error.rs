@@ -17,14 +17,35 @@ pub enum Error {
     #[display("{_0:?}")]
     TaskSpawn(#[error(not(source))] embassy_executor::SpawnError),
 
-    #[display("bits_to_indexes does not have enough preallocated space")]
-    BitsToIndexesNotEnoughSpace,
+    #[display("{_0}")]
+    Core(clock_core::Error),
 
     #[display("Error setting output state")]
     CannotSetOutputState,
 
     #[display("Index out of bounds")]
     IndexOutOfBounds,
+
+    #[display("TCP error: {_0:?}")]
+    Tcp(#[error(not(source))] embassy_net::tcp::Error),
+
+    #[display("USB error: {_0:?}")]
+    Usb(#[error(not(source))] embassy_usb::driver::EndpointError),
+
+    #[display("SPI error: {_0:?}")]
+    Spi(#[error(not(source))] embassy_rp::spi::Error),
+
+    #[display("Flash error: {_0:?}")]
+    Flash(#[error(not(source))] embassy_rp::flash::Error),
+
+    #[display("Button events: {_0:?}")]
+    PubSub(#[error(not(source))] embassy_sync::pubsub::Error),
+
+    #[display("Supply voltage too low to write flash safely")]
+    SupplyTooLow,
+
+    #[display("Formatted text does not fit in its buffer")]
+    Format(#[error(not(source))] core::fmt::Error),
 }
 
 impl From<Infallible> for Error {
@@ -0,0 +1,74 @@
+//! Logging macros that forward to `defmt` with the `logging` feature, and compile to nothing
+//! without it.
+//!
+//! Log with `crate::{info, warn, error}` (or `lib::` from `main.rs`) rather than `defmt::`
+//! directly, so a build without `defmt` or `defmt-rtt` still compiles. Without logging, the
+//! arguments are only borrowed, so they cost nothing and raise no unused-variable warnings.
+
+#[cfg(feature = "logging")]
+pub use defmt::Display2Format;
+
+/// Logs a value through its `core::fmt::Display` (stand-in for `defmt::Display2Format`).
+#[cfg(not(feature = "logging"))]
+pub struct Display2Format<'a, T: ?Sized>(pub &'a T);
+
+/// Logs at the info level.
+#[cfg(feature = "logging")]
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => { ::defmt::info!($($arg)*) };
+}
+
+/// Logs at the info level.
+#[cfg(not(feature = "logging"))]
+#[macro_export]
+macro_rules! info {
+    ($format:literal $(, $arg:expr)* $(,)?) => {{ $( let _ = &$arg; )* }};
+}
+
+/// Logs at the warn level.
+#[cfg(feature = "logging")]
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)*) => { ::defmt::warn!($($arg)*) };
+}
+
+/// Logs at the warn level.
+#[cfg(not(feature = "logging"))]
+#[macro_export]
+macro_rules! warn {
+    ($format:literal $(, $arg:expr)* $(,)?) => {{ $( let _ = &$arg; )* }};
+}
+
+/// Logs at the error level.
+#[cfg(feature = "logging")]
+#[macro_export]
+macro_rules! error {
+    ($($arg:tt)*) => { ::defmt::error!($($arg)*) };
+}
+
+/// Logs at the error level.
+#[cfg(not(feature = "logging"))]
+#[macro_export]
+macro_rules! error {
+    ($format:literal $(, $arg:expr)* $(,)?) => {{ $( let _ = &$arg; )* }};
+}
+
+/// Unwraps a `Result`, panicking (and logging the error, with `logging`) if it is `Err`.
+#[cfg(feature = "logging")]
+#[macro_export]
+macro_rules! unwrap {
+    ($($arg:tt)*) => { ::defmt::unwrap!($($arg)*) };
+}
+
+/// Unwraps a `Result`, panicking (and logging the error, with `logging`) if it is `Err`.
+#[cfg(not(feature = "logging"))]
+#[macro_export]
+macro_rules! unwrap {
+    ($result:expr) => {
+        match $result {
+            ::core::result::Result::Ok(value) => value,
+            ::core::result::Result::Err(err) => ::core::panic!("unwrap failed: {:?}", err),
+        }
+    };
+}
@@ -0,0 +1,150 @@
+//! GPS virtual device - sets the clock from a GPS receiver's NMEA sentences, without WiFi
+
+use core::cell::RefCell;
+
+use embassy_executor::{SpawnError, Spawner};
+use embassy_rp::{
+    bind_interrupts,
+    peripherals::{DMA_CH1, PIN_21, UART1},
+    uart::{self, Async, UartRx},
+    Peri,
+};
+use embassy_sync::blocking_mutex::{raw::CriticalSectionRawMutex, Mutex};
+use embassy_time::{Duration, Instant};
+use heapless::String;
+
+use clock_core::{
+    LeapIndicator, Location, NmeaFix, TimeSanity, TimeSource, MAX_NMEA_SENTENCE_LEN,
+};
+
+use crate::{info, warn};
+use crate::settings::{build_unix_seconds, gps_baud, max_time_jump};
+use crate::time_sync::{SyncAccuracy, TimeSync, TimeSyncEvent};
+
+/// How often a receiver that keeps its fix sets the clock again. It sends a sentence every
+/// second, but the clock drifts far less than the sentence's own delay in that time.
+const GPS_SYNC_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// The sentence buffer the GPS task holds.
+pub(crate) const RAM_BYTES: usize = MAX_NMEA_SENTENCE_LEN;
+
+/// How many years after the build a GPS time may be before it is rejected.
+const MAX_YEARS_AFTER_BUILD: u16 = 20;
+
+/// The latest position from the receiver, if it has reported one.
+static GPS_LOCATION: Mutex<CriticalSectionRawMutex, RefCell<Option<Location>>> =
+    Mutex::new(RefCell::new(None));
+
+bind_interrupts!(struct Irqs {
+    UART1_IRQ => uart::InterruptHandler<UART1>;
+});
+
+/// Where the GPS receiver is, once it has a fix, for night dimming when `LATITUDE` and
+/// `LONGITUDE` are not set.
+pub(crate) fn gps_location() -> Option<Location> {
+    GPS_LOCATION.lock(|cell| *cell.borrow())
+}
+
+/// GPS virtual device - reads NMEA sentences from a receiver's TX on GPIO 21 (UART1 RX)
+///
+/// It does nothing unless `GPS_BAUD` is set at build time.
+pub struct Gps;
+
+impl Gps {
+    /// Create the GPS device and spawn its task, which reports each time it reads through
+    /// `time_sync`, tagged `TimeSource::Gps`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SpawnError` if the task cannot be spawned.
+    pub fn new(
+        uart: Peri<'static, UART1>,
+        rx_pin: Peri<'static, PIN_21>,
+        dma: Peri<'static, DMA_CH1>,
+        time_sync: &'static TimeSync,
+        spawner: Spawner,
+    ) -> Result<Self, SpawnError> {
+        spawner.spawn(device_loop(uart, rx_pin, dma, time_sync))?;
+        Ok(Self)
+    }
+}
+
+#[embassy_executor::task]
+async fn device_loop(
+    uart: Peri<'static, UART1>,
+    rx_pin: Peri<'static, PIN_21>,
+    dma: Peri<'static, DMA_CH1>,
+    time_sync: &'static TimeSync,
+) -> ! {
+    let Some(baud) = gps_baud() else {
+        info!("GPS disabled: GPS_BAUD not set");
+        loop {
+            core::future::pending::<()>().await;
+        }
+    };
+    let mut config = uart::Config::default();
+    config.baudrate = baud;
+    let mut rx = UartRx::new(uart, rx_pin, Irqs, dma, config);
+
+    let mut sanity = TimeSanity::new(build_unix_seconds(), MAX_YEARS_AFTER_BUILD, max_time_jump());
+    let mut synced_at: Option<Instant> = None;
+    loop {
+        let Some(fix) = read_fix(&mut rx).await else {
+            continue;
+        };
+        if let Some(location) = fix.location {
+            GPS_LOCATION.lock(|cell| *cell.borrow_mut() = Some(location));
+        }
+        if synced_at.is_some_and(|at| at.elapsed() < GPS_SYNC_PERIOD) {
+            continue;
+        }
+        match sanity.check(fix.unix_seconds, Instant::now()) {
+            Ok(()) => {
+                info!("GPS time: {} (unix timestamp)", fix.unix_seconds.as_i64());
+                time_sync.post(TimeSyncEvent::Success {
+                    unix_seconds: fix.unix_seconds,
+                    // RMC sentences do not announce leap seconds
+                    leap: LeapIndicator::NoWarning,
+                    accuracy: SyncAccuracy::Coarse,
+                    source: TimeSource::Gps,
+                });
+                synced_at = Some(Instant::now());
+            }
+            Err(reason) => {
+                warn!("Rejected GPS time {}: {:?}", fix.unix_seconds.as_i64(), reason);
+                time_sync.post(TimeSyncEvent::Rejected(reason));
+                synced_at = Some(Instant::now());
+            }
+        }
+    }
+}
+
+/// Reads one line from the receiver, returning the fix it gives if it is a valid `RMC`
+/// sentence. Other sentences, garbled lines, and read errors give `None`.
+async fn read_fix(rx: &mut UartRx<'static, Async>) -> Option<NmeaFix> {
+    let mut line = String::<MAX_NMEA_SENTENCE_LEN>::new();
+    let mut overflowed = false;
+    loop {
+        let mut byte = [0_u8];
+        if let Err(err) = rx.read(&mut byte).await {
+            warn!("GPS read failed: {:?}", err);
+            return None;
+        }
+        match byte {
+            [b'\n'] => break,
+            [b'\r'] => {}
+            [b'$'] => {
+                // A sentence starts over, whatever came before it
+                line.clear();
+                overflowed = line.push('$').is_err();
+            }
+            [other] => {
+                overflowed |= line.push(char::from(other)).is_err();
+            }
+        }
+    }
+    if overflowed {
+        return None;
+    }
+    NmeaFix::parse_rmc(&line)
+}
@@ -1,10 +1,69 @@
 use embassy_rp::{
     gpio::{self, Level},
-    peripherals::{DMA_CH0, PIN_23, PIN_24, PIN_25, PIN_29, PIO0},
+    peripherals::{
+        ADC, DMA_CH0, DMA_CH1, PIN_21, PIN_23, PIN_24, PIN_25, PIN_26, PIN_29, PIO0, UART1, USB,
+    },
+    watchdog::Watchdog,
     Peri,
 };
 
-use crate::{output_array::OutputArray, CELL_COUNT, SEGMENT_COUNT};
+#[cfg(feature = "oled")]
+use embassy_rp::peripherals::{I2C0, PIN_17};
+#[cfg(any(feature = "oled", feature = "talking-clock"))]
+use embassy_rp::peripherals::PIN_16;
+#[cfg(not(feature = "fourteen-segment"))]
+use embassy_rp::peripherals::{PIN_27, PWM_SLICE5};
+#[cfg(feature = "talking-clock")]
+use embassy_rp::peripherals::{DMA_CH4, PIN_14, PIN_15, PIO1};
+
+#[cfg(not(any(feature = "ws2812", feature = "nixie")))]
+use crate::display::MultiplexedDisplay;
+#[cfg(feature = "nixie")]
+use crate::nixie::NixieDisplay;
+#[cfg(feature = "ws2812")]
+use crate::ws2812::Ws2812Display;
+use crate::{
+    button::{ActiveLevel, Button},
+    output_array::OutputArray,
+    settings::default_button_config,
+    storage::Storage,
+    CELL_COUNT, SEGMENT_COUNT,
+};
+
+#[cfg(all(feature = "eight-digits", feature = "fourteen-segment"))]
+compile_error!("`eight-digits` and `fourteen-segment` both need GPIO 14-17; enable only one");
+#[cfg(all(feature = "oled", any(feature = "eight-digits", feature = "fourteen-segment")))]
+compile_error!("`oled` needs GPIO 16 and 17, which `eight-digits` and `fourteen-segment` use");
+#[cfg(all(feature = "nixie", feature = "ws2812"))]
+compile_error!("`nixie` and `ws2812` are both displays; enable only one");
+#[cfg(all(feature = "nixie", feature = "fourteen-segment"))]
+compile_error!("Nixie tubes show digits only, so `nixie` cannot use `fourteen-segment`");
+#[cfg(all(feature = "talking-clock", feature = "ws2812"))]
+compile_error!("`talking-clock` and `ws2812` both need PIO1; enable only one");
+#[cfg(all(
+    feature = "talking-clock",
+    any(feature = "eight-digits", feature = "fourteen-segment", feature = "oled")
+))]
+compile_error!("`talking-clock` needs GPIO 14-16, which `eight-digits`, `fourteen-segment`, and `oled` use");
+
+/// The display's cell (digit) pins on this board.
+pub type CellPins = OutputArray<gpio::Output<'static>, CELL_COUNT>;
+/// The display's segment pins on this board.
+pub type SegmentPins = OutputArray<gpio::Output<'static>, SEGMENT_COUNT>;
+/// The display hardware on this board: a multiplexed LED display, a strip of WS2812 LEDs
+/// with the `ws2812` feature, or Nixie tubes with the `nixie` feature.
+#[cfg(not(any(feature = "ws2812", feature = "nixie")))]
+pub type DisplayBackend = MultiplexedDisplay;
+/// The display hardware on this board: a multiplexed LED display, a strip of WS2812 LEDs
+/// with the `ws2812` feature, or Nixie tubes with the `nixie` feature.
+#[cfg(feature = "ws2812")]
+pub type DisplayBackend = Ws2812Display;
+/// The display hardware on this board: a multiplexed LED display, a strip of WS2812 LEDs
+/// with the `ws2812` feature, or Nixie tubes with the `nixie` feature.
+#[cfg(feature = "nixie")]
+pub type DisplayBackend = NixieDisplay;
+/// The button's input pin on this board.
+pub type ButtonPin = gpio::Input<'static>;
 
 /// WiFi hardware peripherals
 pub struct WifiHardware {
@@ -19,14 +78,70 @@ pub struct WifiHardware {
 /// Represents the hardware components of the clock.
 pub struct Hardware {
     // TODO replace the 'static's with <'a> lifetimes
-    /// The four cell pins that control the digits of the display.
-    pub cells: OutputArray<'static, CELL_COUNT>,
-    /// The eight segment pins that control the segments of the display.
-    pub segments: OutputArray<'static, SEGMENT_COUNT>,
+    /// The display: cell and segment pins (eight segments, or fifteen with the
+    /// `fourteen-segment` feature), a WS2812 strip on GPIO 5 with the `ws2812` feature, or
+    /// exixe Nixie boards on SPI0 with the `nixie` feature.
+    pub display: DisplayBackend,
     /// The button that controls the clock.
-    pub button: gpio::Input<'static>,
-    /// An LED (not currently used).
+    pub button: Button<ButtonPin>,
+    /// Flash storage for settings that survive a reboot.
+    pub storage: Storage,
+    /// An LED (not currently used). Its pin drives a segment with `fourteen-segment`.
+    #[cfg(not(feature = "fourteen-segment"))]
     pub led: gpio::Output<'static>,
+    /// A spare output for the `Switch`, e.g. to a relay driver.
+    pub switch: gpio::Output<'static>,
+    /// The output of an IR receiver module, for the `IrRemote`.
+    pub ir_receiver: gpio::Input<'static>,
+    /// A touch pad or touch module, for the `TouchPad`.
+    pub touch_pad: gpio::Flex<'static>,
+    /// The ADC, for the `SupplyMonitor`.
+    pub adc: Peri<'static, ADC>,
+    /// The ADC pin a divider brings the supply voltage to, for the `SupplyMonitor`.
+    pub supply_pin: Peri<'static, PIN_26>,
+    /// The output of a DCF77 or WWVB receiver module, for the `RadioTime`.
+    pub radio_receiver: gpio::Input<'static>,
+    /// The UART a GPS receiver's TX feeds, for the `Gps`.
+    pub gps_uart: Peri<'static, UART1>,
+    /// The UART's RX pin, wired to the GPS receiver's TX.
+    pub gps_rx: Peri<'static, PIN_21>,
+    /// The DMA channel the `Gps` reads the UART with.
+    pub gps_dma: Peri<'static, DMA_CH1>,
+    /// The I2C block an SSD1306 OLED is on, for the `StatusDisplay`.
+    #[cfg(feature = "oled")]
+    pub status_i2c: Peri<'static, I2C0>,
+    /// The OLED's SDA pin.
+    #[cfg(feature = "oled")]
+    pub status_sda: Peri<'static, PIN_16>,
+    /// The OLED's SCL pin.
+    #[cfg(feature = "oled")]
+    pub status_scl: Peri<'static, PIN_17>,
+    /// The PWM slice for a buzzer, for the `Buzzer`. Its pin drives a segment with
+    /// `fourteen-segment`.
+    #[cfg(not(feature = "fourteen-segment"))]
+    pub buzzer_pwm: Peri<'static, PWM_SLICE5>,
+    /// The buzzer's pin.
+    #[cfg(not(feature = "fourteen-segment"))]
+    pub buzzer_pin: Peri<'static, PIN_27>,
+    /// The PIO block that clocks samples out to an I2S DAC, for the `Talker`.
+    #[cfg(feature = "talking-clock")]
+    pub talker_pio: Peri<'static, PIO1>,
+    /// The DMA channel that feeds the `Talker`'s samples to the PIO block.
+    #[cfg(feature = "talking-clock")]
+    pub talker_dma: Peri<'static, DMA_CH4>,
+    /// The DAC's data pin.
+    #[cfg(feature = "talking-clock")]
+    pub talker_data: Peri<'static, PIN_14>,
+    /// The DAC's bit clock pin.
+    #[cfg(feature = "talking-clock")]
+    pub talker_bit_clock: Peri<'static, PIN_15>,
+    /// The DAC's word (left/right) clock pin.
+    #[cfg(feature = "talking-clock")]
+    pub talker_word_clock: Peri<'static, PIN_16>,
+    /// The USB device, for the `UsbConsole`.
+    pub usb: Peri<'static, USB>,
+    /// The watchdog, whose scratch registers carry state across a `SoftReboot`.
+    pub watchdog: Watchdog,
     /// WiFi hardware peripherals
     pub wifi: WifiHardware,
 }
@@ -36,8 +151,10 @@ impl Default for Hardware {
         let peripherals: embassy_rp::Peripherals =
             embassy_rp::init(embassy_rp::config::Config::default());
 
+        #[cfg(not(feature = "fourteen-segment"))]
         let led = gpio::Output::new(peripherals.PIN_0, Level::Low);
 
+        #[cfg(all(not(feature = "ws2812"), not(feature = "eight-digits")))]
         let cells = OutputArray::new([
             gpio::Output::new(peripherals.PIN_1, Level::High),
             gpio::Output::new(peripherals.PIN_2, Level::High),
@@ -45,6 +162,20 @@ impl Default for Hardware {
             gpio::Output::new(peripherals.PIN_4, Level::High),
         ]);
 
+        // The second module's cells continue on the first free pins after the button.
+        #[cfg(all(not(feature = "ws2812"), feature = "eight-digits"))]
+        let cells = OutputArray::new([
+            gpio::Output::new(peripherals.PIN_1, Level::High),
+            gpio::Output::new(peripherals.PIN_2, Level::High),
+            gpio::Output::new(peripherals.PIN_3, Level::High),
+            gpio::Output::new(peripherals.PIN_4, Level::High),
+            gpio::Output::new(peripherals.PIN_14, Level::High),
+            gpio::Output::new(peripherals.PIN_15, Level::High),
+            gpio::Output::new(peripherals.PIN_16, Level::High),
+            gpio::Output::new(peripherals.PIN_17, Level::High),
+        ]);
+
+        #[cfg(not(any(feature = "ws2812", feature = "nixie", feature = "fourteen-segment")))]
         let segments = OutputArray::new([
             gpio::Output::new(peripherals.PIN_5, Level::Low),
             gpio::Output::new(peripherals.PIN_6, Level::Low),
@@ -56,7 +187,64 @@ impl Default for Hardware {
             gpio::Output::new(peripherals.PIN_12, Level::Low),
         ]);
 
-        let button = gpio::Input::new(peripherals.PIN_13, gpio::Pull::Down);
+        // The seven extra segments (G2 and H to M) take the second module's cell pins, which
+        // is why this cannot be combined with eight digits, then the free pins past the ADC
+        // and the unused LED's.
+        #[cfg(all(not(feature = "ws2812"), feature = "fourteen-segment"))]
+        let segments = OutputArray::new([
+            gpio::Output::new(peripherals.PIN_5, Level::Low),
+            gpio::Output::new(peripherals.PIN_6, Level::Low),
+            gpio::Output::new(peripherals.PIN_7, Level::Low),
+            gpio::Output::new(peripherals.PIN_8, Level::Low),
+            gpio::Output::new(peripherals.PIN_9, Level::Low),
+            gpio::Output::new(peripherals.PIN_10, Level::Low),
+            gpio::Output::new(peripherals.PIN_11, Level::Low),
+            gpio::Output::new(peripherals.PIN_12, Level::Low),
+            gpio::Output::new(peripherals.PIN_14, Level::Low),
+            gpio::Output::new(peripherals.PIN_15, Level::Low),
+            gpio::Output::new(peripherals.PIN_16, Level::Low),
+            gpio::Output::new(peripherals.PIN_17, Level::Low),
+            gpio::Output::new(peripherals.PIN_27, Level::Low),
+            gpio::Output::new(peripherals.PIN_28, Level::Low),
+            gpio::Output::new(peripherals.PIN_0, Level::Low),
+        ]);
+
+        #[cfg(not(any(feature = "ws2812", feature = "nixie")))]
+        let display = MultiplexedDisplay::new(cells, segments);
+        // The strip's data line takes the first segment pin; the other display pins are free.
+        // PIO0 and DMA channels 0 and 1 belong to the WiFi chip and the GPS.
+        #[cfg(feature = "ws2812")]
+        let display =
+            Ws2812Display::new(peripherals.PIO1, peripherals.DMA_CH2, peripherals.PIN_5);
+        // The cell pins select each tube's board, and SPI0 takes two of the segment pins.
+        #[cfg(feature = "nixie")]
+        let display = NixieDisplay::new(
+            peripherals.SPI0,
+            peripherals.PIN_6,
+            peripherals.PIN_7,
+            peripherals.DMA_CH3,
+            cells,
+        );
+
+        let button_config = default_button_config();
+        let pull = match button_config.active_level {
+            ActiveLevel::High => gpio::Pull::Down,
+            ActiveLevel::Low => gpio::Pull::Up,
+        };
+        let button = Button::new(gpio::Input::new(peripherals.PIN_13, pull), button_config);
+
+        let storage = Storage::new(peripherals.FLASH);
+
+        // The first pin free with either display size
+        let switch = gpio::Output::new(peripherals.PIN_18, Level::Low);
+        // The receiver idles high; the pull-up keeps the pin quiet when none is fitted
+        let ir_receiver = gpio::Input::new(peripherals.PIN_19, gpio::Pull::Up);
+        // Left as an input until the `TouchPad` knows what is fitted
+        let touch_pad = gpio::Flex::new(peripherals.PIN_20);
+        // Many receiver modules have an open-collector output
+        let radio_receiver = gpio::Input::new(peripherals.PIN_22, gpio::Pull::Up);
+
+        let watchdog = Watchdog::new(peripherals.WATCHDOG);
 
         let wifi = WifiHardware {
             pin_23: peripherals.PIN_23,
@@ -68,10 +256,42 @@ impl Default for Hardware {
         };
 
         Self {
-            cells,
-            segments,
+            display,
             button,
+            storage,
+            #[cfg(not(feature = "fourteen-segment"))]
             led,
+            switch,
+            ir_receiver,
+            touch_pad,
+            adc: peripherals.ADC,
+            supply_pin: peripherals.PIN_26,
+            radio_receiver,
+            gps_uart: peripherals.UART1,
+            gps_rx: peripherals.PIN_21,
+            gps_dma: peripherals.DMA_CH1,
+            #[cfg(feature = "oled")]
+            status_i2c: peripherals.I2C0,
+            #[cfg(feature = "oled")]
+            status_sda: peripherals.PIN_16,
+            #[cfg(feature = "oled")]
+            status_scl: peripherals.PIN_17,
+            #[cfg(not(feature = "fourteen-segment"))]
+            buzzer_pwm: peripherals.PWM_SLICE5,
+            #[cfg(not(feature = "fourteen-segment"))]
+            buzzer_pin: peripherals.PIN_27,
+            #[cfg(feature = "talking-clock")]
+            talker_pio: peripherals.PIO1,
+            #[cfg(feature = "talking-clock")]
+            talker_dma: peripherals.DMA_CH4,
+            #[cfg(feature = "talking-clock")]
+            talker_data: peripherals.PIN_14,
+            #[cfg(feature = "talking-clock")]
+            talker_bit_clock: peripherals.PIN_15,
+            #[cfg(feature = "talking-clock")]
+            talker_word_clock: peripherals.PIN_16,
+            usb: peripherals.USB,
+            watchdog,
             wifi,
         }
     }
@@ -0,0 +1,761 @@
+//! HTTP virtual device - serves a small JSON API on the local network
+
+#![allow(clippy::future_not_send, reason = "single-threaded")]
+
+use core::fmt::Write;
+
+use embassy_executor::{SpawnError, Spawner};
+use embassy_net::tcp::TcpSocket;
+use embassy_time::{Duration, Instant};
+use embedded_io_async::Write as _;
+use heapless::{String, Vec};
+
+use clock_core::{
+    parse_time_of_day, AlarmSound, BlinkState, Message, Reminders, SkewHistory, TimeArbiter,
+    UnixSeconds, CLIMATE_CSV_HEADER, MAX_PUSHED_MESSAGE_LEN,
+};
+
+use crate::{info, warn, Display2Format, CELL_COUNT};
+use crate::build_info;
+use crate::button::{gesture_timings, set_gesture_timings, simulate_presses, PressDuration};
+use crate::buzzer::{play_sound, silence_sound};
+use crate::clock::Clock;
+use crate::clock_state::OffRequests;
+use crate::config::{config, set_config};
+use crate::http_client::read_until;
+use crate::display::{shown_frame, Frame};
+use crate::memory_report::write_memory_report;
+use crate::metrics::{skew_history, sync_failures};
+use crate::network_policy::network_quiet;
+use crate::scheduler::{cancel_countdown, countdown_remaining, start_countdown};
+use crate::soft_reboot::{wall_clock_at, SoftReboot};
+use crate::storage::{next_slot, SharedStorage, CLIMATE_LOG_SLOTS};
+use crate::supply::supply_millivolts;
+use crate::switch::{Switch, SwitchCommand, MAX_PULSE};
+use crate::time_sync::time_arbiter;
+use crate::wifi::{Wifi, WifiScanResult};
+use crate::{Result, SEGMENT_COUNT};
+
+/// The TCP port the server listens on.
+const HTTP_PORT: u16 = 80;
+
+/// How long a client may stay silent before its connection is dropped.
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The largest request line (plus any headers that arrive with it) the server reads.
+const MAX_REQUEST_LEN: usize = 512;
+
+/// The largest response body the server can send.
+const MAX_BODY_LEN: usize = 2048;
+
+/// Each of the TCP socket's receive and transmit buffers.
+const SOCKET_BUFFER_LEN: usize = 1024;
+
+/// The buffers the server's task holds while it answers a request.
+pub(crate) const RAM_BYTES: usize = 2 * SOCKET_BUFFER_LEN + MAX_REQUEST_LEN + MAX_BODY_LEN;
+
+/// The longest line of `GET /climate.csv`, such as `4294967295,-32768,100` and a newline.
+const MAX_CSV_ROW_LEN: usize = 22;
+
+/// The longest countdown `POST /timer` starts: a day.
+const MAX_TIMER_SECONDS: u64 = 24 * 60 * 60;
+
+/// The most UTF-8 bytes a pushed message's text decodes to.
+const MAX_MESSAGE_BYTES: usize = MAX_PUSHED_MESSAGE_LEN * 4;
+
+/// The page `GET /mirror` serves, which polls `GET /display` and draws the segments.
+const MIRROR_PAGE: &str = include_str!("mirror.html");
+
+// ============================================================================
+// HTTP Virtual Device
+// ============================================================================
+
+/// HTTP virtual device - answers requests one connection at a time
+///
+/// Endpoints:
+/// * `GET /scan` - nearby WiFi networks as `[{"ssid":"...","rssi":-50},...]`, strongest first
+/// * `GET /metrics` - sync corrections and failures in the Prometheus text format
+/// * `GET /reminders` - the reminder table as `0517ANNA,1225XMAS`
+/// * `PUT /reminders?list=0517ANNA,1225XMAS` - replaces and saves the reminder table
+/// * `POST /message?text=DOOR+OPEN&times=3&blink=1` - scrolls the text past `times` times
+///   (default 1), blinking if `blink` is set, then goes back to the clock
+/// * `PUT /time?local=07:30:00` - sets the local time of day (24-hour, seconds optional),
+///   keeping the date and time zone
+/// * `GET /config` - the runtime config as `blink_on=150,blink_off=50,...`
+/// * `PUT /config?sync_minutes=30` - changes and saves any of the runtime config
+/// * `POST /sound?name=chime` - plays an alarm sound (the config's without `name`) until it
+///   is silenced
+/// * `DELETE /sound` - silences the alarm sound
+/// * `POST /button?kind=short` - presses the button (`short`, `long`, or `double`) as the
+///   remote would, driving the same state machine
+/// * `GET /display` - what the display shows now, as `{"segments":8,"cells":[6,91,79,102],
+///   "dimmed":[false,false,true,true],"brightness":15}`: each cell's lit segments as bits
+///   (segment A first), which cells are dimmed, and the display's brightness
+/// * `GET /mirror` - a page that draws a live copy of the display from `GET /display`
+/// * `GET /version` - the firmware version, commit, and build date as JSON
+/// * `GET /memory` - the RAM the named statics and task buffers take, a line each, and the
+///   stack's high-water mark
+/// * `POST /hold` - freezes the display on what it shows now, blinking, until the next press
+/// * `POST /resume` - ends a hold
+/// * `GET /climate.csv` - the logged temperature samples, oldest first, as CSV
+/// * `POST /reboot` - reboots, coming back in the same mode and on time
+/// * `GET /last-crash` - the panic saved before the last reboot, if any
+/// * `DELETE /last-crash` - clears the saved panic
+/// * `POST /timer?seconds=300` - starts the countdown timer, which carries out
+///   `TIMER_ACTIONS` when it reaches zero
+/// * `GET /timer` - the seconds the countdown timer has left
+/// * `DELETE /timer` - stops the countdown timer
+pub struct HttpServer;
+
+impl HttpServer {
+    /// Create the HTTP server and spawn its task.
+    ///
+    /// The server starts listening once WiFi has connected.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SpawnError` if the task cannot be spawned.
+    pub fn new(
+        wifi: &'static Wifi,
+        clock: Clock<'static>,
+        storage: &'static SharedStorage,
+        off_requests: &'static OffRequests,
+        switch: Switch<'static>,
+        soft_reboot: SoftReboot<'static>,
+        spawner: Spawner,
+    ) -> Result<Self, SpawnError> {
+        spawner.spawn(device_loop(
+            wifi,
+            clock,
+            storage,
+            off_requests,
+            switch,
+            soft_reboot,
+        ))?;
+        Ok(Self)
+    }
+}
+
+/// The resources a request can name.
+enum Route<'a> {
+    Scan,
+    Metrics,
+    GetReminders,
+    /// The `list` query parameter, or `""` if it is missing.
+    PutReminders(&'a str),
+    GetGestures,
+    /// The whole query string, such as `long=800&double=400`.
+    PutGestures(&'a str),
+    /// The `local` query parameter, or `""` if it is missing.
+    PutTime(&'a str),
+    GetConfig,
+    /// The whole query string, such as `blink_on=200&sync_minutes=30`.
+    PutConfig(&'a str),
+    /// The `name` query parameter, if any.
+    PlaySound(Option<&'a str>),
+    Silence,
+    /// The `kind` query parameter, or `""` if it is missing.
+    Button(&'a str),
+    Display,
+    Mirror,
+    /// The query, with its still percent-encoded `text`, `times`, and `blink`.
+    PostMessage(&'a str),
+    Version,
+    Memory,
+    Hold,
+    Resume,
+    Off,
+    Climate,
+    Reboot,
+    LastCrash,
+    ClearLastCrash,
+    /// The `state` and `ms` query parameters.
+    Switch {
+        state: &'a str,
+        ms: Option<&'a str>,
+    },
+    GetTimer,
+    /// The `seconds` query parameter, or `""` if it is missing.
+    StartTimer(&'a str),
+    CancelTimer,
+    NotFound,
+}
+
+impl<'a> Route<'a> {
+    fn parse(method: &str, target: &'a str) -> Self {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        match (method, path) {
+            ("GET", "/scan") => Self::Scan,
+            ("GET", "/metrics") => Self::Metrics,
+            ("GET", "/reminders") => Self::GetReminders,
+            ("PUT", "/reminders") => {
+                Self::PutReminders(query_param(query, "list").unwrap_or_default())
+            }
+            ("POST", "/message") => Self::PostMessage(query),
+            ("GET", "/gestures") => Self::GetGestures,
+            ("PUT", "/gestures") => Self::PutGestures(query),
+            ("PUT", "/time") => Self::PutTime(query_param(query, "local").unwrap_or_default()),
+            ("GET", "/config") => Self::GetConfig,
+            ("PUT", "/config") => Self::PutConfig(query),
+            ("POST", "/sound") => Self::PlaySound(query_param(query, "name")),
+            ("DELETE", "/sound") => Self::Silence,
+            ("POST", "/button") => Self::Button(query_param(query, "kind").unwrap_or_default()),
+            ("GET", "/display") => Self::Display,
+            ("GET", "/mirror") => Self::Mirror,
+            ("GET", "/version") => Self::Version,
+            ("GET", "/memory") => Self::Memory,
+            ("POST", "/hold") => Self::Hold,
+            ("POST", "/resume") => Self::Resume,
+            ("POST", "/off") => Self::Off,
+            ("GET", "/climate.csv") => Self::Climate,
+            ("POST", "/reboot") => Self::Reboot,
+            ("GET", "/last-crash") => Self::LastCrash,
+            ("DELETE", "/last-crash") => Self::ClearLastCrash,
+            ("POST", "/switch") => Self::Switch {
+                state: query_param(query, "state").unwrap_or_default(),
+                ms: query_param(query, "ms"),
+            },
+            ("GET", "/timer") => Self::GetTimer,
+            ("POST", "/timer") => {
+                Self::StartTimer(query_param(query, "seconds").unwrap_or_default())
+            }
+            ("DELETE", "/timer") => Self::CancelTimer,
+            _ => Self::NotFound,
+        }
+    }
+}
+
+/// The value of `name` in a query string such as `text=HI&times=2`.
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query.split('&').find_map(|pair| {
+        pair.split_once('=')
+            .filter(|&(key, _)| key == name)
+            .map(|(_, value)| value)
+    })
+}
+
+/// The command for a `/switch` request; a pulse lasts one second unless `ms` says otherwise.
+pub(crate) fn switch_command(state: &str, ms: Option<&str>) -> Option<SwitchCommand> {
+    match state {
+        "on" => Some(SwitchCommand::On),
+        "off" => Some(SwitchCommand::Off),
+        "pulse" => {
+            let millis = ms.map_or(Some(1000), |val| val.parse::<u64>().ok())?;
+            let duration = Duration::from_millis(millis);
+            (millis > 0 && duration <= MAX_PULSE).then_some(SwitchCommand::Pulse(duration))
+        }
+        _ => None,
+    }
+}
+
+#[embassy_executor::task]
+async fn device_loop(
+    wifi: &'static Wifi,
+    clock: Clock<'static>,
+    storage: &'static SharedStorage,
+    off_requests: &'static OffRequests,
+    switch: Switch<'static>,
+    soft_reboot: SoftReboot<'static>,
+) -> ! {
+    let Some(&stack) = wifi.stack().await else {
+        info!("HTTP server disabled: WiFi unavailable");
+        loop {
+            core::future::pending::<()>().await;
+        }
+    };
+    info!("HTTP server listening on port {}", HTTP_PORT);
+
+    let mut rx_buffer = [0_u8; SOCKET_BUFFER_LEN];
+    let mut tx_buffer = [0_u8; SOCKET_BUFFER_LEN];
+    loop {
+        let mut socket = TcpSocket::new(stack, &mut rx_buffer, &mut tx_buffer);
+        socket.set_timeout(Some(HTTP_TIMEOUT));
+        if let Err(err) = socket.accept(HTTP_PORT).await {
+            warn!("HTTP accept failed: {:?}", err);
+            continue;
+        }
+        if let Err(err) = handle_connection(
+            &mut socket,
+            wifi,
+            clock,
+            storage,
+            off_requests,
+            switch,
+            soft_reboot,
+        )
+        .await
+        {
+            warn!("HTTP request failed: {}", Display2Format(&err));
+        }
+        socket.close();
+        // Let the close handshake finish before the buffers are reused
+        if let Err(err) = socket.flush().await {
+            warn!("HTTP flush failed: {:?}", err);
+        }
+    }
+}
+
+async fn handle_connection(
+    socket: &mut TcpSocket<'_>,
+    wifi: &Wifi,
+    clock: Clock<'_>,
+    storage: &SharedStorage,
+    off_requests: &OffRequests,
+    switch: Switch<'_>,
+    soft_reboot: SoftReboot<'_>,
+) -> Result<()> {
+    let mut request = [0_u8; MAX_REQUEST_LEN];
+    let request_len = read_request_line(socket, &mut request).await?;
+    let Some((method, target)) = parse_request_line(request.get(..request_len).unwrap_or_default())
+    else {
+        return respond(socket, "400 Bad Request", "text/plain", "Bad Request").await;
+    };
+    info!("HTTP {=str} {=str}", method, target);
+
+    let mut body = String::<MAX_BODY_LEN>::new();
+    match Route::parse(method, target) {
+        Route::Scan => {
+            write_scan_json(&mut body, &wifi.scan().await)?;
+            respond(socket, "200 OK", "application/json", &body).await
+        }
+        Route::Metrics => {
+            if let Some(quiet) = network_quiet() {
+                return respond(socket, "503 Service Unavailable", "text/plain", quiet.name())
+                    .await;
+            }
+            write_metrics(
+                &mut body,
+                &skew_history(),
+                supply_millivolts(),
+                &time_arbiter(),
+                wall_clock_at,
+            )?;
+            respond(socket, "200 OK", "text/plain; version=0.0.4", &body).await
+        }
+        Route::GetReminders => {
+            storage.lock().await.load_reminders()?.write_list(&mut body)?;
+            respond(socket, "200 OK", "text/plain", &body).await
+        }
+        Route::PutReminders(list) => {
+            let Ok(reminders) = Reminders::parse(list) else {
+                return respond(
+                    socket,
+                    "400 Bad Request",
+                    "text/plain",
+                    "Expected ?list=MMDDLABL,... with up to 8 entries",
+                )
+                .await;
+            };
+            storage.lock().await.save_reminders(&reminders)?;
+            clock.set_reminders(reminders).await;
+            reminders.write_list(&mut body)?;
+            respond(socket, "200 OK", "text/plain", &body).await
+        }
+        Route::GetGestures => {
+            gesture_timings().write_list(&mut body)?;
+            respond(socket, "200 OK", "text/plain", &body).await
+        }
+        Route::PutGestures(changes) => {
+            let Ok(timings) = gesture_timings().with_changes(changes) else {
+                return respond(
+                    socket,
+                    "400 Bad Request",
+                    "text/plain",
+                    "Expected ?long=200..3000&double=100..1500&very_long=3000..60000 (ms, very \
+                     long at least twice long)",
+                )
+                .await;
+            };
+            storage.lock().await.save_gesture_timings(&timings)?;
+            set_gesture_timings(timings);
+            timings.write_list(&mut body)?;
+            respond(socket, "200 OK", "text/plain", &body).await
+        }
+        Route::PutTime(local) => {
+            let Some((hours, minutes, seconds)) = parse_time_of_day(local) else {
+                return respond(
+                    socket,
+                    "400 Bad Request",
+                    "text/plain",
+                    "Expected ?local=HH:MM or HH:MM:SS (24-hour)",
+                )
+                .await;
+            };
+            clock.set_time(hours, minutes, seconds).await;
+            respond(socket, "200 OK", "text/plain", "OK").await
+        }
+        Route::GetConfig => {
+            config().write_list(&mut body)?;
+            respond(socket, "200 OK", "text/plain", &body).await
+        }
+        Route::PutConfig(changes) => {
+            let Ok(new_config) = config().with_changes(changes) else {
+                return respond(
+                    socket,
+                    "400 Bad Request",
+                    "text/plain",
+                    "Expected ?blink_on=20..2000&blink_off=20..2000&multiplex_us=500..5000\
+                     &blanking_us=0..250&segment_compensation=0..50&sync_minutes=5..1440\
+                     &tick_volume=0..100\
+                     &alarm_sound=beep|beeps|chime|tune|custom",
+                )
+                .await;
+            };
+            storage.lock().await.save_config(&new_config)?;
+            set_config(new_config);
+            clock
+                .set_blink_timing(new_config.blink_on_ms.into(), new_config.blink_off_ms.into())
+                .await;
+            new_config.write_list(&mut body)?;
+            respond(socket, "200 OK", "text/plain", &body).await
+        }
+        Route::PostMessage(query) => {
+            let Some(message) = parse_message(query) else {
+                return respond(
+                    socket,
+                    "400 Bad Request",
+                    "text/plain",
+                    "Expected ?text=... (up to 32 characters) with optional &times=1..20&blink=1",
+                )
+                .await;
+            };
+            clock.show_message(message).await;
+            respond(socket, "200 OK", "text/plain", "OK").await
+        }
+        Route::PlaySound(name) => {
+            let Some(sound) = name.map_or(Some(config().alarm_sound), AlarmSound::parse) else {
+                return respond(
+                    socket,
+                    "400 Bad Request",
+                    "text/plain",
+                    "Expected ?name=beep, beeps, chime, tune, or custom",
+                )
+                .await;
+            };
+            play_sound(sound);
+            respond(socket, "200 OK", "text/plain", "OK").await
+        }
+        Route::Silence => {
+            silence_sound();
+            respond(socket, "200 OK", "text/plain", "OK").await
+        }
+        Route::Button(kind) => {
+            let presses: &[PressDuration] = match kind {
+                "short" => &[PressDuration::Short],
+                "long" => &[PressDuration::Long],
+                "double" => &[PressDuration::Short, PressDuration::Short],
+                _ => {
+                    return respond(
+                        socket,
+                        "400 Bad Request",
+                        "text/plain",
+                        "Expected ?kind=short, long, or double",
+                    )
+                    .await;
+                }
+            };
+            if !simulate_presses(presses) {
+                return respond(
+                    socket,
+                    "503 Service Unavailable",
+                    "text/plain",
+                    "Earlier presses are still waiting",
+                )
+                .await;
+            }
+            respond(socket, "200 OK", "text/plain", "OK").await
+        }
+        Route::Display => {
+            write_display_json(&mut body, shown_frame())?;
+            respond(socket, "200 OK", "application/json", &body).await
+        }
+        Route::Mirror => respond(socket, "200 OK", "text/html", MIRROR_PAGE).await,
+        Route::Memory => {
+            write_memory_report(&mut body)?;
+            respond(socket, "200 OK", "text/plain", &body).await
+        }
+        Route::Version => {
+            write!(
+                body,
+                "{{\"version\":\"{}\",\"git\":\"{}\",\"built\":\"{}\"}}",
+                build_info::VERSION,
+                build_info::GIT_HASH,
+                build_info::BUILD_DATE
+            )?;
+            respond(socket, "200 OK", "application/json", &body).await
+        }
+        Route::Hold => {
+            clock.hold().await;
+            respond(socket, "200 OK", "text/plain", "OK").await
+        }
+        Route::Resume => {
+            clock.resume().await;
+            respond(socket, "200 OK", "text/plain", "OK").await
+        }
+        Route::Off => {
+            // Reply before the radio goes down, or the client never hears back
+            respond(socket, "200 OK", "text/plain", "OK").await?;
+            off_requests.signal(());
+            Ok(())
+        }
+        Route::Climate => respond_climate_csv(socket, storage, &mut body).await,
+        Route::LastCrash => match storage.lock().await.load_crash_report()? {
+            Some(report) => {
+                write!(body, "{report}")?;
+                respond(socket, "200 OK", "text/plain", &body).await
+            }
+            None => respond(socket, "200 OK", "text/plain", "No crash saved\n").await,
+        },
+        Route::ClearLastCrash => {
+            storage.lock().await.clear_crash_report()?;
+            respond(socket, "200 OK", "text/plain", "OK").await
+        }
+        Route::Reboot => {
+            // Reply before rebooting, or the client never hears back
+            respond(socket, "200 OK", "text/plain", "OK").await?;
+            socket.flush().await?;
+            soft_reboot.reboot();
+            Ok(())
+        }
+        Route::Switch { state, ms } => {
+            let Some(command) = switch_command(state, ms) else {
+                return respond(
+                    socket,
+                    "400 Bad Request",
+                    "text/plain",
+                    "Expected ?state=on, ?state=off, or ?state=pulse with optional &ms=1..3600000",
+                )
+                .await;
+            };
+            switch.command(command);
+            respond(socket, "200 OK", "text/plain", "OK").await
+        }
+        Route::GetTimer => match countdown_remaining() {
+            Some(remaining) => {
+                writeln!(body, "{}", remaining.as_secs())?;
+                respond(socket, "200 OK", "text/plain", &body).await
+            }
+            None => respond(socket, "200 OK", "text/plain", "Not running\n").await,
+        },
+        Route::StartTimer(seconds) => {
+            let Some(seconds) = seconds
+                .parse::<u64>()
+                .ok()
+                .filter(|seconds| (1..=MAX_TIMER_SECONDS).contains(seconds))
+            else {
+                return respond(
+                    socket,
+                    "400 Bad Request",
+                    "text/plain",
+                    "Expected ?seconds=1..86400",
+                )
+                .await;
+            };
+            start_countdown(Duration::from_secs(seconds));
+            respond(socket, "200 OK", "text/plain", "OK").await
+        }
+        Route::CancelTimer => {
+            cancel_countdown();
+            respond(socket, "200 OK", "text/plain", "OK").await
+        }
+        Route::NotFound => respond(socket, "404 Not Found", "text/plain", "Not Found").await,
+    }
+}
+
+// ============================================================================
+// Protocol Helpers
+// ============================================================================
+
+/// Reads until the request line is complete (or `buffer` is full) and returns the number of
+/// bytes read.
+async fn read_request_line(socket: &mut TcpSocket<'_>, buffer: &mut [u8]) -> Result<usize> {
+    let has_line_end = |bytes: &[u8]| bytes.windows(2).any(|window| window == b"\r\n");
+    Ok(read_until(socket, buffer, has_line_end).await?)
+}
+
+/// Splits a request line such as `GET /scan HTTP/1.1` into its method and target.
+fn parse_request_line(request: &[u8]) -> Option<(&str, &str)> {
+    let line_len = request.windows(2).position(|window| window == b"\r\n")?;
+    let line = core::str::from_utf8(request.get(..line_len)?).ok()?;
+    let mut parts = line.split(' ');
+    Some((parts.next()?, parts.next()?))
+}
+
+/// The message a query such as `text=DOOR+OPEN&times=3&blink=1` asks for (see
+/// `POST /message`), or `None` if it is malformed.
+pub(crate) fn parse_message(query: &str) -> Option<Message> {
+    let mut decoded = Vec::<u8, MAX_MESSAGE_BYTES>::new();
+    let text = percent_decode(query_param(query, "text").unwrap_or_default(), &mut decoded)?;
+    let repeats = query_param(query, "times").map_or(Some(1), |val| val.parse::<u8>().ok())?;
+    let blink = query_param(query, "blink").is_some_and(|val| val == "1" || val == "true");
+    let blink_state = if blink {
+        BlinkState::BlinkingAndOn
+    } else {
+        BlinkState::Solid
+    };
+    Message::new(text, repeats, blink_state).ok()
+}
+
+/// Decodes a percent-encoded query value (`+` is a space) into `buffer` as UTF-8.
+/// Returns `None` if it is malformed or does not fit.
+fn percent_decode<'a, const N: usize>(
+    encoded: &str,
+    buffer: &'a mut Vec<u8, N>,
+) -> Option<&'a str> {
+    let mut bytes = encoded.bytes();
+    while let Some(byte) = bytes.next() {
+        let decoded = match byte {
+            b'+' => b' ',
+            b'%' => {
+                let high = char::from(bytes.next()?).to_digit(16)?;
+                let low = char::from(bytes.next()?).to_digit(16)?;
+                u8::try_from(high.checked_mul(16)?.checked_add(low)?).ok()?
+            }
+            _ => byte,
+        };
+        buffer.push(decoded).ok()?;
+    }
+    core::str::from_utf8(buffer).ok()
+}
+
+async fn respond(
+    socket: &mut TcpSocket<'_>,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> Result<()> {
+    let mut header = String::<128>::new();
+    write!(
+        header,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    socket.write_all(header.as_bytes()).await?;
+    Ok(socket.write_all(body.as_bytes()).await?)
+}
+
+/// Streams the climate log as CSV, oldest sample first. It can be far larger than one body,
+/// so it is sent in `body`-sized chunks without a `Content-Length`, ending when the
+/// connection closes.
+async fn respond_climate_csv(
+    socket: &mut TcpSocket<'_>,
+    storage: &SharedStorage,
+    chunk: &mut String<MAX_BODY_LEN>,
+) -> Result<()> {
+    socket
+        .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/csv\r\nConnection: close\r\n\r\n")
+        .await?;
+    chunk.write_str(CLIMATE_CSV_HEADER)?;
+    let mut slot = storage.lock().await.next_climate_slot()?;
+    for _ in 0..CLIMATE_LOG_SLOTS {
+        // Lock per sample, so a long download does not hold up saving settings
+        if let Some(sample) = storage.lock().await.climate_sample(slot)? {
+            if chunk.len() > MAX_BODY_LEN.saturating_sub(MAX_CSV_ROW_LEN) {
+                socket.write_all(chunk.as_bytes()).await?;
+                chunk.clear();
+            }
+            sample.write_csv_row(chunk)?;
+        }
+        slot = next_slot(slot);
+    }
+    Ok(socket.write_all(chunk.as_bytes()).await?)
+}
+
+// ============================================================================
+// Bodies
+// ============================================================================
+
+/// Writes the sync corrections as Prometheus text, labeled by age (`0` is the latest sync).
+fn write_metrics(
+    out: &mut impl Write,
+    history: &SkewHistory,
+    supply_millivolts: Option<u16>,
+    arbiter: &TimeArbiter,
+    wall_clock_at: impl Fn(Instant) -> Option<UnixSeconds>,
+) -> core::fmt::Result {
+    out.write_str("# HELP clock_sync_correction_ms Correction applied at a recent sync; positive means the clock was behind.\n")?;
+    out.write_str("# TYPE clock_sync_correction_ms gauge\n")?;
+    for (age, correction_millis) in history.iter_recent_first().enumerate() {
+        writeln!(out, "clock_sync_correction_ms{{age=\"{age}\"}} {correction_millis}")?;
+    }
+    if let Some(millivolts) = supply_millivolts {
+        out.write_str("# HELP clock_supply_millivolts The supply voltage, when monitored.\n")?;
+        out.write_str("# TYPE clock_supply_millivolts gauge\n")?;
+        writeln!(out, "clock_supply_millivolts {millivolts}")?;
+    }
+    if let Some((source, set_at)) = arbiter.current() {
+        out.write_str("# HELP clock_time_source_age_seconds Time since the source that disciplines the clock last set it.\n")?;
+        out.write_str("# TYPE clock_time_source_age_seconds gauge\n")?;
+        let age = set_at.elapsed().as_secs();
+        writeln!(out, "clock_time_source_age_seconds{{source=\"{}\"}} {age}", source.name())?;
+        if let Some(set_unix_seconds) = wall_clock_at(set_at) {
+            out.write_str("# HELP clock_time_source_set_timestamp_seconds When that source last set the clock, in Unix time.\n")?;
+            out.write_str("# TYPE clock_time_source_set_timestamp_seconds gauge\n")?;
+            writeln!(
+                out,
+                "clock_time_source_set_timestamp_seconds{{source=\"{}\"}} {}",
+                source.name(),
+                set_unix_seconds.as_i64()
+            )?;
+        }
+    }
+    out.write_str("# HELP clock_time_source_outranked_total Times ignored because a better source was fresh.\n")?;
+    out.write_str("# TYPE clock_time_source_outranked_total counter\n")?;
+    writeln!(out, "clock_time_source_outranked_total {}", arbiter.outranked_count())?;
+    out.write_str("# HELP clock_sync_failures_total Syncs that got no time, by reason.\n")?;
+    out.write_str("# TYPE clock_sync_failures_total counter\n")?;
+    for (error, count) in sync_failures() {
+        writeln!(out, "clock_sync_failures_total{{reason=\"{}\"}} {count}", error.name())?;
+    }
+    Ok(())
+}
+
+/// Writes what the display shows as JSON: how many segments each cell has, each cell's lit
+/// segments, whether each cell is dimmed, and the display's brightness.
+fn write_display_json(out: &mut impl Write, frame: Frame) -> core::fmt::Result {
+    let (bit_matrix, brightness) = frame;
+    write!(out, "{{\"segments\":{SEGMENT_COUNT},\"cells\":[")?;
+    for (index, bits) in bit_matrix.iter().enumerate() {
+        if index > 0 {
+            out.write_char(',')?;
+        }
+        write!(out, "{bits}")?;
+    }
+    out.write_str("],\"dimmed\":[")?;
+    for index in 0..CELL_COUNT {
+        if index > 0 {
+            out.write_char(',')?;
+        }
+        write!(out, "{}", bit_matrix.is_dimmed(index))?;
+    }
+    write!(out, "],\"brightness\":{}}}", brightness.level())
+}
+
+fn write_scan_json(out: &mut impl Write, networks: &[WifiScanResult]) -> core::fmt::Result {
+    out.write_char('[')?;
+    for (index, network) in networks.iter().enumerate() {
+        if index > 0 {
+            out.write_char(',')?;
+        }
+        out.write_str("{\"ssid\":")?;
+        write_json_string(out, &network.ssid)?;
+        write!(out, ",\"rssi\":{}}}", network.rssi)?;
+    }
+    out.write_char(']')
+}
+
+/// Writes `text` as a quoted JSON string, escaping quotes, backslashes, and control characters.
+fn write_json_string(out: &mut impl Write, text: &str) -> core::fmt::Result {
+    out.write_char('"')?;
+    for char in text.chars() {
+        match char {
+            '"' | '\\' => {
+                out.write_char('\\')?;
+                out.write_char(char)?;
+            }
+            _ if char.is_control() => write!(out, "\\u{:04x}", u32::from(char))?,
+            _ => out.write_char(char)?,
+        }
+    }
+    out.write_char('"')
+}
@@ -0,0 +1,187 @@
+//! HTTP client - sends one request over a fresh TCP connection and reads the response into a
+//! caller's buffer, without a heap
+//!
+//! Devices that fetch from the web (the weather, the HTTP date time fallback) share this
+//! instead of each opening its own socket. `https://` URLs need the `tls` feature.
+
+use core::fmt::Write;
+
+use embassy_net::{dns::DnsQueryType, tcp::TcpSocket, Stack};
+use embassy_time::Duration;
+use embedded_io_async::{Error as _, Read, Write as _};
+use heapless::String;
+
+use clock_core::HttpResponse;
+
+#[cfg(feature = "tls")]
+use crate::tls;
+use crate::wifi::TransmitBurst;
+use crate::{info, warn};
+
+/// The longest request line and headers (the URL's path and host make up most of it).
+const MAX_REQUEST_HEAD_LEN: usize = 512;
+
+/// The TCP socket's receive buffer.
+const RX_BUFFER_LEN: usize = 1024;
+
+/// The TCP socket's transmit buffer.
+const TX_BUFFER_LEN: usize = 512;
+
+/// The buffers a request holds on its caller's stack, besides the caller's response buffer.
+pub(crate) const RAM_BYTES: usize = RX_BUFFER_LEN + TX_BUFFER_LEN + MAX_REQUEST_HEAD_LEN;
+
+/// How long the server may go quiet before the request is abandoned.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The HTTP methods the client sends.
+#[cfg_attr(feature = "logging", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Method {
+    Get,
+    /// Like `Get`, but the server sends only the headers.
+    Head,
+}
+
+impl Method {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Get => "GET",
+            Self::Head => "HEAD",
+        }
+    }
+}
+
+/// Whether a URL is fetched in the clear or over TLS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Scheme {
+    Http,
+    Https,
+}
+
+/// Sends `method` for `url` and reads the response into `buffer`.
+///
+/// Reading stops when the response is complete, when the server closes the connection, or
+/// when `buffer` is full, in which case the body is cut short. A chunked body is decoded.
+///
+/// # Errors
+///
+/// Returns what went wrong, for logging, if the URL is not `http://` (or `https://` with the
+/// `tls` feature), the host cannot be resolved or reached, the TLS handshake fails, or the
+/// response cannot be parsed. Any status code is a success.
+pub(crate) async fn request<'buf>(
+    stack: &Stack<'static>,
+    method: Method,
+    url: &str,
+    buffer: &'buf mut [u8],
+) -> Result<HttpResponse<'buf>, &'static str> {
+    let (scheme, host, port, path) = split_http_url(url).ok_or("Unsupported URL")?;
+
+    info!("Resolving {}...", host);
+    let dns_result = stack
+        .dns_query(host, DnsQueryType::A)
+        .await
+        .map_err(|err| {
+            warn!("DNS lookup failed: {:?}", err);
+            "DNS lookup failed"
+        })?;
+    let server_addr = *dns_result.first().ok_or("No DNS results")?;
+
+    let mut rx_buffer = [0; RX_BUFFER_LEN];
+    let mut tx_buffer = [0; TX_BUFFER_LEN];
+    let mut socket = TcpSocket::new(*stack, &mut rx_buffer, &mut tx_buffer);
+    socket.set_timeout(Some(TIMEOUT));
+    socket.connect((server_addr, port)).await.map_err(|err| {
+        warn!("HTTP connect failed: {:?}", err);
+        "HTTP connect failed"
+    })?;
+
+    let mut head = String::<MAX_REQUEST_HEAD_LEN>::new();
+    write!(
+        head,
+        "{} {path} HTTP/1.1\r\nHost: {host}\r\nConnection: close\r\n",
+        method.as_str()
+    )
+    .map_err(|_| "URL too long")?;
+    head.push_str("\r\n").map_err(|_| "URL too long")?;
+
+    let head_only = method == Method::Head;
+    let len = match scheme {
+        Scheme::Http => exchange(&mut socket, head.as_bytes(), head_only, buffer).await,
+        #[cfg(feature = "tls")]
+        Scheme::Https => tls::exchange(&mut socket, host, head.as_bytes(), head_only, buffer).await,
+        #[cfg(not(feature = "tls"))]
+        Scheme::Https => Err("HTTPS needs the `tls` feature"),
+    };
+    socket.close();
+
+    HttpResponse::parse(buffer.get_mut(..len?).unwrap_or_default()).ok_or("Bad HTTP response")
+}
+
+/// Sends a request's `head` over `transport` and reads the response into `buffer`, returning
+/// how many bytes arrived.
+pub(crate) async fn exchange<T: Read + embedded_io_async::Write>(
+    transport: &mut T,
+    head: &[u8],
+    head_only: bool,
+    buffer: &mut [u8],
+) -> Result<usize, &'static str> {
+    let send_failed = |err: T::Error| {
+        warn!("HTTP send failed: {:?}", err.kind());
+        "HTTP send failed"
+    };
+    {
+        // Dim only while the request goes out, not while waiting for the answer
+        let _burst = TransmitBurst::start();
+        transport.write_all(head).await.map_err(send_failed)?;
+        transport.flush().await.map_err(send_failed)?;
+    }
+
+    read_until(transport, buffer, |received| HttpResponse::is_complete(received, head_only))
+        .await
+        .map_err(|err| {
+            warn!("HTTP receive failed: {:?}", err.kind());
+            "HTTP receive failed"
+        })
+}
+
+/// Reads from `transport` into `buffer` until `complete` says the bytes so far are enough,
+/// the buffer is full, or the other end closes, and returns how many bytes arrived. The HTTP
+/// server reads its requests with this too.
+pub(crate) async fn read_until<T: Read>(
+    transport: &mut T,
+    buffer: &mut [u8],
+    complete: impl Fn(&[u8]) -> bool,
+) -> Result<usize, T::Error> {
+    let mut len = 0_usize;
+    while let Some(unread) = buffer.get_mut(len..).filter(|unread| !unread.is_empty()) {
+        let read = transport.read(unread).await?;
+        if read == 0 {
+            break;
+        }
+        len = len.saturating_add(read);
+        if complete(buffer.get(..len).unwrap_or_default()) {
+            break;
+        }
+    }
+    Ok(len)
+}
+
+/// Splits `http://host[:port][/path]` (or `https://`) into its scheme, host, port, and path.
+pub(crate) fn split_http_url(url: &str) -> Option<(Scheme, &str, u16, &str)> {
+    let (scheme, rest) = url
+        .strip_prefix("http://")
+        .map(|rest| (Scheme::Http, rest))
+        .or_else(|| url.strip_prefix("https://").map(|rest| (Scheme::Https, rest)))?;
+    let default_port = match scheme {
+        Scheme::Http => 80,
+        Scheme::Https => 443,
+    };
+    let (authority, path) = rest
+        .find('/')
+        .map_or((rest, "/"), |slash| rest.split_at(slash));
+    let (host, port) = match authority.split_once(':') {
+        Some((host, port)) => (host, port.parse().ok()?),
+        None => (authority, default_port),
+    };
+    Some((scheme, host, port, path))
+}
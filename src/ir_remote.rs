@@ -0,0 +1,99 @@
+//! IR remote virtual device - turns NEC remote keys into button presses
+
+use embassy_executor::{SpawnError, Spawner};
+use embassy_futures::select::{select, Either};
+use embassy_rp::gpio;
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, signal::Signal};
+use embassy_time::{Duration, Instant, Timer};
+
+use clock_core::{NecDecoder, NecEvent};
+
+use crate::info;
+use crate::button::PressDuration;
+use crate::settings::{ir_remote_keys, IrRemoteKeys};
+
+/// A space longer than any in a frame, after which the receiver is considered idle.
+const IDLE_SPACE: Duration = Duration::from_millis(20);
+
+/// IR remote virtual device - reads an IR receiver module and reports mapped keys as presses
+///
+/// Keys are mapped with `IR_MODE_KEY` and `IR_SET_KEY` at build time. Unmapped keys are
+/// logged with their codes, so a new remote can be learned from the log.
+pub struct IrRemote;
+
+/// A type alias for the notifier that carries presses from the `IrRemote` to the `Button`.
+pub type IrRemoteNotifier = Signal<CriticalSectionRawMutex, PressDuration>;
+
+impl IrRemote {
+    /// Create a new `IrRemote` instance, which entails starting an Embassy task.
+    ///
+    /// # Arguments
+    ///
+    /// * `pin` - The receiver module's output, which idles high (use a pull-up).
+    /// * `notifier` - The static notifier that carries presses. Pass it to
+    ///          `Button::add_remote` too. It is created with `IrRemote::notifier()`.
+    /// * `spawner` - The spawner that will spawn the task that reads the receiver.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SpawnError` if the task cannot be spawned.
+    #[must_use = "Must be used to manage the spawned task"]
+    pub fn new(
+        pin: gpio::Input<'static>,
+        notifier: &'static IrRemoteNotifier,
+        spawner: Spawner,
+    ) -> Result<Self, SpawnError> {
+        spawner.spawn(device_loop(pin, notifier, ir_remote_keys()))?;
+        Ok(Self)
+    }
+
+    /// Creates a new `IrRemoteNotifier` instance.
+    ///
+    /// This should be assigned to a static variable and passed to the `IrRemote::new()` method.
+    #[must_use]
+    pub const fn notifier() -> IrRemoteNotifier {
+        Signal::new()
+    }
+}
+
+#[embassy_executor::task]
+async fn device_loop(
+    mut pin: gpio::Input<'static>,
+    notifier: &'static IrRemoteNotifier,
+    keys: IrRemoteKeys,
+) -> ! {
+    let mut decoder = NecDecoder::new();
+    pin.wait_for_low().await;
+    loop {
+        let mark_start = Instant::now();
+        pin.wait_for_high().await;
+        let space_start = Instant::now();
+        let mark = space_start.saturating_duration_since(mark_start);
+        let idle = matches!(
+            select(pin.wait_for_low(), Timer::after(IDLE_SPACE)).await,
+            Either::Second(())
+        );
+        let space = Instant::now().saturating_duration_since(space_start);
+        let event = decoder.push(micros(mark), micros(space));
+        if let Some(NecEvent::Command { address, command }) = event {
+            match keys.press_for(command) {
+                Some(press_duration) => {
+                    info!("IR key {:#04x}: {:?}", command, press_duration);
+                    notifier.signal(press_duration);
+                }
+                None => {
+                    info!("IR key {:#04x} from address {:#06x} is not mapped", command, address);
+                }
+            }
+        }
+        // Held keys only repeat; a press is reported once
+        if idle {
+            pin.wait_for_low().await;
+        }
+    }
+}
+
+/// A pulse length in whole microseconds, saturating (far longer than any NEC pulse).
+fn micros(duration: Duration) -> u32 {
+    u32::try_from(duration.as_micros()).unwrap_or(u32::MAX)
+}
@@ -1,37 +1,126 @@
 //! Shared items for the clock project.
+//!
+//! Another firmware crate can depend on this one as a library. `ClockApp::run` starts the
+//! standard clock with one call; its documentation lists the order to create the virtual
+//! devices in when wiring a different set by hand.
 #![no_std]
 #![no_main]
-#![feature(never_type)]
 
-mod bit_matrix;
+mod action;
 mod blink_state;
 mod blinker;
+pub mod build_info;
 mod button;
+mod buzzer;
+mod climate_log;
 mod clock;
+mod clock_app;
 mod clock_state;
-mod clock_time;
+mod config;
+mod crash_dump;
 mod display;
 mod error;
+mod fmt;
+mod gps;
 mod hardware;
-mod leds;
+mod http;
+mod http_client;
+mod ir_remote;
+mod memory_report;
+mod metrics;
+mod mqtt;
+mod network_policy;
+#[cfg(feature = "nixie")]
+mod nixie;
 mod output_array;
+mod provisioning;
+mod radio_time;
+mod scheduler;
+mod settings;
 mod shared_constants;
+mod soft_reboot;
+mod stack_guard;
+#[cfg(feature = "oled")]
+mod status_display;
+mod storage;
+mod supply;
+mod switch;
+#[cfg(feature = "talking-clock")]
+mod talker;
 mod time_sync;
-mod unix_seconds;
+#[cfg(feature = "tls")]
+mod tls;
+mod touch_pad;
+mod usb_console;
+mod weather;
 mod wifi;
+#[cfg(feature = "ws2812")]
+mod ws2812;
 
 // Re-export commonly used items
-pub use blink_state::BlinkState;
-pub use blinker::{Blinker, BlinkerNotifier};
-pub use button::Button;
-pub use clock::{Clock, ClockNotifier, ClockOuterNotifier};
-pub use clock_state::ClockState;
-pub use clock_time::ClockTime;
-pub use display::{Display, DisplayNotifier};
+pub use action::Action;
+pub use blink_state::BlinkTiming;
+pub use blinker::{
+    BlinkTimingNotifier, Blinker, BlinkerNotifier, BlinkerOuterNotifier, BrightnessNotifier,
+    PageNotifier,
+};
+pub use button::{
+    gesture_timings, set_gesture_timings, simulate_presses, ActiveLevel, Button, ButtonConfig,
+    ButtonEvent, ButtonEvents, ButtonMonitor, PressDuration,
+};
+pub use buzzer::Buzzer;
+pub use climate_log::ClimateLog;
+pub use clock::{
+    subscribe_midnight, Clock, ClockNotice, ClockNotifier, ClockOuterNotifier, MidnightTicks,
+};
+pub use clock_app::{ClockApp, ClockAppBuilder, Subsystem};
+pub use clock_core::{
+    centered, centered_index, hour_digits, join, right_align, temperature_chars, two_digits,
+    BitMatrix, BlinkState, Brightness, Carousel, ClockState, ClockTime, DisplayContent,
+    LeapIndicator, Leds, Page, PendingSettings, Settings, Temperature, Text, TimeSource,
+    UnixSeconds,
+};
+pub use clock_state::{ClockStateExt, OffRequests};
+pub use config::{config, set_config};
+pub use crash_dump::{CrashReport, CRASH_RECORD_LEN, MAX_CRASH_FILE_LEN, MAX_CRASH_TEXT_LEN};
+pub use display::{Display, DisplayDriver, DisplayNotifier, Frame, MultiplexedDisplay};
 pub use error::{Error, Result};
-pub use hardware::Hardware;
-pub use leds::Leds;
+pub use fmt::Display2Format;
+pub use gps::Gps;
+pub use hardware::{ButtonPin, CellPins, DisplayBackend, Hardware, SegmentPins};
+pub use http::HttpServer;
+pub use ir_remote::{IrRemote, IrRemoteNotifier};
+pub use memory_report::{
+    log_memory_report, paint_stack, stack_bytes, stack_guard_intact, stack_high_water,
+    statics_bytes, write_memory_report,
+    MIN_STACK_BYTES, RP2040_RAM_BYTES, STATIC_BYTES, STATIC_ITEMS,
+};
+pub use mqtt::Mqtt;
+#[cfg(feature = "nixie")]
+pub use nixie::NixieDisplay;
+pub use provisioning::{provisioning, set_provisioning};
+pub use radio_time::RadioTime;
+pub use scheduler::Scheduler;
+pub use settings::{
+    auto_return, build_unix_seconds, days_since, default_button_config, default_carousel,
+    default_gesture_timings, default_settings, default_utc_offset_minutes, leap_smear,
+    max_backward_hold, max_slew, max_time_jump, solar_dimming, time_auth_key, time_fallback_url,
+    time_server, weather_json_key, weather_url, MAX_TIME_AUTH_KEY_LEN,
+};
 pub use shared_constants::*;
-pub use time_sync::{TimeSync, TimeSyncNotifier};
-pub use unix_seconds::UnixSeconds;
-pub use wifi::{Wifi, WifiNotifier};
+pub use soft_reboot::{Snapshot, SoftReboot, SoftRebootNotifier};
+pub use stack_guard::StackGuard;
+#[cfg(feature = "oled")]
+pub use status_display::StatusDisplay;
+pub use storage::{SharedStorage, Storage};
+pub use supply::SupplyMonitor;
+pub use switch::{Switch, SwitchCommand, SwitchNotifier, MAX_PULSE};
+#[cfg(feature = "talking-clock")]
+pub use talker::Talker;
+pub use time_sync::{SyncAccuracy, TimeSync, TimeSyncEvent, TimeSyncNotifier};
+pub use touch_pad::{TouchPad, TouchPadNotifier};
+pub use usb_console::UsbConsole;
+pub use weather::Weather;
+pub use wifi::{Wifi, WifiNotifier, WifiScanResult, MAX_SCAN_RESULTS};
+#[cfg(feature = "ws2812")]
+pub use ws2812::Ws2812Display;
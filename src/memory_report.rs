@@ -0,0 +1,187 @@
+//! Memory report - how much RAM the clock's statics and buffers take and how deep the stack
+//! has gone. `memory.x` fails the link if the statics leave less than `MIN_STACK_BYTES`.
+
+use core::fmt::{self, Write};
+
+use crate::clock::ClockNotifier;
+use crate::clock_app::Subsystem;
+use crate::clock_state::OffRequests;
+use crate::info;
+use crate::ir_remote::IrRemoteNotifier;
+use crate::mqtt;
+use crate::soft_reboot::SoftRebootNotifier;
+use crate::storage::SharedStorage;
+use crate::switch::SwitchNotifier;
+use crate::time_sync::{self, TimeSyncNotifier};
+use crate::touch_pad::TouchPadNotifier;
+use crate::wifi;
+
+/// The RP2040's SRAM, used as one block (see `memory.x`).
+pub const RP2040_RAM_BYTES: usize = 264 * 1024;
+
+/// The stack the clock needs besides its statics. Embassy tasks have no stacks of their own:
+/// they all run on this one, and keep their state between awaits in statics.
+///
+/// The link fails if the statics leave less than this, by an `ASSERT` in `memory.x` that
+/// must be kept in step.
+pub const MIN_STACK_BYTES: usize = 16 * 1024;
+
+/// The statics and task buffers the report can name, in bytes. Each task's other state is
+/// laid out by the compiler and not counted.
+pub const STATIC_ITEMS: [(&str, usize); 17] = [
+    ("clock notifier", size_of::<ClockNotifier>()),
+    ("time sync notifier", size_of::<TimeSyncNotifier>()),
+    ("IR remote notifier", size_of::<IrRemoteNotifier>()),
+    ("touch pad notifier", size_of::<TouchPadNotifier>()),
+    ("soft reboot notifier", size_of::<SoftRebootNotifier>()),
+    ("switch notifier", size_of::<SwitchNotifier>()),
+    ("off requests", size_of::<OffRequests>()),
+    ("storage", size_of::<SharedStorage>()),
+    ("wifi", wifi::RAM_BYTES),
+    ("time sync buffers", time_sync::RAM_BYTES),
+    ("http buffers", Subsystem::Http.ram_bytes()),
+    ("weather buffers", Subsystem::Weather.ram_bytes()),
+    ("mqtt buffers", Subsystem::Mqtt.ram_bytes()),
+    ("mqtt outbox", mqtt::OUTBOX_BYTES),
+    ("sensor buffers", Subsystem::Sensors.ram_bytes()),
+    ("console buffers", Subsystem::Console.ram_bytes()),
+    ("alarm buffers", Subsystem::Alarm.ram_bytes()),
+];
+
+/// The sum of `STATIC_ITEMS`. It leaves out the task futures and the cyw43 driver's
+/// statics, so it is a breakdown, not the total (see `statics_bytes`).
+pub const STATIC_BYTES: usize = total(&STATIC_ITEMS);
+
+/// Adds up the bytes of `items`.
+const fn total(items: &[(&str, usize)]) -> usize {
+    let mut sum = 0_usize;
+    let mut rest = items;
+    while let [(_, bytes), tail @ ..] = rest {
+        sum = sum.saturating_add(*bytes);
+        rest = tail;
+    }
+    sum
+}
+
+/// The value `paint_stack` fills the unused stack with.
+const STACK_PAINT: u32 = 0xC0DE_C10C;
+
+/// The bottom of the stack that `stack_guard_intact` watches. A stack that reaches it is
+/// about to overwrite the statics below.
+const GUARD_BYTES: usize = 256;
+
+/// The stack left unpainted below the caller of `paint_stack`, for interrupts that arrive
+/// while it runs and for its own frame.
+const PAINT_MARGIN: usize = 256;
+
+// The linker's bounds for the stack: it grows down from `_stack_start` (the top of RAM)
+// toward `__sheap` (the end of the statics)
+#[expect(unsafe_code, reason = "The linker script defines these symbols.")]
+unsafe extern "C" {
+    safe static __sheap: u32;
+    safe static _stack_start: u32;
+}
+
+/// The lowest address the stack may reach.
+fn stack_bottom() -> usize {
+    core::ptr::addr_of!(__sheap).addr()
+}
+
+/// The address the stack starts at.
+fn stack_top() -> usize {
+    core::ptr::addr_of!(_stack_start).addr()
+}
+
+/// The RAM left for the stack once the statics are placed, in bytes.
+#[must_use]
+pub fn stack_bytes() -> usize {
+    stack_top().saturating_sub(stack_bottom())
+}
+
+/// The RAM all the statics take, as the linker placed them, in bytes.
+#[must_use]
+pub fn statics_bytes() -> usize {
+    RP2040_RAM_BYTES.saturating_sub(stack_bytes())
+}
+
+/// Fills the unused stack with a known value, so `stack_high_water` can tell how deep the
+/// stack has gone since. `ClockApp` calls it first thing.
+#[expect(unsafe_code, reason = "Writes the unused stack, which nothing else owns yet.")]
+pub fn paint_stack() {
+    cortex_m::interrupt::free(|_| {
+        let stack_pointer = usize::try_from(cortex_m::register::msp::read()).unwrap_or_default();
+        let end = stack_pointer.saturating_sub(PAINT_MARGIN);
+        for address in (stack_bottom()..end).step_by(size_of::<u32>()) {
+            // SAFETY: The words between the statics and the stack pointer, less the margin,
+            // are not in use, and interrupts, which would push below the stack pointer, are
+            // off
+            unsafe {
+                core::ptr::with_exposed_provenance_mut::<u32>(address).write_volatile(STACK_PAINT);
+            }
+        }
+    });
+}
+
+/// Whether the stack word at `address` still holds the paint.
+#[expect(unsafe_code, reason = "Reads the unused stack, which only changes by being used.")]
+fn painted(address: usize) -> bool {
+    // SAFETY: Callers pass aligned words of RAM between the statics and the top of the stack
+    unsafe { core::ptr::with_exposed_provenance::<u32>(address).read_volatile() == STACK_PAINT }
+}
+
+/// The most stack used since `paint_stack`, in bytes: from the top of the stack down to
+/// the lowest word that no longer holds the paint. It reads every unused word, so it takes
+/// a millisecond or two.
+#[must_use]
+pub fn stack_high_water() -> usize {
+    let top = stack_top();
+    let deepest = (stack_bottom()..top)
+        .step_by(size_of::<u32>())
+        .find(|&address| !painted(address))
+        .unwrap_or(top);
+    top.saturating_sub(deepest)
+}
+
+/// Whether the bottom `GUARD_BYTES` of the stack still hold the paint: `false` once the
+/// stack has grown into them, and has likely gone on into the statics.
+#[must_use]
+pub fn stack_guard_intact() -> bool {
+    let bottom = stack_bottom();
+    (bottom..bottom.saturating_add(GUARD_BYTES))
+        .step_by(size_of::<u32>())
+        .all(painted)
+}
+
+/// Writes the report, a line per item: each of `STATIC_ITEMS`, their total, all the statics
+/// as linked, and the stack's high-water mark out of the stack's room.
+///
+/// # Errors
+///
+/// Returns an error if `out` runs out of room.
+pub fn write_memory_report(out: &mut impl Write) -> fmt::Result {
+    for (name, bytes) in STATIC_ITEMS {
+        writeln!(out, "{name}: {bytes} bytes")?;
+    }
+    writeln!(out, "statics counted: {STATIC_BYTES} of {RP2040_RAM_BYTES} bytes")?;
+    writeln!(out, "statics linked: {} of {RP2040_RAM_BYTES} bytes", statics_bytes())?;
+    writeln!(
+        out,
+        "stack high water: {} of {} bytes",
+        stack_high_water(),
+        stack_bytes()
+    )
+}
+
+/// Logs the report.
+pub fn log_memory_report() {
+    for (name, bytes) in STATIC_ITEMS {
+        info!("{=str}: {} bytes", name, bytes);
+    }
+    info!("Statics counted: {} of {} bytes", STATIC_BYTES, RP2040_RAM_BYTES);
+    info!("Statics linked: {} of {} bytes", statics_bytes(), RP2040_RAM_BYTES);
+    info!(
+        "Stack high water: {} of {} bytes",
+        stack_high_water(),
+        stack_bytes()
+    );
+}
@@ -0,0 +1,44 @@
+//! Telemetry the clock and time sync tasks publish for the HTTP server.
+
+use core::cell::{Cell, RefCell};
+
+use clock_core::{SkewHistory, SyncError};
+use embassy_sync::blocking_mutex::{raw::CriticalSectionRawMutex, Mutex};
+
+/// The latest copy of the clock's sync corrections.
+static SKEW_HISTORY: Mutex<CriticalSectionRawMutex, RefCell<SkewHistory>> =
+    Mutex::new(RefCell::new(SkewHistory::new()));
+
+/// How many syncs have failed since boot, for each of `SyncError::ALL`.
+static SYNC_FAILURES: Mutex<CriticalSectionRawMutex, Cell<[u32; SyncError::ALL.len()]>> =
+    Mutex::new(Cell::new([0; SyncError::ALL.len()]));
+
+/// Publishes the clock's sync corrections (called by the clock task after each sync).
+pub(crate) fn publish_skew_history(history: &SkewHistory) {
+    SKEW_HISTORY.lock(|cell| cell.borrow_mut().clone_from(history));
+}
+
+/// A copy of the most recently published sync corrections.
+pub(crate) fn skew_history() -> SkewHistory {
+    SKEW_HISTORY.lock(|cell| cell.borrow().clone())
+}
+
+/// Counts a failed sync (called by the time sync task).
+pub(crate) fn record_sync_failure(error: SyncError) {
+    SYNC_FAILURES.lock(|cell| {
+        let mut counts = cell.get();
+        if let Some(count) = SyncError::ALL
+            .iter()
+            .position(|&kind| kind == error)
+            .and_then(|index| counts.get_mut(index))
+        {
+            *count = count.saturating_add(1);
+        }
+        cell.set(counts);
+    });
+}
+
+/// Each of `SyncError::ALL` with how many syncs have failed that way since boot.
+pub(crate) fn sync_failures() -> impl Iterator<Item = (SyncError, u32)> {
+    SyncError::ALL.into_iter().zip(SYNC_FAILURES.lock(Cell::get))
+}
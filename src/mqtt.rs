@@ -0,0 +1,424 @@
+//! MQTT virtual device - keeps a connection to a broker (`MQTT_BROKER`), scrolls the messages
+//! published to the clock's message topic, and publishes what other devices hand it
+//!
+//! The clock's topics start with `MQTT_TOPIC` (`clock` by default):
+//! * `clock/message` - a message to scroll, as plain text or as `POST /message`'s query
+//!   (`text=DOOR+OPEN&times=3&blink=1`)
+//! * `clock/switch` - a command for the switch output: `on`, `off`, `pulse` (a second), or
+//!   `pulse:500` (milliseconds)
+//! * `clock/event` - published by the `publish` action (see `Action::parse`), such as `alarm`
+//! * `clock/alert` - published when the supply level changes, such as `supply low 3390mV`
+//!
+//! Everything is sent and received at quality of service 0: a message published while the
+//! clock is not connected is lost, as is one the clock publishes while its outbox is full.
+//! The connection is not essential, so it closes during network quiet hours and while the
+//! supply is low (see `network_policy`), and what the clock publishes meanwhile waits, except
+//! for alerts (see `publish_urgent`), which open a connection just long enough to go out.
+
+#![allow(clippy::future_not_send, reason = "single-threaded")]
+
+use core::fmt::Write as _;
+
+use embassy_executor::{SpawnError, Spawner};
+use embassy_futures::select::{select, select3, Either, Either3};
+use embassy_net::{dns::DnsQueryType, tcp::TcpSocket, Stack};
+use embassy_sync::{
+    blocking_mutex::raw::CriticalSectionRawMutex, channel::Channel, signal::Signal,
+};
+use embassy_time::{Duration, Instant, Timer};
+use embedded_io_async::Write as _;
+use heapless::String;
+
+use clock_core::{
+    mqtt_connect, mqtt_packet_len, mqtt_publish, mqtt_subscribe, BlinkState, Message,
+    MqttPacket, MQTT_DISCONNECT, MQTT_PINGREQ, MQTT_PORT,
+};
+
+use crate::{info, warn};
+use crate::clock::Clock;
+use crate::http::{parse_message, switch_command};
+use crate::network_policy::network_quiet;
+use crate::settings::{mqtt_broker, mqtt_credentials, mqtt_topic};
+use crate::switch::Switch;
+use crate::wifi::Wifi;
+
+/// The topic, after `MQTT_TOPIC/`, whose messages the clock scrolls.
+const MESSAGE_TOPIC: &str = "message";
+
+/// The topic, after `MQTT_TOPIC/`, whose messages drive the switch output.
+const SWITCH_TOPIC: &str = "switch";
+
+/// How often the clock pings the broker when it has sent nothing else. The broker drops a
+/// client silent for one and a half times this.
+const KEEP_ALIVE: Duration = Duration::from_secs(60);
+
+/// How long the broker may stay silent (it answers every ping) before the clock reconnects.
+const BROKER_SILENCE: Duration = Duration::from_secs(90);
+
+/// How soon a lost or refused connection is tried again.
+const RECONNECT: Duration = Duration::from_secs(30);
+
+/// How often the clock looks again whether network quiet is over, while it keeps the
+/// connection closed.
+const QUIET_RECHECK: Duration = Duration::from_secs(60);
+
+/// The longest topic, `MQTT_TOPIC` included.
+const MAX_TOPIC_LEN: usize = 64;
+
+/// The longest payload the clock publishes.
+pub(crate) const MAX_PAYLOAD_LEN: usize = 64;
+
+/// How many messages wait to be published at once.
+const OUTBOX_LEN: usize = 4;
+
+/// The largest packet the clock reads. Longer ones (such as a long retained message) are
+/// skipped.
+const MAX_PACKET_LEN: usize = 256;
+
+/// Each of the TCP socket's receive and transmit buffers.
+const SOCKET_BUFFER_LEN: usize = 512;
+
+/// The buffers the MQTT task holds while it is connected: the socket's, and one packet each
+/// way.
+pub(crate) const RAM_BYTES: usize = 2 * SOCKET_BUFFER_LEN + 2 * MAX_PACKET_LEN;
+
+/// A message waiting to be published.
+struct Outgoing {
+    /// The topic after `MQTT_TOPIC/`.
+    subtopic: &'static str,
+    payload: String<MAX_PAYLOAD_LEN>,
+}
+
+/// Messages other devices hand the MQTT task to publish.
+type Outbox = Channel<CriticalSectionRawMutex, Outgoing, OUTBOX_LEN>;
+
+/// The RAM the outbox takes.
+pub(crate) const OUTBOX_BYTES: usize = size_of::<Outbox>();
+
+static OUTBOX: Outbox = Channel::new();
+
+/// Signaled when an alert waits in the outbox that should go out even during network quiet.
+static URGENT: Signal<CriticalSectionRawMutex, ()> = Signal::new();
+
+/// Publishes `payload` to `MQTT_TOPIC/subtopic` as soon as the clock is connected.
+///
+/// Returns `false` if it is dropped instead: MQTT is off, the payload is longer than
+/// `MAX_PAYLOAD_LEN`, or `OUTBOX_LEN` messages are already waiting.
+pub(crate) fn publish(subtopic: &'static str, payload: &str) -> bool {
+    if mqtt_broker().is_none() {
+        return false;
+    }
+    let Ok(payload) = String::try_from(payload) else {
+        return false;
+    };
+    OUTBOX.try_send(Outgoing { subtopic, payload }).is_ok()
+}
+
+/// Publishes `payload` to `MQTT_TOPIC/subtopic` as `publish` does, but without waiting out
+/// network quiet: the clock connects just long enough to send what waits in the outbox.
+///
+/// For alerts, such as the supply running low, that matter more than the traffic they take.
+pub(crate) fn publish_urgent(subtopic: &'static str, payload: &str) -> bool {
+    let queued = publish(subtopic, payload);
+    if queued {
+        URGENT.signal(());
+    }
+    queued
+}
+
+// ============================================================================
+// MQTT Virtual Device
+// ============================================================================
+
+/// MQTT virtual device - connects to the broker once WiFi is up, and again whenever the
+/// connection drops
+///
+/// It does nothing unless `MQTT_BROKER` is set at build time.
+pub struct Mqtt;
+
+impl Mqtt {
+    /// Create the MQTT device and spawn its task.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SpawnError` if the task cannot be spawned.
+    pub fn new(
+        wifi: &'static Wifi,
+        clock: Clock<'static>,
+        switch: Switch<'static>,
+        spawner: Spawner,
+    ) -> Result<Self, SpawnError> {
+        spawner.spawn(device_loop(wifi, clock, switch))?;
+        Ok(Self)
+    }
+}
+
+#[embassy_executor::task]
+async fn device_loop(wifi: &'static Wifi, clock: Clock<'static>, switch: Switch<'static>) -> ! {
+    let Some(broker) = mqtt_broker() else {
+        info!("MQTT disabled: MQTT_BROKER not set");
+        loop {
+            core::future::pending::<()>().await;
+        }
+    };
+    let Some(&stack) = wifi.stack().await else {
+        info!("MQTT disabled: WiFi unavailable");
+        loop {
+            core::future::pending::<()>().await;
+        }
+    };
+
+    loop {
+        if let Some(quiet) = network_quiet() {
+            info!("MQTT held back: {}", quiet.name());
+            if let Either::Second(()) = select(Timer::after(QUIET_RECHECK), URGENT.wait()).await {
+                if let Err(msg) = flush(&stack, broker).await {
+                    warn!("MQTT alert not sent: {}", msg);
+                }
+            }
+            continue;
+        }
+        if let Err(msg) = session(&stack, broker, &clock, &switch).await {
+            warn!("MQTT connection ended: {}", msg);
+            Timer::after(RECONNECT).await;
+        }
+    }
+}
+
+/// Connects to `broker`, subscribes to the clock's topics, and then handles packets both ways
+/// until the connection fails, or until network quiet (see `network_quiet`) begins, when it
+/// disconnects and returns `Ok`.
+async fn session(
+    stack: &Stack<'static>,
+    broker: &str,
+    clock: &Clock<'static>,
+    switch: &Switch<'static>,
+) -> Result<(), &'static str> {
+    let mut rx_buffer = [0; SOCKET_BUFFER_LEN];
+    let mut tx_buffer = [0; SOCKET_BUFFER_LEN];
+    let mut outgoing = [0_u8; MAX_PACKET_LEN];
+    let mut socket = connect(stack, broker, &mut rx_buffer, &mut tx_buffer, &mut outgoing).await?;
+    // A client may subscribe before the broker acknowledges the connection
+    let topics = Topics {
+        message: topic(MESSAGE_TOPIC).ok_or("MQTT_TOPIC too long")?,
+        switch: topic(SWITCH_TOPIC).ok_or("MQTT_TOPIC too long")?,
+    };
+    for (packet_id, subscribed) in [(1, &topics.message), (2, &topics.switch)] {
+        let len =
+            mqtt_subscribe(&mut outgoing, packet_id, subscribed).ok_or("MQTT_TOPIC too long")?;
+        send(&mut socket, outgoing.get(..len).unwrap_or_default()).await?;
+    }
+
+    let mut incoming = [0_u8; MAX_PACKET_LEN];
+    let mut filled = 0_usize;
+    // Bytes still to throw away from a packet too big for `incoming`
+    let mut skipping = 0_usize;
+    let mut last_heard = Instant::now();
+    let mut next_ping = ping_due();
+    loop {
+        let unread = incoming.get_mut(filled..).unwrap_or_default();
+        let event = select3(socket.read(unread), OUTBOX.receive(), Timer::at(next_ping)).await;
+        match event {
+            Either3::First(read) => {
+                let read = read.map_err(|err| {
+                    warn!("MQTT receive failed: {:?}", err);
+                    "MQTT receive failed"
+                })?;
+                if read == 0 {
+                    return Err("Broker closed the connection");
+                }
+                last_heard = Instant::now();
+                let dropped = read.min(skipping);
+                skipping = skipping.saturating_sub(dropped);
+                let kept = filled.saturating_add(dropped)..filled.saturating_add(read);
+                incoming.copy_within(kept, filled);
+                filled = filled.saturating_add(read.saturating_sub(dropped));
+                while let Some((packet, used)) =
+                    MqttPacket::parse(incoming.get(..filled).unwrap_or_default())
+                        .map_err(|_| "Malformed MQTT packet")?
+                {
+                    handle(packet, &topics, clock, switch).await?;
+                    incoming.copy_within(used..filled, 0);
+                    filled = filled.saturating_sub(used);
+                }
+                if filled == MAX_PACKET_LEN {
+                    let packet_len = mqtt_packet_len(&incoming)
+                        .map_err(|_| "Malformed MQTT packet")?
+                        .unwrap_or(MAX_PACKET_LEN);
+                    warn!("Skipping a {}-byte MQTT packet", packet_len);
+                    skipping = packet_len.saturating_sub(MAX_PACKET_LEN);
+                    filled = 0;
+                }
+            }
+            Either3::Second(message) => {
+                publish_outgoing(&mut socket, &mut outgoing, &message).await?;
+                next_ping = ping_due();
+            }
+            Either3::Third(()) => {
+                // Keeping the connection alive is not essential, so it waits out quiet
+                if let Some(quiet) = network_quiet() {
+                    info!("MQTT disconnecting: {}", quiet.name());
+                    send(&mut socket, &MQTT_DISCONNECT).await?;
+                    socket.close();
+                    return Ok(());
+                }
+                if last_heard.elapsed() > BROKER_SILENCE {
+                    return Err("Broker stopped answering");
+                }
+                send(&mut socket, &MQTT_PINGREQ).await?;
+                next_ping = ping_due();
+            }
+        }
+    }
+}
+
+/// Opens a TCP connection to `broker` on buffers the caller holds, and sends `CONNECT`
+/// (written in `outgoing`).
+async fn connect<'a>(
+    stack: &Stack<'static>,
+    broker: &str,
+    rx_buffer: &'a mut [u8],
+    tx_buffer: &'a mut [u8],
+    outgoing: &mut [u8],
+) -> Result<TcpSocket<'a>, &'static str> {
+    let (host, port) = split_broker(broker).ok_or("Bad MQTT_BROKER")?;
+    info!("Connecting to MQTT broker {}...", host);
+    let dns_result = stack
+        .dns_query(host, DnsQueryType::A)
+        .await
+        .map_err(|err| {
+            warn!("DNS lookup failed: {:?}", err);
+            "DNS lookup failed"
+        })?;
+    let broker_addr = *dns_result.first().ok_or("No DNS results")?;
+
+    let mut socket = TcpSocket::new(*stack, rx_buffer, tx_buffer);
+    socket.connect((broker_addr, port)).await.map_err(|err| {
+        warn!("MQTT connect failed: {:?}", err);
+        "MQTT connect failed"
+    })?;
+
+    let keep_alive_seconds = u16::try_from(KEEP_ALIVE.as_secs()).unwrap_or(u16::MAX);
+    let len = mqtt_connect(
+        outgoing,
+        mqtt_topic(),
+        mqtt_credentials(),
+        keep_alive_seconds,
+    )
+    .ok_or("MQTT_TOPIC or credentials too long")?;
+    send(&mut socket, outgoing.get(..len).unwrap_or_default()).await?;
+    Ok(socket)
+}
+
+/// Connects to `broker` just long enough to publish what waits in the outbox, then
+/// disconnects. Does not connect if nothing waits.
+async fn flush(stack: &Stack<'static>, broker: &str) -> Result<(), &'static str> {
+    let Ok(first) = OUTBOX.try_receive() else {
+        return Ok(());
+    };
+    let mut rx_buffer = [0; SOCKET_BUFFER_LEN];
+    let mut tx_buffer = [0; SOCKET_BUFFER_LEN];
+    let mut outgoing = [0_u8; MAX_PACKET_LEN];
+    let mut socket = connect(stack, broker, &mut rx_buffer, &mut tx_buffer, &mut outgoing).await?;
+    let mut next = Some(first);
+    while let Some(message) = next {
+        publish_outgoing(&mut socket, &mut outgoing, &message).await?;
+        next = OUTBOX.try_receive().ok();
+    }
+    send(&mut socket, &MQTT_DISCONNECT).await?;
+    // Dropping the socket discards what the broker has not yet acknowledged
+    socket.flush().await.map_err(|_| "MQTT send failed")?;
+    socket.close();
+    info!("MQTT alert sent");
+    Ok(())
+}
+
+/// Sends `message` as a `PUBLISH`, written in `outgoing`.
+async fn publish_outgoing(
+    socket: &mut TcpSocket<'_>,
+    outgoing: &mut [u8],
+    message: &Outgoing,
+) -> Result<(), &'static str> {
+    let full_topic = topic(message.subtopic).ok_or("MQTT_TOPIC too long")?;
+    let len = mqtt_publish(outgoing, &full_topic, message.payload.as_bytes(), false)
+        .ok_or("MQTT message too long")?;
+    send(socket, outgoing.get(..len).unwrap_or_default()).await
+}
+
+/// The topics the clock subscribes to, `MQTT_TOPIC` included.
+struct Topics {
+    message: String<MAX_TOPIC_LEN>,
+    switch: String<MAX_TOPIC_LEN>,
+}
+
+/// Acts on a packet from the broker.
+async fn handle(
+    packet: MqttPacket<'_>,
+    topics: &Topics,
+    clock: &Clock<'static>,
+    switch: &Switch<'static>,
+) -> Result<(), &'static str> {
+    match packet {
+        MqttPacket::ConnAck { return_code: 0 } => info!("MQTT connected"),
+        MqttPacket::ConnAck { return_code } => {
+            warn!("MQTT broker refused the connection: code {}", return_code);
+            return Err("Broker refused the connection");
+        }
+        MqttPacket::SubAck { granted: false } => warn!("MQTT broker refused a subscription"),
+        MqttPacket::Publish { topic, payload } if topic == topics.message.as_str() => {
+            let text = core::str::from_utf8(payload).unwrap_or_default();
+            // A query's text is percent-encoded, and plain text scrolls past once
+            let message = if text.starts_with("text=") {
+                parse_message(text)
+            } else {
+                Message::new(text, 1, BlinkState::Solid).ok()
+            };
+            match message {
+                Some(message) => clock.show_message(message).await,
+                None => warn!("MQTT message not shown: {=str}", text),
+            }
+        }
+        MqttPacket::Publish { topic, payload } if topic == topics.switch.as_str() => {
+            let text = core::str::from_utf8(payload).unwrap_or_default().trim();
+            let (state, ms) = text
+                .split_once(':')
+                .map_or((text, None), |(state, ms)| (state, Some(ms)));
+            match switch_command(state, ms) {
+                Some(command) => switch.command(command),
+                None => warn!("MQTT switch command not understood: {=str}", text),
+            }
+        }
+        MqttPacket::SubAck { granted: true }
+        | MqttPacket::Publish { .. }
+        | MqttPacket::PingResp
+        | MqttPacket::Other(_) => {}
+    }
+    Ok(())
+}
+
+/// When to ping the broker if nothing else is sent first.
+fn ping_due() -> Instant {
+    Instant::now().checked_add(KEEP_ALIVE).unwrap_or(Instant::MAX)
+}
+
+async fn send(socket: &mut TcpSocket<'_>, packet: &[u8]) -> Result<(), &'static str> {
+    socket.write_all(packet).await.map_err(|err| {
+        warn!("MQTT send failed: {:?}", err);
+        "MQTT send failed"
+    })
+}
+
+/// `MQTT_TOPIC/subtopic`, or `None` if it is longer than `MAX_TOPIC_LEN`.
+fn topic(subtopic: &str) -> Option<String<MAX_TOPIC_LEN>> {
+    let mut full_topic = String::new();
+    write!(full_topic, "{}/{subtopic}", mqtt_topic()).ok()?;
+    Some(full_topic)
+}
+
+/// Splits `host[:port]` into its host and port.
+fn split_broker(broker: &str) -> Option<(&str, u16)> {
+    match broker.split_once(':') {
+        Some((host, port)) => Some((host, port.parse().ok()?)),
+        None => Some((broker, MQTT_PORT)),
+    }
+}
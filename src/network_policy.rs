@@ -0,0 +1,56 @@
+//! Network policy - holds back non-essential network traffic during quiet hours
+//! (`NETWORK_QUIET_HOURS`) and while the supply is low
+//!
+//! Time syncs and the HTTP API are essential and always go ahead. The weather fetch waits,
+//! the MQTT connection closes (and with it the keepalive pings), and `GET /metrics` answers
+//! 503 so a scraper backs off instead of keeping the radio busy.
+
+use core::sync::atomic::{AtomicI32, Ordering};
+
+use crate::settings::network_quiet_hours;
+use crate::soft_reboot::latest_unix_seconds;
+use crate::supply::supply_low;
+
+/// The clock's offset from UTC, so quiet hours (and time-of-day colors) follow local time.
+static UTC_OFFSET_MINUTES: AtomicI32 = AtomicI32::new(0);
+
+/// Why non-essential traffic is held back.
+#[cfg_attr(feature = "logging", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Quiet {
+    QuietHours,
+    LowSupply,
+}
+
+impl Quiet {
+    /// A short description, for logs and HTTP replies.
+    pub(crate) const fn name(self) -> &'static str {
+        match self {
+            Self::QuietHours => "quiet hours",
+            Self::LowSupply => "low supply",
+        }
+    }
+}
+
+/// Called by the clock task whenever its UTC offset changes.
+pub(crate) fn publish_utc_offset_minutes(utc_offset_minutes: i32) {
+    UTC_OFFSET_MINUTES.store(utc_offset_minutes, Ordering::Relaxed);
+}
+
+/// The clock's offset from UTC, as last published by the clock task.
+pub(crate) fn utc_offset_minutes() -> i32 {
+    UTC_OFFSET_MINUTES.load(Ordering::Relaxed)
+}
+
+/// Why non-essential traffic should wait, or `None` if it may go ahead. Quiet hours only
+/// apply once the clock knows the time.
+pub(crate) fn network_quiet() -> Option<Quiet> {
+    if supply_low() {
+        return Some(Quiet::LowSupply);
+    }
+    let quiet_hours = network_quiet_hours()?;
+    let unix_seconds = latest_unix_seconds()?;
+    quiet_hours
+        .contains(unix_seconds, utc_offset_minutes())
+        .then_some(Quiet::QuietHours)
+}
@@ -0,0 +1,122 @@
+//! A display backend for Nixie tubes on exixe driver boards: one board per cell, sharing an
+//! SPI bus, each with its own chip select (see `README.md`).
+
+use embassy_futures::select::{select, Either};
+use embassy_rp::{
+    peripherals::{DMA_CH3, PIN_6, PIN_7, SPI0},
+    spi::{self, Async, Spi},
+    Peri,
+};
+use embassy_time::Timer;
+use embedded_hal::digital::PinState;
+
+use clock_core::{exixe_command, nixie_digit, BitMatrix, Brightness, Leds, EXIXE_COMMAND_LEN};
+
+use crate::{
+    display::{cell_brightness, next_frame, DisplayDriver, DisplayNotifier, Frame},
+    error::Result,
+    hardware::CellPins,
+    info,
+    shared_constants::{SLOT_MACHINE_ROUNDS, SLOT_MACHINE_STEP},
+    CELL_COUNT_U8,
+};
+
+/// exixe boards take up to a few MHz; this leaves margin for long wires to the tubes.
+const SPI_FREQUENCY: u32 = 1_000_000;
+
+/// Shows each cell's digit on its own tube. Cells showing anything but a digit (or a space)
+/// leave their tube dark.
+pub struct NixieDisplay {
+    spi: Spi<'static, SPI0, Async>,
+    chip_selects: CellPins,
+}
+
+impl NixieDisplay {
+    /// Creates a driver for tubes whose boards share SPI0 (`clk` and `mosi`, fed by `dma`),
+    /// each selected by its cell's pin in `chip_selects`.
+    #[must_use]
+    pub fn new(
+        spi: Peri<'static, SPI0>,
+        clk: Peri<'static, PIN_6>,
+        mosi: Peri<'static, PIN_7>,
+        dma: Peri<'static, DMA_CH3>,
+        chip_selects: CellPins,
+    ) -> Self {
+        let mut config = spi::Config::default();
+        config.frequency = SPI_FREQUENCY;
+        Self {
+            spi: Spi::new_txonly(spi, clk, mosi, dma, config),
+            chip_selects,
+        }
+    }
+
+    /// Sends each cell's board the command `command_for` gives its index.
+    async fn write_tubes(
+        &mut self,
+        command_for: impl Fn(u8) -> [u8; EXIXE_COMMAND_LEN],
+    ) -> Result<()> {
+        for index in 0..CELL_COUNT_U8 {
+            let command = command_for(index);
+            self.chip_selects.set_level_at_index(index, PinState::Low)?;
+            let written = self.spi.write(&command).await;
+            self.chip_selects.set_level_at_index(index, PinState::High)?;
+            written?;
+        }
+        Ok(())
+    }
+
+    /// Shows `bit_matrix`'s digits at `brightness`, or about half of it for a dimmed tube.
+    async fn write_bit_matrix(
+        &mut self,
+        bit_matrix: BitMatrix,
+        brightness: Brightness,
+    ) -> Result<()> {
+        self.write_tubes(|index| {
+            let segments = bit_matrix[index];
+            exixe_command(
+                nixie_digit(segments),
+                segments & Leds::DECIMAL != 0,
+                cell_brightness(&bit_matrix, usize::from(index), brightness),
+            )
+        })
+        .await
+    }
+
+    /// Spins every tube through all ten digits like a slot machine's reels, each tube a
+    /// digit ahead of the one to its left, so every cathode gets lit.
+    #[expect(
+        clippy::arithmetic_side_effects,
+        clippy::integer_division_remainder_used,
+        reason = "Round and index are small, and the modulus is a non-zero constant."
+    )]
+    async fn run_slot_machine(&mut self, brightness: Brightness) -> Result<()> {
+        info!("Exercising the Nixie cathodes");
+        for round in 0..SLOT_MACHINE_ROUNDS {
+            self.write_tubes(|index| {
+                let digit = u8::try_from((u16::from(round) + u16::from(index)) % 10).ok();
+                exixe_command(digit, false, brightness)
+            })
+            .await?;
+            Timer::after(SLOT_MACHINE_STEP).await;
+        }
+        Ok(())
+    }
+}
+
+impl DisplayDriver for NixieDisplay {
+    async fn show(&mut self, frame: Frame, notifier: &DisplayNotifier) -> Result<Frame> {
+        let (bit_matrix, brightness) = frame;
+        info!("nixie frame: {:?}", bit_matrix.decode());
+        self.write_bit_matrix(bit_matrix, brightness).await?;
+        let (_, _, cathode_notifier) = notifier;
+        match select(next_frame(notifier, frame), cathode_notifier.wait()).await {
+            Either::First(notification) => Ok(notification),
+            // Spin at the display's brightness, but lit even if the night turned it off, then
+            // show the frame again. New notifications wait in their signals meanwhile.
+            Either::Second(()) => {
+                self.run_slot_machine(brightness.max(Brightness::MIN)).await?;
+                Ok(frame)
+            }
+        }
+    }
+}
@@ -1,27 +1,30 @@
-use crate::error::Error::IndexOutOfBounds;
-use crate::Result;
-use core::num::NonZeroU8;
-use embassy_rp::gpio::{self, Level};
+use crate::error::Error::{CannotSetOutputState, IndexOutOfBounds};
+use crate::{Result, Segments, SEGMENT_COUNT};
+use core::num::NonZero;
+use embedded_hal::digital::{OutputPin, PinState};
 
-pub struct OutputArray<'a, const N: usize>([gpio::Output<'a>; N]);
+/// A fixed-size group of output pins, such as the display's cell or segment pins.
+///
+/// Generic over `embedded-hal`'s `OutputPin` so that any board's GPIO can drive the display.
+pub struct OutputArray<P, const N: usize>([P; N]);
 
-impl<'a, const N: usize> OutputArray<'a, N> {
-    pub const fn new(outputs: [gpio::Output<'a>; N]) -> Self {
+impl<P: OutputPin, const N: usize> OutputArray<P, N> {
+    pub const fn new(outputs: [P; N]) -> Self {
         Self(outputs)
     }
 
     #[inline]
-    pub fn iter(&self) -> impl Iterator<Item = &gpio::Output<'a>> {
+    pub fn iter(&self) -> impl Iterator<Item = &P> {
         self.0.iter()
     }
 
     #[inline]
-    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut gpio::Output<'a>> {
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut P> {
         self.0.iter_mut()
     }
 
     #[inline]
-    pub fn set_levels_at_indexes(&mut self, indexes: &[u8], level: Level) -> Result<()> {
+    pub fn set_levels_at_indexes(&mut self, indexes: &[u8], level: PinState) -> Result<()> {
         for &index in indexes {
             self.set_level_at_index(index, level)?;
         }
@@ -29,37 +32,33 @@ impl<'a, const N: usize> OutputArray<'a, N> {
     }
 
     #[inline]
-    pub fn set_level_at_index(&mut self, index: u8, level: Level) -> Result<()> {
+    pub fn set_level_at_index(&mut self, index: u8, level: PinState) -> Result<()> {
         self.get_mut(index as usize) // Mutable access
             .ok_or(IndexOutOfBounds)? // Return error if index is out of bounds
-            .set_level(level); // Mutate the item
-        Ok(())
+            .set_state(level) // Mutate the item
+            .map_err(|_| CannotSetOutputState)
     }
 
     #[inline]
-    pub fn get_mut(&mut self, index: usize) -> Option<&mut gpio::Output<'a>> {
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut P> {
         self.0.get_mut(index)
     }
 }
 
-impl OutputArray<'_, { u8::BITS as usize }> {
-    #[expect(clippy::shadow_reuse, reason = "Just converting a NonZeroU8 to a u8.")]
+impl<P: OutputPin> OutputArray<P, SEGMENT_COUNT> {
     #[inline]
-    pub fn set_from_nonzero_bits(&mut self, bits: NonZeroU8) {
-        let mut bits = bits.get();
-        for output in &mut self.0 {
-            let level: Level = ((bits & 1) == 1).into();
-            output.set_level(level);
-            bits >>= 1;
-        }
+    pub fn set_from_nonzero_bits(&mut self, bits: NonZero<Segments>) -> Result<()> {
+        self.set_from_bits(bits.get())
     }
 
     #[inline]
-    pub fn set_from_bits(&mut self, mut bits: u8) {
+    pub fn set_from_bits(&mut self, mut bits: Segments) -> Result<()> {
         for output in &mut self.0 {
-            let level: Level = ((bits & 1) == 1).into();
-            output.set_level(level);
+            output
+                .set_state(PinState::from((bits & 1) == 1))
+                .map_err(|_| CannotSetOutputState)?;
             bits >>= 1;
         }
+        Ok(())
     }
 }
@@ -0,0 +1,23 @@
+//! Provisioning - the config page written by the `clock-flash` host tool (the WiFi network,
+//! time zone, display settings, and alarm settings), so one firmware image serves every clock
+
+use core::cell::RefCell;
+
+use clock_core::Provisioning;
+use embassy_sync::blocking_mutex::{raw::CriticalSectionRawMutex, Mutex};
+
+/// The config page read at boot, if the clock has one.
+static PROVISIONING: Mutex<CriticalSectionRawMutex, RefCell<Option<Provisioning>>> =
+    Mutex::new(RefCell::new(None));
+
+/// Uses `provisioning`, read from flash at boot. Call it before creating the `TimeSync` (and
+/// so the WiFi), which joins at once.
+pub fn set_provisioning(provisioning: Option<Provisioning>) {
+    PROVISIONING.lock(|cell| *cell.borrow_mut() = provisioning);
+}
+
+/// The clock's config page, or `None` if it has none (and so no network to join).
+#[must_use]
+pub fn provisioning() -> Option<Provisioning> {
+    PROVISIONING.lock(|cell| cell.borrow().clone())
+}
@@ -0,0 +1,109 @@
+//! Radio time virtual device - sets the clock from a DCF77 or WWVB receiver module
+
+use embassy_executor::{SpawnError, Spawner};
+use embassy_rp::gpio;
+use embassy_time::{Duration, Instant};
+
+use clock_core::{LeapIndicator, RadioTimeDecoder, TimeSanity, TimeSource};
+
+use crate::{info, warn};
+use crate::button::ActiveLevel;
+use crate::settings::{build_unix_seconds, max_time_jump, radio_time};
+use crate::time_sync::{SyncAccuracy, TimeSync, TimeSyncEvent};
+
+/// How often a receiver with good reception sets the clock again. A time decodes every
+/// minute, but the clock drifts far less than a pulse's own length in an hour.
+const RADIO_SYNC_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// Pulses shorter than this are interference, not part of the signal, and are skipped.
+const GLITCH: Duration = Duration::from_millis(40);
+
+/// How many years after the build a radio time may be before it is rejected.
+const MAX_YEARS_AFTER_BUILD: u16 = 20;
+
+/// Radio time virtual device - decodes the pulses from a receiver module on GPIO 22
+///
+/// It does nothing unless the firmware is built with the `radio-time` feature and
+/// `RADIO_TIME` names the station.
+pub struct RadioTime;
+
+impl RadioTime {
+    /// Create the radio time device and spawn its task, which reports each time it decodes
+    /// through `time_sync`, tagged `TimeSource::Radio`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SpawnError` if the task cannot be spawned.
+    pub fn new(
+        pin: gpio::Input<'static>,
+        time_sync: &'static TimeSync,
+        spawner: Spawner,
+    ) -> Result<Self, SpawnError> {
+        spawner.spawn(device_loop(pin, time_sync))?;
+        Ok(Self)
+    }
+}
+
+#[embassy_executor::task]
+async fn device_loop(mut pin: gpio::Input<'static>, time_sync: &'static TimeSync) -> ! {
+    let Some((station, active_level)) = radio_time() else {
+        info!("Radio time disabled: `radio-time` feature or RADIO_TIME not set");
+        loop {
+            core::future::pending::<()>().await;
+        }
+    };
+    info!("Listening for {:?} on GPIO 22", station);
+    let mut decoder = RadioTimeDecoder::new(station);
+    let mut sanity = TimeSanity::new(build_unix_seconds(), MAX_YEARS_AFTER_BUILD, max_time_jump());
+    let mut synced_at: Option<Instant> = None;
+    let mut previous_start = Instant::now();
+    loop {
+        wait_for_level(&mut pin, active_level, true).await;
+        let start = Instant::now();
+        wait_for_level(&mut pin, active_level, false).await;
+        let pulse = start.elapsed();
+        if pulse < GLITCH {
+            continue;
+        }
+        let since_previous = start.saturating_duration_since(previous_start);
+        previous_start = start;
+        let Some(unix_seconds) = decoder.push(millis(pulse), millis(since_previous)) else {
+            continue;
+        };
+        if synced_at.is_some_and(|at| at.elapsed() < RADIO_SYNC_PERIOD) {
+            continue;
+        }
+        synced_at = Some(Instant::now());
+        match sanity.check(unix_seconds, Instant::now()) {
+            Ok(()) => {
+                info!("Radio time: {} (unix timestamp)", unix_seconds.as_i64());
+                time_sync.post(TimeSyncEvent::Success {
+                    unix_seconds,
+                    // Both stations announce leap seconds, but only an hour ahead, which this
+                    // decoder does not follow
+                    leap: LeapIndicator::NoWarning,
+                    accuracy: SyncAccuracy::Coarse,
+                    source: TimeSource::Radio,
+                });
+            }
+            Err(reason) => {
+                warn!("Rejected radio time {}: {:?}", unix_seconds.as_i64(), reason);
+                time_sync.post(TimeSyncEvent::Rejected(reason));
+            }
+        }
+    }
+}
+
+/// Waits until the module's output shows a pulse (`in_pulse`) or its absence.
+async fn wait_for_level(pin: &mut gpio::Input<'static>, active_level: ActiveLevel, in_pulse: bool) {
+    if (active_level == ActiveLevel::High) == in_pulse {
+        pin.wait_for_high().await;
+    } else {
+        pin.wait_for_low().await;
+    }
+}
+
+/// A length in whole milliseconds, saturating (far longer than any pulse or gap).
+fn millis(duration: Duration) -> u32 {
+    u32::try_from(duration.as_millis()).unwrap_or(u32::MAX)
+}
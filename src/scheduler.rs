@@ -0,0 +1,172 @@
+//! Scheduler virtual device - runs the clock's timed chores from one place: the nightly Nixie
+//! cathode exercise, the periodic time sync, the network quiet-hours transitions, the daily
+//! alarm, and the countdown timer
+//!
+//! The new day at midnight is the clock task's to announce (see `clock::subscribe_midnight`).
+
+#![allow(clippy::future_not_send, reason = "single-threaded")]
+
+use core::cell::Cell;
+
+use embassy_executor::{SpawnError, Spawner};
+use embassy_futures::select::{select, Either};
+use embassy_sync::blocking_mutex::{raw::CriticalSectionRawMutex, Mutex};
+use embassy_sync::signal::Signal;
+use embassy_time::{Duration, Instant, Timer};
+
+use clock_core::{Schedule, When};
+
+use crate::info;
+use crate::action::run_actions;
+use crate::clock::Clock;
+use crate::config::config;
+use crate::network_policy::utc_offset_minutes;
+use crate::settings::{alarm_actions, alarm_at, network_quiet_hours, timer_actions};
+use crate::shared_constants::CATHODE_REFRESH_AT;
+use crate::soft_reboot::latest_unix_seconds;
+use crate::switch::Switch;
+use crate::time_sync::TimeSync;
+use crate::weather;
+
+/// When the countdown timer goes off, if it is running.
+static COUNTDOWN_END: Mutex<CriticalSectionRawMutex, Cell<Option<Instant>>> =
+    Mutex::new(Cell::new(None));
+
+/// Wakes the scheduler when the countdown timer is started or cancelled.
+static COUNTDOWN_CHANGED: Signal<CriticalSectionRawMutex, ()> = Signal::new();
+
+/// Starts the countdown timer to go off `duration` from now, in place of any running. When
+/// it goes off, the scheduler carries out `TIMER_ACTIONS`.
+pub(crate) fn start_countdown(duration: Duration) {
+    let end = Instant::now().checked_add(duration).unwrap_or(Instant::MAX);
+    COUNTDOWN_END.lock(|countdown_end| countdown_end.set(Some(end)));
+    COUNTDOWN_CHANGED.signal(());
+}
+
+/// Stops the countdown timer, if it is running, without its actions.
+pub(crate) fn cancel_countdown() {
+    COUNTDOWN_END.lock(|countdown_end| countdown_end.set(None));
+    COUNTDOWN_CHANGED.signal(());
+}
+
+/// How long until the countdown timer goes off, if it is running.
+pub(crate) fn countdown_remaining() -> Option<Duration> {
+    COUNTDOWN_END
+        .lock(Cell::get)
+        .map(|end| end.saturating_duration_since(Instant::now()))
+}
+
+/// A chore the scheduler runs.
+#[cfg_attr(feature = "logging", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Job {
+    /// Exercise a Nixie display's cathodes, at `CATHODE_REFRESH_AT`.
+    RefreshCathodes,
+    /// Sync the time, every `Config::sync_interval`.
+    Resync,
+    /// Network quiet hours begin.
+    QuietHoursStart,
+    /// Network quiet hours end: fetch what they held back.
+    QuietHoursEnd,
+    /// The alarm goes off, at `ALARM_AT`.
+    Alarm,
+}
+
+// ============================================================================
+// Scheduler Virtual Device
+// ============================================================================
+
+/// Scheduler virtual device - runs the jobs above at their local times or intervals, and
+/// the countdown timer's actions when it goes off
+///
+/// Daily jobs wait until the clock knows the time. The scheduler looks at the wall clock at
+/// least once a minute, so a sync or a new UTC offset is followed within a minute. The
+/// countdown timer counts time since boot, so it runs whether or not the clock is synced.
+pub struct Scheduler;
+
+impl Scheduler {
+    /// Create the scheduler and spawn its task.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SpawnError` if the task cannot be spawned.
+    pub fn new(
+        clock: Clock<'static>,
+        time_sync: &'static TimeSync,
+        switch: Switch<'static>,
+        spawner: Spawner,
+    ) -> Result<Self, SpawnError> {
+        spawner.spawn(device_loop(clock, time_sync, switch))?;
+        Ok(Self)
+    }
+}
+
+#[embassy_executor::task]
+async fn device_loop(
+    clock: Clock<'static>,
+    time_sync: &'static TimeSync,
+    switch: Switch<'static>,
+) -> ! {
+    let mut schedule = Schedule::new();
+    let now = Instant::now();
+    // Cannot fail: there are fewer jobs than `MAX_SCHEDULED_JOBS`
+    let _added = schedule.add(Job::RefreshCathodes, When::Daily(CATHODE_REFRESH_AT), now);
+    let _added = schedule.add(Job::Resync, When::Every(config().sync_interval()), now);
+    if let Some(quiet_hours) = network_quiet_hours() {
+        let _added = schedule.add(Job::QuietHoursStart, When::Daily(quiet_hours.start()), now);
+        let _added = schedule.add(Job::QuietHoursEnd, When::Daily(quiet_hours.end()), now);
+    }
+    if let Some(alarm_at) = alarm_at() {
+        let _added = schedule.add(Job::Alarm, When::Daily(alarm_at), now);
+    }
+    info!("Scheduler device started");
+
+    loop {
+        // The sync interval may have changed through `PUT /config` or the console
+        schedule.set_period(Job::Resync, config().sync_interval());
+        for job in schedule.take_due(Instant::now(), local_seconds()) {
+            info!("Scheduled job: {:?}", job);
+            match job {
+                Job::RefreshCathodes => clock.refresh_cathodes().await,
+                Job::Resync => time_sync.request_sync(),
+                Job::QuietHoursStart => {}
+                Job::QuietHoursEnd => weather::request_fetch(),
+                Job::Alarm => run_actions(alarm_actions(), "alarm", &clock, &switch).await,
+            }
+        }
+        if take_countdown_end() {
+            info!("Countdown timer went off");
+            run_actions(timer_actions(), "timer", &clock, &switch).await;
+        }
+        // Reset before reading, so a countdown started from now on wakes the wait below
+        COUNTDOWN_CHANGED.reset();
+        let next_check = schedule.next_check(Instant::now(), local_seconds());
+        let countdown_end = COUNTDOWN_END.lock(Cell::get).unwrap_or(Instant::MAX);
+        if let Either::Second(()) =
+            select(Timer::at(next_check.min(countdown_end)), COUNTDOWN_CHANGED.wait()).await
+        {
+            info!("Countdown timer: {:?}", countdown_remaining());
+        }
+    }
+}
+
+/// Whether the countdown timer has gone off, stopping it if so.
+fn take_countdown_end() -> bool {
+    COUNTDOWN_END.lock(|countdown_end| {
+        let went_off = countdown_end.get().is_some_and(|end| end <= Instant::now());
+        if went_off {
+            countdown_end.set(None);
+        }
+        went_off
+    })
+}
+
+/// Local time in seconds since 1970, if the clock knows the time.
+fn local_seconds() -> Option<i64> {
+    let unix_seconds = latest_unix_seconds()?;
+    Some(
+        unix_seconds
+            .as_i64()
+            .saturating_add(i64::from(utc_offset_minutes()).saturating_mul(60)),
+    )
+}
@@ -0,0 +1,503 @@
+//! Compile-time defaults for the clock's settings (see `README.md` and `build.rs`), behind
+//! the config page's display settings and UTC offset.
+
+use core::cell::Cell;
+
+use clock_core::{
+    parse_time_of_day, BatteryChemistry, Brightness, Carousel, DigitColors, GestureTimings,
+    Location, Melody, ModeConditions, MqttCredentials, PageRotation, QuietHours, RadioStation,
+    Settings, SolarDimming, SupplyConfig, UnixSeconds, EARLIEST_PLAUSIBLE,
+};
+use embassy_sync::blocking_mutex::{raw::CriticalSectionRawMutex, Mutex};
+use embassy_time::Duration;
+use heapless::Vec;
+
+use crate::button::{ActiveLevel, ButtonConfig, PressDuration};
+use crate::provisioning::provisioning;
+
+/// The settings the clock starts with: the config page's, or else those from compile-time
+/// environment variables.
+#[must_use]
+pub fn default_settings() -> Settings {
+    provisioning()
+        .and_then(|provisioning| provisioning.settings)
+        .unwrap_or_else(|| Settings {
+            pm_indicator: env_flag(option_env!("PM_INDICATOR")),
+            leading_zero_hours: env_flag(option_env!("LEADING_ZERO_HOURS")),
+            rotate_180: env_flag(option_env!("ROTATE_180")),
+            days_since: days_since(),
+        })
+}
+
+/// The date the `DaysSince` mode counts up from (`DAYS_SINCE`, as `YYYY-MM-DD`).
+/// Unset or unparsable means the mode is skipped.
+#[must_use]
+pub fn days_since() -> Option<UnixSeconds> {
+    option_env!("DAYS_SINCE").and_then(UnixSeconds::from_iso_date)
+}
+
+/// The sequence the `Carousel` mode rotates through (`CAROUSEL`, such as
+/// `time:10,date:5,temp:5,msg:8`) and the message it scrolls (`CAROUSEL_MESSAGE`).
+/// Unset or unparsable means the mode is skipped.
+#[must_use]
+pub fn default_carousel() -> Carousel {
+    option_env!("CAROUSEL").map_or_else(Carousel::new, |sequence| {
+        Carousel::parse(sequence, option_env!("CAROUSEL_MESSAGE").unwrap_or_default())
+            .unwrap_or_default()
+    })
+}
+
+/// Night dimming from the compile-time `LATITUDE` and `LONGITUDE` (decimal degrees, north
+/// and east positive), with `NIGHT_BRIGHTNESS` (0 to 15, default 4; 0 turns the display off).
+/// Without a valid location the display stays fully lit.
+#[must_use]
+pub fn solar_dimming() -> Option<SolarDimming> {
+    let location = Location::parse(option_env!("LATITUDE")?, option_env!("LONGITUDE")?)?;
+    Some(SolarDimming {
+        location,
+        night: night_brightness(),
+    })
+}
+
+/// The brightness from sunset to sunrise, from `NIGHT_BRIGHTNESS` (0 to 15, default 4).
+#[must_use]
+pub fn night_brightness() -> Brightness {
+    option_env!("NIGHT_BRIGHTNESS")
+        .and_then(|val| val.parse::<u8>().ok())
+        .map_or(Brightness::new(4), Brightness::new)
+}
+
+/// The UTC offset the clock starts with: the config page's, or else UTC.
+#[must_use]
+pub fn default_utc_offset_minutes() -> i32 {
+    provisioning().map_or(0, |provisioning| provisioning.utc_offset_minutes)
+}
+
+/// Whether a multiplexed display dims during WiFi transmit bursts (`TX_DIMMING`), easing the
+/// supply droop that flickers it on weak USB power.
+#[must_use]
+pub fn tx_dimming() -> bool {
+    env_flag(option_env!("TX_DIMMING"))
+}
+
+/// How long the time and then the sensor page show, in turn, while the clock shows the time
+/// (`PAGE_ROTATION`, such as `20:5` in seconds). Unset means the time page always shows.
+#[must_use]
+pub fn page_rotation() -> Option<PageRotation> {
+    option_env!("PAGE_ROTATION").and_then(PageRotation::parse)
+}
+
+/// Whether `ClockState::Off` puts the chip into dormant sleep (`DEEP_SLEEP`), from which
+/// only the button wakes it.
+#[must_use]
+pub fn deep_sleep() -> bool {
+    env_flag(option_env!("DEEP_SLEEP"))
+}
+
+/// How a battery supply is monitored: `SUPPLY_DIVIDER` (the ratio of the divider feeding
+/// GPIO 26, e.g. 3), `LOW_SUPPLY_MV` (default 3400), and `CRITICAL_SUPPLY_MV` (default 3100).
+/// Without a divider, nothing is monitored.
+#[must_use]
+pub fn supply_config() -> Option<SupplyConfig> {
+    let divider = option_env!("SUPPLY_DIVIDER")?
+        .parse::<u16>()
+        .ok()
+        .filter(|&val| val > 0)?;
+    let millivolts = |value: Option<&str>, default| {
+        value.and_then(|val| val.parse::<u16>().ok()).unwrap_or(default)
+    };
+    Some(SupplyConfig {
+        divider,
+        low_millivolts: millivolts(option_env!("LOW_SUPPLY_MV"), 3400),
+        critical_millivolts: millivolts(option_env!("CRITICAL_SUPPLY_MV"), 3100),
+    })
+}
+
+/// The battery behind the monitored supply (`BATTERY`: `lipo`, the default, or `3xaa`),
+/// which sets how `ClockState::Battery` turns its voltage into a percentage.
+#[must_use]
+pub fn battery_chemistry() -> BatteryChemistry {
+    option_env!("BATTERY")
+        .and_then(BatteryChemistry::parse)
+        .unwrap_or_default()
+}
+
+/// The baud rate of a GPS receiver on GPIO 21 (`GPS_BAUD`, usually 9600). Without it, no
+/// receiver is listened for.
+#[must_use]
+pub fn gps_baud() -> Option<u32> {
+    option_env!("GPS_BAUD")?
+        .parse::<u32>()
+        .ok()
+        .filter(|&baud| baud > 0)
+}
+
+/// The radio time signal a receiver on GPIO 22 is tuned to (`RADIO_TIME`: `dcf77` or
+/// `wwvb`), and whether its output goes low during each pulse (`RADIO_ACTIVE_LOW`) rather
+/// than high. Always `None` without the `radio-time` feature.
+#[must_use]
+pub fn radio_time() -> Option<(RadioStation, ActiveLevel)> {
+    if !cfg!(feature = "radio-time") {
+        return None;
+    }
+    let station = RadioStation::parse(option_env!("RADIO_TIME")?)?;
+    let active_level = if env_flag(option_env!("RADIO_ACTIVE_LOW")) {
+        ActiveLevel::Low
+    } else {
+        ActiveLevel::High
+    };
+    Some((station, active_level))
+}
+
+/// The digit colors of a WS2812 display (`WS2812_COLORS`): hex colors separated by commas, one
+/// per digit from the left (such as `ff0000,ff0000,00ff00,00ff00`), or `time` to follow the
+/// time of day. Unset or unparsable means white.
+#[must_use]
+pub fn ws2812_colors() -> DigitColors {
+    option_env!("WS2812_COLORS")
+        .and_then(DigitColors::parse)
+        .unwrap_or_default()
+}
+
+/// Whether a leap second is spread over the following hour (`LEAP_SMEAR`) instead of
+/// shown as a one-second jump.
+#[must_use]
+pub fn leap_smear() -> bool {
+    env_flag(option_env!("LEAP_SMEAR"))
+}
+
+/// When the firmware was built (`BUILD_UNIX_SECONDS`, set by `build.rs`), used to bound how
+/// far in the future a network time may be.
+#[must_use]
+pub fn build_unix_seconds() -> UnixSeconds {
+    option_env!("BUILD_UNIX_SECONDS")
+        .and_then(|val| val.parse::<i64>().ok())
+        .map_or(EARLIEST_PLAUSIBLE, UnixSeconds)
+}
+
+/// The most a network time may move the clock after the first sync, from
+/// `MAX_TIME_JUMP_SECONDS`. Defaults to 15 minutes.
+#[must_use]
+pub fn max_time_jump() -> Duration {
+    option_env!("MAX_TIME_JUMP_SECONDS")
+        .and_then(|val| val.parse::<u64>().ok())
+        .map_or(Duration::from_secs(15 * 60), Duration::from_secs)
+}
+
+/// The largest sync correction worked in gradually rather than as a jump, from
+/// `MAX_SLEW_MS` (see `ClockTime::set_max_slew`). Defaults to 2 seconds; 0 always jumps.
+#[must_use]
+pub fn max_slew() -> Duration {
+    option_env!("MAX_SLEW_MS")
+        .and_then(|val| val.parse::<u64>().ok())
+        .map_or(Duration::from_secs(2), Duration::from_millis)
+}
+
+/// The largest backward sync correction for which the display waits for the corrected time
+/// to catch up rather than counting back, from `MAX_BACKWARD_HOLD_SECONDS` (see
+/// `ClockTime::set_max_hold`). Defaults to a minute; 0 always counts back.
+#[must_use]
+pub fn max_backward_hold() -> Duration {
+    option_env!("MAX_BACKWARD_HOLD_SECONDS")
+        .and_then(|val| val.parse::<u64>().ok())
+        .map_or(Duration::from_secs(60), Duration::from_secs)
+}
+
+/// How long `MinutesSeconds` and the diagnostic modes stay up without a button press before
+/// going back to `HoursMinutes` (`AUTO_RETURN_MINUTES`). Unset or 0 means they stay.
+#[must_use]
+pub fn auto_return() -> Option<Duration> {
+    option_env!("AUTO_RETURN_MINUTES")
+        .and_then(|val| val.parse::<u64>().ok())
+        .filter(|&minutes| minutes > 0)
+        .map(|minutes| Duration::from_secs(minutes.saturating_mul(60)))
+}
+
+/// The NTP server to sync from (`TIME_SERVER`), a hostname or IP address.
+/// Defaults to `pool.ntp.org`.
+#[must_use]
+pub fn time_server() -> &'static str {
+    option_env!("TIME_SERVER").unwrap_or("pool.ntp.org")
+}
+
+/// An `http://` URL whose `Date:` header is used when NTP fails (`TIME_FALLBACK_URL`), for
+/// networks that block UDP port 123. Unset means no fallback.
+#[must_use]
+pub fn time_fallback_url() -> Option<&'static str> {
+    option_env!("TIME_FALLBACK_URL")
+}
+
+/// An `http://` URL that answers `204 No Content` unless a captive portal stands in the way
+/// (`PORTAL_CHECK_URL`), checked before each NTP sync. Defaults to Google's
+/// `connectivitycheck.gstatic.com/generate_204`; set it empty to skip the check.
+#[must_use]
+pub fn portal_check_url() -> Option<&'static str> {
+    Some(
+        option_env!("PORTAL_CHECK_URL")
+            .unwrap_or("http://connectivitycheck.gstatic.com/generate_204"),
+    )
+    .filter(|url| !url.is_empty())
+}
+
+/// An `http://` (or, with `tls`, `https://`) URL that returns the outdoor temperature as JSON
+/// (`WEATHER_URL`), such as Open-Meteo's `current=temperature_2m` forecast. Unset means no
+/// weather.
+#[must_use]
+pub fn weather_url() -> Option<&'static str> {
+    option_env!("WEATHER_URL")
+}
+
+/// Certificate pins for `https://` URLs (`TLS_PINS`): `host=fingerprint` entries separated by
+/// commas, each the SHA-256 fingerprint of the host's certificate. Unset means no pins.
+#[must_use]
+pub fn tls_pins() -> &'static str {
+    option_env!("TLS_PINS").unwrap_or("")
+}
+
+/// The local hours when non-essential network traffic waits (`NETWORK_QUIET_HOURS`), such as
+/// `23-6` for 11 pm to 6 am. Unset means none.
+#[must_use]
+pub fn network_quiet_hours() -> Option<QuietHours> {
+    option_env!("NETWORK_QUIET_HOURS").and_then(QuietHours::parse)
+}
+
+/// The JSON key whose number is the temperature (`WEATHER_JSON_KEY`).
+/// Defaults to Open-Meteo's `temperature_2m`.
+#[must_use]
+pub fn weather_json_key() -> &'static str {
+    option_env!("WEATHER_JSON_KEY").unwrap_or("temperature_2m")
+}
+
+/// The local time the alarm goes off each day (`ALARM_AT`, as `HH:MM`), as time since
+/// midnight. Unset or unparsable means no alarm.
+#[must_use]
+pub fn alarm_at() -> Option<Duration> {
+    let (hours, minutes, seconds) = parse_time_of_day(option_env!("ALARM_AT")?)?;
+    Some(Duration::from_secs(
+        u64::from(hours)
+            .saturating_mul(3600)
+            .saturating_add(u64::from(minutes).saturating_mul(60))
+            .saturating_add(u64::from(seconds)),
+    ))
+}
+
+/// What the alarm does when it goes off (`ALARM_ACTIONS`), as a list for `run_actions`.
+/// Defaults to `blink,buzz`.
+#[must_use]
+pub fn alarm_actions() -> &'static str {
+    option_env!("ALARM_ACTIONS").unwrap_or("blink,buzz")
+}
+
+/// What the countdown timer does when it reaches zero (`TIMER_ACTIONS`), as a list for
+/// `run_actions`. Defaults to `blink,buzz`.
+#[must_use]
+pub fn timer_actions() -> &'static str {
+    option_env!("TIMER_ACTIONS").unwrap_or("blink,buzz")
+}
+
+/// The MQTT broker to connect to (`MQTT_BROKER`), as `host` or `host:port` (port 1883 by
+/// default). Unset means no MQTT.
+#[must_use]
+pub fn mqtt_broker() -> Option<&'static str> {
+    option_env!("MQTT_BROKER").filter(|broker| !broker.is_empty())
+}
+
+/// The user name and password the broker asks for (`MQTT_USER` and `MQTT_PASSWORD`). Unset
+/// means the broker takes anyone.
+#[must_use]
+pub fn mqtt_credentials() -> Option<MqttCredentials<'static>> {
+    Some(MqttCredentials {
+        user: option_env!("MQTT_USER").filter(|user| !user.is_empty())?,
+        password: option_env!("MQTT_PASSWORD").unwrap_or(""),
+    })
+}
+
+/// What the clock's MQTT topics start with (`MQTT_TOPIC`), which is also its client ID, so
+/// each clock on a broker needs its own. Defaults to `clock`.
+#[must_use]
+pub fn mqtt_topic() -> &'static str {
+    option_env!("MQTT_TOPIC")
+        .filter(|topic| !topic.is_empty())
+        .unwrap_or("clock")
+}
+
+/// The longest shared key `TIME_AUTH_KEY` may hold, in bytes.
+pub const MAX_TIME_AUTH_KEY_LEN: usize = 64;
+
+/// The key shared with a signing time server (`TIME_AUTH_KEY`, as hex), if one is configured.
+///
+/// When set, NTP replies must carry a matching HMAC-SHA256. A key that is not valid hex, or
+/// is longer than `MAX_TIME_AUTH_KEY_LEN` bytes, is treated as unset.
+#[must_use]
+pub fn time_auth_key() -> Option<Vec<u8, MAX_TIME_AUTH_KEY_LEN>> {
+    let hex = option_env!("TIME_AUTH_KEY")?.as_bytes();
+    let mut key = Vec::new();
+    for pair in hex.chunks(2) {
+        let [high, low] = pair else {
+            return None;
+        };
+        let byte = hex_value(*high)?
+            .checked_mul(16)?
+            .checked_add(hex_value(*low)?)?;
+        key.push(byte).ok()?;
+    }
+    (!key.is_empty()).then_some(key)
+}
+
+/// The value of one hex digit.
+fn hex_value(digit: u8) -> Option<u8> {
+    char::from(digit)
+        .to_digit(16)
+        .and_then(|value| u8::try_from(value).ok())
+}
+
+/// The clock's live settings, as last published by the clock task, or `None` before it first
+/// publishes them.
+static LIVE_SETTINGS: Mutex<CriticalSectionRawMutex, Cell<Option<Settings>>> =
+    Mutex::new(Cell::new(None));
+
+/// Called by the clock task whenever its settings change, so the short-press cycle follows a
+/// `days_since` date saved or cleared at run time, and edits start from what the clock shows.
+pub(crate) fn publish_live_settings(settings: &Settings) {
+    LIVE_SETTINGS.lock(|cell| cell.set(Some(*settings)));
+}
+
+/// The clock's live settings, as last published by the clock task (the build's defaults
+/// before it first publishes them).
+pub(crate) fn live_settings() -> Settings {
+    LIVE_SETTINGS.lock(Cell::get).unwrap_or_else(default_settings)
+}
+
+/// Which optional display modes the build and the live settings enable, for the short-press
+/// cycle in `clock_core::MODES`.
+#[must_use]
+pub fn mode_conditions() -> ModeConditions {
+    ModeConditions {
+        weather: weather_url().is_some(),
+        days_since: live_settings().days_since.is_some(),
+        carousel: !default_carousel().is_empty(),
+        battery: supply_config().is_some(),
+        diagnostics: cfg!(feature = "diagnostics"),
+    }
+}
+
+/// The button wiring, from the compile-time `BUTTON_ACTIVE_LOW` and `BUTTON_DEBOUNCE_MS`.
+/// Unset values keep `ButtonConfig::default()`.
+#[must_use]
+pub fn default_button_config() -> ButtonConfig {
+    let defaults = ButtonConfig::default();
+    ButtonConfig {
+        active_level: if env_flag(option_env!("BUTTON_ACTIVE_LOW")) {
+            ActiveLevel::Low
+        } else {
+            defaults.active_level
+        },
+        debounce_delay: env_millis(option_env!("BUTTON_DEBOUNCE_MS"))
+            .unwrap_or(defaults.debounce_delay),
+    }
+}
+
+/// The gesture timings used until some are saved, from the compile-time `LONG_PRESS_MS`,
+/// `DOUBLE_PRESS_MS`, and `VERY_LONG_PRESS_MS`. Unset values keep `GestureTimings::DEFAULT`,
+/// and timings that are out of range are ignored as a whole.
+#[must_use]
+pub fn default_gesture_timings() -> GestureTimings {
+    let defaults = GestureTimings::DEFAULT;
+    let env_ms = |value: Option<&str>| value.and_then(|ms| ms.parse::<u16>().ok());
+    Some(GestureTimings {
+        long_press_ms: env_ms(option_env!("LONG_PRESS_MS")).unwrap_or(defaults.long_press_ms),
+        double_press_ms: env_ms(option_env!("DOUBLE_PRESS_MS"))
+            .unwrap_or(defaults.double_press_ms),
+        very_long_press_ms: env_ms(option_env!("VERY_LONG_PRESS_MS"))
+            .unwrap_or(defaults.very_long_press_ms),
+    })
+    .filter(GestureTimings::is_valid)
+    .unwrap_or(defaults)
+}
+
+/// The NEC command codes an IR remote's keys send, for the keys the clock responds to.
+#[cfg_attr(feature = "logging", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct IrRemoteKeys {
+    /// The key that acts as a short press: next mode, or the next offset while editing.
+    pub mode: Option<u8>,
+    /// The key that acts as a long press: enter or leave UTC offset editing.
+    pub set: Option<u8>,
+}
+
+impl IrRemoteKeys {
+    /// The press a key's command stands for, if it is mapped.
+    #[must_use]
+    pub fn press_for(&self, command: u8) -> Option<PressDuration> {
+        if self.mode == Some(command) {
+            Some(PressDuration::Short)
+        } else if self.set == Some(command) {
+            Some(PressDuration::Long)
+        } else {
+            None
+        }
+    }
+}
+
+/// The IR remote keys, from the compile-time `IR_MODE_KEY` and `IR_SET_KEY` (command codes
+/// in hex, such as `46`, as the log shows them). Unset keys are ignored.
+#[must_use]
+pub fn ir_remote_keys() -> IrRemoteKeys {
+    IrRemoteKeys {
+        mode: option_env!("IR_MODE_KEY").and_then(hex_byte),
+        set: option_env!("IR_SET_KEY").and_then(hex_byte),
+    }
+}
+
+/// The kind of touch pad fitted.
+#[cfg_attr(feature = "logging", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TouchPadKind {
+    /// A touch module, such as a TTP223, whose output goes high while touched.
+    Module,
+    /// A bare copper pad, sensed by how long it takes to charge.
+    Bare,
+}
+
+/// The touch pad fitted (`TOUCH_PAD`, `module` or `bare`). Unset or unknown means none.
+#[must_use]
+pub fn touch_pad_kind() -> Option<TouchPadKind> {
+    match option_env!("TOUCH_PAD")? {
+        "module" | "ttp223" => Some(TouchPadKind::Module),
+        "bare" => Some(TouchPadKind::Bare),
+        _ => None,
+    }
+}
+
+/// Parses one byte written as hex, with or without a `0x` prefix.
+fn hex_byte(text: &str) -> Option<u8> {
+    u8::from_str_radix(text.trim_start_matches("0x"), 16).ok()
+}
+
+/// Interprets an optional environment variable as a number of milliseconds.
+fn env_millis(value: Option<&str>) -> Option<Duration> {
+    value
+        .and_then(|val| val.parse::<u64>().ok())
+        .map(Duration::from_millis)
+}
+
+/// Interprets an optional environment variable as a yes/no flag. Unset means "no".
+fn env_flag(value: Option<&str>) -> bool {
+    value.is_some_and(|val| val == "1" || val.eq_ignore_ascii_case("true"))
+}
+
+/// The melody the `custom` alarm sound plays (`ALARM_MELODY`, such as `b=160,o=5:e,e,f,g`),
+/// or `None` if it is unset or malformed.
+#[must_use]
+pub fn alarm_melody() -> Option<Melody<'static>> {
+    option_env!("ALARM_MELODY").and_then(|text| Melody::parse(text).ok())
+}
+
+/// The local hours when the buzzer does not tick (`TICK_QUIET_HOURS`, such as `22-7`).
+/// Unset means the network's quiet hours (`NETWORK_QUIET_HOURS`), if any.
+#[must_use]
+pub fn tick_quiet_hours() -> Option<QuietHours> {
+    option_env!("TICK_QUIET_HOURS")
+        .and_then(QuietHours::parse)
+        .or_else(network_quiet_hours)
+}
@@ -1,51 +1,74 @@
-use core::num::NonZeroU8;
-
 use embassy_time::Duration;
-use heapless::{LinearMap, Vec};
 
-/// The number of cells (digits) in the display.
-/// The Display a 4-digit, 8-segment display.
-pub const CELL_COUNT_U8: u8 = 4;
-pub const CELL_COUNT: usize = CELL_COUNT_U8 as usize;
+pub use clock_core::{
+    BitsToIndexes, Segments, CELL_COUNT, CELL_COUNT_U8, ONE_DAY, ONE_HOUR, ONE_MINUTE,
+    ONE_SECOND, SEGMENT_COUNT, TICKS_IN_ONE_DAY,
+};
+
+/// Debounce delay for the button.
+/// This prevents registering multiple presses from a single button press event.
+pub const BUTTON_DEBOUNCE_DELAY: Duration = Duration::from_millis(10);
 
-/// The number of segments per digit in the display.
-/// Each digit has 8 segments (7 segments plus a decimal point).
-pub const SEGMENT_COUNT: usize = 8;
+/// How often a held long press sends `ButtonEvent::Repeat`. `SET_TIME_RAMP` and
+/// `HOUR_EDIT_SPEED` are counted in these.
+pub const BUTTON_REPEAT_INTERVAL: Duration = Duration::from_millis(100);
 
-/// Duration representing one second.
-pub const ONE_SECOND: Duration = Duration::from_secs(1);
+/// How long a requested time sync may take before it is shown as failed.
+pub const SYNC_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
 
-/// Duration representing one minute (60 seconds).
-pub const ONE_MINUTE: Duration = Duration::from_secs(60);
+/// How long `Sync` or `FAIL` stays on the display after a requested time sync.
+pub const SYNC_RESULT_DISPLAY_DURATION: Duration = Duration::from_secs(2);
 
-/// Duration representing one hour (60 minutes).
-pub const ONE_HOUR: Duration = Duration::from_secs(60 * 60);
+/// How long `Survey` waits after measuring the signal before it scans again.
+pub const SURVEY_INTERVAL: Duration = Duration::from_secs(1);
 
-/// Duration representing one day (24 hours).
-pub const ONE_DAY: Duration = Duration::from_secs(60 * 60 * 24);
+/// How long an editor waits for a press before giving up, leaving the setting unchanged.
+pub const EDIT_INACTIVITY_TIMEOUT: Duration = Duration::from_secs(30);
 
-/// Duration representing the number of ticks in one day.
-pub const TICKS_IN_ONE_DAY: u64 = ONE_DAY.as_ticks();
+/// How long `SetTime` waits after the last press before going back to `HoursMinutes`.
+pub const SET_TIME_DONE_DELAY: Duration = Duration::from_secs(5);
 
-/// Debounce delay for the button.
-/// This prevents registering multiple presses from a single button press event.
-pub const BUTTON_DEBOUNCE_DELAY: Duration = Duration::from_millis(10);
+/// How the time moves while the button is held in `SetTime`: a minute at a time, first once
+/// a second and then ten times a second, each for the given number of steps. After that it
+/// moves an hour every `HOUR_EDIT_SPEED`.
+pub const SET_TIME_RAMP: [(Duration, u8); 2] = [
+    (Duration::from_secs(1), 5),
+    (Duration::from_millis(100), 50),
+];
 
-/// Duration representing a long button press.
-/// Holding the button for this duration triggers additional functionality.
-pub const LONG_PRESS_DURATION: Duration = Duration::from_millis(500);
+/// How long `Off` waits, with the display blank and the radio going down, before dormant sleep
+/// halts everything.
+pub const DEEP_SLEEP_SETTLE: Duration = Duration::from_secs(1);
 
-/// Sleep duration between multiplexing updates.
-/// Determines how often the multiplexed display is refreshed.
-pub const MULTIPLEX_SLEEP: Duration = Duration::from_millis(3);
+/// How long `rSt` stays on the display before a factory reset reboots the clock.
+pub const FACTORY_RESET_DISPLAY_DURATION: Duration = Duration::from_secs(1);
 
-/// Delay for the "off" state during blinking.
-/// Controls how long the display or segment remains off during a blink cycle.
-pub const BLINK_OFF_DELAY: Duration = Duration::from_millis(50);
+/// How many steps a change of brightness takes, so it fades rather than jumps. With
+/// `BRIGHTNESS_RAMP_STEP`, a change takes half a second.
+pub const BRIGHTNESS_RAMP_STEPS: u16 = 20;
 
-/// Delay for the "on" state during blinking.
-/// Controls how long the display or segment remains on during a blink cycle.
-pub const BLINK_ON_DELAY: Duration = Duration::from_millis(150);
+/// How long each step of a brightness ramp lasts.
+pub const BRIGHTNESS_RAMP_STEP: Duration = Duration::from_millis(25);
+
+/// How long the display dims when the button is pressed, so a press registers even when
+/// what it does is hard to see.
+pub const PRESS_FEEDBACK_DURATION: Duration = Duration::from_millis(80);
+
+/// How long each second's tick sounds on the buzzer: a click, not a beep.
+pub const TICK_DURATION: Duration = Duration::from_millis(2);
+
+/// The longest an alarm sound plays before it silences itself.
+pub const ALARM_SOUND_LIMIT: Duration = Duration::from_secs(60);
+
+/// How much of each note of a melody is left silent, so repeated notes stay apart.
+pub const NOTE_GAP: Duration = Duration::from_millis(20);
+
+/// The pause the talking clock leaves after each recorded word.
+pub const WORD_GAP: Duration = Duration::from_millis(60);
+
+/// How long the button must stay held after a long press in `HHMM` has the talking clock say
+/// the time, to go on to the UTC offset editor.
+pub const ANNOUNCE_EDIT_HOLD: Duration = Duration::from_secs(2);
 
 /// Speed for editing minutes in the clock.
 /// Controls how quickly the minutes increment during manual adjustments.
@@ -55,14 +78,12 @@ pub const MINUTE_EDIT_SPEED: Duration = Duration::from_millis(250);
 /// Controls how quickly the hours increment during manual adjustments.
 pub const HOUR_EDIT_SPEED: Duration = Duration::from_millis(500);
 
-/// A map from patterns to the indexes of the cells that contain that pattern.
-///
-/// For example, the numeral five has the LED bit pattern `0b_0110_1101`.
-/// If the display is " 55 ", then the map will contain just one entry with the key`0b_0110_1101`
-/// and the value `[1, 2]` representing the indexes of the cells that contain the numeral five.
-///
-/// The map is used to multiplex efficiently.
-///
-/// - The inner vector is statically allocated with a capacity of `CELL_COUNT`, the most possible.
-/// - The map itself can hold up to `CELL_COUNT` entries, the most possible.
-pub type BitsToIndexes = LinearMap<NonZeroU8, Vec<u8, CELL_COUNT>, CELL_COUNT>;
+/// How many digits each Nixie tube spins through when its cathodes are exercised: three
+/// times through all ten.
+pub const SLOT_MACHINE_ROUNDS: u8 = 30;
+/// How long each digit shows while a Nixie display's cathodes are exercised.
+pub const SLOT_MACHINE_STEP: Duration = Duration::from_millis(100);
+
+/// When the scheduler exercises a Nixie display's cathodes each night, after local midnight:
+/// 3 am, while the clock is least likely to be watched.
+pub const CATHODE_REFRESH_AT: Duration = Duration::from_secs(3 * 60 * 60);
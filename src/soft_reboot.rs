@@ -0,0 +1,167 @@
+//! Soft reboot virtual device - reboots for maintenance without the user noticing
+//!
+//! Before rebooting, the display mode and the time are written to the watchdog's scratch
+//! registers, which keep their values through a watchdog reboot (but not a power cycle). At
+//! start-up, `main` reads them back, so the clock comes back in the same mode, on time, and
+//! (because night dimming follows the time) at the same brightness, without waiting for a sync.
+
+use core::cell::RefCell;
+
+use embassy_executor::{SpawnError, Spawner};
+use embassy_rp::watchdog::Watchdog;
+use embassy_sync::blocking_mutex::{raw::CriticalSectionRawMutex, Mutex};
+use embassy_sync::signal::Signal;
+use embassy_time::Instant;
+
+use clock_core::{ClockState, UnixSeconds};
+
+use crate::info;
+
+/// Marks scratch registers written by `SoftReboot`. Anything else (such as the zeros after a
+/// power cycle) means there is nothing to resume.
+const SNAPSHOT_MAGIC: u32 = 0x434C_4B31; // "CLK1"
+
+/// The watchdog scratch registers used. The boot ROM owns registers 4 to 7.
+const MAGIC_SCRATCH: usize = 0;
+const STATE_SCRATCH: usize = 1;
+const UNIX_SECONDS_SCRATCH: usize = 2;
+
+/// The Unix seconds stored when the time was never synced.
+const NO_TIME: u32 = 0;
+
+/// What a soft reboot keeps: the display mode and, once synced, the time.
+#[cfg_attr(feature = "logging", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug)]
+pub struct Snapshot {
+    /// The mode to come back in.
+    pub clock_state: ClockState,
+    /// The time at the reboot, if the clock had synced.
+    pub unix_seconds: Option<UnixSeconds>,
+}
+
+/// The clock's latest mode, and its time at a given instant.
+type Latest = (ClockState, Option<(UnixSeconds, Instant)>);
+
+/// The latest mode and time, published by the clock task.
+static LATEST: Mutex<CriticalSectionRawMutex, RefCell<Latest>> =
+    Mutex::new(RefCell::new((ClockState::HoursMinutes, None)));
+
+/// Publishes the clock's mode (called by the clock task when it changes).
+pub(crate) fn publish_clock_state(clock_state: ClockState) {
+    LATEST.lock(|cell| cell.borrow_mut().0 = clock_state);
+}
+
+/// Publishes the clock's time (called by the clock task whenever it is set).
+pub(crate) fn publish_unix_seconds(unix_seconds: Option<UnixSeconds>) {
+    let time = unix_seconds.map(|seconds| (seconds, Instant::now()));
+    LATEST.lock(|cell| cell.borrow_mut().1 = time);
+}
+
+/// The clock's time now, from the latest published time and how long ago it was published,
+/// if the clock has one.
+pub(crate) fn latest_unix_seconds() -> Option<UnixSeconds> {
+    wall_clock_at(Instant::now())
+}
+
+/// The Unix time at `instant`, reckoned from the latest published time, if the clock has one
+/// (as `ClockTime::to_wall_clock` does), for reporting when something happened.
+pub(crate) fn wall_clock_at(instant: Instant) -> Option<UnixSeconds> {
+    let (_, time) = LATEST.lock(|cell| *cell.borrow());
+    let (seconds, at) = time?;
+    let since = if instant >= at {
+        i64::try_from(instant.duration_since(at).as_secs()).ok()?
+    } else {
+        i64::try_from(at.duration_since(instant).as_secs())
+            .ok()?
+            .checked_neg()?
+    };
+    Some(UnixSeconds(seconds.as_i64().saturating_add(since)))
+}
+
+/// A type alias for the notifier that asks the `SoftReboot` to reboot.
+pub type SoftRebootNotifier = Signal<CriticalSectionRawMutex, ()>;
+
+/// Soft reboot virtual device - saves a `Snapshot` and reboots when asked
+#[derive(Clone, Copy)]
+pub struct SoftReboot<'a>(&'a SoftRebootNotifier);
+
+impl SoftReboot<'_> {
+    /// Create a new `SoftReboot` instance, which entails starting an Embassy task.
+    ///
+    /// # Arguments
+    ///
+    /// * `watchdog` - The watchdog, already used by `SoftReboot::take_snapshot`.
+    /// * `notifier` - The static notifier that carries reboot requests. It is created with
+    ///          `SoftReboot::notifier()`.
+    /// * `spawner` - The spawner that will spawn the task that reboots.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SpawnError` if the task cannot be spawned.
+    #[must_use = "Must be used to manage the spawned task"]
+    pub fn new(
+        watchdog: Watchdog,
+        notifier: &'static SoftRebootNotifier,
+        spawner: Spawner,
+    ) -> Result<Self, SpawnError> {
+        spawner.spawn(device_loop(watchdog, notifier))?;
+        Ok(Self(notifier))
+    }
+
+    /// Creates a new `SoftRebootNotifier` instance.
+    ///
+    /// This should be assigned to a static variable and passed to the `SoftReboot::new()`
+    /// method.
+    #[must_use]
+    pub const fn notifier() -> SoftRebootNotifier {
+        Signal::new()
+    }
+
+    /// Saves the clock's mode and time, then reboots.
+    pub fn reboot(&self) {
+        self.0.signal(());
+    }
+
+    /// Returns what the last soft reboot saved, if this start-up followed one, and clears it
+    /// so that a later reboot of another kind does not resume it again.
+    #[must_use]
+    pub fn take_snapshot(watchdog: &mut Watchdog) -> Option<Snapshot> {
+        let magic = watchdog.get_scratch(MAGIC_SCRATCH);
+        watchdog.set_scratch(MAGIC_SCRATCH, 0);
+        if magic != SNAPSHOT_MAGIC {
+            return None;
+        }
+        let clock_state = u8::try_from(watchdog.get_scratch(STATE_SCRATCH))
+            .ok()
+            .and_then(ClockState::from_resume_code)
+            .unwrap_or_default();
+        let unix_seconds = match watchdog.get_scratch(UNIX_SECONDS_SCRATCH) {
+            NO_TIME => None,
+            seconds => Some(UnixSeconds(i64::from(seconds))),
+        };
+        Some(Snapshot {
+            clock_state,
+            unix_seconds,
+        })
+    }
+}
+
+#[embassy_executor::task]
+async fn device_loop(mut watchdog: Watchdog, notifier: &'static SoftRebootNotifier) -> ! {
+    notifier.wait().await;
+    let clock_state = LATEST.lock(|cell| cell.borrow().0);
+    let unix_seconds =
+        latest_unix_seconds().and_then(|seconds| u32::try_from(seconds.as_i64()).ok());
+    info!("Soft reboot from {:?}", clock_state);
+    watchdog.set_scratch(
+        STATE_SCRATCH,
+        u32::from(clock_state.resume_code().unwrap_or_default()),
+    );
+    watchdog.set_scratch(UNIX_SECONDS_SCRATCH, unix_seconds.unwrap_or(NO_TIME));
+    watchdog.set_scratch(MAGIC_SCRATCH, SNAPSHOT_MAGIC);
+    watchdog.trigger_reset();
+    // The reset takes effect within a few cycles
+    loop {
+        core::future::pending::<()>().await;
+    }
+}
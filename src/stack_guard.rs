@@ -0,0 +1,64 @@
+//! Stack guard virtual device - watches the bottom of the stack and panics once the stack
+//! reaches it, so the clock stops (saving the crash, without `logging`) instead of running on
+//! with its statics overwritten
+//!
+//! The RP2040's Cortex-M0+ has no stack limit register (`MSPLIM` is ARMv8-M), so the guard
+//! is the painted words `paint_stack` leaves at the stack's bottom, checked on a timer.
+
+use embassy_executor::{SpawnError, Spawner};
+use embassy_time::{Duration, Timer};
+
+use crate::memory_report::{stack_bytes, stack_guard_intact, stack_high_water};
+use crate::{info, warn};
+
+/// How often the guard is checked. Reading it takes a few microseconds.
+const STACK_CHECK_PERIOD: Duration = Duration::from_secs(1);
+
+/// How often the high-water mark is checked, which reads the whole unused stack.
+const HIGH_WATER_CHECK_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// Stack guard virtual device - panics, through the crash handler that saves the report and
+/// reboots (or `panic-probe`, with `logging`), as soon as it finds the stack has overflowed.
+/// It also warns once the stack's high-water mark passes three quarters of the room it has.
+pub struct StackGuard;
+
+impl StackGuard {
+    /// Create the stack guard and spawn its task. Call `paint_stack` first.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SpawnError` if the task cannot be spawned.
+    pub fn new(spawner: Spawner) -> Result<Self, SpawnError> {
+        spawner.spawn(device_loop())?;
+        Ok(Self)
+    }
+}
+
+#[embassy_executor::task]
+async fn device_loop() -> ! {
+    let checks_per_high_water = HIGH_WATER_CHECK_PERIOD
+        .as_ticks()
+        .checked_div(STACK_CHECK_PERIOD.as_ticks())
+        .unwrap_or(1);
+    let mut warned = false;
+    let mut checks = 0_u64;
+    loop {
+        Timer::after(STACK_CHECK_PERIOD).await;
+        if !stack_guard_intact() {
+            panic!(
+                "Stack overflow: the stack reached its guard ({} bytes of room)",
+                stack_bytes()
+            );
+        }
+        checks = checks.wrapping_add(1);
+        if warned || checks.checked_rem(checks_per_high_water) != Some(0) {
+            continue;
+        }
+        let high_water = stack_high_water();
+        info!("Stack high water: {} of {} bytes", high_water, stack_bytes());
+        if high_water.saturating_mul(4) > stack_bytes().saturating_mul(3) {
+            warn!("Stack is three quarters full: {} bytes used", high_water);
+            warned = true;
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! Status display virtual device - an SSD1306 OLED (128x64, I2C) beside the LED digits,
+//! showing what they have no room for: the full date, the network, and the time syncs
+
+use core::fmt::Write;
+use core::task::Poll;
+
+use embassy_executor::{SpawnError, Spawner};
+use embassy_futures::{poll_once, select::select};
+use embassy_net::Stack;
+use embassy_rp::{
+    bind_interrupts,
+    i2c::{self, Async, I2c},
+    peripherals::{I2C0, PIN_16, PIN_17},
+    Peri,
+};
+use embassy_time::Timer;
+use embedded_graphics::{
+    mono_font::{ascii::FONT_6X10, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    prelude::{Drawable, Point},
+    text::{Baseline, Text},
+};
+use heapless::String;
+use ssd1306::{
+    mode::DisplayConfigAsync, rotation::DisplayRotation, size::DisplaySize128x64,
+    I2CDisplayInterface, Ssd1306Async,
+};
+
+use crate::{
+    button::{self, wait_for_press},
+    info,
+    metrics::skew_history,
+    network_policy::utc_offset_minutes,
+    provisioning::provisioning,
+    shared_constants::{ONE_MINUTE, ONE_SECOND},
+    soft_reboot::latest_unix_seconds,
+    time_sync::time_arbiter,
+    warn,
+    wifi::Wifi,
+    Display2Format,
+};
+
+/// Room for the six lines of 21 characters that fit the screen in `FONT_6X10`.
+const STATUS_TEXT_LEN: usize = 6 * 22;
+
+/// Sync corrections shown, most recent first.
+const SHOWN_CORRECTIONS: usize = 3;
+
+bind_interrupts!(struct Irqs {
+    I2C0_IRQ => i2c::InterruptHandler<I2C0>;
+});
+
+/// Status display virtual device - redraws an SSD1306 on I2C0 (SDA on GPIO 16, SCL on
+/// GPIO 17) every second, and at once on a button press
+pub struct StatusDisplay;
+
+impl StatusDisplay {
+    /// Create the status display and spawn its task. With no display fitted, the task
+    /// checks again each minute.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SpawnError` if the task cannot be spawned.
+    pub fn new(
+        i2c: Peri<'static, I2C0>,
+        sda: Peri<'static, PIN_16>,
+        scl: Peri<'static, PIN_17>,
+        wifi: &'static Wifi,
+        spawner: Spawner,
+    ) -> Result<Self, SpawnError> {
+        let mut config = i2c::Config::default();
+        config.frequency = 400_000;
+        let bus = I2c::new_async(i2c, scl, sda, Irqs, config);
+        spawner.spawn(device_loop(bus, wifi))?;
+        Ok(Self)
+    }
+}
+
+#[embassy_executor::task]
+async fn device_loop(bus: I2c<'static, I2C0, Async>, wifi: &'static Wifi) -> ! {
+    let mut presses = button::subscribe()
+        .inspect_err(|err| warn!("Status display ignores presses: {}", Display2Format(err)))
+        .ok();
+    let mut display = Ssd1306Async::new(
+        I2CDisplayInterface::new(bus),
+        DisplaySize128x64,
+        DisplayRotation::Rotate0,
+    )
+    .into_buffered_graphics_mode();
+    while display.init().await.is_err() {
+        info!("No status display found; checking again in a minute");
+        Timer::after(ONE_MINUTE).await;
+    }
+    info!("Status display ready");
+
+    let style = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+    let mut stack = None;
+    let mut text = String::<STATUS_TEXT_LEN>::new();
+    loop {
+        // The stack is ready once WiFi has joined (or never, without WiFi)
+        if let Poll::Ready(ready_stack) = poll_once(wifi.stack()) {
+            stack = ready_stack;
+        }
+        text.clear();
+        // A full buffer just cuts the last line short
+        write_status(&mut text, stack).ok();
+        display.clear_buffer();
+        let drawn = Text::with_baseline(&text, Point::zero(), style, Baseline::Top)
+            .draw(&mut display)
+            .is_ok();
+        if !drawn || display.flush().await.is_err() {
+            warn!("Status display did not take the update");
+        }
+        // A press may change the mode or settings, so show it without waiting
+        select(Timer::after(ONE_SECOND), wait_for_press(presses.as_mut())).await;
+    }
+}
+
+/// Writes the status lines: the local date and time, the network, the current time source,
+/// and the latest sync corrections.
+fn write_status(out: &mut impl Write, stack: Option<&Stack<'static>>) -> core::fmt::Result {
+    let utc_offset_minutes = utc_offset_minutes();
+    match latest_unix_seconds() {
+        Some(unix_seconds) => {
+            unix_seconds.write_local_date(out, utc_offset_minutes)?;
+            out.write_char('\n')?;
+            unix_seconds.write_local_time(out, utc_offset_minutes)?;
+            writeln!(out, " UTC{utc_offset_minutes:+}m")?;
+        }
+        None => out.write_str("Time not set\n\n")?,
+    }
+    match provisioning() {
+        Some(provisioning) => writeln!(out, "WiFi {}", provisioning.ssid)?,
+        None => out.write_str("WiFi none\n")?,
+    }
+    match stack.and_then(Stack::config_v4) {
+        Some(config) => writeln!(out, "IP {}", config.address.address())?,
+        None => out.write_str("IP none\n")?,
+    }
+    match time_arbiter().current() {
+        Some((source, set_at)) => writeln!(
+            out,
+            "Sync {} {}s ago",
+            source.name(),
+            set_at.elapsed().as_secs()
+        )?,
+        None => out.write_str("Sync none\n")?,
+    }
+    out.write_str("Adj")?;
+    let history = skew_history();
+    if history.is_empty() {
+        out.write_str(" none")?;
+    }
+    for correction_millis in history.iter_recent_first().take(SHOWN_CORRECTIONS) {
+        write!(out, " {correction_millis:+}")?;
+    }
+    if !history.is_empty() {
+        out.write_str(" ms")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,463 @@
+//! Settings, the UTC offset, reminders, gesture timings, and the `Config` that survive a
+//! reboot, kept in the last 4K sector of flash, a log of climate samples in the two sectors
+//! before it, the last crash report in the sector before that, and the config page written
+//! by the `clock-flash` host tool in the sector before that (all reserved in `memory.x`).
+
+use clock_core::{
+    climate_log_head, ClimateSample, Config, GestureTimings, PendingSettings, Provisioning,
+    Reminders, Settings, UnixSeconds, CLIMATE_SAMPLE_BYTES, CONFIG_BYTES, GESTURE_TIMINGS_BYTES,
+    PROVISIONING_BYTES, PROVISIONING_OFFSET, REMINDERS_BYTES,
+};
+use embassy_rp::{
+    flash::{Blocking, Flash},
+    peripherals::FLASH,
+    Peri,
+};
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, mutex::Mutex};
+
+use crate::crash_dump::{CrashReport, CRASH_RECORD_LEN};
+use crate::settings::days_since;
+use crate::supply::flash_writes_safe;
+use crate::{Error, Result};
+
+/// Size of the Pico's flash chip.
+const FLASH_SIZE: usize = 0x20_0000;
+/// Start of the last 4K erase sector, which `memory.x` keeps out of the firmware image.
+const SECTOR_START: u32 = 0x1F_F000;
+/// End of the flash chip (and of the sector).
+const SECTOR_END: u32 = 0x20_0000;
+/// Marks a sector that holds a saved record. An erased sector reads as all `0xFF`.
+const MAGIC: [u8; 4] = *b"CLK2";
+/// Marks a record saved before `days_since`: `CLK1` and one byte of `Settings::to_bits`. It
+/// still loads, with the build's `DAYS_SINCE`, until the settings are next saved.
+const MAGIC_V1: [u8; 4] = *b"CLK1";
+/// A saved record is `MAGIC`, one byte of `Settings::to_bits`, then the `days_since` date as
+/// big-endian Unix seconds (all `0xFF` for none).
+const RECORD_LEN: usize = 13;
+/// The stored `days_since` when there is none. It is never a midnight, so it cannot clash.
+const NO_DATE: [u8; 8] = [0xFF; 8];
+/// Where the UTC offset record starts, relative to `SECTOR_START`.
+const UTC_OFFSET_OFFSET: u32 = 32;
+/// Marks a saved UTC offset record.
+const UTC_OFFSET_MAGIC: [u8; 4] = *b"UTC1";
+/// A saved UTC offset record is `UTC_OFFSET_MAGIC` followed by the offset in minutes as a
+/// big-endian `i32`.
+const UTC_OFFSET_RECORD_LEN: usize = 8;
+/// Where the reminders record starts, relative to `SECTOR_START`.
+const REMINDERS_OFFSET: u32 = 64;
+/// Marks a saved reminders record.
+const REMINDERS_MAGIC: [u8; 4] = *b"RMD1";
+/// A saved reminders record is `REMINDERS_MAGIC` followed by `Reminders::to_bytes`.
+const REMINDERS_RECORD_LEN: usize = 4 + REMINDERS_BYTES;
+/// Where the gesture timings record starts, relative to `SECTOR_START`.
+const GESTURE_TIMINGS_OFFSET: u32 = 128;
+/// Marks a saved gesture timings record.
+const GESTURE_TIMINGS_MAGIC: [u8; 4] = *b"GST1";
+/// A saved gesture timings record is `GESTURE_TIMINGS_MAGIC` followed by
+/// `GestureTimings::to_bytes`.
+const GESTURE_TIMINGS_RECORD_LEN: usize = 4 + GESTURE_TIMINGS_BYTES;
+/// Where the config record starts, relative to `SECTOR_START`.
+const CONFIG_OFFSET: u32 = 144;
+/// Marks a saved config record. Records saved before the tick volume, the alarm sound, and
+/// segment compensation (`CFG1` to `CFG4`) are ignored.
+const CONFIG_MAGIC: [u8; 4] = *b"CFG5";
+/// A saved config record is `CONFIG_MAGIC` followed by `Config::to_bytes`.
+const CONFIG_RECORD_LEN: usize = 4 + CONFIG_BYTES;
+/// The start of the sector that holds every record, rewritten as a whole because flash can
+/// only be erased a sector at a time.
+const USED_LEN: usize = 176;
+
+/// The size of a flash erase sector.
+const ERASE_SECTOR_LEN: u32 = 0x1000;
+/// Start of the climate log, a ring of `ClimateSample` records that ends where the settings
+/// sector starts.
+const CLIMATE_LOG_START: u32 = 0x1F_D000;
+/// How many samples the climate log's 8K has room for. A sector is erased ahead of the writes,
+/// so it keeps three to six weeks of hourly samples.
+pub const CLIMATE_LOG_SLOTS: usize = 1024;
+/// How many samples fit in one erase sector.
+const CLIMATE_SLOTS_PER_SECTOR: usize = 512;
+
+/// Start of the sector that holds the last crash report.
+const CRASH_SECTOR_START: u32 = 0x1F_C000;
+
+/// Reads, writes, and erases the clock's saved settings, reminders, gesture timings, and
+/// config.
+pub struct Storage(Flash<'static, FLASH, Blocking, FLASH_SIZE>);
+
+/// `Storage` shared between the button state machine and the HTTP server.
+pub type SharedStorage = Mutex<CriticalSectionRawMutex, Storage>;
+
+impl Storage {
+    /// Creates a new `Storage` from the flash peripheral.
+    #[must_use]
+    pub fn new(flash: Peri<'static, FLASH>) -> Self {
+        Self(Flash::new_blocking(flash))
+    }
+
+    /// Returns the saved settings, or `None` if nothing has been saved (or it was erased).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the flash cannot be read.
+    pub fn load_settings(&mut self) -> Result<Option<Settings>> {
+        let mut record = [0; RECORD_LEN];
+        self.0.blocking_read(SECTOR_START, &mut record)?;
+        let [m0, m1, m2, m3, settings_bits, date @ ..] = record;
+        Ok(match [m0, m1, m2, m3] {
+            MAGIC => Some(Settings {
+                days_since: (date != NO_DATE).then(|| UnixSeconds(i64::from_be_bytes(date))),
+                ..Settings::from_bits(settings_bits)
+            }),
+            MAGIC_V1 => Some(Settings {
+                days_since: days_since(),
+                ..Settings::from_bits(settings_bits)
+            }),
+            _ => None,
+        })
+    }
+
+    /// Saves settings so that they are used after the next reboot.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the supply is too low, or the flash cannot be erased or written.
+    pub fn save_settings(&mut self, settings: Settings) -> Result<()> {
+        self.rewrite(&[(0, settings_record(settings).as_slice())])
+    }
+
+    /// Returns the saved UTC offset in minutes, or `None` if none has been saved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the flash cannot be read.
+    pub fn load_utc_offset_minutes(&mut self) -> Result<Option<i32>> {
+        let mut record = [0; UTC_OFFSET_RECORD_LEN];
+        self.0
+            .blocking_read(SECTOR_START.saturating_add(UTC_OFFSET_OFFSET), &mut record)?;
+        let [m0, m1, m2, m3, minutes @ ..] = record;
+        Ok(([m0, m1, m2, m3] == UTC_OFFSET_MAGIC).then(|| i32::from_be_bytes(minutes)))
+    }
+
+    /// Saves a committed edit (the UTC offset and the settings) in one sector rewrite, so a
+    /// power cut cannot leave half of it saved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the supply is too low, or the flash cannot be erased or written.
+    pub fn save_pending_settings(&mut self, pending: &PendingSettings) -> Result<()> {
+        let mut utc_offset = [0; UTC_OFFSET_RECORD_LEN];
+        let (magic, minutes) = utc_offset.split_at_mut(UTC_OFFSET_MAGIC.len());
+        magic.copy_from_slice(&UTC_OFFSET_MAGIC);
+        minutes.copy_from_slice(&pending.utc_offset_minutes.to_be_bytes());
+        self.rewrite(&[
+            (0, settings_record(pending.settings).as_slice()),
+            (UTC_OFFSET_OFFSET as usize, utc_offset.as_slice()),
+        ])
+    }
+
+    /// Returns the saved reminders, or an empty table if none have been saved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the flash cannot be read.
+    pub fn load_reminders(&mut self) -> Result<Reminders> {
+        let mut record = [0; REMINDERS_RECORD_LEN];
+        self.0
+            .blocking_read(SECTOR_START.saturating_add(REMINDERS_OFFSET), &mut record)?;
+        let (magic, bytes) = record.split_at(REMINDERS_MAGIC.len());
+        Ok(match <&[u8; REMINDERS_BYTES]>::try_from(bytes) {
+            Ok(bytes) if magic == REMINDERS_MAGIC => Reminders::from_bytes(bytes),
+            _ => Reminders::new(),
+        })
+    }
+
+    /// Saves reminders so that they are used after the next reboot.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the supply is too low, or the flash cannot be erased or written.
+    pub fn save_reminders(&mut self, reminders: &Reminders) -> Result<()> {
+        let mut record = [0; REMINDERS_RECORD_LEN];
+        let (magic, bytes) = record.split_at_mut(REMINDERS_MAGIC.len());
+        magic.copy_from_slice(&REMINDERS_MAGIC);
+        bytes.copy_from_slice(&reminders.to_bytes());
+        self.rewrite(&[(REMINDERS_OFFSET as usize, record.as_slice())])
+    }
+
+    /// Returns the saved gesture timings, or `None` if none have been saved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the flash cannot be read.
+    pub fn load_gesture_timings(&mut self) -> Result<Option<GestureTimings>> {
+        let mut record = [0; GESTURE_TIMINGS_RECORD_LEN];
+        self.0.blocking_read(
+            SECTOR_START.saturating_add(GESTURE_TIMINGS_OFFSET),
+            &mut record,
+        )?;
+        let (magic, bytes) = record.split_at(GESTURE_TIMINGS_MAGIC.len());
+        Ok(match <&[u8; GESTURE_TIMINGS_BYTES]>::try_from(bytes) {
+            Ok(bytes) if magic == GESTURE_TIMINGS_MAGIC => GestureTimings::from_bytes(bytes),
+            _ => None,
+        })
+    }
+
+    /// Saves gesture timings so that they are used after the next reboot.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the supply is too low, or the flash cannot be erased or written.
+    pub fn save_gesture_timings(&mut self, timings: &GestureTimings) -> Result<()> {
+        let mut record = [0; GESTURE_TIMINGS_RECORD_LEN];
+        let (magic, bytes) = record.split_at_mut(GESTURE_TIMINGS_MAGIC.len());
+        magic.copy_from_slice(&GESTURE_TIMINGS_MAGIC);
+        bytes.copy_from_slice(&timings.to_bytes());
+        self.rewrite(&[(GESTURE_TIMINGS_OFFSET as usize, record.as_slice())])
+    }
+
+    /// Returns the saved config, or `None` if none has been saved.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the flash cannot be read.
+    pub fn load_config(&mut self) -> Result<Option<Config>> {
+        let mut record = [0; CONFIG_RECORD_LEN];
+        self.0
+            .blocking_read(SECTOR_START.saturating_add(CONFIG_OFFSET), &mut record)?;
+        let (magic, bytes) = record.split_at(CONFIG_MAGIC.len());
+        Ok(match <&[u8; CONFIG_BYTES]>::try_from(bytes) {
+            Ok(bytes) if magic == CONFIG_MAGIC => Config::from_bytes(bytes),
+            _ => None,
+        })
+    }
+
+    /// Saves a config so that it is used after the next reboot.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the supply is too low, or the flash cannot be erased or written.
+    pub fn save_config(&mut self, config: &Config) -> Result<()> {
+        let mut record = [0; CONFIG_RECORD_LEN];
+        let (magic, bytes) = record.split_at_mut(CONFIG_MAGIC.len());
+        magic.copy_from_slice(&CONFIG_MAGIC);
+        bytes.copy_from_slice(&config.to_bytes());
+        self.rewrite(&[(CONFIG_OFFSET as usize, record.as_slice())])
+    }
+
+    /// Returns the sample in climate log slot `slot`, or `None` if the slot is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the slot is out of range or the flash cannot be read.
+    pub fn climate_sample(&mut self, slot: usize) -> Result<Option<ClimateSample>> {
+        let mut record = [0; CLIMATE_SAMPLE_BYTES];
+        self.0.blocking_read(climate_slot_address(slot)?, &mut record)?;
+        Ok(ClimateSample::from_bytes(&record))
+    }
+
+    /// Returns the climate log slot the next sample goes in, just after the newest sample, or
+    /// slot 0 if the log is empty or does not look like one (see `climate_log_head`). Reading
+    /// the slots oldest first starts here too.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the flash cannot be read.
+    pub fn next_climate_slot(&mut self) -> Result<usize> {
+        Ok(self.climate_log_head()?.unwrap_or(0))
+    }
+
+    /// Adds `sample` to the climate log. Before the last slot of a sector is written, the next
+    /// sector (the oldest samples, once the log has wrapped) is erased, so there is always an
+    /// erased slot to show where the log goes on. A log that does not look like one, such as
+    /// flash left over from other firmware, is erased as a whole first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sample's time cannot be stored, the supply is too low, or the
+    /// flash cannot be read, erased, or written.
+    #[expect(
+        clippy::integer_division_remainder_used,
+        reason = "The divisor is a non-zero constant."
+    )]
+    pub fn append_climate_sample(&mut self, sample: &ClimateSample) -> Result<()> {
+        check_supply()?;
+        let record = sample.to_bytes().ok_or(Error::IndexOutOfBounds)?;
+        let slot = match self.climate_log_head()? {
+            Some(slot) => slot,
+            None => {
+                self.0.blocking_erase(CLIMATE_LOG_START, SECTOR_START)?;
+                0
+            }
+        };
+        let next = next_slot(slot);
+        if next % CLIMATE_SLOTS_PER_SECTOR == 0 {
+            let next_address = climate_slot_address(next)?;
+            self.0.blocking_erase(
+                next_address,
+                next_address.saturating_add(ERASE_SECTOR_LEN),
+            )?;
+        }
+        self.0.blocking_write(climate_slot_address(slot)?, &record)?;
+        Ok(())
+    }
+
+    /// The climate log slot the next sample goes in, or `None` if the log must be erased first.
+    fn climate_log_head(&mut self) -> Result<Option<usize>> {
+        climate_log_head(CLIMATE_LOG_SLOTS, |slot| {
+            let mut record = [0; CLIMATE_SAMPLE_BYTES];
+            self.0.blocking_read(climate_slot_address(slot)?, &mut record)?;
+            Ok(record == [0xFF; CLIMATE_SAMPLE_BYTES])
+        })
+    }
+
+    /// Returns the config page written by the `clock-flash` host tool (or `save_provisioning`),
+    /// or `None` if the clock has none or its CRC does not match. A firmware update leaves it,
+    /// but a factory reset erases it (see `erase_provisioning`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the flash cannot be read.
+    pub fn load_provisioning(&mut self) -> Result<Option<Provisioning>> {
+        let mut page = [0; PROVISIONING_BYTES];
+        self.0.blocking_read(PROVISIONING_OFFSET, &mut page)?;
+        Ok(Provisioning::from_bytes(&page))
+    }
+
+    /// Replaces the config page, such as to join another network after the next reboot.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the supply is too low, or the flash cannot be erased or written.
+    pub fn save_provisioning(&mut self, provisioning: &Provisioning) -> Result<()> {
+        self.erase_provisioning()?;
+        self.0.blocking_write(PROVISIONING_OFFSET, &provisioning.to_bytes())?;
+        Ok(())
+    }
+
+    /// Erases the config page, and with it the WiFi network and password, so the clock runs
+    /// without WiFi until it is provisioned again.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the supply is too low, or the flash cannot be erased.
+    pub fn erase_provisioning(&mut self) -> Result<()> {
+        check_supply()?;
+        self.0.blocking_erase(
+            PROVISIONING_OFFSET,
+            PROVISIONING_OFFSET.saturating_add(ERASE_SECTOR_LEN),
+        )?;
+        Ok(())
+    }
+
+    /// Returns the saved crash report, or `None` if there is none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the flash cannot be read.
+    pub fn load_crash_report(&mut self) -> Result<Option<CrashReport>> {
+        let mut record = [0; CRASH_RECORD_LEN];
+        self.0.blocking_read(CRASH_SECTOR_START, &mut record)?;
+        Ok(CrashReport::from_bytes(&record))
+    }
+
+    /// Saves `report` unless an earlier one is still saved, which it keeps.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the supply is too low, or the flash cannot be read, erased, or
+    /// written.
+    pub fn save_crash_report(&mut self, report: &CrashReport) -> Result<()> {
+        if self.load_crash_report()?.is_some() {
+            return Ok(());
+        }
+        self.clear_crash_report()?;
+        self.0.blocking_write(CRASH_SECTOR_START, &report.to_bytes())?;
+        Ok(())
+    }
+
+    /// Erases the saved crash report, acknowledging it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the supply is too low, or the flash cannot be erased.
+    pub fn clear_crash_report(&mut self) -> Result<()> {
+        check_supply()?;
+        self.0.blocking_erase(
+            CRASH_SECTOR_START,
+            CRASH_SECTOR_START.saturating_add(ERASE_SECTOR_LEN),
+        )?;
+        Ok(())
+    }
+
+    /// Erases everything saved, so the clock falls back to its compile-time defaults.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the supply is too low, or the flash cannot be erased.
+    pub fn erase(&mut self) -> Result<()> {
+        check_supply()?;
+        self.0.blocking_erase(SECTOR_START, SECTOR_END)?;
+        Ok(())
+    }
+
+    /// Replaces the bytes at each offset in the sector with its record, keeping the other
+    /// records.
+    fn rewrite(&mut self, records: &[(usize, &[u8])]) -> Result<()> {
+        check_supply()?;
+        let mut used = [0; USED_LEN];
+        self.0.blocking_read(SECTOR_START, &mut used)?;
+        for &(offset, record) in records {
+            used.get_mut(offset..offset.saturating_add(record.len()))
+                .ok_or(Error::IndexOutOfBounds)?
+                .copy_from_slice(record);
+        }
+        self.erase()?;
+        self.0.blocking_write(SECTOR_START, &used)?;
+        Ok(())
+    }
+}
+
+/// Fails with `Error::SupplyTooLow` if power might fail before a flash write finishes.
+fn check_supply() -> Result<()> {
+    if flash_writes_safe() {
+        Ok(())
+    } else {
+        Err(Error::SupplyTooLow)
+    }
+}
+
+/// The flash address of climate log slot `slot`.
+fn climate_slot_address(slot: usize) -> Result<u32> {
+    if slot >= CLIMATE_LOG_SLOTS {
+        return Err(Error::IndexOutOfBounds);
+    }
+    let offset = u32::try_from(slot.saturating_mul(CLIMATE_SAMPLE_BYTES))
+        .map_err(|_| Error::IndexOutOfBounds)?;
+    Ok(CLIMATE_LOG_START.saturating_add(offset))
+}
+
+/// The climate log slot after `slot`, wrapping to the first.
+pub(crate) const fn next_slot(slot: usize) -> usize {
+    let next = slot.saturating_add(1);
+    if next >= CLIMATE_LOG_SLOTS {
+        0
+    } else {
+        next
+    }
+}
+
+/// The settings record: `MAGIC`, the packed options, then the `days_since` date.
+fn settings_record(settings: Settings) -> [u8; RECORD_LEN] {
+    let mut record = [0; RECORD_LEN];
+    let (magic, rest) = record.split_at_mut(MAGIC.len());
+    let (settings_bits, date) = rest.split_at_mut(1);
+    magic.copy_from_slice(&MAGIC);
+    settings_bits.fill(settings.to_bits());
+    date.copy_from_slice(
+        &settings
+            .days_since
+            .map_or(NO_DATE, |date| date.as_i64().to_be_bytes()),
+    );
+    record
+}
@@ -0,0 +1,178 @@
+//! Supply monitor virtual device - watches a battery supply and warns before it runs flat
+
+use core::fmt::Write as _;
+use core::sync::atomic::{AtomicBool, AtomicU16, Ordering};
+
+use embassy_executor::{SpawnError, Spawner};
+use embassy_rp::{
+    adc::{self, Adc, Channel},
+    bind_interrupts,
+    gpio::Pull,
+    peripherals::{ADC, PIN_26},
+    Peri,
+};
+use embassy_time::{Duration, Instant, Timer};
+use heapless::String;
+
+use clock_core::{BlinkState, Message, SupplyConfig, SupplyLevel};
+
+use crate::{info, warn};
+use crate::clock::Clock;
+use crate::mqtt;
+use crate::settings::{battery_chemistry, supply_config};
+
+/// How often the supply is measured.
+const SUPPLY_SAMPLE_PERIOD: Duration = Duration::from_secs(10);
+
+/// How many ADC readings are averaged for each measurement, to smooth out noise.
+const SUPPLY_READINGS: u16 = 8;
+
+/// How often `bAtt` is shown again while the supply stays low.
+const LOW_SUPPLY_REMINDER: Duration = Duration::from_secs(10 * 60);
+
+/// The topic, after `MQTT_TOPIC/`, that supply level changes are published to.
+const ALERT_TOPIC: &str = "alert";
+
+/// The latest supply voltage in millivolts, or 0 before the first measurement.
+static SUPPLY_MILLIVOLTS: AtomicU16 = AtomicU16::new(0);
+
+/// Whether the supply is critically low.
+static SUPPLY_CRITICAL: AtomicBool = AtomicBool::new(false);
+
+/// Whether the supply is low or critically low.
+static SUPPLY_LOW: AtomicBool = AtomicBool::new(false);
+
+bind_interrupts!(struct Irqs {
+    ADC_IRQ_FIFO => adc::InterruptHandler;
+});
+
+/// The topic, after `MQTT_TOPIC/`, that supply level changes are published to.
+const ALERT_TOPIC: &str = "alert";
+
+/// The latest supply voltage in millivolts, if it is being monitored.
+pub(crate) fn supply_millivolts() -> Option<u16> {
+    Some(SUPPLY_MILLIVOLTS.load(Ordering::Relaxed)).filter(|&millivolts| millivolts > 0)
+}
+
+/// Whether there is enough supply to start erasing or writing flash. Storage never has
+/// unsaved changes (each is written when made), so refusing new writes is what keeps a
+/// failing battery from cutting one off partway, which would lose the whole sector.
+pub(crate) fn flash_writes_safe() -> bool {
+    !SUPPLY_CRITICAL.load(Ordering::Relaxed)
+}
+
+/// Whether the supply is low, so the clock should spare the battery where it can.
+pub(crate) fn supply_low() -> bool {
+    SUPPLY_LOW.load(Ordering::Relaxed)
+}
+
+/// Supply monitor virtual device - measures the supply through a divider on GPIO 26
+///
+/// It does nothing unless `SUPPLY_DIVIDER` is set at build time, since an unconnected pin
+/// would read as a flat battery.
+pub struct SupplyMonitor;
+
+impl SupplyMonitor {
+    /// Create the supply monitor and spawn its task.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SpawnError` if the task cannot be spawned.
+    pub fn new(
+        adc: Peri<'static, ADC>,
+        pin: Peri<'static, PIN_26>,
+        clock: Clock<'static>,
+        spawner: Spawner,
+    ) -> Result<Self, SpawnError> {
+        spawner.spawn(device_loop(adc, pin, clock))?;
+        Ok(Self)
+    }
+}
+
+#[embassy_executor::task]
+async fn device_loop(
+    adc: Peri<'static, ADC>,
+    pin: Peri<'static, PIN_26>,
+    clock: Clock<'static>,
+) -> ! {
+    let Some(config) = supply_config() else {
+        info!("Supply monitor disabled: SUPPLY_DIVIDER not set");
+        loop {
+            core::future::pending::<()>().await;
+        }
+    };
+    let mut adc = Adc::new(adc, Irqs, adc::Config::default());
+    let mut channel = Channel::new_pin(pin, Pull::None);
+    let chemistry = battery_chemistry();
+
+    let mut level = SupplyLevel::Normal;
+    let mut warned_at: Option<Instant> = None;
+    let mut shown_percent = None;
+    loop {
+        match read_millivolts(&mut adc, &mut channel, &config).await {
+            Ok(millivolts) => {
+                SUPPLY_MILLIVOLTS.store(millivolts, Ordering::Relaxed);
+                let percent = Some(chemistry.percent(millivolts));
+                if percent != shown_percent {
+                    clock.set_battery_percent(percent).await;
+                    shown_percent = percent;
+                }
+                let new_level = level.next(millivolts, &config);
+                if new_level != level {
+                    info!("Supply {} mV: {:?}", millivolts, new_level);
+                    level = new_level;
+                    SUPPLY_CRITICAL.store(level == SupplyLevel::Critical, Ordering::Relaxed);
+                    SUPPLY_LOW.store(level != SupplyLevel::Normal, Ordering::Relaxed);
+                    warned_at = None;
+                    alert(level, millivolts);
+                }
+            }
+            Err(err) => warn!("Supply measurement failed: {:?}", err),
+        }
+        if level != SupplyLevel::Normal
+            && warned_at.is_none_or(|at| at.elapsed() >= LOW_SUPPLY_REMINDER)
+        {
+            if let Ok(message) = Message::new("bAtt", 2, BlinkState::BlinkingAndOn) {
+                clock.show_message(message).await;
+            }
+            warned_at = Some(Instant::now());
+        }
+        Timer::after(SUPPLY_SAMPLE_PERIOD).await;
+    }
+}
+
+/// Publishes the new supply level to `MQTT_TOPIC/alert`, as in `supply low 3390mV`.
+///
+/// A low supply holds back network traffic (see `network_policy`), so the alert goes out on
+/// a connection opened just for it.
+fn alert(level: SupplyLevel, millivolts: u16) {
+    let name = match level {
+        SupplyLevel::Normal => "normal",
+        SupplyLevel::Low => "low",
+        SupplyLevel::Critical => "critical",
+    };
+    let mut payload = String::<{ mqtt::MAX_PAYLOAD_LEN }>::new();
+    if write!(payload, "supply {name} {millivolts}mV").is_ok()
+        && !mqtt::publish_urgent(ALERT_TOPIC, &payload)
+    {
+        warn!("Supply alert not published: MQTT is off or its outbox is full");
+    }
+}
+
+/// The supply voltage, averaged over `SUPPLY_READINGS` readings.
+#[expect(
+    clippy::integer_division_remainder_used,
+    reason = "Averaging needs no rounding."
+)]
+async fn read_millivolts(
+    adc: &mut Adc<'static, adc::Async>,
+    channel: &mut Channel<'static>,
+    config: &SupplyConfig,
+) -> Result<u16, adc::Error> {
+    let mut sum = 0_u32;
+    for _ in 0..SUPPLY_READINGS {
+        sum = sum.saturating_add(u32::from(adc.read(channel).await?));
+    }
+    let average = u16::try_from(sum / u32::from(SUPPLY_READINGS)).unwrap_or(u16::MAX);
+    Ok(config.millivolts(average))
+}
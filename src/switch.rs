@@ -0,0 +1,97 @@
+//! Switch virtual device - drives a spare GPIO, e.g. a relay for a lamp
+
+use embassy_executor::{SpawnError, Spawner};
+use embassy_futures::select::{select, Either};
+use embassy_rp::gpio;
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, signal::Signal};
+use embassy_time::{Duration, Timer};
+
+use crate::info;
+
+/// The longest pulse a `SwitchCommand::Pulse` may ask for.
+pub const MAX_PULSE: Duration = Duration::from_secs(60 * 60);
+
+/// A struct representing a switched output, such as a relay.
+#[derive(Clone, Copy)]
+pub struct Switch<'a>(&'a SwitchNotifier);
+
+/// A type alias for the notifier that sends commands to the `Switch`.
+///
+/// Only the latest command matters, so a newer one replaces one not yet carried out.
+pub type SwitchNotifier = Signal<CriticalSectionRawMutex, SwitchCommand>;
+
+/// What the `Switch` should do with its output.
+#[cfg_attr(feature = "logging", derive(defmt::Format))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwitchCommand {
+    /// Drive the output high.
+    On,
+    /// Drive the output low.
+    Off,
+    /// Drive the output high for a while, then low. A command that arrives during the pulse
+    /// ends it early.
+    Pulse(Duration),
+}
+
+impl Switch<'_> {
+    /// Create a new `Switch` instance, which entails starting an Embassy task.
+    ///
+    /// # Arguments
+    ///
+    /// * `pin` - The output the switch drives. It starts low.
+    /// * `notifier` - The static notifier that sends commands to the `Switch`.
+    ///          This notifier is created with the `Switch::notifier()` method.
+    /// * `spawner` - The spawner that will spawn the task that controls the switch.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SpawnError` if the task cannot be spawned.
+    #[must_use = "Must be used to manage the spawned task"]
+    pub fn new(
+        pin: gpio::Output<'static>,
+        notifier: &'static SwitchNotifier,
+        spawner: Spawner,
+    ) -> Result<Self, SpawnError> {
+        spawner.spawn(device_loop(pin, notifier))?;
+        Ok(Self(notifier))
+    }
+
+    /// Creates a new `SwitchNotifier` instance.
+    ///
+    /// This should be assigned to a static variable and passed to the `Switch::new()` method.
+    #[must_use]
+    pub const fn notifier() -> SwitchNotifier {
+        Signal::new()
+    }
+
+    /// Sends a command to the switch, replacing any not yet carried out.
+    pub fn command(&self, command: SwitchCommand) {
+        self.0.signal(command);
+    }
+}
+
+#[embassy_executor::task]
+async fn device_loop(mut pin: gpio::Output<'static>, notifier: &'static SwitchNotifier) -> ! {
+    pin.set_low();
+    let mut command = notifier.wait().await;
+    loop {
+        info!("Switch: {:?}", command);
+        command = match command {
+            SwitchCommand::On => {
+                pin.set_high();
+                notifier.wait().await
+            }
+            SwitchCommand::Off => {
+                pin.set_low();
+                notifier.wait().await
+            }
+            SwitchCommand::Pulse(duration) => {
+                pin.set_high();
+                match select(Timer::after(duration.min(MAX_PULSE)), notifier.wait()).await {
+                    Either::First(()) => SwitchCommand::Off,
+                    Either::Second(next_command) => next_command,
+                }
+            }
+        };
+    }
+}
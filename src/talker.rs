@@ -0,0 +1,140 @@
+//! Talker virtual device - says the time through an I2S DAC and amplifier (such as a
+//! MAX98357A), from recorded words kept in flash
+
+use embassy_executor::{SpawnError, Spawner};
+use embassy_rp::{
+    bind_interrupts,
+    peripherals::{DMA_CH4, PIN_14, PIN_15, PIN_16, PIO1},
+    pio::{Common, InterruptHandler, Pio},
+    pio_programs::i2s::{PioI2sOut, PioI2sOutProgram},
+    Peri,
+};
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, signal::Signal};
+use static_cell::StaticCell;
+
+use clock_core::{spoken_time, ClockTime, VoiceBank};
+
+use crate::{
+    info,
+    shared_constants::{ONE_MINUTE, WORD_GAP},
+    warn, Display2Format,
+};
+
+/// Stereo frames in each of the two DMA buffers: 16 ms at 16 kHz.
+const FRAMES_PER_BUFFER: usize = 256;
+
+/// The two sample buffers the talker fills and plays in turn.
+pub(crate) const RAM_BYTES: usize = FRAMES_PER_BUFFER * 2 * size_of::<u32>();
+
+/// The voice file `tools/make_voice.py` wrote, copied in by `build.rs` from
+/// `TALKING_CLOCK_VOICE`.
+static VOICE: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/voice.bin"));
+
+/// The hour (1-12) and minute to say, published by the clock task when asked.
+static ANNOUNCEMENTS: Signal<CriticalSectionRawMutex, (u8, u8)> = Signal::new();
+
+bind_interrupts!(struct Irqs {
+    PIO1_IRQ_0 => InterruptHandler<PIO1>;
+});
+
+/// Asks the talker to say the time `clock_time` shows (called by the clock task).
+pub(crate) fn announce_time(clock_time: &ClockTime) {
+    let (hours, minutes, _, _) = clock_time.h_m_s_sleep_duration(ONE_MINUTE);
+    ANNOUNCEMENTS.signal((hours, minutes));
+}
+
+/// Talker virtual device - says "It's eight thirty" and the like, one recorded word after
+/// another, over I2S from PIO1: data on GPIO 14, bit clock on GPIO 15, and word (left/right)
+/// clock on GPIO 16
+pub struct Talker;
+
+impl Talker {
+    /// Create the talker and spawn its task. With a malformed voice file it stays silent.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SpawnError` if the task cannot be spawned.
+    pub fn new(
+        pio: Peri<'static, PIO1>,
+        dma: Peri<'static, DMA_CH4>,
+        data_pin: Peri<'static, PIN_14>,
+        bit_clock_pin: Peri<'static, PIN_15>,
+        word_clock_pin: Peri<'static, PIN_16>,
+        spawner: Spawner,
+    ) -> Result<Self, SpawnError> {
+        let voice = match VoiceBank::new(VOICE) {
+            Ok(voice) => voice,
+            Err(err) => {
+                warn!("The talking clock is silent: {}", Display2Format(&err));
+                return Ok(Self);
+            }
+        };
+        let Pio {
+            mut common, sm0, ..
+        } = Pio::new(pio, Irqs);
+        let program = PioI2sOutProgram::new(&mut common);
+        let i2s = PioI2sOut::new(
+            &mut common,
+            sm0,
+            dma,
+            data_pin,
+            bit_clock_pin,
+            word_clock_pin,
+            voice.sample_rate_hz(),
+            16,
+            2,
+            &program,
+        );
+        static BUFFERS: StaticCell<[u32; FRAMES_PER_BUFFER * 2]> = StaticCell::new();
+        let buffers = BUFFERS.init([0; FRAMES_PER_BUFFER * 2]);
+        spawner.spawn(device_loop(common, i2s, voice, buffers))?;
+        Ok(Self)
+    }
+}
+
+#[embassy_executor::task]
+async fn device_loop(
+    // Kept so the PIO block stays set up for the state machine
+    _common: Common<'static, PIO1>,
+    mut i2s: PioI2sOut<'static, PIO1, 0>,
+    voice: VoiceBank<'static>,
+    buffers: &'static mut [u32; FRAMES_PER_BUFFER * 2],
+) -> ! {
+    let gap_samples = u64::from(voice.sample_rate_hz())
+        .saturating_mul(WORD_GAP.as_millis())
+        .checked_div(1_000)
+        .and_then(|samples| usize::try_from(samples).ok())
+        .unwrap_or_default();
+    loop {
+        let (hours, minutes) = ANNOUNCEMENTS.wait().await;
+        info!("Saying {}:{:02}", hours, minutes);
+        let words = spoken_time(hours, minutes);
+        let mut samples = voice.samples(&words, gap_samples);
+        let (mut front, mut back) = buffers.split_at_mut(FRAMES_PER_BUFFER);
+        fill(front, &mut samples);
+        // One buffer plays while the next is filled, so the words run on without a break
+        loop {
+            let transfer = i2s.write(front);
+            let more = fill(back, &mut samples);
+            transfer.await;
+            if !more {
+                break;
+            }
+            core::mem::swap(&mut front, &mut back);
+        }
+    }
+}
+
+/// Fills `buffer` with the next of `samples`, the same on both channels, and silence after
+/// the last. Returns whether there were any samples left.
+fn fill(buffer: &mut [u32], samples: &mut impl Iterator<Item = i16>) -> bool {
+    let mut any = false;
+    for frame in buffer {
+        *frame = samples.next().map_or(0, |sample| {
+            any = true;
+            let [high, low] = sample.to_be_bytes();
+            u32::from_be_bytes([high, low, high, low])
+        });
+    }
+    any
+}
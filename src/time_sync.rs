@@ -2,37 +2,132 @@
 
 #![allow(clippy::future_not_send, reason = "single-threaded")]
 
+use core::cell::RefCell;
 use core::convert::Infallible;
-use defmt::*;
 use embassy_executor::Spawner;
+use embassy_futures::select::{select, Either};
+use embassy_rp::clocks::RoscRng;
 use embassy_rp::peripherals::{PIN_23, PIN_24, PIN_25, PIN_29, PIO0, DMA_CH0};
 use embassy_net::{Stack, dns, udp};
 use embassy_rp::Peri;
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use embassy_sync::blocking_mutex::Mutex;
 use embassy_sync::signal::Signal;
-use embassy_time::{Duration, Timer};
+use embassy_time::{Duration, Instant, Timer};
+use rand_core::RngCore as _;
 use static_cell::StaticCell;
 
-use crate::unix_seconds::UnixSeconds;
-use crate::wifi::{Wifi, WifiNotifier};
+use clock_core::{
+    ntp_request, verify_ntp_auth, Arbitration, LeapIndicator, TimeArbiter, TimeSanity,
+    TimeSource, TimeSyncPort, UnixSeconds, NTP_AUTH_CODE_LEN, NTP_PACKET_LEN,
+};
+pub use clock_core::{SyncAccuracy, SyncError, TimeSyncEvent};
+
+use crate::{info, unwrap, warn, Display2Format};
+use crate::http_client::{self, Method};
+use crate::metrics::record_sync_failure;
+use crate::settings::{
+    build_unix_seconds, max_time_jump, portal_check_url, time_auth_key, time_fallback_url,
+    time_server,
+};
+use crate::wifi::{TransmitBurst, Wifi, WifiNotifier};
 use crate::Result;
 
+/// How many years after the build a network time may be before it is rejected.
+const MAX_YEARS_AFTER_BUILD: u16 = 20;
+
+/// Each of the NTP socket's receive and transmit buffers: one 48-byte packet, with room.
+const NTP_BUFFER_LEN: usize = 128;
+
+/// The largest response head read for the HTTP `Date:` fallback and the captive portal check.
+const MAX_DATE_RESPONSE_LEN: usize = 768;
+
+/// The buffers a sync holds: the NTP socket's, or the HTTP fallback's.
+pub(crate) const RAM_BYTES: usize =
+    2 * NTP_BUFFER_LEN + MAX_DATE_RESPONSE_LEN + http_client::RAM_BYTES;
+
 // ============================================================================
 // Types
 // ============================================================================
 
-#[derive(Clone)]
-pub enum TimeSyncEvent {
-    Success { unix_seconds: UnixSeconds },
-    // cmk consider changing to Error type?
-    Failed(&'static str),
+/// Carries sync outcomes from the sync task to the state machine.
+///
+/// It holds only the latest unread event, so posting never blocks the sync task, however long
+/// the state machine goes without calling `TimeSync::wait` (e.g. while in an editor). The one
+/// exception to "latest wins": a failure or rejection never replaces an unread success, so a
+/// burst of failures during a reconnect storm cannot hide a good time from the clock.
+pub struct TimeSyncEvents {
+    latest: Mutex<CriticalSectionRawMutex, RefCell<Option<TimeSyncEvent>>>,
+    posted: Signal<CriticalSectionRawMutex, ()>,
+}
+
+impl TimeSyncEvents {
+    const fn new() -> Self {
+        Self {
+            latest: Mutex::new(RefCell::new(None)),
+            posted: Signal::new(),
+        }
+    }
+
+    /// Stores `event` as the latest unread event (unless it would hide an unread success).
+    fn signal(&self, event: TimeSyncEvent) {
+        self.latest.lock(|cell| {
+            let mut latest = cell.borrow_mut();
+            if matches!(*latest, Some(TimeSyncEvent::Success { .. }))
+                && !matches!(event, TimeSyncEvent::Success { .. })
+            {
+                info!("Keeping unread sync success over a later failure");
+            } else {
+                *latest = Some(event);
+            }
+        });
+        self.posted.signal(());
+    }
+
+    /// Counts `error` in the metrics and reports it as the latest event.
+    fn fail(&self, error: SyncError) {
+        record_sync_failure(error);
+        self.signal(TimeSyncEvent::Failed(error));
+    }
+
+    /// Takes the latest unread event, without waiting for one.
+    fn take(&self) -> Option<TimeSyncEvent> {
+        self.posted.reset();
+        self.latest.lock(|cell| cell.borrow_mut().take())
+    }
+
+    /// Waits for and takes the latest unread event.
+    async fn wait(&self) -> TimeSyncEvent {
+        loop {
+            self.posted.wait().await;
+            if let Some(event) = self.latest.lock(|cell| cell.borrow_mut().take()) {
+                return event;
+            }
+        }
+    }
+}
+
+/// Which source disciplines the clock, shared by everything that handles sync events.
+static ARBITER: Mutex<CriticalSectionRawMutex, RefCell<TimeArbiter>> =
+    Mutex::new(RefCell::new(TimeArbiter::new()));
+
+/// Decides whether a time from `source` should set the clock (see `TimeArbiter::offer`).
+pub(crate) fn arbitrate(source: TimeSource) -> Arbitration {
+    ARBITER.lock(|cell| cell.borrow_mut().offer(source, Instant::now()))
 }
 
-pub type TimeSyncEvents = Signal<CriticalSectionRawMutex, TimeSyncEvent>;
+/// A copy of the arbiter, for reporting which source disciplines the clock.
+pub(crate) fn time_arbiter() -> TimeArbiter {
+    ARBITER.lock(|cell| *cell.borrow())
+}
+
+/// Asks the sync task to sync now rather than at its next scheduled time.
+pub type TimeSyncRequests = Signal<CriticalSectionRawMutex, ()>;
 
 /// Resources needed by TimeSync device (includes WiFi resources)
 pub struct TimeSyncNotifier {
     events: TimeSyncEvents,
+    requests: TimeSyncRequests,
     wifi: WifiNotifier,
     time_sync_cell: StaticCell<TimeSync>,
 }
@@ -44,7 +139,7 @@ pub struct TimeSyncNotifier {
 /// TimeSync virtual device - manages time synchronization
 pub struct TimeSync {
     events: &'static TimeSyncEvents,
-    #[allow(dead_code, reason = "Keeps WiFi alive")]
+    requests: &'static TimeSyncRequests,
     wifi: &'static Wifi,
 }
 
@@ -53,7 +148,8 @@ impl TimeSync {
     #[must_use]
     pub const fn notifier() -> TimeSyncNotifier {
         TimeSyncNotifier {
-            events: Signal::new(),
+            events: TimeSyncEvents::new(),
+            requests: Signal::new(),
             wifi: Wifi::notifier(),
             time_sync_cell: StaticCell::new(),
         }
@@ -83,10 +179,15 @@ impl TimeSync {
         );
 
         // Spawn TimeSync task
-        unwrap!(spawner.spawn(time_sync_device_loop(wifi, &resources.events)));
-        
+        unwrap!(spawner.spawn(time_sync_device_loop(
+            wifi,
+            &resources.events,
+            &resources.requests
+        )));
+
         resources.time_sync_cell.init(Self {
             events: &resources.events,
+            requests: &resources.requests,
             wifi,
         })
     }
@@ -95,14 +196,53 @@ impl TimeSync {
     pub async fn wait(&self) -> TimeSyncEvent {
         self.events.wait().await
     }
+
+    /// Reports a time (or a failure) from a source other than the network, such as `Gps`,
+    /// to the state machine, just as a network sync would.
+    pub fn post(&self, event: TimeSyncEvent) {
+        self.events.signal(event);
+    }
+
+    /// Syncs now instead of at the next scheduled time. The outcome arrives through `wait`
+    /// like any other sync. Ignored while a sync is already under way or WiFi is unavailable.
+    pub fn request_sync(&self) {
+        self.requests.signal(());
+    }
+
+    /// The WiFi device this TimeSync created, for sharing with other network devices
+    #[must_use]
+    pub const fn wifi(&self) -> &'static Wifi {
+        self.wifi
+    }
+}
+
+impl TimeSyncPort for TimeSync {
+    type Wifi = Wifi;
+
+    async fn wait(&self) -> TimeSyncEvent {
+        self.events.wait().await
+    }
+
+    fn request_sync(&self) {
+        self.requests.signal(());
+    }
+
+    fn take_unread(&self) -> Option<TimeSyncEvent> {
+        self.events.take()
+    }
+
+    fn wifi(&self) -> &Wifi {
+        self.wifi
+    }
 }
 
 #[embassy_executor::task]
 async fn time_sync_device_loop(
     wifi: &'static Wifi,
     sync_events: &'static TimeSyncEvents,
+    sync_requests: &'static TimeSyncRequests,
 ) -> ! {
-    let err = inner_time_sync_device_loop(wifi, sync_events)
+    let err = inner_time_sync_device_loop(wifi, sync_events, sync_requests)
         .await
         .unwrap_err();
     core::panic!("{err}");
@@ -111,70 +251,144 @@ async fn time_sync_device_loop(
 async fn inner_time_sync_device_loop(
     wifi: &'static Wifi,
     sync_events: &'static TimeSyncEvents,
+    sync_requests: &'static TimeSyncRequests,
 ) -> Result<Infallible> {
     info!("TimeSync device awaiting network stack...");
     
     // Wait for WiFi to be ready and get the stack
-    let stack = wifi.stack().await;
+    let Some(stack) = wifi.stack().await else {
+        // Without WiFi the clock is set by hand, so report once and stay quiet
+        warn!("TimeSync disabled: WiFi unavailable");
+        sync_events.fail(SyncError::Wifi);
+        loop {
+            sync_requests.wait().await;
+            sync_events.fail(SyncError::Wifi);
+        }
+    };
     info!("TimeSync received network stack");
     
     info!("TimeSync device started");
 
+    let mut sanity = TimeSanity::new(build_unix_seconds(), MAX_YEARS_AFTER_BUILD, max_time_jump());
+
     // Initial sync with retry (exponential backoff: 10s, 30s, 60s, then 5min intervals)
     let mut attempt = 0;
     loop {
         attempt += 1;
         info!("Sync attempt {}", attempt);
-        match fetch_ntp_time(stack).await {
-            Ok(unix_seconds) => {
-                info!("Initial sync successful: unix_seconds={}", unix_seconds.as_i64());
-
-                sync_events.signal(TimeSyncEvent::Success { unix_seconds });
-                break;
-            }
-            Err(e) => {
-                info!("Sync failed: {}", e);
-                sync_events.signal(TimeSyncEvent::Failed(e));
-                // Exponential backoff: 10s, 30s, 60s, then 5min intervals
-                let delay_secs = if attempt == 1 {
-                    10
-                } else if attempt == 2 {
-                    30
-                } else if attempt == 3 {
-                    60
-                } else {
-                    300 // 5 minutes for subsequent attempts
-                };
-                info!("Sync failed, retrying in {}s...", delay_secs);
-                Timer::after_secs(delay_secs).await;
-            }
+        if sync_once(stack, &mut sanity, sync_events).await {
+            info!("Initial sync successful");
+            break;
         }
+        // Exponential backoff: 10s, 30s, 60s, then 5min intervals
+        let delay_secs = if attempt == 1 {
+            10
+        } else if attempt == 2 {
+            30
+        } else if attempt == 3 {
+            60
+        } else {
+            300 // 5 minutes for subsequent attempts
+        };
+        info!("Sync failed, retrying in {}s...", delay_secs);
+        sleep_unless_requested(sync_requests, delay_secs).await;
     }
 
-    // Hourly sync loop (on failure, retry every 5 minutes)
-    let mut last_success_elapsed = 0_u64;
+    // Later syncs: the scheduler asks for one every `Config::sync_interval` (an hour by
+    // default), and a failed sync is retried every 5 minutes
+    let mut synced = true;
     loop {
-        // Wait 1 hour after last success, or 5 minutes after failure
-        let wait_secs = if last_success_elapsed == 0 { 3600 } else { 300 };
-        Timer::after_secs(wait_secs).await;
-        last_success_elapsed = last_success_elapsed.saturating_add(wait_secs);
-
-        info!(
-            "Periodic sync ({}s since last success)...",
-            last_success_elapsed
-        );
-        match fetch_ntp_time(stack).await {
-            Ok(unix_seconds) => {
-                info!("Periodic sync successful: unix_seconds={}", unix_seconds.as_i64());
+        if synced {
+            // A request made while a sync was under way has been answered by it
+            sync_requests.reset();
+            sync_requests.wait().await;
+            info!("Sync requested");
+        } else {
+            sleep_unless_requested(sync_requests, 300).await;
+        }
+
+        info!("Sync...");
+        synced = sync_once(stack, &mut sanity, sync_events).await;
+        if synced {
+            info!("Sync successful");
+        } else {
+            info!("Sync failed, will retry in 5 minutes");
+        }
+    }
+}
 
-                sync_events.signal(TimeSyncEvent::Success { unix_seconds });
-                last_success_elapsed = 0; // reset backoff
+/// Sleeps for `secs`, or less if a sync is requested.
+async fn sleep_unless_requested(sync_requests: &TimeSyncRequests, secs: u64) {
+    // A request made while a sync was under way has been answered by it
+    sync_requests.reset();
+    if let Either::Second(()) = select(Timer::after_secs(secs), sync_requests.wait()).await {
+        info!("Sync requested");
+    }
+}
+
+/// Fetches the time once, checks that it is plausible, and reports the outcome.
+///
+/// Returns `true` if the clock was sent a new time.
+async fn sync_once(
+    stack: &Stack<'static>,
+    sanity: &mut TimeSanity,
+    sync_events: &'static TimeSyncEvents,
+) -> bool {
+    // Behind a portal every request gets its sign-in page, so say so rather than time out
+    if let Some(url) = portal_check_url() {
+        if behind_portal(stack, url).await {
+            sync_events.fail(SyncError::Portal);
+            return false;
+        }
+    }
+    let fetched = match fetch_ntp_time(stack).await {
+        Ok((unix_seconds, leap)) => Ok((unix_seconds, leap, SyncAccuracy::Fine, TimeSource::Ntp)),
+        // The HTTP fallback is unauthenticated, so it is off when a time key is configured
+        Err(e) => match time_fallback_url() {
+            Some(url) if time_auth_key().is_none() => {
+                info!("NTP failed ({=str}), trying HTTP date", e.name());
+                fetch_http_date(stack, url)
+                    .await
+                    .map(|unix_seconds| {
+                        (
+                            unix_seconds,
+                            LeapIndicator::NoWarning,
+                            SyncAccuracy::Coarse,
+                            TimeSource::HttpDate,
+                        )
+                    })
             }
-            Err(e) => {
-                info!("Periodic sync failed: {}", e);
-                sync_events.signal(TimeSyncEvent::Failed(e));
-                info!("Sync failed, will retry in 5 minutes");
+            _ => Err(e),
+        },
+    };
+    let fetched_at = Instant::now();
+    match fetched {
+        Ok((unix_seconds, leap, accuracy, source)) => match sanity.check(unix_seconds, fetched_at) {
+            Ok(()) => {
+                info!(
+                    "Sync successful: unix_seconds={} ({:?} from {:?})",
+                    unix_seconds.as_i64(),
+                    accuracy,
+                    source
+                );
+                sync_events.signal(TimeSyncEvent::Success {
+                    unix_seconds,
+                    leap,
+                    accuracy,
+                    source,
+                });
+                true
             }
+            Err(reason) => {
+                warn!("Rejected NTP time {}: {:?}", unix_seconds.as_i64(), reason);
+                sync_events.signal(TimeSyncEvent::Rejected(reason));
+                false
+            }
+        },
+        Err(e) => {
+            info!("Sync failed: {=str}", e.name());
+            sync_events.fail(e);
+            false
         }
     }
 }
@@ -183,32 +397,34 @@ async fn inner_time_sync_device_loop(
 // Network - NTP Fetch
 // ============================================================================
 
-async fn fetch_ntp_time(stack: &Stack<'static>) -> Result<UnixSeconds, &'static str> {
+async fn fetch_ntp_time(
+    stack: &Stack<'static>,
+) -> Result<(UnixSeconds, LeapIndicator), SyncError> {
     use dns::DnsQueryType;
     use udp::UdpSocket;
 
-    // NTP server
-    const NTP_SERVER: &str = "pool.ntp.org";
+    // NTP server (a hostname or an IP address)
+    let ntp_server = time_server();
     const NTP_PORT: u16 = 123;
 
     // DNS lookup
-    info!("Resolving {}...", NTP_SERVER);
+    info!("Resolving {}...", ntp_server);
     let dns_result = stack
-        .dns_query(NTP_SERVER, DnsQueryType::A)
+        .dns_query(ntp_server, DnsQueryType::A)
         .await
         .map_err(|e| {
             warn!("DNS lookup failed: {:?}", e);
-            "DNS lookup failed"
+            SyncError::Dns
         })?;
-    let server_addr = dns_result.first().ok_or("No DNS results")?;
+    let server_addr = dns_result.first().ok_or(SyncError::Dns)?;
 
     info!("NTP Server IP: {}", server_addr);
 
     // Create UDP socket
     let mut rx_meta = [udp::PacketMetadata::EMPTY; 1];
-    let mut rx_buffer = [0; 128];
+    let mut rx_buffer = [0; NTP_BUFFER_LEN];
     let mut tx_meta = [udp::PacketMetadata::EMPTY; 1];
-    let mut tx_buffer = [0; 128];
+    let mut tx_buffer = [0; NTP_BUFFER_LEN];
     let mut socket = UdpSocket::new(
         *stack,
         &mut rx_meta,
@@ -219,40 +435,65 @@ async fn fetch_ntp_time(stack: &Stack<'static>) -> Result<UnixSeconds, &'static
 
     socket.bind(0).map_err(|e| {
         warn!("Socket bind failed: {:?}", e);
-        "Socket bind failed"
+        SyncError::Wifi
     })?;
 
-    // Build NTP request (48 bytes, version 3, client mode)
-    let mut ntp_request = [0u8; 48];
-    ntp_request[0] = 0x1B; // LI=0, VN=3, Mode=3 (client)
+    // Build NTP request (48 bytes, version 3, client mode). The nonce lets an authenticating
+    // server prove its reply answers this request rather than replaying an old one, so it
+    // comes from the ring oscillator, as TLS's randomness does: uptime would be guessable.
+    let mut nonce = [0_u8; 8];
+    RoscRng.fill_bytes(&mut nonce);
+    let ntp_request = ntp_request(nonce);
 
     // Send request
     info!("Sending NTP request to {}...", server_addr);
-    socket
-        .send_to(&ntp_request, (*server_addr, NTP_PORT))
-        .await
-        .map_err(|e| {
-            warn!("NTP send failed: {:?}", e);
-            "NTP send failed"
-        })?;
+    let burst = TransmitBurst::start();
+    let sent = socket.send_to(&ntp_request, (*server_addr, NTP_PORT)).await;
+    // Dim only while the request goes out, not while waiting for the answer
+    drop(burst);
+    sent.map_err(|e| {
+        warn!("NTP send failed: {:?}", e);
+        SyncError::Wifi
+    })?;
 
     // Receive response with timeout
-    let mut response = [0u8; 48];
+    let mut response = [0u8; NTP_PACKET_LEN + NTP_AUTH_CODE_LEN];
     let (n, _from) =
         embassy_time::with_timeout(Duration::from_secs(5), socket.recv_from(&mut response))
             .await
             .map_err(|_| {
                 warn!("NTP receive timeout");
-                "NTP receive timeout"
+                SyncError::Timeout
             })?
             .map_err(|e| {
                 warn!("NTP receive failed: {:?}", e);
-                "NTP receive failed"
+                SyncError::Wifi
             })?;
 
-    if n < 48 {
+    if n < NTP_PACKET_LEN {
         warn!("NTP response too short: {} bytes", n);
-        return Err("NTP response too short");
+        return Err(SyncError::Parse);
+    }
+
+    // With a shared key, only a reply signed by our own time server is trusted
+    if let Some(key) = time_auth_key() {
+        verify_ntp_auth(&response[..n], nonce, &key).map_err(|e| {
+            warn!("NTP authentication failed: {}", Display2Format(&e));
+            SyncError::Rejected
+        })?;
+    }
+
+    // The top two bits of the first byte warn of a leap second (or an unsynchronized server)
+    let leap = LeapIndicator::from_ntp_header(response[0]);
+    match leap {
+        LeapIndicator::NoWarning => {}
+        LeapIndicator::InsertSecond | LeapIndicator::DeleteSecond => {
+            warn!("NTP server announces a leap second: {:?}", leap);
+        }
+        LeapIndicator::Unsynchronized => {
+            warn!("NTP server is not synchronized");
+            return Err(SyncError::Rejected);
+        }
     }
 
     // Extract transmit timestamp (bytes 40-47, big-endian)
@@ -260,8 +501,47 @@ async fn fetch_ntp_time(stack: &Stack<'static>) -> Result<UnixSeconds, &'static
 
     // Convert NTP timestamp to Unix seconds
     let unix_time = UnixSeconds::from_ntp_seconds(ntp_seconds)
-        .ok_or("Invalid NTP timestamp")?;
+        .ok_or(SyncError::Parse)?;
 
     info!("NTP time: {} (unix timestamp)", unix_time.as_i64());
+    Ok((unix_time, leap))
+}
+
+// ============================================================================
+// Network - Captive Portal Check
+// ============================================================================
+
+/// Whether a captive portal holds the network: `url` answers with something other than its
+/// `204 No Content`, such as a redirect to a sign-in page. A check that gets no answer at
+/// all proves nothing, so the sync goes ahead.
+async fn behind_portal(stack: &Stack<'static>, url: &str) -> bool {
+    let mut buffer = [0_u8; MAX_DATE_RESPONSE_LEN];
+    match http_client::request(stack, Method::Head, url, &mut buffer).await {
+        Ok(response) if response.status() != 204 => {
+            warn!("Captive portal: the check answered {}", response.status());
+            true
+        }
+        Ok(_) | Err(_) => false,
+    }
+}
+
+// ============================================================================
+// Network - HTTP Date Fallback
+// ============================================================================
+
+/// Gets a coarse time from the `Date:` header of an HTTP response, for networks that block
+/// UDP port 123. Only plain `http://` URLs (for example, a LAN router) are supported.
+async fn fetch_http_date(stack: &Stack<'static>, url: &str) -> Result<UnixSeconds, SyncError> {
+    // HEAD: only the headers are needed
+    let mut buffer = [0_u8; MAX_DATE_RESPONSE_LEN];
+    // The client has logged why; its reasons are text shared with its other callers, so a
+    // failed request counts against the network
+    let response = http_client::request(stack, Method::Head, url, &mut buffer)
+        .await
+        .map_err(|_| SyncError::Wifi)?;
+    let date = response.header("date").ok_or(SyncError::Parse)?;
+    let unix_time = UnixSeconds::from_http_date(date).ok_or(SyncError::Parse)?;
+
+    info!("HTTP date: {} (unix timestamp)", unix_time.as_i64());
     Ok(unix_time)
 }
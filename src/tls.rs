@@ -0,0 +1,154 @@
+//! TLS for the HTTP client - wraps a TCP connection in TLS 1.3 (`embedded-tls`) for
+//! `https://` URLs
+//!
+//! A host with a pin in `TLS_PINS` must present the pinned certificate, with a P-256 key, and
+//! prove it holds the key. A host without one is encrypted but not authenticated.
+
+use embassy_rp::clocks::RoscRng;
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, mutex::Mutex};
+use embedded_io_async::{Read, Write};
+use embedded_tls::{
+    Aes128GcmSha256, CertificateEntryRef, CertificateRef, CryptoProvider, HandshakeVerifyRef,
+    SignatureScheme, TlsCipherSuite, TlsConfig, TlsConnection, TlsContext, TlsError,
+    TlsVerifier, UnsecureProvider,
+};
+use p256::ecdsa::{signature::Verifier as _, Signature, VerifyingKey};
+use rand_core::CryptoRngCore;
+use sha2::Digest as _;
+
+use clock_core::{
+    certificate_fingerprint, certificate_verify_message, find_tls_pin, p256_public_key,
+    CERT_FINGERPRINT_LEN,
+};
+
+use crate::http_client::exchange as plain_exchange;
+use crate::settings::tls_pins;
+use crate::warn;
+
+/// The largest TLS record read: 16 KiB of plaintext plus the record's overhead. Servers may
+/// send records this large, so anything smaller risks failing mid-response.
+const READ_RECORD_LEN: usize = 16_640;
+
+/// The largest TLS record written (requests are small).
+const WRITE_RECORD_LEN: usize = 4_096;
+
+/// The record buffers, shared by every HTTPS request (one at a time) rather than each
+/// client's task holding 20 KiB of its own.
+static RECORD_BUFFERS: Mutex<
+    CriticalSectionRawMutex,
+    ([u8; READ_RECORD_LEN], [u8; WRITE_RECORD_LEN]),
+> = Mutex::new(([0; READ_RECORD_LEN], [0; WRITE_RECORD_LEN]));
+
+/// Opens TLS to `host` over `socket`, then sends a request's `head` and reads the response
+/// into `buffer`, returning how many bytes arrived.
+pub(crate) async fn exchange<S: Read + Write>(
+    socket: S,
+    host: &str,
+    head: &[u8],
+    head_only: bool,
+    buffer: &mut [u8],
+) -> Result<usize, &'static str> {
+    let mut record_buffers = RECORD_BUFFERS.lock().await;
+    let (read_record, write_record) = &mut *record_buffers;
+    let config = TlsConfig::new().with_server_name(host);
+    let mut tls = TlsConnection::new(socket, read_record, write_record);
+    let opened = match find_tls_pin(tls_pins(), host) {
+        Some(pin) => tls.open(TlsContext::new(&config, PinnedProvider::new(pin))).await,
+        None => {
+            warn!("No TLS pin for {}, so the server is not authenticated", host);
+            let provider = UnsecureProvider::new::<Aes128GcmSha256>(RoscRng);
+            tls.open(TlsContext::new(&config, provider)).await
+        }
+    };
+    opened.map_err(|err| {
+        warn!("TLS handshake failed: {:?}", err);
+        "TLS handshake failed"
+    })?;
+    let len = plain_exchange(&mut tls, head, head_only, buffer).await;
+    // The connection is done with either way, so a failed close_notify changes nothing
+    let _ = tls.close().await;
+    len
+}
+
+/// Supplies `embedded-tls` with randomness and a `PinnedVerifier`.
+struct PinnedProvider {
+    rng: RoscRng,
+    verifier: PinnedVerifier,
+}
+
+impl PinnedProvider {
+    const fn new(pin: [u8; CERT_FINGERPRINT_LEN]) -> Self {
+        Self {
+            rng: RoscRng,
+            verifier: PinnedVerifier {
+                pin,
+                public_key: None,
+                transcript_hash: None,
+            },
+        }
+    }
+}
+
+impl CryptoProvider for PinnedProvider {
+    type CipherSuite = Aes128GcmSha256;
+    type Signature = &'static [u8];
+
+    fn rng(&mut self) -> impl CryptoRngCore {
+        &mut self.rng
+    }
+
+    fn verifier(&mut self) -> Result<&mut impl TlsVerifier<Self::CipherSuite>, TlsError> {
+        Ok(&mut self.verifier)
+    }
+}
+
+/// Accepts only the certificate whose fingerprint is `pin`, and only from a server that signs
+/// the handshake with its key.
+struct PinnedVerifier {
+    pin: [u8; CERT_FINGERPRINT_LEN],
+    /// The pinned certificate's key, once the server has presented it.
+    public_key: Option<VerifyingKey>,
+    /// The handshake up to the certificate, which the server's signature covers.
+    transcript_hash: Option<[u8; CERT_FINGERPRINT_LEN]>,
+}
+
+impl TlsVerifier<Aes128GcmSha256> for PinnedVerifier {
+    fn set_hostname_verification(&mut self, _hostname: &str) -> Result<(), TlsError> {
+        // The pin names the one certificate to accept, which already ties it to the host
+        Ok(())
+    }
+
+    fn verify_certificate(
+        &mut self,
+        transcript: &<Aes128GcmSha256 as TlsCipherSuite>::Hash,
+        cert: CertificateRef,
+    ) -> Result<(), TlsError> {
+        let Some(CertificateEntryRef::X509(leaf)) = cert.entries.first() else {
+            return Err(TlsError::InvalidCertificate);
+        };
+        if certificate_fingerprint(leaf) != self.pin {
+            warn!("TLS certificate does not match its pin");
+            return Err(TlsError::InvalidCertificate);
+        }
+        let point = p256_public_key(leaf).ok_or(TlsError::InvalidCertificate)?;
+        self.public_key =
+            Some(VerifyingKey::from_sec1_bytes(point).map_err(|_| TlsError::InvalidCertificate)?);
+        self.transcript_hash = Some(transcript.clone().finalize().into());
+        Ok(())
+    }
+
+    fn verify_signature(&mut self, verify: HandshakeVerifyRef) -> Result<(), TlsError> {
+        if verify.signature_scheme != SignatureScheme::EcdsaSecp256r1Sha256 {
+            return Err(TlsError::InvalidSignatureScheme);
+        }
+        let (Some(public_key), Some(transcript_hash)) = (&self.public_key, &self.transcript_hash)
+        else {
+            return Err(TlsError::InvalidCertificate);
+        };
+        let signature =
+            Signature::from_der(verify.signature).map_err(|_| TlsError::InvalidSignature)?;
+        public_key
+            .verify(&certificate_verify_message(transcript_hash), &signature)
+            .map_err(|_| TlsError::InvalidSignature)
+    }
+}
@@ -0,0 +1,109 @@
+//! Touch pad virtual device - reports taps on a capacitive touch pad
+
+use embassy_executor::{SpawnError, Spawner};
+use embassy_rp::gpio::{self, Pull};
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, signal::Signal};
+use embassy_time::{Duration, Timer};
+
+use clock_core::TouchDetector;
+
+use crate::info;
+use crate::settings::{touch_pad_kind, TouchPadKind};
+
+/// How often a bare pad is measured.
+const TOUCH_SAMPLE_PERIOD: Duration = Duration::from_millis(20);
+
+/// How long a bare pad is held low before each measurement, to empty it.
+const TOUCH_DISCHARGE: Duration = Duration::from_micros(10);
+
+/// The most loop turns a charge is counted for, in case the pad is shorted to ground.
+const MAX_CHARGE_COUNT: u32 = 10_000;
+
+/// Touch pad virtual device - reads a touch pad and reports each tap
+///
+/// The kind of pad is set with `TOUCH_PAD` at build time; without it, the pin is left alone.
+pub struct TouchPad;
+
+/// A type alias for the notifier that carries taps from the `TouchPad` to the `Button`.
+pub type TouchPadNotifier = Signal<CriticalSectionRawMutex, ()>;
+
+impl TouchPad {
+    /// Create a new `TouchPad` instance, which entails starting an Embassy task if a pad is
+    /// configured.
+    ///
+    /// # Arguments
+    ///
+    /// * `pin` - The pad (or the touch module's output).
+    /// * `notifier` - The static notifier that carries taps. Pass it to `Button::add_touch_pad`
+    ///          too. It is created with `TouchPad::notifier()`.
+    /// * `spawner` - The spawner that will spawn the task that reads the pad.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SpawnError` if the task cannot be spawned.
+    #[must_use = "Must be used to manage the spawned task"]
+    pub fn new(
+        pin: gpio::Flex<'static>,
+        notifier: &'static TouchPadNotifier,
+        spawner: Spawner,
+    ) -> Result<Self, SpawnError> {
+        if let Some(kind) = touch_pad_kind() {
+            info!("Touch pad: {:?}", kind);
+            spawner.spawn(device_loop(pin, notifier, kind))?;
+        }
+        Ok(Self)
+    }
+
+    /// Creates a new `TouchPadNotifier` instance.
+    ///
+    /// This should be assigned to a static variable and passed to the `TouchPad::new()` method.
+    #[must_use]
+    pub const fn notifier() -> TouchPadNotifier {
+        Signal::new()
+    }
+}
+
+#[embassy_executor::task]
+async fn device_loop(
+    mut pin: gpio::Flex<'static>,
+    notifier: &'static TouchPadNotifier,
+    kind: TouchPadKind,
+) -> ! {
+    match kind {
+        TouchPadKind::Module => {
+            // A TTP223-style module does its own sensing and drives its output high while touched
+            pin.set_as_input();
+            pin.set_pull(Pull::Down);
+            loop {
+                pin.wait_for_rising_edge().await;
+                info!("Touch pad tapped");
+                notifier.signal(());
+            }
+        }
+        TouchPadKind::Bare => {
+            let mut detector = TouchDetector::new();
+            loop {
+                if detector.sample(charge_count(&mut pin).await) {
+                    info!("Touch pad tapped");
+                    notifier.signal(());
+                }
+                Timer::after(TOUCH_SAMPLE_PERIOD).await;
+            }
+        }
+    }
+}
+
+/// Empties a bare pad, then counts loop turns until the pull-up charges it to a high reading.
+async fn charge_count(pin: &mut gpio::Flex<'static>) -> u32 {
+    pin.set_low();
+    pin.set_as_output();
+    Timer::after(TOUCH_DISCHARGE).await;
+    pin.set_pull(Pull::Up);
+    pin.set_as_input();
+    let mut count = 0_u32;
+    // The charge takes microseconds, too short to await
+    while pin.is_low() && count < MAX_CHARGE_COUNT {
+        count = count.saturating_add(1);
+    }
+    count
+}
@@ -0,0 +1,488 @@
+//! USB console virtual device - a line-oriented command console over USB serial (CDC ACM)
+//!
+//! Plug the Pico's USB port into a laptop and open the serial port it offers (any baud rate)
+//! to set the clock up without WiFi or a debug probe. Type `help` for the commands.
+
+use core::convert::Infallible;
+use core::fmt::Write;
+
+use embassy_executor::{SpawnError, Spawner};
+use embassy_rp::{
+    bind_interrupts,
+    peripherals::USB,
+    usb::{Driver, InterruptHandler},
+    Peri,
+};
+use embassy_time::{with_timeout, Duration, Instant};
+use embassy_usb::{
+    class::cdc_acm::{CdcAcmClass, State},
+    Builder, UsbDevice,
+};
+use heapless::String;
+use static_cell::StaticCell;
+
+use clock_core::{
+    parse_time_of_day, AlarmSound, Bssid, Config, GestureTimings, LeapIndicator, Provisioning,
+    TimeSanity, TimeSource, UnixSeconds, WifiCountry, CLIMATE_CSV_HEADER, MAX_SSID_LEN,
+    MAX_UTC_OFFSET_HOURS, MAX_WIFI_PASSWORD_LEN, MIN_UTC_OFFSET_HOURS,
+};
+
+use crate::{info, Display2Format};
+use crate::build_info;
+use crate::button::{gesture_timings, set_gesture_timings};
+use crate::buzzer::{play_sound, silence_sound};
+use crate::clock::Clock;
+use crate::clock_state::{commit_settings, live_pending_settings};
+use crate::config::{config, set_config};
+use crate::network_policy::utc_offset_minutes;
+use crate::settings::{build_unix_seconds, max_time_jump};
+use crate::soft_reboot::{latest_unix_seconds, wall_clock_at};
+use crate::storage::{next_slot, SharedStorage, CLIMATE_LOG_SLOTS};
+use crate::supply::supply_millivolts;
+use crate::time_sync::{time_arbiter, SyncAccuracy, TimeSync, TimeSyncEvent};
+use crate::Result;
+
+/// The largest USB packet, which is also the most bytes read or written at a time.
+const PACKET_LEN: usize = 64;
+
+/// The longest command line accepted, room for `set wifi` with the longest SSID and password.
+const MAX_LINE_LEN: usize = 112;
+
+/// The longest reply to a single command (besides `logs`, which is sent a piece at a time).
+const MAX_REPLY_LEN: usize = 1280;
+
+/// The USB descriptor and control buffers.
+const USB_BUFFERS_LEN: usize = 256 + 256 + 64;
+
+/// The buffers the console holds: the USB stack's, a packet, a command line, and a reply.
+pub(crate) const RAM_BYTES: usize = USB_BUFFERS_LEN + PACKET_LEN + MAX_LINE_LEN + MAX_REPLY_LEN;
+
+/// How long `scan` waits for the WiFi task, which answers only once the chip is up.
+const SCAN_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How many years after the build a time set by hand may be before it is refused.
+const MAX_YEARS_AFTER_BUILD: u16 = 20;
+
+/// Shown after each reply, when the console is ready for the next command.
+const PROMPT: &str = "> ";
+
+const HELP: &str = "\
+Commands:\r
+  status              Show the time, time zone, time source, timings, config, and supply\r
+  set time <seconds>  Set the clock to a Unix time (UTC), e.g. set time 1760000000\r
+  set clock <hh:mm[:ss]>  Set the local time of day, e.g. set clock 07:30\r
+  set tz <minutes>    Set and save the UTC offset, e.g. set tz -420\r
+  set since <date|none>  Set and save the date the days-since mode counts from\r
+  set gestures <list> Set and save button timings (ms), e.g. set gestures long=800\r
+  set config <list>   Set and save the runtime config, e.g. set config sync_minutes=30\r
+  scan                List nearby WiFi networks, strongest first, to pick one for set wifi\r
+  set wifi <ssid> [password]  Save the WiFi network to join after a reboot\r
+  set bssid <address|any>  Save the access point to join after a reboot\r
+  set country <code>  Save the WiFi channels' country (XX: worldwide), e.g. set country DE\r
+  play [sound]        Play an alarm sound until silenced, e.g. play chime\r
+  silence             Stop the alarm sound\r
+  sync                Sync the time from the network now\r
+  logs                Show the last crash and the climate log\r
+";
+
+bind_interrupts!(struct Irqs {
+    USBCTRL_IRQ => InterruptHandler<USB>;
+});
+
+/// A type alias for the serial port the console talks through.
+type Serial = CdcAcmClass<'static, Driver<'static, USB>>;
+
+/// USB console virtual device - answers commands typed over the USB serial port
+pub struct UsbConsole;
+
+impl UsbConsole {
+    /// Create the USB console and spawn its tasks: one that runs the USB device and one that
+    /// reads commands.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SpawnError` if a task cannot be spawned.
+    pub fn new(
+        usb: Peri<'static, USB>,
+        clock: Clock<'static>,
+        time_sync: &'static TimeSync,
+        storage: &'static SharedStorage,
+        spawner: Spawner,
+    ) -> Result<Self, SpawnError> {
+        static CONFIG_DESCRIPTOR: StaticCell<[u8; 256]> = StaticCell::new();
+        static BOS_DESCRIPTOR: StaticCell<[u8; 256]> = StaticCell::new();
+        static CONTROL_BUF: StaticCell<[u8; 64]> = StaticCell::new();
+        static STATE: StaticCell<State<'static>> = StaticCell::new();
+
+        // A test vendor and product ID: the port shows up as a generic USB serial device
+        let mut config = embassy_usb::Config::new(0xC0DE, 0xCAFE);
+        config.manufacturer = Some("clock-wifi");
+        config.product = Some("Clock console");
+        config.max_power = 100;
+        config.max_packet_size_0 = 64;
+
+        let mut builder = Builder::new(
+            Driver::new(usb, Irqs),
+            config,
+            CONFIG_DESCRIPTOR.init([0; 256]),
+            BOS_DESCRIPTOR.init([0; 256]),
+            &mut [],
+            CONTROL_BUF.init([0; 64]),
+        );
+        let serial = CdcAcmClass::new(&mut builder, STATE.init(State::new()), 64);
+        spawner.spawn(usb_loop(builder.build()))?;
+        spawner.spawn(device_loop(serial, clock, time_sync, storage))?;
+        Ok(Self)
+    }
+}
+
+#[embassy_executor::task]
+async fn usb_loop(mut usb: UsbDevice<'static, Driver<'static, USB>>) -> ! {
+    usb.run().await
+}
+
+#[embassy_executor::task]
+async fn device_loop(
+    mut serial: Serial,
+    clock: Clock<'static>,
+    time_sync: &'static TimeSync,
+    storage: &'static SharedStorage,
+) -> ! {
+    loop {
+        serial.wait_connection().await;
+        info!("USB console connected");
+        let Err(err) = session(&mut serial, clock, time_sync, storage).await;
+        info!("USB console closed: {}", Display2Format(&err));
+    }
+}
+
+/// Reads commands a line at a time and answers each, until the port closes.
+async fn session(
+    serial: &mut Serial,
+    clock: Clock<'static>,
+    time_sync: &TimeSync,
+    storage: &SharedStorage,
+) -> Result<Infallible> {
+    write_all(serial, PROMPT.as_bytes()).await?;
+    let mut line = String::<MAX_LINE_LEN>::new();
+    let mut too_long = false;
+    let mut packet = [0; PACKET_LEN];
+    loop {
+        let len = serial.read_packet(&mut packet).await?;
+        for &byte in packet.get(..len).unwrap_or_default() {
+            match byte {
+                b'\r' | b'\n' => {
+                    if too_long {
+                        write_all(serial, b"Line too long\r\n").await?;
+                    } else if !line.trim().is_empty() {
+                        let command = Command::parse(line.trim());
+                        run(serial, command, clock, time_sync, storage).await?;
+                    } else {
+                        continue;
+                    }
+                    line.clear();
+                    too_long = false;
+                    write_all(serial, PROMPT.as_bytes()).await?;
+                }
+                // Backspace and delete
+                0x08 | 0x7F => {
+                    line.pop();
+                }
+                _ => too_long |= line.push(char::from(byte)).is_err(),
+            }
+        }
+    }
+}
+
+/// A console command.
+enum Command {
+    Help,
+    Status,
+    SetTime(UnixSeconds),
+    SetTimeOfDay(u8, u8, u8),
+    SetUtcOffsetMinutes(i32),
+    SetDaysSince(Option<UnixSeconds>),
+    SetGestureTimings(GestureTimings),
+    SetConfig(Config),
+    Scan,
+    SetWifi {
+        ssid: String<MAX_SSID_LEN>,
+        password: String<MAX_WIFI_PASSWORD_LEN>,
+    },
+    SetBssid(Option<Bssid>),
+    SetWifiCountry(WifiCountry),
+    PlaySound(AlarmSound),
+    Silence,
+    Sync,
+    Logs,
+}
+
+impl Command {
+    /// Parses a command line, or returns what to tell the user if it is not one.
+    fn parse(line: &str) -> Result<Self, &'static str> {
+        let mut words = line.split_ascii_whitespace();
+        let command = match (words.next(), words.next(), words.next()) {
+            (Some("help" | "?"), None, None) => Self::Help,
+            (Some("status"), None, None) => Self::Status,
+            (Some("sync"), None, None) => Self::Sync,
+            (Some("scan"), None, None) => Self::Scan,
+            (Some("silence"), None, None) => Self::Silence,
+            (Some("play"), sound, None) => Self::PlaySound(
+                sound
+                    .map_or(Some(config().alarm_sound), AlarmSound::parse)
+                    .ok_or("Expected: play [beep|beeps|chime|tune|custom]")?,
+            ),
+            (Some("logs"), None, None) => Self::Logs,
+            (Some("set"), Some("time"), Some(seconds)) => Self::SetTime(UnixSeconds(
+                seconds.parse().map_err(|_| "Expected: set time <unix seconds>")?,
+            )),
+            (Some("set"), Some("clock"), Some(time)) => {
+                let (hours, minutes, seconds) =
+                    parse_time_of_day(time).ok_or("Expected: set clock <hh:mm[:ss]>, 24-hour")?;
+                Self::SetTimeOfDay(hours, minutes, seconds)
+            }
+            (Some("set"), Some("tz"), Some(minutes)) => Self::SetUtcOffsetMinutes(
+                minutes
+                    .parse()
+                    .ok()
+                    .filter(|offset| {
+                        (MIN_UTC_OFFSET_HOURS.saturating_mul(60)
+                            ..=MAX_UTC_OFFSET_HOURS.saturating_mul(60))
+                            .contains(offset)
+                    })
+                    .ok_or("Expected: set tz <minutes>, from -720 to 840")?,
+            ),
+            (Some("set"), Some("since"), Some(date)) => Self::SetDaysSince(match date {
+                "none" => None,
+                _ => Some(
+                    UnixSeconds::from_iso_date(date)
+                        .ok_or("Expected: set since <yyyy-mm-dd>, or none")?,
+                ),
+            }),
+            (Some("set"), Some("gestures"), Some(changes)) => Self::SetGestureTimings(
+                gesture_timings().with_changes(changes).map_err(|_| {
+                    "Expected: set gestures long=200..3000,double=100..1500,very_long=3000..60000"
+                })?,
+            ),
+            (Some("set"), Some("config"), Some(changes)) => Self::SetConfig(
+                config().with_changes(changes).map_err(|_| {
+                    "Expected: set config blink_on=20..2000,blink_off=20..2000,\
+                     multiplex_us=500..5000,blanking_us=0..250,segment_compensation=0..50,\
+                     sync_minutes=5..1440,tick_volume=0..100,\
+                     alarm_sound=beep|beeps|chime|tune|custom"
+                })?,
+            ),
+            (Some("set"), Some("wifi"), Some(ssid)) => Self::SetWifi {
+                ssid: String::try_from(ssid).map_err(|_| "The SSID is longer than 32 bytes")?,
+                password: String::try_from(words.next().unwrap_or_default())
+                    .map_err(|_| "The password is longer than 64 characters")?,
+            },
+            (Some("set"), Some("bssid"), Some(address)) => Self::SetBssid(match address {
+                "any" => None,
+                _ => Some(
+                    Bssid::parse(address)
+                        .ok_or("Expected: set bssid <address>, e.g. a4:2b:b0:12:34:56, or any")?,
+                ),
+            }),
+            (Some("set"), Some("country"), Some(code)) => Self::SetWifiCountry(
+                WifiCountry::parse(code)
+                    .ok_or("Expected: set country <two-letter code>, or XX for worldwide")?,
+            ),
+            _ => return Err("Unknown command (type help for a list)"),
+        };
+        if words.next().is_some() {
+            return Err("Too many words (type help for a list)");
+        }
+        Ok(command)
+    }
+}
+
+/// Carries out `command` and writes its reply.
+async fn run(
+    serial: &mut Serial,
+    command: Result<Command, &'static str>,
+    clock: Clock<'static>,
+    time_sync: &TimeSync,
+    storage: &SharedStorage,
+) -> Result<()> {
+    let mut reply = String::<MAX_REPLY_LEN>::new();
+    match command {
+        Err(message) => writeln!(reply, "{message}\r")?,
+        Ok(Command::Help) => reply.write_str(HELP)?,
+        Ok(Command::Status) => write_status(&mut reply)?,
+        Ok(Command::SetTime(unix_seconds)) => {
+            let mut sanity =
+                TimeSanity::new(build_unix_seconds(), MAX_YEARS_AFTER_BUILD, max_time_jump());
+            match sanity.check(unix_seconds, Instant::now()) {
+                Ok(()) => {
+                    info!("USB console time: {} (unix timestamp)", unix_seconds.as_i64());
+                    // Arbitrated like any other source, so a fresh NTP sync still wins
+                    time_sync.post(TimeSyncEvent::Success {
+                        unix_seconds,
+                        leap: LeapIndicator::NoWarning,
+                        accuracy: SyncAccuracy::Coarse,
+                        source: TimeSource::Usb,
+                    });
+                    writeln!(reply, "OK\r")?;
+                }
+                Err(reason) => {
+                    time_sync.post(TimeSyncEvent::Rejected(reason));
+                    writeln!(reply, "Refused: {reason:?}\r")?;
+                }
+            }
+        }
+        Ok(Command::SetTimeOfDay(hours, minutes, seconds)) => {
+            clock.set_time(hours, minutes, seconds).await;
+            writeln!(reply, "OK\r")?;
+        }
+        Ok(Command::SetUtcOffsetMinutes(utc_offset_minutes)) => {
+            let mut pending = live_pending_settings();
+            pending.utc_offset_minutes = utc_offset_minutes;
+            commit_settings(&clock, storage, pending).await;
+            writeln!(reply, "OK\r")?;
+        }
+        Ok(Command::SetDaysSince(date)) => {
+            let mut pending = live_pending_settings();
+            pending.settings.days_since = date;
+            commit_settings(&clock, storage, pending).await;
+            writeln!(reply, "OK\r")?;
+        }
+        Ok(Command::SetGestureTimings(timings)) => {
+            storage.lock().await.save_gesture_timings(&timings)?;
+            set_gesture_timings(timings);
+            writeln!(reply, "OK\r")?;
+        }
+        Ok(Command::SetConfig(new_config)) => {
+            storage.lock().await.save_config(&new_config)?;
+            set_config(new_config);
+            clock
+                .set_blink_timing(new_config.blink_on_ms.into(), new_config.blink_off_ms.into())
+                .await;
+            writeln!(reply, "OK\r")?;
+        }
+        Ok(Command::Scan) => {
+            // Served even while the saved network cannot be joined
+            match with_timeout(SCAN_TIMEOUT, time_sync.wifi().scan()).await {
+                Ok(networks) if networks.is_empty() => writeln!(reply, "No networks found\r")?,
+                Ok(networks) => {
+                    for network in &networks {
+                        writeln!(reply, "{:>4} dBm  {}\r", network.rssi, network.ssid)?;
+                    }
+                }
+                Err(_) => writeln!(reply, "No answer from the WiFi chip\r")?,
+            }
+        }
+        Ok(Command::SetWifi { ssid, password }) => {
+            // Keep the rest of the config page, or start one with the clock's time zone
+            let mut storage = storage.lock().await;
+            let mut page = match storage.load_provisioning()? {
+                Some(page) => page,
+                None => Provisioning::new(&ssid, "", utc_offset_minutes())?,
+            };
+            page.ssid = ssid;
+            page.password = password;
+            storage.save_provisioning(&page)?;
+            writeln!(reply, "Saved; reboot to join {}\r", page.ssid)?;
+        }
+        Ok(Command::SetBssid(bssid)) => {
+            // An access point is no use without a network, which `set wifi` saves first
+            let mut storage = storage.lock().await;
+            if let Some(mut page) = storage.load_provisioning()? {
+                page.bssid = bssid;
+                storage.save_provisioning(&page)?;
+                writeln!(reply, "Saved; reboot to use it\r")?;
+            } else {
+                writeln!(reply, "No WiFi network saved; use set wifi first\r")?;
+            }
+        }
+        Ok(Command::SetWifiCountry(country)) => {
+            // A country is no use without a network, which `set wifi` saves first
+            let mut storage = storage.lock().await;
+            if let Some(mut page) = storage.load_provisioning()? {
+                page.country = country;
+                storage.save_provisioning(&page)?;
+                writeln!(reply, "Saved; reboot to use {}'s channels\r", country.as_str())?;
+            } else {
+                writeln!(reply, "No WiFi network saved; use set wifi first\r")?;
+            }
+        }
+        Ok(Command::PlaySound(sound)) => {
+            play_sound(sound);
+            writeln!(reply, "Playing {}\r", sound.name())?;
+        }
+        Ok(Command::Silence) => {
+            silence_sound();
+            writeln!(reply, "OK\r")?;
+        }
+        Ok(Command::Sync) => {
+            time_sync.request_sync();
+            writeln!(reply, "Sync requested\r")?;
+        }
+        Ok(Command::Logs) => return write_logs(serial, storage).await,
+    }
+    write_all(serial, reply.as_bytes()).await
+}
+
+/// Writes the clock's time, time zone, time source, gesture timings, config, and supply, one
+/// per line.
+fn write_status(out: &mut impl Write) -> Result<()> {
+    writeln!(out, "firmware {}\r", build_info::SUMMARY)?;
+    match latest_unix_seconds() {
+        Some(unix_seconds) => writeln!(out, "time {} (unix seconds)\r", unix_seconds.as_i64())?,
+        None => writeln!(out, "time not set\r")?,
+    }
+    writeln!(out, "utc offset {} minutes\r", utc_offset_minutes())?;
+    match time_arbiter().current() {
+        Some((source, set_at)) => writeln!(
+            out,
+            "time source {}, set {} s ago (unix seconds {})\r",
+            source.name(),
+            set_at.elapsed().as_secs(),
+            wall_clock_at(set_at).map_or(0, UnixSeconds::as_i64)
+        )?,
+        None => writeln!(out, "time source none\r")?,
+    }
+    out.write_str("gestures ")?;
+    gesture_timings().write_list(out)?;
+    out.write_str("\r\nconfig ")?;
+    config().write_list(out)?;
+    out.write_str("\r\n")?;
+    if let Some(millivolts) = supply_millivolts() {
+        writeln!(out, "supply {millivolts} mV\r")?;
+    }
+    Ok(())
+}
+
+/// Writes the last crash report and then the climate log as CSV, a sample at a time.
+async fn write_logs(serial: &mut Serial, storage: &SharedStorage) -> Result<()> {
+    let mut chunk = String::<MAX_REPLY_LEN>::new();
+    match storage.lock().await.load_crash_report()? {
+        Some(report) => write!(chunk, "Last crash:\r\n{report}")?,
+        None => writeln!(chunk, "No crash saved\r")?,
+    }
+    write_all(serial, chunk.as_bytes()).await?;
+    write_all(serial, CLIMATE_CSV_HEADER.as_bytes()).await?;
+    let mut slot = storage.lock().await.next_climate_slot()?;
+    for _ in 0..CLIMATE_LOG_SLOTS {
+        // Lock per sample, so a long dump does not hold up saving settings
+        if let Some(sample) = storage.lock().await.climate_sample(slot)? {
+            chunk.clear();
+            sample.write_csv_row(&mut chunk)?;
+            write_all(serial, chunk.as_bytes()).await?;
+        }
+        slot = next_slot(slot);
+    }
+    Ok(())
+}
+
+/// Sends `bytes` a packet at a time, ending with an empty packet if the last one was full so
+/// the host knows the transfer is over.
+async fn write_all(serial: &mut Serial, bytes: &[u8]) -> Result<()> {
+    for packet in bytes.chunks(PACKET_LEN) {
+        serial.write_packet(packet).await?;
+    }
+    if !bytes.is_empty() && bytes.len().is_multiple_of(PACKET_LEN) {
+        serial.write_packet(&[]).await?;
+    }
+    Ok(())
+}
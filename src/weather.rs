@@ -0,0 +1,131 @@
+//! Weather virtual device - fetches the outdoor temperature from an HTTP API
+
+#![allow(clippy::future_not_send, reason = "single-threaded")]
+
+use embassy_executor::{SpawnError, Spawner};
+use embassy_futures::select::select;
+use embassy_net::Stack;
+use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, signal::Signal};
+use embassy_time::{Duration, Instant, Timer};
+
+use clock_core::{find_number, Temperature};
+
+use crate::{info, warn};
+use crate::clock::Clock;
+use crate::http_client::{self, Method};
+use crate::network_policy::{network_quiet, Quiet};
+use crate::settings::{weather_json_key, weather_url};
+use crate::wifi::Wifi;
+
+/// How often the temperature is fetched.
+const WEATHER_REFRESH: Duration = Duration::from_secs(30 * 60);
+
+/// How soon a failed fetch is retried.
+const WEATHER_RETRY: Duration = Duration::from_secs(60);
+
+/// How old the last reading may get before the clock stops showing it.
+const WEATHER_STALE: Duration = Duration::from_secs(2 * 60 * 60);
+
+/// The largest response (headers and body) read from the weather API.
+const MAX_RESPONSE_LEN: usize = 2048;
+
+/// The buffers the weather task holds while it fetches.
+pub(crate) const RAM_BYTES: usize = MAX_RESPONSE_LEN + http_client::RAM_BYTES;
+
+/// Wakes the weather task to fetch at once.
+static FETCH_REQUESTS: Signal<CriticalSectionRawMutex, ()> = Signal::new();
+
+/// Asks the weather task to fetch now, such as when network quiet hours end.
+pub(crate) fn request_fetch() {
+    FETCH_REQUESTS.signal(());
+}
+
+// ============================================================================
+// Weather Virtual Device
+// ============================================================================
+
+/// Weather virtual device - keeps the clock's outdoor temperature up to date
+///
+/// It does nothing unless `WEATHER_URL` is set at build time.
+pub struct Weather;
+
+impl Weather {
+    /// Create the weather device and spawn its task.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `SpawnError` if the task cannot be spawned.
+    pub fn new(
+        wifi: &'static Wifi,
+        clock: Clock<'static>,
+        spawner: Spawner,
+    ) -> Result<Self, SpawnError> {
+        spawner.spawn(device_loop(wifi, clock))?;
+        Ok(Self)
+    }
+}
+
+#[embassy_executor::task]
+async fn device_loop(wifi: &'static Wifi, clock: Clock<'static>) -> ! {
+    let Some(url) = weather_url() else {
+        info!("Weather disabled: WEATHER_URL not set");
+        loop {
+            core::future::pending::<()>().await;
+        }
+    };
+    let Some(&stack) = wifi.stack().await else {
+        info!("Weather disabled: WiFi unavailable");
+        loop {
+            core::future::pending::<()>().await;
+        }
+    };
+
+    let mut last_success: Option<Instant> = None;
+    loop {
+        let wait = if let Some(quiet) = network_quiet() {
+            info!("Weather fetch held back: {}", quiet.name());
+            // The scheduler asks for a fetch when quiet hours end
+            match quiet {
+                Quiet::QuietHours => WEATHER_REFRESH,
+                Quiet::LowSupply => WEATHER_RETRY,
+            }
+        } else {
+            match fetch_temperature(&stack, url, weather_json_key()).await {
+                Ok(temperature) => {
+                    info!("Outdoor temperature: {:?}", temperature);
+                    clock.set_temperature(Some(temperature)).await;
+                    last_success = Some(Instant::now());
+                    WEATHER_REFRESH
+                }
+                Err(msg) => {
+                    warn!("Weather fetch failed: {}", msg);
+                    WEATHER_RETRY
+                }
+            }
+        };
+        if last_success.is_some_and(|success| success.elapsed() > WEATHER_STALE) {
+            clock.set_temperature(None).await;
+            last_success = None;
+        }
+        FETCH_REQUESTS.reset();
+        select(Timer::after(wait), FETCH_REQUESTS.wait()).await;
+    }
+}
+
+/// GETs `url` and reads the number after `"key":` in the response.
+async fn fetch_temperature(
+    stack: &Stack<'static>,
+    url: &str,
+    key: &str,
+) -> Result<Temperature, &'static str> {
+    let mut buffer = [0_u8; MAX_RESPONSE_LEN];
+    let response = http_client::request(stack, Method::Get, url, &mut buffer).await?;
+    if !response.is_success() {
+        warn!("Weather API status {}", response.status());
+        return Err("Weather API error");
+    }
+    let value = find_number(response.text(), key).ok_or("No temperature in weather response")?;
+    Ok(Temperature(
+        i16::try_from(value).map_err(|_| "Temperature out of range")?,
+    ))
+}
@@ -1,25 +1,52 @@
 //! WiFi virtual device - manages WiFi connection and network stack
+//!
+//! This is the Pico W's `cyw43` driver. Another board would replace this module with one
+//! offering the same `Wifi` methods, since the Embassy tasks that use it cannot be generic;
+//! the state machine only needs `WifiPort`.
 
 #![allow(clippy::future_not_send, reason = "single-threaded")]
 #![allow(unsafe_code, reason = "StackStorage uses UnsafeCell in single-threaded context")]
 
-use cyw43::JoinOptions;
+#[cfg(not(feature = "no-wifi"))]
+use cyw43::{JoinOptions, ScanOptions};
+#[cfg(not(feature = "no-wifi"))]
 use cyw43_pio::{DEFAULT_CLOCK_DIVIDER, PioSpi};
-use defmt::*;
 use embassy_executor::Spawner;
-use embassy_net::{Config, Stack, StackResources};
-use embassy_rp::{Peri, bind_interrupts};
+use embassy_net::Stack;
+#[cfg(not(feature = "no-wifi"))]
+use embassy_net::{Config, StackResources};
+use embassy_rp::Peri;
+#[cfg(not(feature = "no-wifi"))]
+use embassy_rp::bind_interrupts;
+#[cfg(not(feature = "no-wifi"))]
 use embassy_rp::gpio::{Level, Output};
 use embassy_rp::peripherals::{DMA_CH0, PIN_23, PIN_24, PIN_25, PIN_29, PIO0};
+#[cfg(not(feature = "no-wifi"))]
 use embassy_rp::pio::{InterruptHandler, Pio};
-use core::cell::UnsafeCell;
-use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+use core::cell::{Cell, UnsafeCell};
+use embassy_sync::blocking_mutex::{Mutex, raw::CriticalSectionRawMutex};
+use embassy_sync::channel::Channel;
 use embassy_sync::signal::Signal;
 use embassy_sync::waitqueue::AtomicWaker;
-use embassy_time::Timer;
-use portable_atomic::{AtomicBool, Ordering};
+#[cfg(not(feature = "no-wifi"))]
+use embassy_futures::select::{Either, select};
+#[cfg(not(feature = "no-wifi"))]
+use embassy_time::{Duration, Timer, with_timeout};
+use heapless::{String, Vec};
+use portable_atomic::{AtomicBool, AtomicU32, Ordering};
 use static_cell::StaticCell;
 
+use clock_core::WifiPort;
+#[cfg(not(feature = "no-wifi"))]
+use clock_core::{Bssid, Provisioning, WifiCountry};
+
+use crate::{info, unwrap, warn};
+#[cfg(not(feature = "no-wifi"))]
+use crate::display::first_frame_shown;
+use crate::provisioning::provisioning;
+#[cfg(not(feature = "no-wifi"))]
+use crate::Display2Format;
+
 // ============================================================================
 // Types
 // ============================================================================
@@ -28,6 +55,54 @@ use static_cell::StaticCell;
 pub enum WifiEvent {
     /// Network stack is initialized and DHCP is configured
     Ready,
+    /// There is no working WiFi chip, so there will be no network stack
+    Unavailable,
+}
+
+/// How long to wait for the CYW43 chip to answer before deciding it is absent.
+#[cfg(not(feature = "no-wifi"))]
+const WIFI_INIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long to wait between attempts to join the configured network.
+#[cfg(not(feature = "no-wifi"))]
+const JOIN_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// The most networks `Wifi::scan` will report.
+pub const MAX_SCAN_RESULTS: usize = 16;
+
+/// How long the WiFi task waits for a scan's caller to take each result before deciding it
+/// gave up on the scan.
+#[cfg(not(feature = "no-wifi"))]
+const SCAN_HANDOFF_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The CYW43 driver's state and the network stack's socket table.
+#[cfg(not(feature = "no-wifi"))]
+pub(crate) const RAM_BYTES: usize =
+    size_of::<cyw43::State>() + size_of::<StackResources<SOCKET_COUNT>>();
+
+/// Without the radio there is no driver or network stack.
+#[cfg(feature = "no-wifi")]
+pub(crate) const RAM_BYTES: usize = 0;
+
+/// Sockets: DHCP, DNS, NTP (UDP), the HTTP server and the MQTT connection (TCP), plus one
+/// spare.
+#[cfg(not(feature = "no-wifi"))]
+const SOCKET_COUNT: usize = 6;
+
+/// A network seen during a WiFi scan.
+#[derive(Clone, Debug)]
+pub struct WifiScanResult {
+    /// The network name (SSID).
+    pub ssid: String<32>,
+    /// The received signal strength in dBm.
+    pub rssi: i16,
+}
+
+/// Messages from the WiFi task back to a pending `Wifi::scan` call, each with the number of
+/// the scan it belongs to.
+enum WifiScanEvent {
+    Found(u32, WifiScanResult),
+    Done(u32),
 }
 
 /// Single-threaded once-storage for network stack
@@ -54,7 +129,9 @@ impl StackStorage {
     }
     
     /// Initialize the stack storage (can only be called once)
-    pub fn init(&self, stack: &'static Stack<'static>) {
+    ///
+    /// `None` records that WiFi is unavailable, so there will never be a stack.
+    pub fn init(&self, stack: Option<&'static Stack<'static>>) {
         if self.initialized.swap(true, Ordering::Release) {
             // Already initialized - this is a bug
             return;
@@ -62,24 +139,24 @@ impl StackStorage {
         
         // SAFETY: We just checked that we're the only initializer
         unsafe {
-            *self.value.get() = Some(stack);
+            *self.value.get() = stack;
         }
         
         self.waker.wake();
     }
     
-    /// Wait for the stack to be initialized and return it
-    pub async fn get(&self) -> &'static Stack<'static> {
+    /// Wait for the stack to be initialized and return it, or `None` if WiFi is unavailable
+    pub async fn get(&self) -> Option<&'static Stack<'static>> {
         core::future::poll_fn(|cx| {
             if self.initialized.load(Ordering::Acquire) {
                 // SAFETY: initialized is true, so value is set
-                let value = unsafe { (*self.value.get()).unwrap() };
+                let value = unsafe { *self.value.get() };
                 core::task::Poll::Ready(value)
             } else {
                 self.waker.register(cx.waker());
                 // Check again after registering to avoid race
                 if self.initialized.load(Ordering::Acquire) {
-                    let value = unsafe { (*self.value.get()).unwrap() };
+                    let value = unsafe { *self.value.get() };
                     core::task::Poll::Ready(value)
                 } else {
                     core::task::Poll::Pending
@@ -89,16 +166,75 @@ impl StackStorage {
     }
 }
 
+// ============================================================================
+// Transmit Bursts
+// ============================================================================
+
+/// How many transmit bursts are under way (see `TransmitBurst`).
+static TRANSMIT_BURSTS: Mutex<CriticalSectionRawMutex, Cell<u8>> = Mutex::new(Cell::new(0));
+
+/// Signaled when the first transmit burst starts or the last one ends.
+static TRANSMIT_CHANGES: Signal<CriticalSectionRawMutex, ()> = Signal::new();
+
+/// Marks a burst of WiFi traffic (a join attempt, an NTP or HTTP request going out) for as
+/// long as it lives, so the display can ease off while the radio draws its peak current.
+/// Keep it to the transmission itself: held across a wait, it keeps the display dimmed.
+pub(crate) struct TransmitBurst;
+
+impl TransmitBurst {
+    /// Starts a burst, which ends when the `TransmitBurst` is dropped.
+    pub(crate) fn start() -> Self {
+        let first = TRANSMIT_BURSTS.lock(|bursts| {
+            bursts.set(bursts.get().saturating_add(1));
+            bursts.get() == 1
+        });
+        if first {
+            TRANSMIT_CHANGES.signal(());
+        }
+        Self
+    }
+}
+
+impl Drop for TransmitBurst {
+    fn drop(&mut self) {
+        let last = TRANSMIT_BURSTS.lock(|bursts| {
+            bursts.set(bursts.get().saturating_sub(1));
+            bursts.get() == 0
+        });
+        if last {
+            TRANSMIT_CHANGES.signal(());
+        }
+    }
+}
+
+/// Whether a transmit burst is under way.
+pub(crate) fn transmitting() -> bool {
+    TRANSMIT_BURSTS.lock(Cell::get) > 0
+}
+
+/// Waits until the first transmit burst starts or the last one ends.
+pub(crate) async fn transmit_changed() {
+    TRANSMIT_CHANGES.wait().await;
+}
+
 // ============================================================================
 // WiFi Virtual Device
 // ============================================================================
 
 pub type WifiEvents = Signal<CriticalSectionRawMutex, WifiEvent>;
+/// Asks the WiFi task for a scan, numbered so its results can be told from an earlier one's.
+type WifiScanRequests = Signal<CriticalSectionRawMutex, u32>;
+type WifiRadioRequests = Signal<CriticalSectionRawMutex, bool>;
+type WifiScanResults = Channel<CriticalSectionRawMutex, WifiScanEvent, 4>;
 
 /// Resources needed by the WiFi device (single static)
 pub struct WifiNotifier {
     events: WifiEvents,
     stack: StackStorage,
+    scan_requests: WifiScanRequests,
+    scan_results: WifiScanResults,
+    scan_count: AtomicU32,
+    radio_requests: WifiRadioRequests,
     wifi_cell: StaticCell<Wifi>,
 }
 
@@ -106,6 +242,10 @@ pub struct WifiNotifier {
 pub struct Wifi {
     events: &'static WifiEvents,
     stack: &'static StackStorage,
+    scan_requests: &'static WifiScanRequests,
+    scan_results: &'static WifiScanResults,
+    scan_count: &'static AtomicU32,
+    radio_requests: &'static WifiRadioRequests,
 }
 
 impl Wifi {
@@ -115,12 +255,18 @@ impl Wifi {
         WifiNotifier {
             events: Signal::new(),
             stack: StackStorage::new(),
+            scan_requests: Signal::new(),
+            scan_results: Channel::new(),
+            scan_count: AtomicU32::new(0),
+            radio_requests: Signal::new(),
             wifi_cell: StaticCell::new(),
         }
     }
     
     /// Wait for the network stack to be ready and return a reference to it
-    pub async fn stack(&self) -> &'static Stack<'static> {
+    ///
+    /// Returns `None` if the WiFi chip is absent or the firmware was built with `no-wifi`.
+    pub async fn stack(&self) -> Option<&'static Stack<'static>> {
         self.stack.get().await
     }
     
@@ -129,6 +275,45 @@ impl Wifi {
         self.events.wait().await
     }
 
+    /// Scan for nearby networks, strongest first.
+    ///
+    /// Scans are served whether or not the configured network has been joined (between join
+    /// attempts while it cannot be), so they can help pick a network. Networks that share an
+    /// SSID (for example, mesh nodes) are reported once with their strongest signal. Hidden
+    /// networks are skipped.
+    ///
+    /// Results left in the channel by an earlier scan whose caller gave up are skipped too.
+    pub async fn scan(&self) -> Vec<WifiScanResult, MAX_SCAN_RESULTS> {
+        let scan = self.scan_count.fetch_add(1, Ordering::Relaxed).wrapping_add(1);
+        self.scan_requests.signal(scan);
+        let mut results = Vec::<WifiScanResult, MAX_SCAN_RESULTS>::new();
+        loop {
+            let result = match self.scan_results.receive().await {
+                WifiScanEvent::Found(from, result) if from == scan => result,
+                WifiScanEvent::Done(from) if from == scan => break,
+                WifiScanEvent::Found(..) | WifiScanEvent::Done(_) => continue,
+            };
+            if let Some(existing) = results.iter_mut().find(|existing| existing.ssid == result.ssid) {
+                existing.rssi = existing.rssi.max(result.rssi);
+            } else if let Err(result) = results.push(result) {
+                // Full: keep the stronger of the new network and the weakest one seen so far
+                if let Some(weakest) = results.iter_mut().min_by_key(|existing| existing.rssi) {
+                    if weakest.rssi < result.rssi {
+                        *weakest = result;
+                    }
+                }
+            }
+        }
+        results.sort_unstable_by_key(|result| core::cmp::Reverse(result.rssi));
+        results
+    }
+
+    /// Turns the radio off (leaving the network, in the chip's deepest power save) or back
+    /// on (rejoining). Until it is back on, network requests time out.
+    pub fn set_radio(&self, on: bool) {
+        self.radio_requests.signal(on);
+    }
+
     /// Create a new Wifi device and spawn its task
     /// Returns a static reference to the Wifi handle
     pub fn new(
@@ -141,20 +326,61 @@ impl Wifi {
         dma_ch0: Peri<'static, DMA_CH0>,
         spawner: Spawner,
     ) -> &'static Self {
+        #[cfg(not(feature = "no-wifi"))]
         unwrap!(spawner.spawn(wifi_device_loop(
-            pin_23, pin_25, pio0, pin_24, pin_29, dma_ch0, &resources.events, &resources.stack, spawner,
+            pin_23,
+            pin_25,
+            pio0,
+            pin_24,
+            pin_29,
+            dma_ch0,
+            &resources.events,
+            &resources.stack,
+            &resources.scan_requests,
+            &resources.scan_results,
+            &resources.radio_requests,
+            spawner,
         )));
+        // Built for a plain Pico: there is no radio to start
+        #[cfg(feature = "no-wifi")]
+        {
+            let _ = (pin_23, pin_25, pio0, pin_24, pin_29, dma_ch0, spawner);
+            info!("Built without WiFi");
+            resources.stack.init(None);
+            resources.events.signal(WifiEvent::Unavailable);
+        }
         resources.wifi_cell.init(Self { 
             events: &resources.events, 
             stack: &resources.stack,
+            scan_requests: &resources.scan_requests,
+            scan_results: &resources.scan_results,
+            scan_count: &resources.scan_count,
+            radio_requests: &resources.radio_requests,
         })
     }
 }
 
+impl WifiPort for Wifi {
+    fn set_radio(&self, on: bool) {
+        self.radio_requests.signal(on);
+    }
+
+    async fn signal_dbm(&self) -> Option<i16> {
+        let ssid = provisioning()?.ssid;
+        self.scan()
+            .await
+            .iter()
+            .find(|network| network.ssid == ssid)
+            .map(|network| network.rssi)
+    }
+}
+
+#[cfg(not(feature = "no-wifi"))]
 bind_interrupts!(struct Irqs {
     PIO0_IRQ_0 => InterruptHandler<PIO0>;
 });
 
+#[cfg(not(feature = "no-wifi"))]
 #[embassy_executor::task]
 async fn wifi_device_loop(
     pin_23: Peri<'static, PIN_23>,
@@ -165,13 +391,20 @@ async fn wifi_device_loop(
     dma_ch0: Peri<'static, DMA_CH0>,
     wifi_events: &'static WifiEvents,
     stack_storage: &'static StackStorage,
+    scan_requests: &'static WifiScanRequests,
+    scan_results: &'static WifiScanResults,
+    radio_requests: &'static WifiRadioRequests,
     spawner: Spawner,
 ) -> ! {
-    // Read WiFi credentials from compile-time environment
-    const WIFI_SSID: &str = env!("WIFI_SSID");
-    const WIFI_PASS: &str = env!("WIFI_PASS");
-
     info!("WiFi device initializing");
+    let provisioning = provisioning();
+    if provisioning.is_none() {
+        warn!("No config page (run clock-flash or set wifi); scanning only, with no network");
+    }
+
+    // Uploading the CYW43 firmware takes a few seconds and starves the other tasks, so show the
+    // boot frame first. Otherwise the display stays blank until the upload finishes.
+    first_frame_shown().await;
 
     // Initialize WiFi hardware
     let fw = cyw43_firmware::CYW43_43439A0;
@@ -193,24 +426,52 @@ async fn wifi_device_loop(
 
     static STATE: StaticCell<cyw43::State> = StaticCell::new();
     let state = STATE.init(cyw43::State::new());
-    let (net_device, mut control, runner) = cyw43::new(state, pwr, spi, fw).await;
+    // A plain (non-W) Pico has no chip to answer, so give up rather than hang
+    let Ok((net_device, mut control, runner)) =
+        with_timeout(WIFI_INIT_TIMEOUT, cyw43::new(state, pwr, spi, fw)).await
+    else {
+        warn!("WiFi chip did not respond; running without WiFi");
+        stack_storage.init(None);
+        wifi_events.signal(WifiEvent::Unavailable);
+        loop {
+            core::future::pending::<()>().await;
+        }
+    };
     unwrap!(spawner.spawn(wifi_task(runner)));
 
     control.init(clm).await;
+    set_country(
+        &mut control,
+        provisioning
+            .as_ref()
+            .map_or(WifiCountry::WORLDWIDE, |provisioning| provisioning.country),
+    )
+    .await;
     control
         .set_power_management(cyw43::PowerManagementMode::PowerSave)
         .await;
 
+    // With no network to join there is no network stack, but scans still list the networks
+    // to choose from for `set wifi`
+    let Some(provisioning) = provisioning else {
+        stack_storage.init(None);
+        wifi_events.signal(WifiEvent::Unavailable);
+        loop {
+            let scan = scan_requests.wait().await;
+            serve_scan(&mut control, scan, scan_results).await;
+        }
+    };
+
     // Initialize network stack
     let config = Config::dhcpv4(Default::default());
     let seed = 0x7c8f_3a2e_9d14_6b5a;
 
-    static RESOURCES: StaticCell<StackResources<3>> = StaticCell::new();
+    static RESOURCES: StaticCell<StackResources<SOCKET_COUNT>> = StaticCell::new();
     static STACK: StaticCell<Stack<'static>> = StaticCell::new();
     let (stack_val, runner) = embassy_net::new(
         net_device,
         config,
-        RESOURCES.init(StackResources::<3>::new()),
+        RESOURCES.init(StackResources::<SOCKET_COUNT>::new()),
         seed,
     );
     let stack = STACK.init(stack_val);
@@ -218,19 +479,7 @@ async fn wifi_device_loop(
     unwrap!(spawner.spawn(net_task(runner)));
 
     // Connect to WiFi
-    info!("Connecting to WiFi: {}", WIFI_SSID);
-    loop {
-        match control
-            .join(WIFI_SSID, JoinOptions::new(WIFI_PASS.as_bytes()))
-            .await
-        {
-            Ok(_) => break,
-            Err(err) => {
-                info!("Join failed: {}", err.status);
-                Timer::after_secs(1).await;
-            }
-        }
-    }
+    join(&mut control, &provisioning, scan_requests, scan_results).await;
 
     info!("WiFi connected! Waiting for DHCP...");
     stack.wait_config_up().await;
@@ -242,19 +491,173 @@ async fn wifi_device_loop(
     info!("WiFi device ready");
     
     // Store stack reference and emit Ready event
-    stack_storage.init(stack);
+    stack_storage.init(Some(stack));
     wifi_events.signal(WifiEvent::Ready);
 
-    // Keep task alive (could monitor link status in future)
+    // Keep task alive, serving scan and radio requests (could monitor link status in future)
+    loop {
+        let scan = match select(scan_requests.wait(), radio_requests.wait()).await {
+            Either::First(scan) => scan,
+            Either::Second(on) => {
+                if on {
+                    info!("WiFi radio on");
+                    control
+                        .set_power_management(cyw43::PowerManagementMode::PowerSave)
+                        .await;
+                    join(&mut control, &provisioning, scan_requests, scan_results).await;
+                } else {
+                    info!("WiFi radio off");
+                    control.leave().await;
+                    control
+                        .set_power_management(cyw43::PowerManagementMode::SuperSave)
+                        .await;
+                }
+                continue;
+            }
+        };
+        serve_scan(&mut control, scan, scan_results).await;
+    }
+}
+
+/// Scans for nearby networks and hands them to the caller of `Wifi::scan`, after dropping
+/// whatever an earlier caller left unread. A caller that stops taking results has given up,
+/// so the scan ends rather than leave the WiFi task waiting on it.
+#[cfg(not(feature = "no-wifi"))]
+async fn serve_scan(
+    control: &mut cyw43::Control<'static>,
+    scan: u32,
+    scan_results: &'static WifiScanResults,
+) {
+    info!("WiFi scan started");
+    scan_results.clear();
+    let mut scanner = control.scan(ScanOptions::default()).await;
+    while let Some(bss) = scanner.next().await {
+        // Copy out of the packed struct before borrowing
+        let (ssid_bytes, ssid_len, rssi) = (bss.ssid, bss.ssid_len, bss.rssi);
+        let Some(Ok(name)) = ssid_bytes
+            .get(..usize::from(ssid_len))
+            .map(core::str::from_utf8)
+        else {
+            continue;
+        };
+        if name.is_empty() {
+            continue; // Hidden network
+        }
+        info!("Found {=str} ({} dBm)", name, rssi);
+        let Ok(ssid) = String::try_from(name) else {
+            continue;
+        };
+        let found = WifiScanEvent::Found(scan, WifiScanResult { ssid, rssi });
+        if with_timeout(SCAN_HANDOFF_TIMEOUT, scan_results.send(found)).await.is_err() {
+            info!("WiFi scan abandoned");
+            return;
+        }
+    }
+    // Left unread, this is dropped by the next scan
+    let done = WifiScanEvent::Done(scan);
+    if with_timeout(SCAN_HANDOFF_TIMEOUT, scan_results.send(done)).await.is_ok() {
+        info!("WiFi scan finished");
+    } else {
+        info!("WiFi scan abandoned");
+    }
+}
+
+/// Keeps the radio to `country`'s channels. `Control::init` leaves it on worldwide ones.
+#[cfg(not(feature = "no-wifi"))]
+async fn set_country(control: &mut cyw43::Control<'static>, country: WifiCountry) {
+    if country == WifiCountry::WORLDWIDE {
+        return;
+    }
+    info!("WiFi country: {=str}", country.as_str());
+    // The `country` iovar is the code as an abbreviation, a revision (-1 for the CLM's
+    // default), and the code again, each field four bytes
+    let [first, second] = country.code();
+    let [rev_0, rev_1, rev_2, rev_3] = (-1_i32).to_le_bytes();
+    let country_info = [first, second, 0, 0, rev_0, rev_1, rev_2, rev_3, first, second, 0, 0];
+    control.set_iovar("country", &country_info).await;
+}
+
+/// Joins the provisioned network, retrying every second until it succeeds. Scans asked for
+/// meanwhile are served between attempts, since they are how a wrong network is put right.
+#[cfg(not(feature = "no-wifi"))]
+async fn join(
+    control: &mut cyw43::Control<'static>,
+    provisioning: &Provisioning,
+    scan_requests: &'static WifiScanRequests,
+    scan_results: &'static WifiScanResults,
+) {
+    info!("Connecting to WiFi: {=str}", provisioning.ssid.as_str());
+    if provisioning.bssid.is_some() {
+        // Stay on the chosen access point rather than wandering to a stronger-looking one
+        control.set_iovar("roam_off", &1_u32.to_le_bytes()).await;
+    }
     loop {
-        Timer::after_secs(3600).await;
+        // Each attempt is a burst, so the display brightens again while waiting to retry
+        let burst = TransmitBurst::start();
+        if (provisioning.hidden || provisioning.bssid.is_some())
+            && !probe(control, provisioning).await
+        {
+            drop(burst);
+            info!("No answer from the access point");
+            pause_for_scans(control, scan_requests, scan_results).await;
+            continue;
+        }
+        let joined = control
+            .join(
+                &provisioning.ssid,
+                JoinOptions::new(provisioning.password.as_bytes()),
+            )
+            .await;
+        drop(burst);
+        match joined {
+            Ok(_) => break,
+            Err(err) => {
+                info!("Join failed: {}", err.status);
+                pause_for_scans(control, scan_requests, scan_results).await;
+            }
+        }
+    }
+}
+
+/// Waits `JOIN_RETRY_DELAY` before the next join attempt, or serves a scan asked for before
+/// then (which takes longer than the delay).
+#[cfg(not(feature = "no-wifi"))]
+async fn pause_for_scans(
+    control: &mut cyw43::Control<'static>,
+    scan_requests: &'static WifiScanRequests,
+    scan_results: &'static WifiScanResults,
+) {
+    let retry = Timer::after(JOIN_RETRY_DELAY);
+    if let Either::Second(scan) = select(retry, scan_requests.wait()).await {
+        serve_scan(control, scan, scan_results).await;
+    }
+}
+
+/// Asks for the network by name, which a hidden network needs before it can be joined, and
+/// only of the provisioned access point if there is one. Returns whether it answered.
+#[cfg(not(feature = "no-wifi"))]
+async fn probe(control: &mut cyw43::Control<'static>, provisioning: &Provisioning) -> bool {
+    let options = ScanOptions {
+        ssid: provisioning.ssid.as_str().try_into().ok(),
+        bssid: provisioning.bssid.map(|Bssid(bssid)| bssid),
+        ..ScanOptions::default()
+    };
+    let mut scanner = control.scan(options).await;
+    let mut answered = false;
+    while let Some(bss) = scanner.next().await {
+        // Copy out of the packed struct before borrowing
+        let (bssid, rssi) = (bss.bssid, bss.rssi);
+        info!("{} answered ({} dBm)", Display2Format(&Bssid(bssid)), rssi);
+        answered = true;
     }
+    answered
 }
 
 // ============================================================================
 // WiFi Tasks
 // ============================================================================
 
+#[cfg(not(feature = "no-wifi"))]
 #[embassy_executor::task]
 async fn wifi_task(
     runner: cyw43::Runner<'static, Output<'static>, PioSpi<'static, PIO0, 0, DMA_CH0>>,
@@ -262,6 +665,7 @@ async fn wifi_task(
     runner.run().await
 }
 
+#[cfg(not(feature = "no-wifi"))]
 #[embassy_executor::task]
 async fn net_task(mut runner: embassy_net::Runner<'static, cyw43::NetDriver<'static>>) -> ! {
     runner.run().await
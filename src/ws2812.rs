@@ -0,0 +1,106 @@
+//! A display backend for WS2812 ("NeoPixel") LEDs: one LED per segment, chained cell by cell,
+//! driven by a PIO state machine (see `README.md`).
+
+use embassy_futures::select::{select, Either};
+use embassy_rp::{
+    bind_interrupts,
+    peripherals::{DMA_CH2, PIN_5, PIO1},
+    pio::{Common, InterruptHandler, Pio},
+    pio_programs::ws2812::{PioWs2812, PioWs2812Program},
+    Peri,
+};
+use embassy_time::Timer;
+use smart_leds::RGB8;
+
+use clock_core::{local_minute_of_day, DigitColors};
+
+use crate::{
+    display::{cell_brightness, next_frame, DisplayDriver, DisplayNotifier, Frame},
+    error::Result,
+    info,
+    network_policy::utc_offset_minutes,
+    settings::ws2812_colors,
+    shared_constants::ONE_MINUTE,
+    soft_reboot::latest_unix_seconds,
+    CELL_COUNT, SEGMENT_COUNT,
+};
+
+/// LEDs on the strip: `SEGMENT_COUNT` per cell, the first cell's first.
+const LED_COUNT: usize = CELL_COUNT * SEGMENT_COUNT;
+
+bind_interrupts!(struct Irqs {
+    PIO1_IRQ_0 => InterruptHandler<PIO1>;
+});
+
+/// Shows each cell's segments on its own run of WS2812 LEDs, segment A first, in the colors
+/// from `WS2812_COLORS`.
+pub struct Ws2812Display {
+    // Kept so the PIO block stays set up for the state machine
+    _common: Common<'static, PIO1>,
+    leds: PioWs2812<'static, PIO1, 0, LED_COUNT>,
+    colors: DigitColors,
+}
+
+impl Ws2812Display {
+    /// Creates a driver for a strip whose data line is on `pin`, fed by PIO1 and `dma`.
+    #[must_use]
+    pub fn new(
+        pio: Peri<'static, PIO1>,
+        dma: Peri<'static, DMA_CH2>,
+        pin: Peri<'static, PIN_5>,
+    ) -> Self {
+        let Pio {
+            mut common, sm0, ..
+        } = Pio::new(pio, Irqs);
+        let program = PioWs2812Program::new(&mut common);
+        let leds = PioWs2812::new(&mut common, sm0, dma, pin, &program);
+        Self {
+            _common: common,
+            leds,
+            colors: ws2812_colors(),
+        }
+    }
+
+    /// The LED colors for `frame`: each lit segment in its cell's color, at the frame's
+    /// brightness (about half for a dimmed cell).
+    fn pixels(&self, frame: Frame) -> [RGB8; LED_COUNT] {
+        let (bit_matrix, display_brightness) = frame;
+        let minute_of_day = latest_unix_seconds()
+            .and_then(|unix_seconds| local_minute_of_day(unix_seconds, utc_offset_minutes()));
+        let mut pixels = [RGB8::default(); LED_COUNT];
+        for ((index, &bits), cell_pixels) in bit_matrix
+            .iter()
+            .enumerate()
+            .zip(pixels.chunks_exact_mut(SEGMENT_COUNT))
+        {
+            let color = self
+                .colors
+                .color(index, minute_of_day)
+                .at_brightness(cell_brightness(&bit_matrix, index, display_brightness));
+            for (bit, pixel) in (0_u32..).zip(cell_pixels) {
+                if bits.checked_shr(bit).is_some_and(|shifted| shifted & 1 == 1) {
+                    *pixel = RGB8::new(color.red, color.green, color.blue);
+                }
+            }
+        }
+        pixels
+    }
+}
+
+impl DisplayDriver for Ws2812Display {
+    async fn show(&mut self, frame: Frame, notifier: &DisplayNotifier) -> Result<Frame> {
+        info!("ws2812 frame: {:?}", frame.0.decode());
+        self.leds.write(&self.pixels(frame)).await;
+        match self.colors {
+            DigitColors::PerDigit(_) => Ok(next_frame(notifier, frame).await),
+            // Redraw each minute so the color follows the time, even when the digits do not
+            // change (such as a static message).
+            DigitColors::TimeOfDay => {
+                match select(Timer::after(ONE_MINUTE), next_frame(notifier, frame)).await {
+                    Either::First(()) => Ok(frame),
+                    Either::Second(notification) => Ok(notification),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+"""Build the talking clock's voice file from a folder of recorded words.
+
+Usage: python make_voice.py <folder> <voice file>, e.g. python make_voice.py words voice.bin.
+
+The folder holds its.wav, oclock.wav, oh.wav, 1.wav to 19.wav, and 20.wav, 30.wav, 40.wav, and
+50.wav: mono, 16-bit, and all at one sample rate from 8 to 48 kHz. Trim the silence from each,
+since the clock leaves its own short pause between words. Set TALKING_CLOCK_VOICE to the voice
+file when building with --features talking-clock.
+"""
+
+import struct
+import sys
+import wave
+from pathlib import Path
+
+# In the order of `Word::clip_index` in clock-core/src/speech.rs
+WORDS = ["its", "oclock", "oh", *map(str, range(1, 20)), "20", "30", "40", "50"]
+HEADER_LEN = 12
+ENTRY_LEN = 8
+
+
+def main():
+    if len(sys.argv) != 3:
+        sys.exit(__doc__)
+    folder = Path(sys.argv[1])
+    sample_rate = None
+    clips = []
+    for word in WORDS:
+        path = folder / f"{word}.wav"
+        with wave.open(str(path), "rb") as recording:
+            if recording.getnchannels() != 1 or recording.getsampwidth() != 2:
+                sys.exit(f"{path}: must be mono and 16-bit")
+            rate = recording.getframerate()
+            if sample_rate is None:
+                sample_rate = rate
+            elif rate != sample_rate:
+                sys.exit(f"{path}: {rate} Hz, but the others are {sample_rate} Hz")
+            clips.append(recording.readframes(recording.getnframes()))
+    if not 8_000 <= sample_rate <= 48_000:
+        sys.exit(f"{sample_rate} Hz is outside 8 to 48 kHz")
+
+    table = b""
+    start = HEADER_LEN + ENTRY_LEN * len(clips)
+    for clip in clips:
+        table += struct.pack("<II", start, len(clip))
+        start += len(clip)
+    header = b"VOX1" + struct.pack("<II", sample_rate, len(clips))
+    Path(sys.argv[2]).write_bytes(header + table + b"".join(clips))
+    print(f"Wrote {start} bytes ({len(clips)} words at {sample_rate} Hz)")
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,29 @@
+"""Set the clock from this PC's time over the USB console.
+
+Usage: python usb_set_time.py <serial port>, e.g. /dev/ttyACM0 or COM3. Needs pyserial.
+"""
+
+import sys
+import time
+
+import serial
+
+
+def main():
+    if len(sys.argv) != 2:
+        sys.exit(__doc__)
+    with serial.Serial(sys.argv[1], timeout=2) as port:
+        # Let the console print its first prompt, then drop it
+        time.sleep(0.2)
+        port.reset_input_buffer()
+        # Send on the start of the next second, so the whole seconds sent are exact
+        time.sleep(1 - time.time() % 1)
+        port.write(f"set time {round(time.time())}\r".encode())
+        reply = port.readline().decode(errors="replace").strip().removeprefix(">").strip()
+    print(reply)
+    if not reply.startswith("OK"):
+        sys.exit(1)
+
+
+if __name__ == "__main__":
+    main()